@@ -0,0 +1,100 @@
+//! Compare do-blocks with the code they are meant to be as fast as.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use do_notation::{flat_m, m};
+
+fn parse(s: &str) -> Result<u32, std::num::ParseIntError> {
+  s.parse()
+}
+
+fn m_block(a: &str, b: &str, c: &str) -> Result<u32, std::num::ParseIntError> {
+  m! {
+    x <- parse(a);
+    y <- parse(b);
+    z <- parse(c);
+    return x + y * z;
+  }
+}
+
+fn flat_m_block(a: &str, b: &str, c: &str) -> Result<u32, std::num::ParseIntError> {
+  flat_m! {
+    x <- parse(a);
+    y <- parse(b);
+    z <- parse(c);
+    return x + y * z;
+  }
+}
+
+fn question_mark(a: &str, b: &str, c: &str) -> Result<u32, std::num::ParseIntError> {
+  let x = parse(a)?;
+  let y = parse(b)?;
+  let z = parse(c)?;
+  Ok(x + y * z)
+}
+
+fn and_then_chain(a: &str, b: &str, c: &str) -> Result<u32, std::num::ParseIntError> {
+  parse(a).and_then(|x| parse(b).and_then(|y| parse(c).map(|z| x + y * z)))
+}
+
+type Block = fn(&str, &str, &str) -> Result<u32, std::num::ParseIntError>;
+
+fn result(c: &mut Criterion) {
+  let blocks: [(&str, Block); 4] = [
+    ("m!", m_block),
+    ("flat_m!", flat_m_block),
+    ("?", question_mark),
+    ("and_then", and_then_chain),
+  ];
+
+  for (inputs, name) in [(("12", "34", "56"), "ok"), (("12", "x", "56"), "err")] {
+    let mut group = c.benchmark_group(format!("result/{}", name));
+
+    for (block_name, block) in blocks {
+      group.bench_function(block_name, |b| {
+        b.iter(|| {
+          block(
+            black_box(inputs.0),
+            black_box(inputs.1),
+            black_box(inputs.2),
+          )
+        })
+      });
+    }
+
+    group.finish();
+  }
+}
+
+fn option(c: &mut Criterion) {
+  let v = (0..64).collect::<Vec<u32>>();
+  let mut group = c.benchmark_group("option");
+
+  group.bench_function("m!", |b| {
+    b.iter(|| {
+      let v = black_box(&v);
+      m! {
+        x <- v.get(3);
+        y <- v.get(*x as usize * 2);
+        z <- v.get(*y as usize * 2);
+        return x + y + z;
+      }
+    })
+  });
+
+  group.bench_function("?", |b| {
+    b.iter(|| {
+      let v = black_box(&v);
+      (|| {
+        let x = v.get(3)?;
+        let y = v.get(*x as usize * 2)?;
+        let z = v.get(*y as usize * 2)?;
+        Some(x + y + z)
+      })()
+    })
+  });
+
+  group.finish();
+}
+
+criterion_group!(benches, result, option);
+criterion_main!(benches);
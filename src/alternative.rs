@@ -0,0 +1,175 @@
+//! Computations with a choice.
+//!
+//! An [`Alternative`] is a type constructor with a computation that fails, [`Alternative::empty`], and a way to try
+//! a computation and fall back on another one, [`Alternative::alt`] (`<|>` in Haskell). For [`Option`], the first
+//! [`Some`] is kept. [`Result`] isn’t [`Alternative`], as it has no meaningful error for the computation that fails.
+//!
+//! Over [`StateT`](crate::state::StateT), every alternative starts with the same state, so a state monad over
+//! [`Option`] is a backtracking parser:
+//!
+//! ```rust
+//! use do_notation::{
+//!   alternative::Alternative,
+//!   applicative::Applicative,
+//!   m,
+//!   monad::OptionKind,
+//!   state::{gets, put, run_state_t, StateT, StateTKind},
+//! };
+//!
+//! type Parser<'a, A> = StateT<'a, &'a str, OptionKind, A>;
+//! type P<'a> = StateTKind<&'a str, OptionKind>;
+//!
+//! fn tag<'a>(t: &'static str) -> Parser<'a, &'static str> {
+//!   m! {
+//!     rest <- gets(move |s: &&'a str| s.strip_prefix(t));
+//!     match rest {
+//!       Some(rest) => put(rest).and_then(move |()| P::pure(t)),
+//!       None => P::empty(),
+//!     }
+//!   }
+//! }
+//!
+//! let bool = || P::alt(tag("true"), tag("false"));
+//!
+//! assert_eq!(run_state_t(bool(), "false!"), Some(("false", "!")));
+//! assert_eq!(run_state_t(bool(), "maybe"), None);
+//! ```
+//!
+//! Monads implement [`MonadPlus`], over their [`Kind`], and are [`Alternative`] through it. `Vec` can’t be a monad
+//! of this crate — its bind calls the continuation once per element — but it is [`Alternative`], through
+//! [`VecKind`]: the empty computation is the empty vector, and the alternatives are concatenated.
+//!
+//! ```rust
+//! use do_notation::{alternative::{Alternative, VecKind}, combinators::msum};
+//!
+//! assert_eq!(VecKind::alt(vec![1, 2], vec![3]), vec![1, 2, 3]);
+//! assert_eq!(msum(vec![vec![1], vec![], vec![2, 3]]), vec![1, 2, 3]);
+//! ```
+
+use crate::{
+  monad::{Kind, Monad, OptionKind},
+  Lift,
+};
+
+/// Type constructors with a failing computation and a choice between computations.
+///
+/// Implementors must ensure that:
+///
+/// - `F::alt(F::empty(), x)` and `F::alt(x, F::empty())` are equivalent to `x`.
+/// - `F::alt(F::alt(x, y), z)` is equivalent to `F::alt(x, F::alt(y, z))`.
+pub trait Alternative<'a>: Sized + 'a {
+  /// The computation carrying values of type `A`.
+  type Of<A: 'a>: Alternated<'a, Item = A, Kind = Self>;
+
+  /// The computation that always fails.
+  fn empty<A: 'a>() -> Self::Of<A>;
+
+  /// Try `fa`, falling back on `fb` if it fails.
+  fn alt<A: 'a>(fa: Self::Of<A>, fb: Self::Of<A>) -> Self::Of<A>;
+}
+
+/// Alternative values.
+///
+/// This trait links a value to its [`Alternative`], the way [`Monad`] does for [`Kind`]. It is implemented by every
+/// monad whose [`Kind`] is [`MonadPlus`].
+pub trait Alternated<'a>: Sized + 'a + Lift<<Self as Alternated<'a>>::Item> {
+  /// Type of the value carried by the computation.
+  type Item: 'a;
+
+  /// Type constructor of the computation.
+  type Kind: Alternative<'a, Of<Self::Item> = Self>;
+}
+
+/// Monads with a failing computation and a choice between computations.
+///
+/// They are [`Alternative`], with [`MonadPlus::mzero`] as [`Alternative::empty`], and [`MonadPlus::mplus`] as
+/// [`Alternative::alt`], which must then satisfy the same laws.
+pub trait MonadPlus<'a>: Kind<'a> {
+  /// The computation that always fails.
+  fn mzero<A: 'a>() -> Self::Of<A>;
+
+  /// Try `fa`, falling back on `fb` if it fails.
+  fn mplus<A: 'a>(fa: Self::Of<A>, fb: Self::Of<A>) -> Self::Of<A>;
+}
+
+impl<'a, K> Alternative<'a> for K
+where
+  K: MonadPlus<'a>,
+{
+  type Of<A: 'a> = <K as Kind<'a>>::Of<A>;
+
+  fn empty<A: 'a>() -> K::Of<A> {
+    K::mzero()
+  }
+
+  fn alt<A: 'a>(fa: K::Of<A>, fb: K::Of<A>) -> K::Of<A> {
+    K::mplus(fa, fb)
+  }
+}
+
+impl<'a, M> Alternated<'a> for M
+where
+  M: Monad<'a>,
+  M::Kind: MonadPlus<'a>,
+{
+  type Item = M::Item;
+  type Kind = M::Kind;
+}
+
+impl<'a> MonadPlus<'a> for OptionKind {
+  fn mzero<A: 'a>() -> Option<A> {
+    None
+  }
+
+  fn mplus<A: 'a>(fa: Option<A>, fb: Option<A>) -> Option<A> {
+    fa.or(fb)
+  }
+}
+
+/// [`Alternative`] of `Vec<_>`, concatenating the alternatives.
+#[derive(Debug)]
+pub struct VecKind;
+
+impl<'a> Alternative<'a> for VecKind {
+  type Of<A: 'a> = Vec<A>;
+
+  fn empty<A: 'a>() -> Vec<A> {
+    Vec::new()
+  }
+
+  fn alt<A: 'a>(mut fa: Vec<A>, fb: Vec<A>) -> Vec<A> {
+    fa.extend(fb);
+    fa
+  }
+}
+
+impl<'a, A: 'a> Alternated<'a> for Vec<A> {
+  type Item = A;
+  type Kind = VecKind;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::state::{modify, run_state_t, StateTKind};
+
+  #[test]
+  fn alternative() {
+    assert_eq!(OptionKind::alt(None, Some(1)), Some(1));
+    assert_eq!(OptionKind::alt(Some(1), Some(2)), Some(1));
+    assert_eq!(OptionKind::empty::<i32>(), None);
+
+    type S = StateTKind<i32, OptionKind>;
+    let r = S::alt(
+      modify(|n: i32| n + 1).and_then(|()| S::empty()),
+      modify(|n: i32| n * 10),
+    );
+    assert_eq!(run_state_t(r, 1), Some(((), 10)));
+
+    assert_eq!(
+      VecKind::alt(vec![1], VecKind::alt(vec![], vec![2, 3])),
+      vec![1, 2, 3]
+    );
+    assert!(VecKind::empty::<i32>().is_empty());
+  }
+}
@@ -0,0 +1,402 @@
+//! Functors and applicative functors.
+//!
+//! Like [`Kind`], [`Functor`] and [`Applicative`] are implemented by marker types standing for type constructors.
+//! They’re weaker than monads: a [`Functor`] can only map the values it carries, and an [`Applicative`] can also
+//! combine independent computations, but neither can make a computation depend on the value produced by another
+//! one.
+//!
+//! Every monad is an applicative functor, so both traits are implemented for every [`Kind`]. Some applicative
+//! functors aren’t monads, though, and only implement these traits.
+//!
+//! ```rust
+//! use do_notation::{applicative::Applicative, monad::OptionKind};
+//!
+//! fn add<'a, F: Applicative<'a>>(a: F::Of<i32>, b: F::Of<i32>) -> F::Of<i32> {
+//!   F::zip_with(a, b, |a, b| a + b)
+//! }
+//!
+//! assert_eq!(add::<OptionKind>(Some(1), Some(2)), Some(3));
+//! assert_eq!(add::<OptionKind>(Some(1), None), None);
+//! ```
+//!
+//! # Applicative do-notation
+//!
+//! The [`ado!`](crate::ado) block binds independent computations of any applicative functor, with the syntax of
+//! [`m!`](crate::m): it desugars through [`Applicative::zip_with`] and [`Functor::map`] instead of `and_then`, so it
+//! works with applicative functors which aren’t monads — accumulating the errors of validations, or batching the
+//! requests of fetches:
+//!
+//! ```rust
+//! use do_notation::{ado, validation::Validation};
+//!
+//! fn positive(n: i32) -> Validation<String, i32> {
+//!   if n > 0 { Validation::Valid(n) } else { Validation::invalid(format!("{} isn’t positive", n)) }
+//! }
+//!
+//! let r = ado! {
+//!   width <- positive(-2);
+//!   height <- positive(0);
+//!   return width * height;
+//! };
+//!
+//! let errors = Vec::from(r.into_result().unwrap_err());
+//! assert_eq!(errors, vec!["-2 isn’t positive", "0 isn’t positive"]);
+//! ```
+//!
+//! Statements can’t use the values bound before them — they are computed independently — which is checked when the
+//! block is compiled:
+//!
+//! ```rust,compile_fail
+//! use do_notation::ado;
+//!
+//! let r = ado! {
+//!   a <- Some(1);
+//!   b <- Some(a + 1);
+//!   return a + b;
+//! };
+//! ```
+
+use crate::{
+  either::Either,
+  monad::{Kind, Monad},
+  Lift,
+};
+
+/// Type constructors of functors.
+///
+/// Implementors must ensure that:
+///
+/// - `F::map(x, |a| a)` is equivalent to `x`.
+/// - `F::map(F::map(x, f), g)` is equivalent to `F::map(x, |a| g(f(a)))`.
+pub trait Functor<'a>: Sized + 'a {
+  /// The functor carrying values of type `A`.
+  type Of<A: 'a>: 'a;
+
+  /// Apply `f` to the value carried by `fa`.
+  fn map<A: 'a, B: 'a>(fa: Self::Of<A>, f: impl FnOnce(A) -> B + 'a) -> Self::Of<B>;
+}
+
+/// Type constructors of applicative functors.
+///
+/// Implementors must ensure that:
+///
+/// - `F::zip_with(F::pure(a), x, f)` is equivalent to `F::map(x, |b| f(a, b))`.
+/// - `F::zip_with(x, F::pure(b), f)` is equivalent to `F::map(x, |a| f(a, b))`.
+/// - `F::zip(F::zip(x, y), z)` is equivalent to `F::zip(x, F::zip(y, z))`, up to the nesting of the pairs.
+pub trait Applicative<'a>: Functor<'a> {
+  /// Embed a value, without any effect.
+  fn pure<A: 'a>(a: A) -> Self::Of<A>;
+
+  /// Combine the values carried by `fa` and `fb` with `f`.
+  fn zip_with<A: 'a, B: 'a, C: 'a>(
+    fa: Self::Of<A>,
+    fb: Self::Of<B>,
+    f: impl FnOnce(A, B) -> C + 'a,
+  ) -> Self::Of<C>;
+
+  /// Pair the values carried by `fa` and `fb`.
+  fn zip<A: 'a, B: 'a>(fa: Self::Of<A>, fb: Self::Of<B>) -> Self::Of<(A, B)> {
+    Self::zip_with(fa, fb, |a, b| (a, b))
+  }
+
+  /// Apply `f` to every element of `iter`, in order, collecting the values.
+  ///
+  /// By default, `f` is applied to every element and the results are combined with [`Applicative::zip_with`].
+  /// Monads override it to stop at the first computation that short-circuits.
+  fn traverse_iter<I, B, F>(iter: I, mut f: F) -> Self::Of<Vec<B>>
+  where
+    I: Iterator + 'a,
+    B: 'a,
+    F: FnMut(I::Item) -> Self::Of<B> + 'a,
+  {
+    iter.fold(Self::pure(Vec::new()), |acc, a| {
+      Self::zip_with(acc, f(a), |mut v, b| {
+        v.push(b);
+        v
+      })
+    })
+  }
+
+  /// Apply `f` to every element of `iter`, in order, discarding the values.
+  ///
+  /// Like [`Applicative::traverse_iter`], monads override it to stop at the first computation that short-circuits.
+  fn for_each_iter<I, B, F>(iter: I, mut f: F) -> Self::Of<()>
+  where
+    I: Iterator + 'a,
+    B: 'a,
+    F: FnMut(I::Item) -> Self::Of<B> + 'a,
+  {
+    iter.fold(Self::pure(()), |acc, a| {
+      Self::zip_with(acc, f(a), |(), _| ())
+    })
+  }
+}
+
+/// Applicative values.
+///
+/// This trait links a value to its [`Applicative`], the way [`Monad`] does for [`Kind`]. It is implemented by every
+/// [`Monad`].
+pub trait Applied<'a>: Sized + 'a {
+  /// Type of the value carried by the applicative functor.
+  type Item: 'a;
+
+  /// Type constructor of the applicative functor.
+  type Kind: Applicative<'a, Of<Self::Item> = Self>;
+}
+
+impl<'a, K> Functor<'a> for K
+where
+  K: Kind<'a>,
+{
+  type Of<A: 'a> = <K as Kind<'a>>::Of<A>;
+
+  fn map<A: 'a, B: 'a>(fa: K::Of<A>, f: impl FnOnce(A) -> B + 'a) -> K::Of<B> {
+    fa.and_then(move |a| Lift::lift(f(a)))
+  }
+}
+
+impl<'a, K> Applicative<'a> for K
+where
+  K: Kind<'a>,
+{
+  fn pure<A: 'a>(a: A) -> K::Of<A> {
+    Lift::lift(a)
+  }
+
+  fn zip_with<A: 'a, B: 'a, C: 'a>(
+    fa: K::Of<A>,
+    fb: K::Of<B>,
+    f: impl FnOnce(A, B) -> C + 'a,
+  ) -> K::Of<C> {
+    fa.and_then(move |a| fb.and_then(move |b| Lift::lift(f(a, b))))
+  }
+
+  fn traverse_iter<I, B, F>(iter: I, f: F) -> K::Of<Vec<B>>
+  where
+    I: Iterator + 'a,
+    B: 'a,
+    F: FnMut(I::Item) -> K::Of<B> + 'a,
+  {
+    K::loop_m(
+      (iter, f, Vec::new()),
+      |(mut iter, mut f, mut acc)| match iter.next() {
+        Some(a) => f(a).and_then(move |b| {
+          acc.push(b);
+          Lift::lift(Either::Left((iter, f, acc)))
+        }),
+        None => Lift::lift(Either::Right(acc)),
+      },
+    )
+  }
+
+  fn for_each_iter<I, B, F>(iter: I, f: F) -> K::Of<()>
+  where
+    I: Iterator + 'a,
+    B: 'a,
+    F: FnMut(I::Item) -> K::Of<B> + 'a,
+  {
+    K::loop_m((iter, f), |(mut iter, mut f)| match iter.next() {
+      Some(a) => f(a).and_then(move |_| Lift::lift(Either::Left((iter, f)))),
+      None => Lift::lift(Either::Right(())),
+    })
+  }
+}
+
+impl<'a, M> Applied<'a> for M
+where
+  M: Monad<'a>,
+{
+  type Item = M::Item;
+  type Kind = M::Kind;
+}
+
+/// Type of the variables bound earlier in an [`ado!`](crate::ado) block, left uninitialized so that statements can’t
+/// use them.
+#[doc(hidden)]
+pub struct EarlierBinding;
+
+#[doc(hidden)]
+pub fn zip_applied<'a, M, B>(
+  m: M,
+  n: <M::Kind as Functor<'a>>::Of<B>,
+) -> <M::Kind as Functor<'a>>::Of<(M::Item, B)>
+where
+  M: Applied<'a>,
+  B: 'a,
+{
+  M::Kind::zip(m, n)
+}
+
+#[doc(hidden)]
+pub fn map_applied<'a, M, B>(
+  m: M,
+  f: impl FnOnce(M::Item) -> B + 'a,
+) -> <M::Kind as Functor<'a>>::Of<B>
+where
+  M: Applied<'a>,
+  B: 'a,
+{
+  M::Kind::map(m, f)
+}
+
+/// Applicative do-notation.
+///
+/// The block is made of binds — `x <- <expr>;`, `_ <- <expr>;` or `<expr>;` — of computations of the same
+/// [`Applicative`], ending with `return <expr>;`. The bound computations are independent: using a value bound before
+/// them doesn’t compile. They are combined with [`Applicative::zip_with`], in order, and the returned expression is
+/// mapped over the combination. See the [`applicative`](crate::applicative) module.
+///
+/// A value bound before a statement can’t be used by it in any way, even when it isn’t involved in a computation:
+///
+/// ```rust,compile_fail
+/// use do_notation::ado;
+///
+/// let r = ado! {
+///   a <- Some(1);
+///   b <- Some(a);
+///   return b;
+/// };
+/// ```
+///
+/// ```rust,compile_fail
+/// use do_notation::ado;
+///
+/// let r = ado! {
+///   a <- Some(1);
+///   b <- Some(format!("{:?}", a));
+///   return b;
+/// };
+/// ```
+#[macro_export]
+macro_rules! ado {
+  (@acc [$($acc:tt)*] $p:tt [$($b:ident)*] return $r:expr ;) => {
+    $crate::applicative::map_applied($($acc)*, move |$p| $r)
+  };
+
+  (@acc [$($acc:tt)*] $p:tt [$($b:ident)*] _ <- $x:expr ; $($t:tt)*) => {
+    $crate::ado!(
+      @acc [$crate::applicative::zip_applied($($acc)*, $crate::ado!(@independent [$($b)*] $x))]
+      ($p, _) [$($b)*] $($t)*
+    )
+  };
+
+  (@acc [$($acc:tt)*] $p:tt [$($b:ident)*] $binding:ident <- $x:expr ; $($t:tt)*) => {
+    $crate::ado!(
+      @acc [$crate::applicative::zip_applied($($acc)*, $crate::ado!(@independent [$($b)*] $x))]
+      ($p, $binding) [$($b)* $binding] $($t)*
+    )
+  };
+
+  (@acc [$($acc:tt)*] $p:tt [$($b:ident)*] $binding:tt <- $($t:tt)*) => {
+    compile_error!(concat!("malformed bind in an `ado!` block: `", stringify!($binding <- $($t)*), "`"))
+  };
+
+  (@acc [$($acc:tt)*] $p:tt [$($b:ident)*] $x:expr ; $($t:tt)*) => {
+    $crate::ado!(@acc [$($acc)*] $p [$($b)*] _ <- $x ; $($t)*)
+  };
+
+  (@acc [$($acc:tt)*] $p:tt [$($b:ident)*] $($t:tt)*) => {
+    compile_error!("malformed `ado!` block: expected `x <- m;` or `m;` statements, followed by `return e;`")
+  };
+
+  // shadow the earlier bindings, so that using them doesn’t compile
+  (@independent [$($b:ident)*] $x:expr) => {{
+    $(
+      #[allow(unused_variables)]
+      let $b: $crate::applicative::EarlierBinding;
+    )*
+    $x
+  }};
+
+  (return $r:expr ;) => {
+    $crate::Lift::lift($r)
+  };
+
+  (_ <- $x:expr ; $($t:tt)*) => {
+    $crate::ado!(@acc [$x] _ [] $($t)*)
+  };
+
+  ($binding:ident <- $x:expr ; $($t:tt)*) => {
+    $crate::ado!(@acc [$x] $binding [$binding] $($t)*)
+  };
+
+  ($x:expr ; $($t:tt)*) => {
+    $crate::ado!(@acc [$x] _ [] $($t)*)
+  };
+
+  ($($t:tt)*) => {
+    compile_error!("malformed `ado!` block: expected `x <- m;` or `m;` statements, followed by `return e;`")
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    combinators::{for_m, map_m},
+    identity::IdentityKind,
+    state::{gets, modify, run_state, StateTKind},
+  };
+
+  #[test]
+  fn applicative() {
+    type S = StateTKind<Vec<i32>, IdentityKind>;
+    let push = |n| {
+      modify(move |mut v: Vec<i32>| {
+        v.push(n);
+        v
+      })
+    };
+
+    let r = S::zip(push(1), push(2));
+    assert_eq!(run_state(r, vec![]), (((), ()), vec![1, 2]));
+
+    let r = S::zip_with(gets(|v: &Vec<i32>| v.len()), S::pure(10), |a, b| a + b);
+    assert_eq!(run_state(S::map(r, |n| n * 2), vec![0]), (22, vec![0]));
+  }
+
+  #[test]
+  fn long_traversals() {
+    assert_eq!(map_m(0..100_000, Some).map(|v| v.len()), Some(100_000));
+    assert_eq!(
+      for_m(0..100_000, |n| if n < 99_999 { Ok(n) } else { Err(n) }),
+      Err(99_999)
+    );
+  }
+
+  #[test]
+  fn ado() {
+    let r = ado! {
+      a <- Some(1);
+      Some("ignored");
+      _ <- Some(());
+      b <- Some(2);
+      return a + b;
+    };
+    assert_eq!(r, Some(3));
+
+    let r = ado! {
+      a <- Some(1);
+      b <- None::<i32>;
+      return a + b;
+    };
+    assert_eq!(r, None);
+
+    let push = |n| {
+      modify(move |mut v: Vec<i32>| {
+        v.push(n);
+        v
+      })
+    };
+    let r = ado! {
+      push(1);
+      len <- gets(|v: &Vec<i32>| v.len());
+      push(2);
+      return len;
+    };
+    assert_eq!(run_state(r, vec![]), (1, vec![1, 2]));
+
+    let r: Option<i32> = ado! { return 4; };
+    assert_eq!(r, Some(4));
+  }
+}
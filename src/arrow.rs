@@ -0,0 +1,353 @@
+//! Arrows.
+//!
+//! An [`Arrow`] is a generalization of functions: a computation taking inputs of some type and producing outputs of
+//! another type, that can be composed with other arrows. Every monad gives rise to an arrow: its [`Kleisli`] arrow,
+//! wrapping functions `B -> M<C>`.
+//!
+//! Arrows are built from plain functions with [`ArrowKind::arr`], composed with [`Arrow::compose`] (`>>>` in
+//! Haskell, `>>` on [`Kleisli`] arrows) and combined with [`Arrow::first`], [`Arrow::second`], [`Arrow::split`]
+//! (`***`) and [`Arrow::fanout`] (`&&&`, `&` on [`Kleisli`] arrows):
+//!
+//! ```rust
+//! use do_notation::{arrow::{Arrow, ArrowKind, Kleisli, KleisliKind}, monad::OptionKind};
+//!
+//! let parse = Kleisli::<OptionKind, &str, i32>::new(|s| s.parse().ok());
+//! let positive = Kleisli::new(|n: i32| if n > 0 { Some(n) } else { None });
+//! let double = KleisliKind::arr(|n: i32| n * 2);
+//!
+//! let f = parse >> positive >> (double & KleisliKind::arr(|n: i32| n + 1));
+//!
+//! assert_eq!(f.run("10"), Some((20, 11)));
+//! assert_eq!(f.run("-10"), None);
+//! assert_eq!(f.run("nope"), None);
+//! ```
+//!
+//! The [`arrow!`](crate::arrow) macro provides a notation to build arrows out of other arrows, akin to the `proc`
+//! notation of Haskell.
+
+use crate::{
+  monad::{Kind, Monad},
+  Lift,
+};
+use std::{
+  marker::PhantomData,
+  ops::{BitAnd, Shr},
+  rc::Rc,
+};
+
+/// Arrow type constructors.
+///
+/// This is the [`Kind`](crate::monad::Kind) counterpart for arrows: it maps input and output types to an arrow
+/// type.
+pub trait ArrowKind<'a>: Sized + 'a {
+  /// Type of arrows taking `B` and producing `C`.
+  type Of<B: 'a, C: 'a>: Arrow<'a, In = B, Out = C, Kind = Self>;
+
+  /// Lift a function into an arrow.
+  fn arr<B: 'a, C: 'a>(f: impl Fn(B) -> C + 'a) -> Self::Of<B, C>;
+}
+
+/// Arrows, composable computations taking inputs of type [`Arrow::In`] and producing outputs of type
+/// [`Arrow::Out`].
+pub trait Arrow<'a>: Sized + 'a {
+  /// Type of the inputs.
+  type In: 'a;
+
+  /// Type of the outputs.
+  type Out: 'a;
+
+  /// [`ArrowKind`] of the arrow.
+  type Kind: ArrowKind<'a, Of<Self::In, Self::Out> = Self>;
+
+  /// Feed the outputs of this arrow to `g` (`>>>`).
+  fn compose<D: 'a>(
+    self,
+    g: <Self::Kind as ArrowKind<'a>>::Of<Self::Out, D>,
+  ) -> <Self::Kind as ArrowKind<'a>>::Of<Self::In, D>;
+
+  /// Apply this arrow to the first component of pairs, leaving the second one unchanged.
+  #[allow(clippy::type_complexity)]
+  fn first<D: 'a>(self) -> <Self::Kind as ArrowKind<'a>>::Of<(Self::In, D), (Self::Out, D)>;
+
+  /// Apply this arrow to the second component of pairs, leaving the first one unchanged.
+  #[allow(clippy::type_complexity)]
+  fn second<D: 'a>(self) -> <Self::Kind as ArrowKind<'a>>::Of<(D, Self::In), (D, Self::Out)> {
+    Self::Kind::arr(|(d, b)| (b, d))
+      .compose(self.first())
+      .compose(Self::Kind::arr(|(c, d)| (d, c)))
+  }
+
+  /// Apply this arrow to the first component of pairs and `g` to the second component (`***`).
+  #[allow(clippy::type_complexity)]
+  fn split<B: 'a, C: 'a>(
+    self,
+    g: <Self::Kind as ArrowKind<'a>>::Of<B, C>,
+  ) -> <Self::Kind as ArrowKind<'a>>::Of<(Self::In, B), (Self::Out, C)> {
+    self.first().compose(g.second())
+  }
+
+  /// Feed the inputs to both this arrow and `g`, pairing their outputs (`&&&`).
+  fn fanout<C: 'a>(
+    self,
+    g: <Self::Kind as ArrowKind<'a>>::Of<Self::In, C>,
+  ) -> <Self::Kind as ArrowKind<'a>>::Of<Self::In, (Self::Out, C)>
+  where
+    Self::In: Clone,
+  {
+    Self::Kind::arr(|b: Self::In| (b.clone(), b)).compose(self.split(g))
+  }
+}
+
+/// Kleisli arrows of the monad `M`, wrapping functions from `B` to `M<C>`.
+pub struct Kleisli<'a, M: Kind<'a>, B, C: 'a> {
+  run: Rc<dyn Fn(B) -> M::Of<C> + 'a>,
+}
+
+impl<'a, M, B, C> Clone for Kleisli<'a, M, B, C>
+where
+  M: Kind<'a>,
+  C: 'a,
+{
+  fn clone(&self) -> Self {
+    Kleisli {
+      run: self.run.clone(),
+    }
+  }
+}
+
+impl<'a, M, B, C> Kleisli<'a, M, B, C>
+where
+  M: Kind<'a>,
+  C: 'a,
+{
+  /// Create a Kleisli arrow from a function.
+  pub fn new(f: impl Fn(B) -> M::Of<C> + 'a) -> Self {
+    Kleisli { run: Rc::new(f) }
+  }
+
+  /// Run the arrow on `b`.
+  pub fn run(&self, b: B) -> M::Of<C> {
+    (self.run)(b)
+  }
+
+  /// Feed the outputs of this arrow to the monadic function `g` (`>=>`).
+  ///
+  /// This is [`Arrow::compose`] without wrapping `g` in a [`Kleisli`] arrow first.
+  ///
+  /// ```rust
+  /// use do_notation::{arrow::Kleisli, monad::OptionKind};
+  ///
+  /// let parse = Kleisli::<OptionKind, &str, i32>::new(|s| s.parse().ok())
+  ///   .then(|n: i32| n.checked_mul(1000))
+  ///   .then(|n: i32| n.checked_add(1));
+  ///
+  /// assert_eq!(parse.run("3"), Some(3001));
+  /// assert_eq!(parse.run("3000000"), None);
+  /// ```
+  pub fn then<D: 'a>(self, g: impl Fn(C) -> M::Of<D> + 'a) -> Kleisli<'a, M, B, D>
+  where
+    B: 'a,
+  {
+    self.compose(Kleisli::new(g))
+  }
+}
+
+/// [`ArrowKind`] of `Kleisli<'_, M, _, _>`.
+#[derive(Debug)]
+pub struct KleisliKind<M>(PhantomData<M>);
+
+impl<'a, M> ArrowKind<'a> for KleisliKind<M>
+where
+  M: Kind<'a>,
+{
+  type Of<B: 'a, C: 'a> = Kleisli<'a, M, B, C>;
+
+  fn arr<B: 'a, C: 'a>(f: impl Fn(B) -> C + 'a) -> Kleisli<'a, M, B, C> {
+    Kleisli::new(move |b| Lift::lift(f(b)))
+  }
+}
+
+impl<'a, M, B, C> Arrow<'a> for Kleisli<'a, M, B, C>
+where
+  M: Kind<'a>,
+  B: 'a,
+  C: 'a,
+{
+  type In = B;
+  type Out = C;
+  type Kind = KleisliKind<M>;
+
+  fn compose<D: 'a>(self, g: Kleisli<'a, M, C, D>) -> Kleisli<'a, M, B, D> {
+    Kleisli::new(move |b| {
+      let g = g.clone();
+      self.run(b).and_then::<D, _>(move |c| g.run(c))
+    })
+  }
+
+  fn first<D: 'a>(self) -> Kleisli<'a, M, (B, D), (C, D)> {
+    Kleisli::new(move |(b, d)| {
+      self
+        .run(b)
+        .and_then::<(C, D), _>(move |c| Lift::lift((c, d)))
+    })
+  }
+}
+
+impl<'a, M, B, C, D> Shr<Kleisli<'a, M, C, D>> for Kleisli<'a, M, B, C>
+where
+  M: Kind<'a>,
+  B: 'a,
+  C: 'a,
+  D: 'a,
+{
+  type Output = Kleisli<'a, M, B, D>;
+
+  fn shr(self, g: Kleisli<'a, M, C, D>) -> Self::Output {
+    self.compose(g)
+  }
+}
+
+impl<'a, M, B, C, D> BitAnd<Kleisli<'a, M, B, D>> for Kleisli<'a, M, B, C>
+where
+  M: Kind<'a>,
+  B: Clone + 'a,
+  C: 'a,
+  D: 'a,
+{
+  type Output = Kleisli<'a, M, B, (C, D)>;
+
+  fn bitand(self, g: Kleisli<'a, M, B, D>) -> Self::Output {
+    self.fanout(g)
+  }
+}
+
+/// Feed the first component produced by `pre` to `f`, keeping the second one alongside the output.
+///
+/// This is what the `binding <- f -< expr;` statement desugars to in [`arrow!`](crate::arrow).
+pub fn bind<'a, F, E, X>(
+  f: F,
+  pre: impl Fn(E) -> (F::In, X) + 'a,
+) -> <F::Kind as ArrowKind<'a>>::Of<E, (F::Out, X)>
+where
+  F: Arrow<'a>,
+  E: 'a,
+  X: 'a,
+{
+  F::Kind::arr(pre).compose(f.first())
+}
+
+/// Feed the output of `pre` to `f`.
+///
+/// This is what the final `f -< expr` statement desugars to in [`arrow!`](crate::arrow).
+pub fn feed<'a, F, E>(
+  f: F,
+  pre: impl Fn(E) -> F::In + 'a,
+) -> <F::Kind as ArrowKind<'a>>::Of<E, F::Out>
+where
+  F: Arrow<'a>,
+  E: 'a,
+{
+  F::Kind::arr(pre).compose(f)
+}
+
+/// Arrow notation.
+///
+/// `arrow! { |x| statements… }` builds an arrow taking `x` as input, out of other arrows:
+///
+/// - `binding <- f -< expr;` feeds `expr` to the arrow `f` and binds its output to `binding`.
+/// - The last line, `f -< expr`, feeds `expr` to the arrow `f`, whose output is the output of the whole arrow.
+///
+/// The arrows must be identifiers or parenthesized expressions. The bound variables are moved into the expressions,
+/// so they must be cloned explicitly if they’re not [`Copy`] and used several times.
+///
+/// ```rust
+/// use do_notation::{arrow, arrow::{ArrowKind, Kleisli, KleisliKind}, monad::OptionKind};
+///
+/// let checked_div = Kleisli::<OptionKind, (i32, i32), i32>::new(|(a, b)| a.checked_div(b));
+/// let add = KleisliKind::arr(|(a, b): (i32, i32)| a + b);
+///
+/// // (a / b) + (b / a)
+/// let f = arrow! { |(a, b)|
+///   x <- (checked_div.clone()) -< (a, b);
+///   y <- checked_div -< (b, a);
+///   add -< (x, y)
+/// };
+///
+/// assert_eq!(f.run((4, 2)), Some(2));
+/// assert_eq!(f.run((4, 0)), None);
+/// ```
+#[macro_export]
+macro_rules! arrow {
+  // bind
+  (@stmts [$($env:tt)*] $binding:ident <- $f:tt -< $e:expr ; $($r:tt)*) => {
+    $crate::arrow::Arrow::compose(
+      $crate::arrow::bind($f, move |env| {
+        #[allow(unused_variables)]
+        let $($env)* = env;
+        ($e, $($env)*)
+      }),
+      $crate::arrow!(@stmts [($binding, $($env)*)] $($r)*),
+    )
+  };
+
+  // feed
+  (@stmts [$($env:tt)*] $f:tt -< $e:expr) => {
+    $crate::arrow::feed($f, move |env| {
+      #[allow(unused_variables)]
+      let $($env)* = env;
+      $e
+    })
+  };
+
+  (|$x:tt| $($r:tt)*) => {
+    $crate::arrow!(@stmts [$x] $($r)*)
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{identity::IdentityKind, monad::ResultKind};
+
+  #[test]
+  fn kleisli() {
+    let inc = KleisliKind::<IdentityKind>::arr(|n: i32| n + 1);
+    let double = KleisliKind::<IdentityKind>::arr(|n: i32| n * 2);
+
+    assert_eq!(inc.clone().compose(double.clone()).run(1).run(), 4);
+    assert_eq!(inc.clone().first::<&str>().run((1, "a")).run(), (2, "a"));
+    assert_eq!(inc.clone().second::<&str>().run(("a", 1)).run(), ("a", 2));
+    assert_eq!(inc.clone().split(double.clone()).run((1, 1)).run(), (2, 2));
+    assert_eq!(inc.fanout(double).run(3).run(), (4, 6));
+  }
+
+  #[test]
+  fn then() {
+    let validate =
+      Kleisli::<ResultKind<&str>, i32, i32>::new(|n| if n >= 0 { Ok(n) } else { Err("negative") });
+    let f = validate
+      .clone()
+      .then(|n| Ok(n - 10))
+      .then(move |n| validate.run(n));
+
+    assert_eq!(f.run(15), Ok(5));
+    assert_eq!(f.run(5), Err("negative"));
+  }
+
+  #[test]
+  fn arrow_notation() {
+    let validate =
+      Kleisli::<ResultKind<&str>, i32, i32>::new(|n| if n >= 0 { Ok(n) } else { Err("negative") });
+    let sum = KleisliKind::arr(|(a, b): (i32, i32)| a + b);
+
+    let f = arrow! { |n|
+      a <- (validate.clone()) -< n;
+      b <- validate -< a - 10;
+      sum -< (a, b)
+    };
+
+    assert_eq!(f.run(15), Ok(20));
+    assert_eq!(f.run(5), Err("negative"));
+    assert_eq!(f.run(-1), Err("negative"));
+  }
+}
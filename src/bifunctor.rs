@@ -0,0 +1,119 @@
+//! Bifunctors.
+//!
+//! A [`Bifunctor`] is a type with two type parameters, both of which can be mapped over. Mapping the error side of a
+//! [`Result`] and the value side of a pair share the same vocabulary:
+//!
+//! ```rust
+//! use do_notation::bifunctor::Bifunctor;
+//!
+//! let r: Result<i32, &str> = Err("nope");
+//! assert_eq!(r.bimap(|n| n + 1, str::len), Err(4));
+//! assert_eq!((1, "a").map_second(str::to_uppercase), (1, "A".to_owned()));
+//! ```
+
+use crate::{either::Either, these::These};
+
+/// Types with two type parameters that can both be mapped over.
+///
+/// Implementors must ensure that:
+///
+/// - `x.bimap(|a| a, |b| b)` is equivalent to `x`.
+/// - `x.bimap(f, g).bimap(h, i)` is equivalent to `x.bimap(|a| h(f(a)), |b| i(g(b)))`.
+pub trait Bifunctor: Sized {
+  /// Type of the first parameter.
+  type First;
+
+  /// Type of the second parameter.
+  type Second;
+
+  /// Same bifunctor, with parameters of types `C` and `D`.
+  type Of<C, D>;
+
+  /// Map both parameters.
+  fn bimap<C, D>(
+    self,
+    f: impl FnOnce(Self::First) -> C,
+    g: impl FnOnce(Self::Second) -> D,
+  ) -> Self::Of<C, D>;
+
+  /// Map the first parameter.
+  fn map_first<C>(self, f: impl FnOnce(Self::First) -> C) -> Self::Of<C, Self::Second> {
+    self.bimap(f, |b| b)
+  }
+
+  /// Map the second parameter.
+  fn map_second<D>(self, g: impl FnOnce(Self::Second) -> D) -> Self::Of<Self::First, D> {
+    self.bimap(|a| a, g)
+  }
+}
+
+/// The first parameter is the value, the second one is the error.
+impl<A, E> Bifunctor for Result<A, E> {
+  type First = A;
+  type Second = E;
+  type Of<C, D> = Result<C, D>;
+
+  fn bimap<C, D>(self, f: impl FnOnce(A) -> C, g: impl FnOnce(E) -> D) -> Result<C, D> {
+    match self {
+      Ok(a) => Ok(f(a)),
+      Err(e) => Err(g(e)),
+    }
+  }
+}
+
+impl<L, R> Bifunctor for Either<L, R> {
+  type First = L;
+  type Second = R;
+  type Of<C, D> = Either<C, D>;
+
+  fn bimap<C, D>(self, f: impl FnOnce(L) -> C, g: impl FnOnce(R) -> D) -> Either<C, D> {
+    match self {
+      Either::Left(l) => Either::Left(f(l)),
+      Either::Right(r) => Either::Right(g(r)),
+    }
+  }
+}
+
+impl<A, B> Bifunctor for These<A, B> {
+  type First = A;
+  type Second = B;
+  type Of<C, D> = These<C, D>;
+
+  fn bimap<C, D>(self, f: impl FnOnce(A) -> C, g: impl FnOnce(B) -> D) -> These<C, D> {
+    match self {
+      These::This(a) => These::This(f(a)),
+      These::That(b) => These::That(g(b)),
+      These::Both(a, b) => These::Both(f(a), g(b)),
+    }
+  }
+}
+
+impl<A, B> Bifunctor for (A, B) {
+  type First = A;
+  type Second = B;
+  type Of<C, D> = (C, D);
+
+  fn bimap<C, D>(self, f: impl FnOnce(A) -> C, g: impl FnOnce(B) -> D) -> (C, D) {
+    (f(self.0), g(self.1))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn bifunctor() {
+    assert_eq!(Ok::<_, i32>(1).map_first(|a| a * 2), Ok(2));
+    assert_eq!(Err::<i32, _>(1).map_second(|e| e * 2), Err(2));
+    assert_eq!(
+      Either::<_, i32>::Left(1).bimap(|l| l + 1, |r| r - 1),
+      Either::Left(2)
+    );
+    assert_eq!(
+      These::Both(1, "a").bimap(|a| a + 1, str::len),
+      These::Both(2, 1)
+    );
+    assert_eq!((1, 2).map_first(|a| a.to_string()), ("1".to_owned(), 2));
+  }
+}
@@ -0,0 +1,201 @@
+//! Decoding binary formats.
+//!
+//! [`ByteReader`] is the monad of decoders of byte slices: a computation reads bytes from the current position and
+//! moves it forward, so the cursor is threaded through the binds of a do-block. Decoders fail with a [`ByteError`],
+//! saying at which byte the decoding failed:
+//!
+//! ```rust
+//! use do_notation::{binary::{take, u16_le, u8, ByteReader}, m, Lift};
+//!
+//! // a magic byte, a little-endian length, and a payload of that length
+//! fn frame<'a>() -> ByteReader<'a, &'a [u8]> {
+//!   m! {
+//!     magic <- u8();
+//!     len <- u16_le();
+//!     payload <- take(len as usize);
+//!     if magic == 0x7f { Lift::lift(payload) } else { ByteReader::fail(format!("bad magic byte {:#x}", magic)) }
+//!   }
+//! }
+//!
+//! assert_eq!(frame().read(&[0x7f, 2, 0, b'h', b'i']), Ok(&b"hi"[..]));
+//! assert_eq!(frame().read(&[0x7f, 3, 0, b'h', b'i']).unwrap_err().to_string(),
+//!            "at byte 3: expected 3 bytes, found 2");
+//! assert_eq!(frame().read(&[0x00, 0, 0]).unwrap_err().to_string(), "at byte 3: bad magic byte 0x0");
+//! ```
+//!
+//! [`ByteReader::read`] ignores the bytes left after the decoded value; check [`remaining`] to reject them.
+
+use crate::{
+  monad::{Kind, Monad},
+  Lift,
+};
+use std::{error::Error, fmt};
+
+/// Errors of [`ByteReader`]s.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ByteError {
+  /// Position of the byte at which the decoding failed.
+  pub position: usize,
+  /// What went wrong.
+  pub message: String,
+}
+
+impl fmt::Display for ByteError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "at byte {}: {}", self.position, self.message)
+  }
+}
+
+impl Error for ByteError {}
+
+/// A decoder of byte slices, producing a value of type `A`.
+#[allow(clippy::type_complexity)]
+pub struct ByteReader<'a, A>(
+  Box<dyn FnOnce(&'a [u8], usize) -> Result<(A, usize), ByteError> + 'a>,
+);
+
+impl<'a, A: 'a> ByteReader<'a, A> {
+  /// Build a decoder out of a function, taking the input and the current position, and returning the value and the
+  /// new position.
+  pub fn new(f: impl FnOnce(&'a [u8], usize) -> Result<(A, usize), ByteError> + 'a) -> Self {
+    ByteReader(Box::new(f))
+  }
+
+  /// Decode `bytes`, from the start.
+  pub fn read(self, bytes: &'a [u8]) -> Result<A, ByteError> {
+    (self.0)(bytes, 0).map(|(a, _)| a)
+  }
+
+  /// Decode, then decode the next bytes with the decoder built by `f` from the value.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(A) -> ByteReader<'a, B> + 'a) -> ByteReader<'a, B> {
+    ByteReader::new(move |bytes, pos| {
+      let (a, pos) = (self.0)(bytes, pos)?;
+      (f(a).0)(bytes, pos)
+    })
+  }
+
+  /// Apply `f` to the decoded value.
+  pub fn map<B: 'a>(self, f: impl FnOnce(A) -> B + 'a) -> ByteReader<'a, B> {
+    ByteReader::new(move |bytes, pos| (self.0)(bytes, pos).map(|(a, pos)| (f(a), pos)))
+  }
+
+  /// Fail with `message` at the current position.
+  pub fn fail(message: impl Into<String>) -> Self {
+    let message = message.into();
+    ByteReader::new(move |_, position| Err(ByteError { position, message }))
+  }
+}
+
+impl<'a, A: 'a> Lift<A> for ByteReader<'a, A> {
+  fn lift(a: A) -> Self {
+    ByteReader::new(move |_, pos| Ok((a, pos)))
+  }
+}
+
+/// [`Kind`] of `ByteReader<'_, _>`.
+#[derive(Debug)]
+pub struct ByteReaderKind;
+
+impl<'a> Kind<'a> for ByteReaderKind {
+  type Of<A: 'a> = ByteReader<'a, A>;
+}
+
+impl<'a, A: 'a> Monad<'a> for ByteReader<'a, A> {
+  type Item = A;
+  type Kind = ByteReaderKind;
+
+  fn and_then<B, F>(self, f: F) -> ByteReader<'a, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> ByteReader<'a, B> + 'a,
+  {
+    ByteReader::and_then(self, f)
+  }
+}
+
+/// Read the next `n` bytes.
+pub fn take<'a>(n: usize) -> ByteReader<'a, &'a [u8]> {
+  ByteReader::new(move |bytes: &'a [u8], pos| {
+    let rest = &bytes[pos..];
+
+    if rest.len() < n {
+      return Err(ByteError {
+        position: pos,
+        message: format!("expected {} bytes, found {}", n, rest.len()),
+      });
+    }
+
+    Ok((&rest[..n], pos + n))
+  })
+}
+
+/// Read a byte.
+pub fn u8<'a>() -> ByteReader<'a, u8> {
+  take(1).map(|b| b[0])
+}
+
+/// Read a little-endian `u16`.
+pub fn u16_le<'a>() -> ByteReader<'a, u16> {
+  take(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+/// Number of bytes left, without reading them.
+pub fn remaining<'a>() -> ByteReader<'a, usize> {
+  ByteReader::new(|bytes, pos| Ok((bytes.len() - pos, pos)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::combinators::replicate_m;
+
+  #[test]
+  fn primitives() {
+    let r = m! {
+      a <- u8();
+      b <- u16_le();
+      n <- remaining();
+      rest <- take(n);
+      end <- remaining();
+      return (a, b, rest, end);
+    };
+
+    assert_eq!(
+      r.read(&[1, 0x34, 0x12, 5, 6]),
+      Ok((1, 0x1234, &[5, 6][..], 0))
+    );
+    assert_eq!(
+      u16_le().read(&[]).unwrap_err().to_string(),
+      "at byte 0: expected 2 bytes, found 0"
+    );
+  }
+
+  #[test]
+  fn positions() {
+    let strings = m! {
+      count <- u8();
+      replicate_m(count as usize, || {
+        m! {
+          len <- u8();
+          s <- take(len as usize);
+          std::str::from_utf8(s).map_or_else(|e| ByteReader::fail(e.to_string()), Lift::lift)
+        }
+      })
+    };
+
+    assert_eq!(strings.read(b"\x02\x01a\x02bc"), Ok(vec!["a", "bc"]));
+
+    let strings = m! {
+      count <- u8();
+      replicate_m(count as usize, || u8().and_then(|len| take(len as usize)))
+    };
+
+    assert_eq!(
+      strings.read(b"\x02\x01a\x03bc"),
+      Err(ByteError {
+        position: 4,
+        message: "expected 3 bytes, found 2".to_owned()
+      })
+    );
+  }
+}
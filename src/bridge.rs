@@ -0,0 +1,229 @@
+//! Interoperability with `higher`.
+//!
+//! > This module requires the `higher` feature.
+//!
+//! The `higher` crate describes monads with its own `Functor`, `Pure` and `Bind` traits. They are bridged in both
+//! directions:
+//!
+//! - [`Higher`] wraps any type implementing `Pure` and `Bind`, so that it can be bound in [`m!`](crate::m) blocks,
+//!   and lifted with [`Lift`].
+//! - [`Identity`], [`NonEmpty`], [`Either`] and [`These`] implement `Functor`, `Pure` and `Bind`, so that they can
+//!   be used in code generic over the traits of `higher`.
+//!
+//! ```rust
+//! use do_notation::{bridge::Higher, identity::Identity, m};
+//! use higher::{Bind, Pure};
+//!
+//! let r = m! {
+//!   x <- Higher(vec![1, 2]);
+//!   y <- Higher(vec![x, x * 10]);
+//!   return x + y;
+//! };
+//!
+//! assert_eq!(r.0, vec![2, 11, 4, 22]);
+//!
+//! fn double<'a, M: Bind<'a, i32, Target<i32> = M> + Pure<i32>>(m: M) -> M {
+//!   m.bind::<i32, _>(|x| M::pure(x * 2))
+//! }
+//!
+//! assert_eq!(double(Identity(21)), Identity(42));
+//! ```
+//!
+//! Blanket implementations aren’t possible in either direction — the traits and the types of the other crate are
+//! foreign — hence the wrapper.
+
+use crate::{
+  either::Either, identity::Identity, monoid::Semigroup, non_empty::NonEmpty, these::These, Lift,
+};
+use higher::{Bind, Functor, Pure};
+
+/// Monads of `higher`, bound in [`m!`](crate::m) blocks.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Higher<M>(pub M);
+
+impl<M> Higher<M> {
+  /// Bind the value to `f`, with the `Bind` implementation of `M`.
+  pub fn and_then<'a, A, B, F>(self, f: F) -> Higher<M::Target<B>>
+  where
+    M: Bind<'a, A>,
+    F: Fn(A) -> Higher<M::Target<B>> + 'a,
+  {
+    Higher(self.0.bind(move |a| f(a).0))
+  }
+}
+
+impl<A, M: Pure<A>> Lift<A> for Higher<M> {
+  fn lift(a: A) -> Self {
+    Higher(M::pure(a))
+  }
+}
+
+impl<'a, A> Functor<'a, A> for Identity<A> {
+  type Target<T> = Identity<T>;
+
+  fn fmap<B, F>(self, f: F) -> Identity<B>
+  where
+    F: Fn(A) -> B + 'a,
+  {
+    Identity(f(self.0))
+  }
+}
+
+impl<A> Pure<A> for Identity<A> {
+  fn pure(a: A) -> Self {
+    Identity(a)
+  }
+}
+
+impl<'a, A> Bind<'a, A> for Identity<A> {
+  type Target<T> = Identity<T>;
+
+  fn bind<B, F>(self, f: F) -> Identity<B>
+  where
+    F: Fn(A) -> Identity<B> + 'a,
+  {
+    self.and_then(f)
+  }
+}
+
+impl<'a, A> Functor<'a, A> for NonEmpty<A> {
+  type Target<T> = NonEmpty<T>;
+
+  fn fmap<B, F>(self, f: F) -> NonEmpty<B>
+  where
+    F: Fn(A) -> B + 'a,
+  {
+    self.map(f)
+  }
+}
+
+impl<A> Pure<A> for NonEmpty<A> {
+  fn pure(a: A) -> Self {
+    NonEmpty::new(a)
+  }
+}
+
+impl<'a, A> Bind<'a, A> for NonEmpty<A> {
+  type Target<T> = NonEmpty<T>;
+
+  fn bind<B, F>(self, f: F) -> NonEmpty<B>
+  where
+    F: Fn(A) -> NonEmpty<B> + 'a,
+  {
+    self.and_then(f)
+  }
+}
+
+impl<'a, L, R> Functor<'a, R> for Either<L, R> {
+  type Target<T> = Either<L, T>;
+
+  fn fmap<B, F>(self, f: F) -> Either<L, B>
+  where
+    F: Fn(R) -> B + 'a,
+  {
+    self.and_then(|r| Either::Right(f(r)))
+  }
+}
+
+impl<L, R> Pure<R> for Either<L, R> {
+  fn pure(r: R) -> Self {
+    Either::Right(r)
+  }
+}
+
+impl<'a, L, R> Bind<'a, R> for Either<L, R> {
+  type Target<T> = Either<L, T>;
+
+  fn bind<B, F>(self, f: F) -> Either<L, B>
+  where
+    F: Fn(R) -> Either<L, B> + 'a,
+  {
+    self.and_then(f)
+  }
+}
+
+impl<'a, A, B> Functor<'a, B> for These<A, B> {
+  type Target<T> = These<A, T>;
+
+  fn fmap<C, F>(self, f: F) -> These<A, C>
+  where
+    F: Fn(B) -> C + 'a,
+  {
+    match self {
+      These::This(a) => These::This(a),
+      These::That(b) => These::That(f(b)),
+      These::Both(a, b) => These::Both(a, f(b)),
+    }
+  }
+}
+
+impl<A, B> Pure<B> for These<A, B> {
+  fn pure(b: B) -> Self {
+    These::That(b)
+  }
+}
+
+impl<'a, A: Semigroup, B> Bind<'a, B> for These<A, B> {
+  type Target<T> = These<A, T>;
+
+  fn bind<C, F>(self, f: F) -> These<A, C>
+  where
+    F: Fn(B) -> These<A, C> + 'a,
+  {
+    self.and_then(f)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn pair<'a, M>(m: M) -> M::Target<(i32, i32)>
+  where
+    M: Functor<'a, i32>,
+  {
+    m.fmap(|x| (x, x + 1))
+  }
+
+  #[test]
+  fn higher_in_m() {
+    let r = m! {
+      x <- Higher(Some(1));
+      y <- Higher("2".parse::<i32>().ok());
+      return x + y;
+    };
+
+    assert_eq!(r, Higher(Some(3)));
+
+    let r: Higher<Result<i32, &str>> = m! {
+      x <- Higher(Ok(1));
+      Higher(Err::<(), _>("nope"));
+      return x;
+    };
+
+    assert_eq!(r, Higher(Err("nope")));
+  }
+
+  #[test]
+  fn crate_types_in_higher() {
+    assert_eq!(pair(Identity(1)), Identity((1, 2)));
+    assert_eq!(pair(Either::<&str, _>::Right(1)), Either::Right((1, 2)));
+    assert_eq!(
+      pair(These::Both(vec!['a'], 1)),
+      These::Both(vec!['a'], (1, 2))
+    );
+
+    let xs = NonEmpty::pure(1).bind(|x| NonEmpty {
+      head: x,
+      tail: vec![x * 10],
+    });
+    assert_eq!(Vec::from(xs), vec![1, 10]);
+
+    let r = These::Both(vec!['a'], 1).bind(|x| These::Both(vec!['b'], x + 1));
+    assert_eq!(r, These::Both(vec!['a', 'b'], 2));
+    assert_eq!(
+      Either::<(), _>::pure(1).bind(|_| Either::<(), i32>::Left(())),
+      Either::Left(())
+    );
+  }
+}
@@ -0,0 +1,254 @@
+//! Assembling structures step by step.
+//!
+//! [`Build<T, A>`] is the monad of computations setting the fields of a partial structure of type `T` — typically
+//! the structure with [`Option`] fields. Binds set or read fields, and [`Build::finish`] turns the partial structure
+//! into the complete one with [`Finish`], reporting all the missing fields at once, instead of a chain of builder
+//! methods ending with `expect`s:
+//!
+//! ```rust
+//! use do_notation::{build::{get, required, set, Build, Finish}, m, validate, validation::Validation};
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Server {
+//!   host: String,
+//!   port: u16,
+//!   workers: usize,
+//! }
+//!
+//! #[derive(Default)]
+//! struct PartialServer {
+//!   host: Option<String>,
+//!   port: Option<u16>,
+//!   workers: Option<usize>,
+//! }
+//!
+//! impl Finish for PartialServer {
+//!   type Output = Server;
+//!
+//!   fn finish(self) -> Validation<&'static str, Server> {
+//!     let workers = self.workers.unwrap_or(1);
+//!
+//!     validate! {
+//!       host <- required("host", self.host);
+//!       port <- required("port", self.port);
+//!       return Server { host, port, workers };
+//!     }
+//!   }
+//! }
+//!
+//! let server: Build<PartialServer, ()> = m! {
+//!   set(|s: &mut PartialServer| s.host = Some("localhost".to_owned()));
+//!   tls <- get(|s: &PartialServer| s.host.as_deref() != Some("localhost"));
+//!   set(move |s: &mut PartialServer| s.port = Some(if tls { 443 } else { 8080 }))
+//! };
+//!
+//! assert_eq!(server.finish(), Ok(Server { host: "localhost".to_owned(), port: 8080, workers: 1 }));
+//!
+//! let server: Build<PartialServer, ()> = set(|s: &mut PartialServer| s.workers = Some(4));
+//! assert_eq!(Vec::from(server.finish().unwrap_err()), vec!["host", "port"]);
+//! ```
+//!
+//! # Checking completeness at compile time
+//!
+//! When each field has its own type parameter — `()` until the field is set — the builder is a typestate, and
+//! setting a field changes its type. Such builders are the states of the parameterised monads of the
+//! [`indexed`](crate::indexed) module: the bound computations go from one builder type to the next, and finishing
+//! a builder which misses a field doesn’t compile:
+//!
+//! ```rust
+//! use do_notation::{indexed::{exec_ix_state, modify, IxState}, m};
+//!
+//! struct Request<U, M> {
+//!   url: U,
+//!   method: M,
+//! }
+//!
+//! fn url<'a, M: 'a>(url: &'a str) -> IxState<'a, Request<(), M>, Request<String, M>, ()> {
+//!   modify(move |r: Request<(), M>| Request { url: url.to_owned(), method: r.method })
+//! }
+//!
+//! fn method<'a, U: 'a>(method: &'static str) -> IxState<'a, Request<U, ()>, Request<U, &'static str>, ()> {
+//!   modify(move |r: Request<U, ()>| Request { url: r.url, method })
+//! }
+//!
+//! fn finish(r: Request<String, &'static str>) -> String {
+//!   format!("{} {}", r.method, r.url)
+//! }
+//!
+//! let request = m! {
+//!   method("GET");
+//!   url("/index.html")
+//! };
+//!
+//! assert_eq!(finish(exec_ix_state(request, Request { url: (), method: () })), "GET /index.html");
+//! ```
+//!
+//! Finishing a request without URL doesn’t compile:
+//!
+//! ```rust,compile_fail
+//! # use do_notation::indexed::{exec_ix_state, modify, IxState};
+//! # struct Request<U, M> {
+//! #   url: U,
+//! #   method: M,
+//! # }
+//! # fn method<'a, U: 'a>(method: &'static str) -> IxState<'a, Request<U, ()>, Request<U, &'static str>, ()> {
+//! #   modify(move |r: Request<U, ()>| Request { url: r.url, method })
+//! # }
+//! # fn finish(r: Request<String, &'static str>) -> String {
+//! #   format!("{} {}", r.method, r.url)
+//! # }
+//! let request = method("GET");
+//! finish(exec_ix_state(request, Request { url: (), method: () }));
+//! ```
+
+use crate::{
+  monad::{Kind, Monad},
+  non_empty::NonEmpty,
+  validation::Validation,
+  Lift,
+};
+use std::marker::PhantomData;
+
+/// Partial structures which can be completed.
+pub trait Finish {
+  /// The complete structure.
+  type Output;
+
+  /// Complete the structure, or fail with the names of the missing fields.
+  fn finish(self) -> Validation<&'static str, Self::Output>;
+}
+
+/// Computations setting the fields of a partial structure of type `T`, producing a value of type `A`.
+pub struct Build<'a, T, A>(Box<dyn FnOnce(&mut T) -> A + 'a>);
+
+impl<'a, T: 'a, A: 'a> Build<'a, T, A> {
+  /// Build a computation out of a function updating the partial structure.
+  pub fn new(f: impl FnOnce(&mut T) -> A + 'a) -> Self {
+    Build(Box::new(f))
+  }
+
+  /// Run the computation on `t`.
+  pub fn run(self, t: &mut T) -> A {
+    (self.0)(t)
+  }
+
+  /// Bind the value to `f`, whose computation continues updating the structure.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(A) -> Build<'a, T, B> + 'a) -> Build<'a, T, B> {
+    Build::new(move |t| {
+      let a = self.run(t);
+      f(a).run(t)
+    })
+  }
+
+  /// Apply `f` to the value.
+  pub fn map<B: 'a>(self, f: impl FnOnce(A) -> B + 'a) -> Build<'a, T, B> {
+    Build::new(move |t| f(self.run(t)))
+  }
+
+  /// Run the computation on the default partial structure, and complete it.
+  pub fn finish(self) -> Result<T::Output, NonEmpty<&'static str>>
+  where
+    T: Default + Finish,
+  {
+    let mut t = T::default();
+    self.run(&mut t);
+    t.finish().into_result()
+  }
+}
+
+impl<'a, T: 'a, A: 'a> Lift<A> for Build<'a, T, A> {
+  fn lift(a: A) -> Self {
+    Build::new(move |_| a)
+  }
+}
+
+/// [`Kind`] of `Build<'_, T, _>`.
+#[derive(Debug)]
+pub struct BuildKind<T>(PhantomData<T>);
+
+impl<'a, T: 'a> Kind<'a> for BuildKind<T> {
+  type Of<A: 'a> = Build<'a, T, A>;
+}
+
+impl<'a, T: 'a, A: 'a> Monad<'a> for Build<'a, T, A> {
+  type Item = A;
+  type Kind = BuildKind<T>;
+
+  fn and_then<B, F>(self, f: F) -> Build<'a, T, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Build<'a, T, B> + 'a,
+  {
+    Build::and_then(self, f)
+  }
+}
+
+/// Set fields with `f`.
+pub fn set<'a, T: 'a>(f: impl FnOnce(&mut T) + 'a) -> Build<'a, T, ()> {
+  Build::new(f)
+}
+
+/// Read fields with `f`.
+pub fn get<'a, T: 'a, A: 'a>(f: impl FnOnce(&T) -> A + 'a) -> Build<'a, T, A> {
+  Build::new(move |t| f(t))
+}
+
+/// The value of the field `name`, which must be set.
+pub fn required<A>(name: &'static str, field: Option<A>) -> Validation<&'static str, A> {
+  match field {
+    Some(a) => Validation::Valid(a),
+    None => Validation::invalid(name),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{combinators::for_m, validate};
+
+  #[derive(Debug, Default)]
+  struct PartialRgb {
+    r: Option<u8>,
+    g: Option<u8>,
+    b: Option<u8>,
+  }
+
+  impl Finish for PartialRgb {
+    type Output = (u8, u8, u8);
+
+    fn finish(self) -> Validation<&'static str, (u8, u8, u8)> {
+      validate! {
+        r <- required("r", self.r);
+        g <- required("g", self.g);
+        b <- required("b", self.b);
+        return (r, g, b);
+      }
+    }
+  }
+
+  #[test]
+  fn build() {
+    let gray = |level| -> Build<PartialRgb, ()> {
+      m! {
+        set(move |c: &mut PartialRgb| c.r = Some(level));
+        r <- get(|c: &PartialRgb| c.r);
+        set(move |c: &mut PartialRgb| {
+          c.g = r;
+          c.b = r;
+        })
+      }
+    };
+
+    assert_eq!(gray(7).finish(), Ok((7, 7, 7)));
+
+    let channels = for_m([0, 2], |i| {
+      set(move |c: &mut PartialRgb| *[&mut c.r, &mut c.g, &mut c.b][i] = Some(1))
+    });
+    assert_eq!(Vec::from(channels.finish().unwrap_err()), vec!["g"]);
+
+    let mut c = PartialRgb::default();
+    let set_r = set(|c: &mut PartialRgb| c.r = Some(1)).map(|()| "set");
+    assert_eq!(set_r.run(&mut c), "set");
+    assert_eq!(c.r, Some(1));
+  }
+}
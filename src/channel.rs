@@ -0,0 +1,172 @@
+//! Scripting channel protocols.
+//!
+//! [`Recv<T, A>`] is the monad of computations receiving messages of type `T` from a [`Receiver`], producing a value
+//! of type `A`. Its primitives — [`recv`], [`recv_timeout`] and [`recv_all`] — receive the next messages, and fail
+//! with a [`RecvTimeoutError`] when no message arrives in time, or when the senders are gone before the protocol
+//! ends. Simple worker protocols are then do-blocks, run against a receiver with [`Recv::run`]:
+//!
+//! ```rust
+//! use do_notation::{channel::{recv, recv_all, recv_timeout, Recv}, m, Lift};
+//! use std::{sync::mpsc, thread, time::Duration};
+//!
+//! // a header with the number of rows, the rows, and a checksum, until the worker hangs up
+//! fn report<'a>() -> Recv<'a, u32, (u32, Vec<u32>)> {
+//!   m! {
+//!     rows <- recv();
+//!     checksum <- recv_timeout(Duration::from_secs(1));
+//!     values <- recv_all();
+//!     if values.len() as u32 == rows && values.iter().sum::<u32>() == checksum {
+//!       Lift::lift((rows, values))
+//!     } else {
+//!       Lift::lift((0, Vec::new()))
+//!     }
+//!   }
+//! }
+//!
+//! let (tx, rx) = mpsc::channel();
+//!
+//! thread::spawn(move || {
+//!   for n in [3, 6, 1, 2, 3] {
+//!     tx.send(n).unwrap();
+//!   }
+//! });
+//!
+//! assert_eq!(report().run(&rx), Ok((3, vec![1, 2, 3])));
+//! ```
+//!
+//! [`recv_all`] receives until the senders are dropped, which is how a protocol ends cleanly; the other primitives
+//! fail with [`RecvTimeoutError::Disconnected`] instead.
+
+use crate::{
+  monad::{Kind, Monad},
+  Lift,
+};
+use std::{
+  marker::PhantomData,
+  sync::mpsc::{Receiver, RecvTimeoutError},
+  time::Duration,
+};
+
+/// Computations receiving messages of type `T`, producing a value of type `A`.
+#[allow(clippy::type_complexity)]
+pub struct Recv<'a, T, A>(Box<dyn FnOnce(&Receiver<T>) -> Result<A, RecvTimeoutError> + 'a>);
+
+impl<'a, T: 'a, A: 'a> Recv<'a, T, A> {
+  /// Build a computation out of a function.
+  pub fn new(f: impl FnOnce(&Receiver<T>) -> Result<A, RecvTimeoutError> + 'a) -> Self {
+    Recv(Box::new(f))
+  }
+
+  /// Run the computation, receiving from `rx`.
+  pub fn run(self, rx: &Receiver<T>) -> Result<A, RecvTimeoutError> {
+    (self.0)(rx)
+  }
+
+  /// Bind the value to `f`, whose computation receives the next messages.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(A) -> Recv<'a, T, B> + 'a) -> Recv<'a, T, B> {
+    Recv::new(move |rx| {
+      let a = self.run(rx)?;
+      f(a).run(rx)
+    })
+  }
+
+  /// Apply `f` to the value.
+  pub fn map<B: 'a>(self, f: impl FnOnce(A) -> B + 'a) -> Recv<'a, T, B> {
+    Recv::new(move |rx| self.run(rx).map(f))
+  }
+}
+
+impl<'a, T: 'a, A: 'a> Lift<A> for Recv<'a, T, A> {
+  fn lift(a: A) -> Self {
+    Recv::new(move |_| Ok(a))
+  }
+}
+
+/// [`Kind`] of `Recv<'_, T, _>`.
+#[derive(Debug)]
+pub struct RecvKind<T>(PhantomData<T>);
+
+impl<'a, T: 'a> Kind<'a> for RecvKind<T> {
+  type Of<A: 'a> = Recv<'a, T, A>;
+}
+
+impl<'a, T: 'a, A: 'a> Monad<'a> for Recv<'a, T, A> {
+  type Item = A;
+  type Kind = RecvKind<T>;
+
+  fn and_then<B, F>(self, f: F) -> Recv<'a, T, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Recv<'a, T, B> + 'a,
+  {
+    Recv::and_then(self, f)
+  }
+}
+
+/// Receive the next message, waiting as long as the senders are alive.
+pub fn recv<'a, T: 'a>() -> Recv<'a, T, T> {
+  Recv::new(|rx| rx.recv().map_err(|_| RecvTimeoutError::Disconnected))
+}
+
+/// Receive the next message, waiting at most `timeout`.
+pub fn recv_timeout<'a, T: 'a>(timeout: Duration) -> Recv<'a, T, T> {
+  Recv::new(move |rx| rx.recv_timeout(timeout))
+}
+
+/// Receive every message until the senders are dropped.
+pub fn recv_all<'a, T: 'a>() -> Recv<'a, T, Vec<T>> {
+  Recv::new(|rx| Ok(rx.iter().collect()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::combinators::replicate_m;
+  use std::sync::mpsc;
+
+  #[test]
+  fn protocol() {
+    let (tx, rx) = mpsc::channel();
+
+    for msg in ["2", "a", "b", "c"] {
+      tx.send(msg).unwrap();
+    }
+
+    let r = m! {
+      n <- recv().map(|n: &str| n.parse().unwrap());
+      args <- replicate_m(n, recv);
+      return args.concat();
+    };
+
+    assert_eq!(r.run(&rx), Ok("ab".to_owned()));
+
+    drop(tx);
+
+    let r = m! {
+      last <- recv();
+      recv::<&str>();
+      return last;
+    };
+
+    assert_eq!(r.run(&rx), Err(RecvTimeoutError::Disconnected));
+  }
+
+  #[test]
+  fn timeout() {
+    let (tx, rx) = mpsc::channel::<i32>();
+
+    let r = recv_timeout(Duration::from_millis(1)).run(&rx);
+    assert_eq!(r, Err(RecvTimeoutError::Timeout));
+
+    tx.send(1).unwrap();
+    drop(tx);
+
+    let r = m! {
+      x <- recv_timeout(Duration::from_millis(1));
+      rest <- recv_all();
+      return (x, rest);
+    };
+
+    assert_eq!(r.run(&rx), Ok((1, Vec::new())));
+  }
+}
@@ -0,0 +1,309 @@
+//! State with checkpoints.
+//!
+//! [`Checkpointed<S, E, A>`] threads a state of type `S` through the statements of a do-block, like
+//! [`State`](crate::state::State), and can fail with an error of type `E`. [`checkpoint`] saves the current state,
+//! and [`rollback`] restores the latest saved one. When a computation fails, the state is rolled back automatically
+//! to its latest checkpoint — or to its initial value — so that a multi-step mutation is either completely applied,
+//! or reverted:
+//!
+//! ```rust
+//! use do_notation::{checkpoint::{checkpoint, fail, get, modify, run_checkpointed, Checkpointed}, m};
+//!
+//! fn withdraw(amount: u32) -> Checkpointed<'static, u32, String, ()> {
+//!   m! {
+//!     balance <- get();
+//!     if balance >= amount { modify(move |b| b - amount) } else { fail(format!("cannot withdraw {}", amount)) }
+//!   }
+//! }
+//!
+//! assert_eq!(run_checkpointed(withdraw(30), 100), (Ok(()), 70));
+//!
+//! let transfers = m! {
+//!   withdraw(30);
+//!   checkpoint();
+//!   withdraw(50);
+//!   withdraw(40);
+//!   return ();
+//! };
+//!
+//! assert_eq!(run_checkpointed(transfers, 100), (Err("cannot withdraw 40".to_owned()), 70));
+//! ```
+//!
+//! [`attempt`] runs a computation atomically inside a larger one: if it fails, its changes are reverted and its
+//! error is returned as a value, so the block can go on.
+
+use crate::{
+  monad::{Kind, Monad},
+  Lift,
+};
+use std::marker::PhantomData;
+
+/// The current state, and the saved ones.
+struct Snapshots<S> {
+  current: S,
+  saved: Vec<S>,
+}
+
+impl<S: Clone> Snapshots<S> {
+  /// Restore the latest state saved since `depth`, and forget the states saved since then.
+  fn restore(&mut self, depth: usize) {
+    if let Some(s) = self.saved.drain(depth..).last() {
+      self.current = s;
+    }
+  }
+}
+
+/// Computations threading a state of type `S` with checkpoints, failing with an error of type `E`, and producing a
+/// value of type `A`.
+#[allow(clippy::type_complexity)]
+pub struct Checkpointed<'a, S, E, A>(Box<dyn FnOnce(&mut Snapshots<S>) -> Result<A, E> + 'a>);
+
+impl<'a, S: 'a, E: 'a, A: 'a> Checkpointed<'a, S, E, A> {
+  fn new(f: impl FnOnce(&mut Snapshots<S>) -> Result<A, E> + 'a) -> Self {
+    Checkpointed(Box::new(f))
+  }
+
+  /// Bind the value to `f`, if the computation didn’t fail.
+  pub fn and_then<B: 'a>(
+    self,
+    f: impl FnOnce(A) -> Checkpointed<'a, S, E, B> + 'a,
+  ) -> Checkpointed<'a, S, E, B> {
+    Checkpointed::new(move |snapshots: &mut Snapshots<S>| {
+      let a = (self.0)(snapshots)?;
+      (f(a).0)(snapshots)
+    })
+  }
+
+  /// Apply `f` to the value.
+  pub fn map<B: 'a>(self, f: impl FnOnce(A) -> B + 'a) -> Checkpointed<'a, S, E, B> {
+    Checkpointed::new(move |snapshots: &mut Snapshots<S>| (self.0)(snapshots).map(f))
+  }
+}
+
+impl<'a, S: 'a, E: 'a, A: 'a> Lift<A> for Checkpointed<'a, S, E, A> {
+  fn lift(a: A) -> Self {
+    Checkpointed::new(move |_| Ok(a))
+  }
+}
+
+/// [`Kind`] of `Checkpointed<'_, S, E, _>`.
+#[derive(Debug)]
+pub struct CheckpointedKind<S, E>(PhantomData<(S, E)>);
+
+impl<'a, S: 'a, E: 'a> Kind<'a> for CheckpointedKind<S, E> {
+  type Of<A: 'a> = Checkpointed<'a, S, E, A>;
+}
+
+impl<'a, S: 'a, E: 'a, A: 'a> Monad<'a> for Checkpointed<'a, S, E, A> {
+  type Item = A;
+  type Kind = CheckpointedKind<S, E>;
+
+  fn and_then<B, F>(self, f: F) -> Checkpointed<'a, S, E, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Checkpointed<'a, S, E, B> + 'a,
+  {
+    Checkpointed::and_then(self, f)
+  }
+}
+
+/// Get a copy of the state.
+pub fn get<'a, S: Clone + 'a, E: 'a>() -> Checkpointed<'a, S, E, S> {
+  Checkpointed::new(|snapshots: &mut Snapshots<S>| Ok(snapshots.current.clone()))
+}
+
+/// Replace the state with `s`.
+pub fn put<'a, S: 'a, E: 'a>(s: S) -> Checkpointed<'a, S, E, ()> {
+  Checkpointed::new(move |snapshots: &mut Snapshots<S>| {
+    snapshots.current = s;
+    Ok(())
+  })
+}
+
+/// Update the state with `f`.
+pub fn modify<'a, S: Clone + 'a, E: 'a>(f: impl FnOnce(S) -> S + 'a) -> Checkpointed<'a, S, E, ()> {
+  Checkpointed::new(move |snapshots: &mut Snapshots<S>| {
+    snapshots.current = f(snapshots.current.clone());
+    Ok(())
+  })
+}
+
+/// Fail with `e`.
+///
+/// The state is rolled back by the enclosing [`attempt`], or by [`run_checkpointed`].
+pub fn fail<'a, S: 'a, E: 'a, A: 'a>(e: E) -> Checkpointed<'a, S, E, A> {
+  Checkpointed::new(move |_| Err(e))
+}
+
+/// Save the current state.
+pub fn checkpoint<'a, S: Clone + 'a, E: 'a>() -> Checkpointed<'a, S, E, ()> {
+  Checkpointed::new(|snapshots: &mut Snapshots<S>| {
+    snapshots.saved.push(snapshots.current.clone());
+    Ok(())
+  })
+}
+
+/// Restore the latest saved state, which stays saved.
+///
+/// Without checkpoint, the state is restored to its initial value — or to its value at the beginning of the
+/// enclosing [`attempt`].
+pub fn rollback<'a, S: Clone + 'a, E: 'a>() -> Checkpointed<'a, S, E, ()> {
+  Checkpointed::new(|snapshots: &mut Snapshots<S>| {
+    if let Some(s) = snapshots.saved.last() {
+      snapshots.current = s.clone();
+    }
+
+    Ok(())
+  })
+}
+
+/// Run `m` atomically: if it fails, the state is rolled back to its latest checkpoint in `m`, or to its value before
+/// `m`, and the error is returned as a value.
+///
+/// The checkpoints saved by `m` are forgotten once it’s done.
+pub fn attempt<'a, S, E, F, A>(m: Checkpointed<'a, S, E, A>) -> Checkpointed<'a, S, F, Result<A, E>>
+where
+  S: Clone + 'a,
+  E: 'a,
+  F: 'a,
+  A: 'a,
+{
+  Checkpointed::new(move |snapshots: &mut Snapshots<S>| {
+    let depth = snapshots.saved.len();
+    snapshots.saved.push(snapshots.current.clone());
+
+    let r = (m.0)(snapshots);
+
+    if r.is_err() {
+      snapshots.restore(depth);
+    } else {
+      snapshots.saved.truncate(depth);
+    }
+
+    Ok(r)
+  })
+}
+
+/// Run a computation with the initial state `s`, returning its result and the final state.
+///
+/// If the computation fails, the final state is its latest checkpoint, or `s`.
+pub fn run_checkpointed<'a, S, E, A>(m: Checkpointed<'a, S, E, A>, s: S) -> (Result<A, E>, S)
+where
+  S: Clone + 'a,
+  E: 'a,
+  A: 'a,
+{
+  let mut snapshots = Snapshots {
+    current: s.clone(),
+    saved: vec![s],
+  };
+
+  let r = (m.0)(&mut snapshots);
+
+  if r.is_err() {
+    snapshots.restore(0);
+  }
+
+  (r, snapshots.current)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn push(n: i32) -> Checkpointed<'static, Vec<i32>, String, ()> {
+    modify(move |mut v: Vec<i32>| {
+      v.push(n);
+      v
+    })
+  }
+
+  fn pop() -> Checkpointed<'static, Vec<i32>, String, i32> {
+    m! {
+      v <- get::<Vec<i32>, _>();
+      match v.split_last() {
+        Some((&n, rest)) => put(rest.to_vec()).map(move |()| n),
+        None => fail("empty stack".to_owned()),
+      }
+    }
+  }
+
+  #[test]
+  fn rollback_on_failure() {
+    let r = m! {
+      push(1);
+      push(2);
+      checkpoint();
+      a <- pop();
+      b <- pop();
+      c <- pop();
+      return a + b + c;
+    };
+
+    assert_eq!(
+      run_checkpointed(r, Vec::new()),
+      (Err("empty stack".to_owned()), vec![1, 2])
+    );
+
+    let r = m! {
+      push(1);
+      pop();
+      pop();
+      pop();
+      return ();
+    };
+
+    assert_eq!(
+      run_checkpointed(r, vec![0]),
+      (Err("empty stack".to_owned()), vec![0])
+    );
+  }
+
+  #[test]
+  fn explicit_rollback() {
+    let r = m! {
+      push(1);
+      rollback();
+      push(2);
+      checkpoint();
+      push(3);
+      rollback();
+      get()
+    };
+
+    assert_eq!(run_checkpointed(r, vec![0]), (Ok(vec![0, 2]), vec![0, 2]));
+  }
+
+  #[test]
+  fn atomic_steps() {
+    let r = m! {
+      push(1);
+      r <- attempt(m! {
+        push(2);
+        pop();
+        pop();
+        pop()
+      });
+      push(4);
+      r2 <- attempt(m! {
+        push(5);
+        checkpoint();
+        push(6);
+        fail::<_, _, ()>("nope".to_owned())
+      });
+      s <- get();
+      return (r, r2, s);
+    };
+
+    let (r, s) = run_checkpointed(r, Vec::new());
+    assert_eq!(
+      r,
+      Ok((
+        Err("empty stack".to_owned()),
+        Err("nope".to_owned()),
+        vec![1, 4, 5]
+      ))
+    );
+    assert_eq!(s, vec![1, 4, 5]);
+  }
+}
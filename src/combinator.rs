@@ -0,0 +1,104 @@
+//! Binding `combine` parsers.
+//!
+//! > This module requires the `combine` feature.
+//!
+//! The `and_then` method of `combine` parsers maps their output to a [`Result`], so they can’t be bound directly in
+//! do-blocks. Wrapped in [`Combine`], they are bound with their `then` combinator instead, so that a block builds a
+//! regular `combine` parser, which can be [run](Combine::parse), or [unwrapped](Combine::into_parser) and used with
+//! any other combinator:
+//!
+//! ```rust
+//! use do_notation::{combinator::Combine, m};
+//! use combine::{parser::{char::{char, digit}, range::recognize}, skip_many1};
+//!
+//! let number = || Combine(recognize(skip_many1(digit())));
+//!
+//! let pair = m! {
+//!   x <- number();
+//!   _ <- Combine(char(','));
+//!   y <- number();
+//!   return (x, y);
+//! };
+//!
+//! assert_eq!(pair.parse("12,34;"), Ok((("12", "34"), ";")));
+//! ```
+//!
+//! As the parser built by a block can run several times, the values it binds must be [`Copy`].
+
+use crate::Lift;
+use combine::{parser::token::Value, value, Parser, Stream};
+
+/// A `combine` parser, bound with its `then` combinator.
+#[derive(Clone, Copy, Debug)]
+pub struct Combine<P>(pub P);
+
+impl<P> Combine<P> {
+  /// Run the parser, then the parser built by `f` from its output.
+  pub fn and_then<Input, Q, F>(self, mut f: F) -> Combine<impl Parser<Input, Output = Q::Output>>
+  where
+    Input: Stream,
+    P: Parser<Input>,
+    Q: Parser<Input>,
+    F: FnMut(P::Output) -> Combine<Q>,
+  {
+    Combine(self.0.then(move |o| f(o).0))
+  }
+
+  /// Run the parser on `input`, producing its output and the remaining input.
+  pub fn parse<Input>(self, input: Input) -> Result<(P::Output, Input), Input::Error>
+  where
+    Input: Stream,
+    P: Parser<Input>,
+  {
+    let mut p = self.0;
+    p.parse(input)
+  }
+
+  /// The wrapped parser.
+  pub fn into_parser(self) -> P {
+    self.0
+  }
+}
+
+impl<Input, A> Lift<A> for Combine<Value<Input, A>>
+where
+  Input: Stream,
+  A: Clone,
+{
+  fn lift(a: A) -> Self {
+    Combine(value(a))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use combine::{
+    parser::{
+      char::{char, letter, spaces},
+      range::recognize,
+    },
+    sep_by1, skip_many1,
+  };
+
+  #[test]
+  fn combine() {
+    let assignment = || {
+      m! {
+        name <- Combine(recognize(skip_many1(letter())));
+        _ <- Combine((spaces(), char('='), spaces()));
+        value <- Combine(recognize(skip_many1(letter())));
+        return (name, value);
+      }
+    };
+
+    assert_eq!(assignment().parse("x = y"), Ok((("x", "y"), "")));
+    assert!(assignment().parse("x y").is_err());
+
+    let mut assignments = sep_by1::<Vec<_>, _, _, _>(assignment().into_parser(), char(','));
+    assert_eq!(
+      assignments.parse("a=b,c=d"),
+      Ok((vec![("a", "b"), ("c", "d")], ""))
+    );
+  }
+}
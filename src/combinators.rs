@@ -0,0 +1,1404 @@
+//! Monadic combinators.
+//!
+//! Generic functions over any [`Monad`], for the patterns that are awkward to spell out in a do-block.
+//!
+//! ```rust
+//! use do_notation::combinators::join;
+//!
+//! assert_eq!(join(Some(Some(1))), Some(1));
+//! assert_eq!(join(Ok::<Result<i32, &str>, _>(Err("nope"))), Err("nope"));
+//! ```
+//!
+//! Combinators traversing a collection with a function producing computations only require these computations to
+//! be [`Applied`], so they work with applicative functors that aren’t monads as well.
+
+use crate::{
+  alternative::{Alternated, Alternative},
+  applicative::{Applicative, Applied, Functor},
+  either::Either,
+  monad::{Kind, Monad},
+  rec::MonadRec,
+  Lift,
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// Flatten a monad of monads.
+pub fn join<'a, M, N>(mm: M) -> N
+where
+  M: Monad<'a, Item = N, Kind = N::Kind>,
+  N: Monad<'a>,
+{
+  mm.and_then::<N::Item, _>(|n| n)
+}
+
+/// Apply `f` to every element of `iter`, in order, collecting the values.
+///
+/// Monads stop at the first computation that short-circuits, without applying `f` to the remaining elements.
+///
+/// ```rust
+/// use do_notation::combinators::map_m;
+///
+/// let parse = |s: &str| s.parse::<i32>().ok();
+///
+/// assert_eq!(map_m(vec!["1", "2"], parse), Some(vec![1, 2]));
+/// assert_eq!(map_m(vec!["1", "a", "2"], parse), None);
+/// ```
+pub fn map_m<'a, I, M>(
+  iter: I,
+  f: impl FnMut(I::Item) -> M + 'a,
+) -> <M::Kind as Functor<'a>>::Of<Vec<M::Item>>
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  M: Applied<'a>,
+{
+  M::Kind::traverse_iter(iter.into_iter(), f)
+}
+
+/// Apply `f` to every element of `iter`, in order, for the effects only.
+///
+/// This is [`map_m`] without the values, so no vector is allocated. [`for_m`] is the same traversal, under the
+/// name of the loop it replaces.
+///
+/// ```rust
+/// use do_notation::{combinators::map_m_, state::{modify, run_state, State}};
+///
+/// let r: State<u64, ()> = map_m_(1..=1000, |n| modify(move |sum: u64| sum + n));
+///
+/// assert_eq!(run_state(r, 0), ((), 500_500));
+/// ```
+pub fn map_m_<'a, I, M>(
+  iter: I,
+  f: impl FnMut(I::Item) -> M + 'a,
+) -> <M::Kind as Functor<'a>>::Of<()>
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  M: Applied<'a>,
+{
+  for_m(iter, f)
+}
+
+/// Apply `f` to every element of `iter`, in order, discarding the values.
+///
+/// This is [`map_m`] for when only the effects matter. Monads stop at the first computation that short-circuits.
+///
+/// ```rust
+/// use do_notation::combinators::for_m;
+///
+/// let check = |n: i32| if n >= 0 { Ok(n) } else { Err(format!("{} is negative", n)) };
+///
+/// assert_eq!(for_m(vec![1, 2], check), Ok(()));
+/// assert_eq!(for_m(vec![1, -2, -3], check), Err("-2 is negative".to_owned()));
+/// ```
+pub fn for_m<'a, I, M>(
+  iter: I,
+  f: impl FnMut(I::Item) -> M + 'a,
+) -> <M::Kind as Functor<'a>>::Of<()>
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  M: Applied<'a>,
+{
+  M::Kind::for_each_iter::<_, M::Item, _>(iter.into_iter(), f)
+}
+
+/// Apply `f`, producing computations without values, to every element of `iter`, in order.
+///
+/// This is [`for_m`], where the computations can’t silently discard their values — like
+/// [`Iterator::for_each`].
+pub fn for_each_m<'a, I, M>(iter: I, f: impl FnMut(I::Item) -> M + 'a) -> M
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  M: Applied<'a, Item = ()>,
+{
+  M::Kind::for_each_iter::<_, M::Item, _>(iter.into_iter(), f)
+}
+
+/// Combine the elements of `iter`, in order, with the monadic function `f`, starting with `init`.
+///
+/// The fold stops as soon as the monad short-circuits. This is what the `for` statement of [`m!`](crate::m)
+/// desugars to.
+///
+/// ```rust
+/// use do_notation::combinators::fold_m;
+///
+/// let safe_div = |acc: i32, n: i32| acc.checked_div(n);
+///
+/// assert_eq!(fold_m(100, vec![2, 5], safe_div), Some(10));
+/// assert_eq!(fold_m(100, vec![0, 5], safe_div), None);
+/// ```
+pub fn fold_m<'a, I, M>(init: M::Item, iter: I, f: impl FnMut(M::Item, I::Item) -> M + 'a) -> M
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  M: Monad<'a>,
+{
+  M::Kind::loop_m(
+    (iter.into_iter(), init, f),
+    |(mut iter, acc, mut f)| match iter.next() {
+      Some(a) => f(acc, a)
+        .and_then::<Either<_, M::Item>, _>(move |acc| Lift::lift(Either::Left((iter, acc, f)))),
+      None => Lift::lift(Either::Right(acc)),
+    },
+  )
+}
+
+/// Keep the elements of `iter` for which the monadic predicate `f` holds, in order.
+///
+/// Monads stop at the first computation that short-circuits.
+///
+/// ```rust
+/// use do_notation::combinators::filter_m;
+///
+/// let is_small = |n: &i32| if *n < 100 { Ok(*n % 2 == 0) } else { Err(format!("{} is too big", n)) };
+///
+/// assert_eq!(filter_m(vec![1, 2, 3, 4], is_small), Ok(vec![2, 4]));
+/// assert_eq!(filter_m(vec![1, 200, 3], is_small), Err("200 is too big".to_owned()));
+/// ```
+pub fn filter_m<'a, I, M>(
+  iter: I,
+  mut f: impl FnMut(&I::Item) -> M + 'a,
+) -> <M::Kind as Functor<'a>>::Of<Vec<I::Item>>
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  I::Item: 'a,
+  M: Applied<'a, Item = bool>,
+{
+  let kept = M::Kind::traverse_iter(iter.into_iter(), move |a| {
+    M::Kind::map(f(&a), move |keep| if keep { Some(a) } else { None })
+  });
+
+  M::Kind::map(kept, |kept: Vec<Option<I::Item>>| {
+    kept.into_iter().flatten().collect::<Vec<_>>()
+  })
+}
+
+/// Combine the elements of `xs` and `ys` pairwise with the monadic function `f`, in order, collecting the values.
+///
+/// The shortest collection determines the number of pairs. Monads stop at the first computation that
+/// short-circuits.
+///
+/// ```rust
+/// use do_notation::combinators::zip_with_m;
+///
+/// let div = |a: i32, b: i32| a.checked_div(b);
+///
+/// assert_eq!(zip_with_m(vec![10, 20], vec![2, 5], div), Some(vec![5, 4]));
+/// assert_eq!(zip_with_m(vec![10, 20], vec![0, 5], div), None);
+/// ```
+pub fn zip_with_m<'a, I, J, M>(
+  xs: I,
+  ys: J,
+  mut f: impl FnMut(I::Item, J::Item) -> M + 'a,
+) -> <M::Kind as Functor<'a>>::Of<Vec<M::Item>>
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  J: IntoIterator,
+  J::IntoIter: 'a,
+  M: Applied<'a>,
+{
+  M::Kind::traverse_iter(xs.into_iter().zip(ys), move |(x, y)| f(x, y))
+}
+
+/// Run the computation built by `m` `n` times, in order, collecting the values.
+///
+/// As computations are consumed when they run, `m` builds a new one every time. Monads stop at the first
+/// computation that short-circuits.
+///
+/// ```rust
+/// use do_notation::{combinators::replicate_m, state::{run_state, state, State}};
+///
+/// let next = || state(|seed: u32| (seed % 10, seed.wrapping_mul(1103515245).wrapping_add(12345)));
+/// let (digits, _): (Vec<u32>, _) = run_state(replicate_m(3, next), 42);
+///
+/// assert_eq!(digits.len(), 3);
+/// ```
+pub fn replicate_m<'a, M>(
+  n: usize,
+  mut m: impl FnMut() -> M + 'a,
+) -> <M::Kind as Functor<'a>>::Of<Vec<M::Item>>
+where
+  M: Applied<'a>,
+{
+  M::Kind::traverse_iter(0..n, move |_| m())
+}
+
+/// Run the computation built by `m` `n` times, in order, discarding the values.
+///
+/// This is [`replicate_m`] for when only the effects matter.
+pub fn replicate_m_<'a, M>(
+  n: usize,
+  mut m: impl FnMut() -> M + 'a,
+) -> <M::Kind as Functor<'a>>::Of<()>
+where
+  M: Applied<'a>,
+{
+  M::Kind::for_each_iter::<_, M::Item, _>(0..n, move |_| m())
+}
+
+/// Run `m` if `cond` holds, and do nothing otherwise.
+///
+/// ```rust
+/// use do_notation::{combinators::when_m, m};
+///
+/// let check = |n: i32| {
+///   m! {
+///     when_m(n < 0, Err(format!("{} is negative", n)));
+///     return n;
+///   }
+/// };
+///
+/// assert_eq!(check(1), Ok(1));
+/// assert_eq!(check(-1), Err("-1 is negative".to_owned()));
+/// ```
+pub fn when_m<'a, M>(cond: bool, m: M) -> M
+where
+  M: Applied<'a, Item = ()>,
+{
+  if cond {
+    m
+  } else {
+    M::Kind::pure(())
+  }
+}
+
+/// Run `m` unless `cond` holds.
+///
+/// This is [`when_m`] with the condition negated.
+pub fn unless_m<'a, M>(cond: bool, m: M) -> M
+where
+  M: Applied<'a, Item = ()>,
+{
+  when_m(!cond, m)
+}
+
+/// Discard the value of `m`, keeping its effects.
+///
+/// ```rust
+/// use do_notation::combinators::void;
+///
+/// assert_eq!(void(Some(1)), Some(()));
+/// assert!(void("a".parse::<i32>()).is_err());
+/// ```
+pub fn void<'a, M>(m: M) -> <M::Kind as Functor<'a>>::Of<()>
+where
+  M: Applied<'a>,
+{
+  M::Kind::map(m, |_: M::Item| ())
+}
+
+/// Apply the pure function `f` to the values of `ma` and `mb`.
+///
+/// Only the [`Applicative`] structure is used: monads stop at the first computation that short-circuits, while
+/// applicative functors accumulating errors combine the ones of both computations.
+///
+/// ```rust
+/// use do_notation::combinators::lift_m2;
+///
+/// let parse = |s: &str| s.parse::<i32>().map_err(|_| format!("{} is not a number", s));
+///
+/// assert_eq!(lift_m2(|a, b| a + b, parse("1"), parse("2")), Ok(3));
+/// assert_eq!(lift_m2(|a, b| a + b, parse("a"), parse("b")), Err("a is not a number".to_owned()));
+/// ```
+pub fn lift_m2<'a, M, B, C>(
+  f: impl FnOnce(M::Item, B) -> C + 'a,
+  ma: M,
+  mb: <M::Kind as Functor<'a>>::Of<B>,
+) -> <M::Kind as Functor<'a>>::Of<C>
+where
+  M: Applied<'a>,
+  B: 'a,
+  C: 'a,
+{
+  M::Kind::zip_with(ma, mb, f)
+}
+
+/// Apply the pure function `f` to the values of `ma`, `mb` and `mc`.
+///
+/// This is [`lift_m2`] with three computations.
+pub fn lift_m3<'a, M, B, C, D>(
+  f: impl FnOnce(M::Item, B, C) -> D + 'a,
+  ma: M,
+  mb: <M::Kind as Functor<'a>>::Of<B>,
+  mc: <M::Kind as Functor<'a>>::Of<C>,
+) -> <M::Kind as Functor<'a>>::Of<D>
+where
+  M: Applied<'a>,
+  B: 'a,
+  C: 'a,
+  D: 'a,
+{
+  M::Kind::zip_with(M::Kind::zip(ma, mb), mc, move |(a, b), c| f(a, b, c))
+}
+
+/// Compose the monadic functions `f` and `g` (`>=>`): the values produced by `f` are fed to `g`.
+///
+/// See [`Kleisli`](crate::arrow::Kleisli) for a composable wrapper around monadic functions.
+///
+/// ```rust
+/// use do_notation::combinators::compose_m;
+///
+/// let parse = |s: &str| s.parse::<u32>().ok();
+/// let half = |n: u32| if n % 2 == 0 { Some(n / 2) } else { None };
+/// let f = compose_m(parse, half);
+///
+/// assert_eq!(f("10"), Some(5));
+/// assert_eq!(f("3"), None);
+/// assert_eq!(f("a"), None);
+/// ```
+pub fn compose_m<'a, A, M, N>(
+  f: impl Fn(A) -> M + 'a,
+  g: impl Fn(M::Item) -> N + 'a,
+) -> impl Fn(A) -> N + 'a
+where
+  M: Monad<'a, Kind = N::Kind>,
+  N: Monad<'a>,
+{
+  let g = Rc::new(g);
+
+  move |a| {
+    let g = g.clone();
+    f(a).and_then::<N::Item, _>(move |b| g(b))
+  }
+}
+
+/// Try the computations of `iter` in order, until one succeeds.
+///
+/// This is `msum` — or `asum` — in Haskell: the computations are combined with [`Alternative::alt`], starting
+/// with [`Alternative::empty`]. Every computation of `iter` is built before they are combined.
+///
+/// ```rust
+/// use do_notation::combinators::msum;
+///
+/// let config = vec![None, Some("from the environment"), Some("from the file")];
+/// assert_eq!(msum(config), Some("from the environment"));
+///
+/// // vectors are concatenated
+/// assert_eq!(msum(vec![vec![1, 2], vec![3]]), vec![1, 2, 3]);
+/// ```
+pub fn msum<'a, I, M>(iter: I) -> M
+where
+  I: IntoIterator<Item = M>,
+  M: Alternated<'a>,
+{
+  iter
+    .into_iter()
+    .fold(M::Kind::empty::<M::Item>(), M::Kind::alt::<M::Item>)
+}
+
+/// Find the first element of `iter` for which the monadic predicate `f` holds.
+///
+/// The search stops at the first element found, or as soon as the monad short-circuits.
+///
+/// ```rust
+/// use do_notation::combinators::find_m;
+///
+/// let is_big = |s: &&str| s.parse::<i32>().map(|n| n > 10);
+///
+/// assert_eq!(find_m(vec!["1", "42", "a"], is_big), Ok(Some("42")));
+/// assert_eq!(find_m(vec!["1", "2"], is_big), Ok(None));
+/// assert!(find_m(vec!["1", "a", "42"], is_big).is_err());
+/// ```
+pub fn find_m<'a, I, M>(
+  iter: I,
+  f: impl FnMut(&I::Item) -> M + 'a,
+) -> <M::Kind as Kind<'a>>::Of<Option<I::Item>>
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  I::Item: 'a,
+  M: Monad<'a, Item = bool>,
+{
+  M::Kind::loop_m((iter.into_iter(), f), |(mut iter, mut f)| {
+    match iter.next() {
+      Some(a) => f(&a).and_then::<Either<_, Option<I::Item>>, _>(move |found| {
+        Lift::lift(if found {
+          Either::Right(Some(a))
+        } else {
+          Either::Left((iter, f))
+        })
+      }),
+      None => Lift::lift(Either::Right(None)),
+    }
+  })
+}
+
+/// Check whether the monadic predicate `f` holds for some element of `iter`.
+///
+/// The check stops at the first element for which `f` holds, or as soon as the monad short-circuits.
+///
+/// ```rust
+/// use do_notation::combinators::{all_m, any_m};
+///
+/// let is_even = |s: &str| s.parse::<i32>().map(|n| n % 2 == 0);
+///
+/// assert_eq!(any_m(vec!["1", "2", "a"], is_even), Ok(true));
+/// assert_eq!(all_m(vec!["2", "1", "a"], is_even), Ok(false));
+/// assert!(all_m(vec!["2", "a"], is_even).is_err());
+/// ```
+pub fn any_m<'a, I, M>(iter: I, f: impl FnMut(I::Item) -> M + 'a) -> M
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  M: Monad<'a, Item = bool>,
+{
+  until_found(iter.into_iter(), f, true)
+}
+
+/// Check whether the monadic predicate `f` holds for every element of `iter`.
+///
+/// The check stops at the first element for which `f` doesn’t hold, or as soon as the monad short-circuits.
+pub fn all_m<'a, I, M>(iter: I, f: impl FnMut(I::Item) -> M + 'a) -> M
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  M: Monad<'a, Item = bool>,
+{
+  until_found(iter.into_iter(), f, false)
+}
+
+/// Apply `f` to the elements of `iter` until it produces `found`, producing whether it did.
+fn until_found<'a, I, M, F>(iter: I, f: F, found: bool) -> M
+where
+  I: Iterator + 'a,
+  M: Monad<'a, Item = bool>,
+  F: FnMut(I::Item) -> M + 'a,
+{
+  M::Kind::loop_m((iter, f), move |(mut iter, mut f)| match iter.next() {
+    Some(a) => f(a).and_then::<Either<_, bool>, _>(move |b| {
+      Lift::lift(if b == found {
+        Either::Right(found)
+      } else {
+        Either::Left((iter, f))
+      })
+    }),
+    None => Lift::lift(Either::Right(!found)),
+  })
+}
+
+/// Split the elements of `iter` into those for which the monadic predicate `f` holds and the others, in order.
+///
+/// Monads stop at the first computation that short-circuits.
+///
+/// ```rust
+/// use do_notation::combinators::partition_m;
+///
+/// let is_even = |s: &&str| s.parse::<i32>().map(|n| n % 2 == 0);
+///
+/// assert_eq!(partition_m(vec!["1", "2", "3"], is_even), Ok((vec!["2"], vec!["1", "3"])));
+/// assert!(partition_m(vec!["1", "a"], is_even).is_err());
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn partition_m<'a, I, M>(
+  iter: I,
+  mut f: impl FnMut(&I::Item) -> M + 'a,
+) -> <M::Kind as Functor<'a>>::Of<(Vec<I::Item>, Vec<I::Item>)>
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  I::Item: 'a,
+  M: Applied<'a, Item = bool>,
+{
+  let tagged = M::Kind::traverse_iter(iter.into_iter(), move |a| {
+    M::Kind::map(f(&a), move |b| (b, a))
+  });
+
+  M::Kind::map(tagged, |tagged: Vec<(bool, I::Item)>| {
+    let (yes, no): (Vec<_>, Vec<_>) = tagged.into_iter().partition(|(b, _)| *b);
+    (
+      yes.into_iter().map(|(_, a)| a).collect(),
+      no.into_iter().map(|(_, a)| a).collect(),
+    )
+  })
+}
+
+/// Run every fallible computation of `iter`, in order, splitting the values from the errors.
+///
+/// Unlike [`map_m`], the computations after a failing one still run.
+///
+/// ```rust
+/// use do_notation::combinators::partition_results;
+///
+/// let parse = |s: &str| s.parse::<i32>().map_err(|_| format!("{:?} is not a number", s));
+///
+/// let (numbers, errors) = partition_results(vec!["1", "a", "2", "b"].into_iter().map(parse));
+/// assert_eq!(numbers, vec![1, 2]);
+/// assert_eq!(errors, vec!["\"a\" is not a number", "\"b\" is not a number"]);
+/// ```
+pub fn partition_results<I, A, E>(iter: I) -> (Vec<A>, Vec<E>)
+where
+  I: IntoIterator<Item = Result<A, E>>,
+{
+  let mut values = Vec::new();
+  let mut errors = Vec::new();
+
+  for r in iter {
+    match r {
+      Ok(a) => values.push(a),
+      Err(e) => errors.push(e),
+    }
+  }
+
+  (values, errors)
+}
+
+/// Run every fallible computation of `iter`, in order, collecting the values if they all succeed, and all the
+/// errors otherwise.
+///
+/// Unlike [`map_m`], the computations after a failing one still run, so that every failure is reported.
+///
+/// ```rust
+/// use do_notation::combinators::collect_errors;
+///
+/// let parse = |s: &str| s.parse::<i32>().map_err(|_| format!("{:?} is not a number", s));
+///
+/// assert_eq!(collect_errors(vec!["1", "2"].into_iter().map(parse)), Ok(vec![1, 2]));
+/// assert_eq!(
+///   collect_errors(vec!["1", "a", "b"].into_iter().map(parse)),
+///   Err(vec!["\"a\" is not a number".to_owned(), "\"b\" is not a number".to_owned()])
+/// );
+/// ```
+pub fn collect_errors<I, A, E>(iter: I) -> Result<Vec<A>, Vec<E>>
+where
+  I: IntoIterator<Item = Result<A, E>>,
+{
+  match partition_results(iter) {
+    (values, errors) if errors.is_empty() => Ok(values),
+    (_, errors) => Err(errors),
+  }
+}
+
+/// Apply `f` to every element of `iter`, in order, concatenating the collections produced.
+///
+/// Monads stop at the first computation that short-circuits.
+///
+/// ```rust
+/// use do_notation::combinators::concat_map_m;
+///
+/// let expand = |s: &'static str| match s {
+///   "@fruits" => Ok(vec!["apple", "pear"]),
+///   s if s.starts_with('@') => Err(format!("unknown group {}", s)),
+///   s => Ok(vec![s]),
+/// };
+///
+/// assert_eq!(concat_map_m(vec!["@fruits", "leek"], expand), Ok(vec!["apple", "pear", "leek"]));
+/// assert_eq!(concat_map_m(vec!["@meat"], expand), Err("unknown group @meat".to_owned()));
+/// ```
+pub fn concat_map_m<'a, I, M>(
+  iter: I,
+  f: impl FnMut(I::Item) -> M + 'a,
+) -> <M::Kind as Functor<'a>>::Of<Vec<<M::Item as IntoIterator>::Item>>
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  M: Applied<'a>,
+  M::Item: IntoIterator,
+  <M::Item as IntoIterator>::Item: 'a,
+{
+  M::Kind::map(
+    M::Kind::traverse_iter(iter.into_iter(), f),
+    |collections: Vec<M::Item>| collections.into_iter().flatten().collect::<Vec<_>>(),
+  )
+}
+
+/// Collect the elements of `iter` as long as the monadic predicate `f` holds.
+///
+/// `f` isn’t applied to the elements after the first one for which it doesn’t hold. Monads stop at the first
+/// computation that short-circuits.
+///
+/// ```rust
+/// use do_notation::combinators::{drop_while_m, take_while_m};
+///
+/// let is_header = |line: &&str| {
+///   if line.is_empty() { Err("empty line") } else { Ok(line.contains(':')) }
+/// };
+/// let lines = vec!["Host: a", "Accept: *", "hello", ""];
+///
+/// assert_eq!(take_while_m(lines.clone(), is_header), Ok(vec!["Host: a", "Accept: *"]));
+/// assert_eq!(drop_while_m(lines, is_header), Ok(vec!["hello", ""]));
+/// assert_eq!(take_while_m(vec![""], is_header), Err("empty line"));
+/// ```
+pub fn take_while_m<'a, I, M>(
+  iter: I,
+  f: impl FnMut(&I::Item) -> M + 'a,
+) -> <M::Kind as Kind<'a>>::Of<Vec<I::Item>>
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  I::Item: 'a,
+  M: Monad<'a, Item = bool>,
+{
+  M::Kind::loop_m(
+    (iter.into_iter(), f, Vec::new()),
+    |(mut iter, mut f, mut taken)| match iter.next() {
+      Some(a) => f(&a).and_then::<Either<_, Vec<I::Item>>, _>(move |keep| {
+        Lift::lift(if keep {
+          taken.push(a);
+          Either::Left((iter, f, taken))
+        } else {
+          Either::Right(taken)
+        })
+      }),
+      None => Lift::lift(Either::Right(taken)),
+    },
+  )
+}
+
+/// Skip the elements of `iter` as long as the monadic predicate `f` holds, collecting the remaining ones.
+///
+/// `f` isn’t applied to the elements after the first one for which it doesn’t hold. Monads stop at the first
+/// computation that short-circuits.
+pub fn drop_while_m<'a, I, M>(
+  iter: I,
+  f: impl FnMut(&I::Item) -> M + 'a,
+) -> <M::Kind as Kind<'a>>::Of<Vec<I::Item>>
+where
+  I: IntoIterator,
+  I::IntoIter: 'a,
+  I::Item: 'a,
+  M: Monad<'a, Item = bool>,
+{
+  M::Kind::loop_m((iter.into_iter(), f), |(mut iter, mut f)| {
+    match iter.next() {
+      Some(a) => f(&a).and_then::<Either<_, Vec<I::Item>>, _>(move |skip| {
+        Lift::lift(if skip {
+          Either::Left((iter, f))
+        } else {
+          Either::Right(Some(a).into_iter().chain(iter).collect())
+        })
+      }),
+      None => Lift::lift(Either::Right(Vec::new())),
+    }
+  })
+}
+
+/// Build a collection from `seed` with the monadic step `f`, until it produces [`None`].
+///
+/// Every step produces either an element and the seed of the next step, or [`None`] to stop. Monads stop at the
+/// first computation that short-circuits.
+///
+/// ```rust
+/// use do_notation::combinators::unfold_m;
+///
+/// // fetch the pages of a paginated resource, following the link to the next page
+/// let fetch = |page: u32| match page {
+///   0..=2 => Ok(vec![page * 10, page * 10 + 1]),
+///   _ => Err(format!("page {} doesn’t exist", page)),
+/// };
+/// let next = |page: Option<u32>| match page {
+///   Some(page) => fetch(page).map(|items| Some((items, if page < 2 { Some(page + 1) } else { None }))),
+///   None => Ok(None),
+/// };
+///
+/// assert_eq!(unfold_m(Some(0), next), Ok(vec![vec![0, 1], vec![10, 11], vec![20, 21]]));
+/// assert!(unfold_m(Some(5), next).is_err());
+/// ```
+pub fn unfold_m<'a, S, A, M>(
+  seed: S,
+  f: impl FnMut(S) -> M + 'a,
+) -> <M::Kind as Kind<'a>>::Of<Vec<A>>
+where
+  S: 'a,
+  A: 'a,
+  M: Monad<'a, Item = Option<(A, S)>>,
+{
+  M::Kind::loop_m((seed, f, Vec::new()), |(seed, mut f, mut built)| {
+    f(seed).and_then::<Either<_, Vec<A>>, _>(move |step| {
+      Lift::lift(match step {
+        Some((a, seed)) => {
+          built.push(a);
+          Either::Left((seed, f, built))
+        }
+        None => Either::Right(built),
+      })
+    })
+  })
+}
+
+/// Run `body` as long as `cond` holds, collecting the values.
+///
+/// Both `cond` and `body` build a new computation at every iteration. The loop runs with
+/// [`MonadRec::tail_rec_m`], so it doesn’t grow the stack.
+///
+/// ```rust
+/// use do_notation::{combinators::while_m, m, state::{get, gets, put, run_state, State}};
+///
+/// let r: State<u32, Vec<u32>> = while_m(
+///   || gets(|n: &u32| *n < 1_000_000),
+///   || m! {
+///     n <- get();
+///     put(n + 1);
+///     return n;
+///   },
+/// );
+///
+/// let (values, n) = run_state(r, 0);
+/// assert_eq!((values.len(), n), (1_000_000, 1_000_000));
+/// ```
+pub fn while_m<'a, M, N>(
+  mut cond: impl FnMut() -> M + 'a,
+  body: impl FnMut() -> N + 'a,
+) -> <N::Kind as Kind<'a>>::Of<Vec<N::Item>>
+where
+  M: Monad<'a, Item = bool, Kind = N::Kind>,
+  N: Monad<'a>,
+  N::Kind: MonadRec<'a>,
+{
+  let body = Rc::new(RefCell::new(body));
+
+  N::Kind::tail_rec_m(Vec::new(), move |mut values: Vec<N::Item>| {
+    let body = body.clone();
+
+    cond().and_then::<Either<Vec<N::Item>, Vec<N::Item>>, _>(move |holds| {
+      if holds {
+        let m = (body.borrow_mut())();
+        m.and_then::<Either<Vec<N::Item>, Vec<N::Item>>, _>(move |a| {
+          values.push(a);
+          Lift::lift(Either::Left(values))
+        })
+      } else {
+        Lift::lift(Either::Right(values))
+      }
+    })
+  })
+}
+
+/// Run `body` as long as `cond` holds, for the effects only.
+///
+/// This is [`while_m`] without the values.
+pub fn while_m_<'a, M, N>(
+  mut cond: impl FnMut() -> M + 'a,
+  body: impl FnMut() -> N + 'a,
+) -> <N::Kind as Kind<'a>>::Of<()>
+where
+  M: Monad<'a, Item = bool, Kind = N::Kind>,
+  N: Monad<'a>,
+  N::Kind: MonadRec<'a>,
+{
+  let body = Rc::new(RefCell::new(body));
+
+  N::Kind::tail_rec_m((), move |()| {
+    let body = body.clone();
+
+    cond().and_then::<Either<(), ()>, _>(move |holds| {
+      if holds {
+        let m = (body.borrow_mut())();
+        m.and_then::<Either<(), ()>, _>(|_| Lift::lift(Either::Left(())))
+      } else {
+        Lift::lift(Either::Right(()))
+      }
+    })
+  })
+}
+
+/// Run `body` until `cond` holds, collecting the values.
+///
+/// Unlike [`while_m`], `body` runs at least once: `cond` is checked after every iteration.
+pub fn until_m<'a, M, N>(
+  mut body: impl FnMut() -> N + 'a,
+  cond: impl FnMut() -> M + 'a,
+) -> <N::Kind as Kind<'a>>::Of<Vec<N::Item>>
+where
+  M: Monad<'a, Item = bool, Kind = N::Kind>,
+  N: Monad<'a>,
+  N::Kind: MonadRec<'a>,
+{
+  let cond = Rc::new(RefCell::new(cond));
+
+  N::Kind::tail_rec_m(Vec::new(), move |mut values: Vec<N::Item>| {
+    let cond = cond.clone();
+
+    body().and_then::<Either<Vec<N::Item>, Vec<N::Item>>, _>(move |a| {
+      values.push(a);
+      let m = (cond.borrow_mut())();
+      m.and_then::<Either<Vec<N::Item>, Vec<N::Item>>, _>(move |holds| {
+        Lift::lift(if holds {
+          Either::Right(values)
+        } else {
+          Either::Left(values)
+        })
+      })
+    })
+  })
+}
+
+/// Run `body` until `cond` holds, for the effects only.
+///
+/// This is [`until_m`] without the values.
+pub fn until_m_<'a, M, N>(
+  mut body: impl FnMut() -> N + 'a,
+  cond: impl FnMut() -> M + 'a,
+) -> <N::Kind as Kind<'a>>::Of<()>
+where
+  M: Monad<'a, Item = bool, Kind = N::Kind>,
+  N: Monad<'a>,
+  N::Kind: MonadRec<'a>,
+{
+  let cond = Rc::new(RefCell::new(cond));
+
+  N::Kind::tail_rec_m((), move |()| {
+    let cond = cond.clone();
+
+    body().and_then::<Either<(), ()>, _>(move |_| {
+      let m = (cond.borrow_mut())();
+      m.and_then::<Either<(), ()>, _>(|holds| {
+        Lift::lift(if holds {
+          Either::Right(())
+        } else {
+          Either::Left(())
+        })
+      })
+    })
+  })
+}
+
+/// Continue with `then` if the computation `cond` produces `true`, and with `otherwise` if it produces `false`.
+///
+/// ```rust
+/// use do_notation::{combinators::if_m, state::{gets, modify, run_state, State}};
+///
+/// let step = || -> State<i32, ()> {
+///   if_m(gets(|n: &i32| n % 2 == 0), modify(|n| n / 2), modify(|n| 3 * n + 1))
+/// };
+///
+/// assert_eq!(run_state(step(), 6), ((), 3));
+/// assert_eq!(run_state(step(), 3), ((), 10));
+/// ```
+pub fn if_m<'a, M, N>(cond: M, then: N, otherwise: N) -> N
+where
+  M: Monad<'a, Item = bool, Kind = N::Kind>,
+  N: Monad<'a>,
+{
+  cond.and_then::<N::Item, _>(move |holds| if holds { then } else { otherwise })
+}
+
+/// Continue if `cond` holds, and fail with [`Alternative::empty`] otherwise.
+///
+/// With [`VecKind`](crate::alternative::VecKind), `guard` produces `vec![()]` or an empty vector, keeping or
+/// discarding the elements of a list comprehension.
+///
+/// ```rust
+/// use do_notation::{combinators::guard, m, monad::OptionKind};
+///
+/// let pythagorean = |a: u32, b: u32, c: u32| {
+///   m! {
+///     guard::<OptionKind>(a * a + b * b == c * c);
+///     return (a, b, c);
+///   }
+/// };
+///
+/// assert_eq!(pythagorean(3, 4, 5), Some((3, 4, 5)));
+/// assert_eq!(pythagorean(3, 4, 6), None);
+/// ```
+pub fn guard<'a, K>(cond: bool) -> K::Of<()>
+where
+  K: Alternative<'a>,
+{
+  if cond {
+    Lift::lift(())
+  } else {
+    K::empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    alternative::VecKind,
+    identity::IdentityKind,
+    monad::OptionKind,
+    state::{get, gets, modify, put, run_state, run_state_t, State, StateT, StateTKind},
+  };
+  use std::cell::Cell;
+
+  #[test]
+  fn join() {
+    assert_eq!(super::join(Some(None::<i32>)), None);
+    assert_eq!(super::join(Ok::<_, ()>(Ok::<_, ()>(1))), Ok(1));
+
+    let r: State<i32, State<i32, i32>> = m! {
+      n <- get();
+      return m! {
+        put(n * 10);
+        get::<i32, IdentityKind>()
+      };
+    };
+
+    assert_eq!(run_state(super::join(r), 4), (40, 40));
+  }
+
+  #[test]
+  fn map_m_short_circuits() {
+    let calls = Cell::new(0);
+    let half = |n: i32| {
+      calls.set(calls.get() + 1);
+      if n % 2 == 0 {
+        Ok(n / 2)
+      } else {
+        Err(n)
+      }
+    };
+
+    assert_eq!(map_m(vec![2, 4, 6], half), Ok(vec![1, 2, 3]));
+    assert_eq!(map_m(vec![2, 3, 5, 6], half), Err(3));
+    assert_eq!(calls.get(), 5);
+  }
+
+  #[test]
+  fn map_m_state() {
+    let r = map_m(0..3, |n| {
+      m! {
+        modify(move |s: Vec<i32>| s.into_iter().chain(Some(n)).collect());
+        return n * 2;
+      }
+    });
+
+    let r: State<Vec<i32>, Vec<i32>> = r;
+    assert_eq!(run_state(r, vec![]), (vec![0, 2, 4], vec![0, 1, 2]));
+  }
+
+  #[test]
+  fn for_m() {
+    let r: State<i32, ()> = super::for_m(1..=4, |n| modify(move |s: i32| s + n));
+    assert_eq!(run_state(r, 0), ((), 10));
+
+    let r: State<Vec<i32>, ()> = for_each_m(vec![1, 2], |n| {
+      modify(move |mut s: Vec<i32>| {
+        s.push(n);
+        s
+      })
+    });
+
+    assert_eq!(run_state(r, vec![0]), ((), vec![0, 1, 2]));
+    assert_eq!(
+      for_each_m(vec![1, 2, 3], |n| if n < 3 { Some(()) } else { None }),
+      None
+    );
+  }
+
+  #[test]
+  fn fold_m() {
+    let r: State<Vec<i32>, i32> = super::fold_m(0, 1..=3, |acc, n| {
+      m! {
+        modify(move |mut s: Vec<i32>| {
+          s.push(acc);
+          s
+        });
+        return acc + n;
+      }
+    });
+
+    assert_eq!(run_state(r, vec![]), (6, vec![0, 1, 3]));
+    assert_eq!(
+      super::fold_m(0, 0..100_000, |acc, n| Some(acc + n)),
+      Some(4_999_950_000u64)
+    );
+  }
+
+  #[test]
+  fn for_statement() {
+    let r: State<Vec<i32>, usize> = m! {
+      for n in 1..=3 => {
+        modify(move |mut s: Vec<i32>| {
+          s.push(n);
+          s
+        });
+      };
+      total <- for acc = 0, n in vec![10, 20] => {
+        s <- get::<Vec<i32>, IdentityKind>();
+        return acc + s.len() * n;
+      };
+      return total;
+    };
+
+    assert_eq!(run_state(r, vec![]), (90, vec![1, 2, 3]));
+
+    let r = m! {
+      for n in vec![1, 2, 3] => {
+        if n < 3 { Some(()) } else { None };
+      }
+    };
+
+    assert_eq!(r, None);
+
+    let r: State<u32, ()> = m! {
+      for _ in 0..100_000 => {
+        modify(|n: u32| n + 1);
+      }
+    };
+
+    assert_eq!(run_state(r, 0), ((), 100_000));
+  }
+
+  #[test]
+  fn filter_m() {
+    let r: State<i32, Vec<char>> = super::filter_m("abcde".chars(), |_| {
+      m! {
+        n <- get::<i32, IdentityKind>();
+        put(n + 1);
+        return n % 2 == 0;
+      }
+    });
+
+    assert_eq!(run_state(r, 0), (vec!['a', 'c', 'e'], 5));
+    assert_eq!(super::filter_m(vec![1, 2], |n| Some(*n > 1)), Some(vec![2]));
+  }
+
+  #[test]
+  fn zip_with_m() {
+    let r: State<i32, Vec<String>> = super::zip_with_m(vec!['a', 'b', 'c'], 1.., |c, n| {
+      m! {
+        modify(move |s: i32| s + n);
+        return c.to_string().repeat(n as usize);
+      }
+    });
+
+    assert_eq!(
+      run_state(r, 0),
+      (vec!["a".to_owned(), "bb".to_owned(), "ccc".to_owned()], 6)
+    );
+  }
+
+  #[test]
+  fn replicate_m() {
+    let tick = || {
+      m! {
+        n <- get::<i32, IdentityKind>();
+        put(n + 1);
+        return n;
+      }
+    };
+
+    let r: State<i32, Vec<i32>> = super::replicate_m(3, tick);
+    assert_eq!(run_state(r, 10), (vec![10, 11, 12], 13));
+
+    let r: State<i32, ()> = replicate_m_(4, tick);
+    assert_eq!(run_state(r, 0), ((), 4));
+
+    assert_eq!(super::replicate_m(0, || None::<i32>), Some(vec![]));
+    assert_eq!(replicate_m_(2, || None::<i32>), None);
+  }
+
+  #[test]
+  fn when_m() {
+    let push = |n: i32| {
+      modify(move |mut s: Vec<i32>| {
+        s.push(n);
+        s
+      })
+    };
+
+    let r: State<Vec<i32>, ()> = m! {
+      super::when_m(true, push(1));
+      super::when_m(false, push(2));
+      unless_m(true, push(3));
+      unless_m(false, push(4))
+    };
+
+    assert_eq!(run_state(r, vec![]), ((), vec![1, 4]));
+    assert_eq!(super::when_m(false, None), Some(()));
+    assert_eq!(unless_m(false, None), None);
+  }
+
+  #[test]
+  fn void() {
+    let r: State<i32, ()> = super::void(m! {
+      modify(|n: i32| n + 1);
+      get()
+    });
+
+    assert_eq!(run_state(r, 1), ((), 2));
+    assert_eq!(super::void(Ok::<_, ()>(1)), Ok(()));
+    assert_eq!(super::void(None::<i32>), None);
+  }
+
+  #[test]
+  fn lift_m() {
+    let tick = || {
+      m! {
+        n <- get::<i32, IdentityKind>();
+        put(n + 1);
+        return n;
+      }
+    };
+
+    let r: State<i32, (i32, i32, i32)> = lift_m3(|a, b, c| (a, b, c), tick(), tick(), tick());
+    assert_eq!(run_state(r, 0), ((0, 1, 2), 3));
+
+    assert_eq!(lift_m2(|a: i32, b: i32| a * b, Some(2), Some(3)), Some(6));
+    assert_eq!(
+      lift_m3(|a: i32, b: i32, c: i32| a + b + c, Some(1), None, Some(3)),
+      None
+    );
+  }
+
+  #[test]
+  fn compose_m() {
+    let push = |n: i32| -> State<Vec<i32>, i32> {
+      m! {
+        modify(move |mut s: Vec<i32>| {
+          s.push(n);
+          s
+        });
+        return n + 1;
+      }
+    };
+    let f = super::compose_m(push, push);
+
+    assert_eq!(run_state(f(1), vec![]), (3, vec![1, 2]));
+    assert_eq!(run_state(f(10), vec![0]), (12, vec![0, 10, 11]));
+  }
+
+  #[test]
+  fn msum() {
+    type P = StateTKind<Vec<i32>, OptionKind>;
+    let pop = |expected: i32| -> StateT<Vec<i32>, OptionKind, i32> {
+      m! {
+        s <- get::<Vec<i32>, OptionKind>();
+        match s.split_first() {
+          Some((&n, rest)) if n == expected => put(rest.to_vec()).and_then(move |()| P::pure(n)),
+          _ => P::empty(),
+        }
+      }
+    };
+
+    let r = super::msum((1..=3).map(pop));
+    assert_eq!(run_state_t(r, vec![2, 0]), Some((2, vec![0])));
+    let r: StateT<Vec<i32>, OptionKind, ()> = super::msum(vec![]);
+    assert_eq!(run_state_t(r, vec![1]), None);
+
+    assert_eq!(
+      super::msum(vec![vec![1, 2], vec![], vec![3]]),
+      vec![1, 2, 3]
+    );
+    assert!(super::msum(Vec::<Vec<i32>>::new()).is_empty());
+  }
+
+  #[test]
+  fn map_m_() {
+    let calls = Cell::new(0);
+    let check = |n: i32| {
+      calls.set(calls.get() + 1);
+      if n > 0 {
+        Ok(n)
+      } else {
+        Err(n)
+      }
+    };
+
+    assert_eq!(super::map_m_(vec![1, 2, 3], check), Ok(()));
+    assert_eq!(super::map_m_(vec![1, -2, 3], check), Err(-2));
+    assert_eq!(calls.get(), 5);
+  }
+
+  #[test]
+  fn find_m() {
+    let r: State<Vec<i32>, Option<i32>> = super::find_m(1..10, |&n| {
+      m! {
+        modify(move |mut s: Vec<i32>| {
+          s.push(n);
+          s
+        });
+        return n * n > 10;
+      }
+    });
+
+    assert_eq!(run_state(r, vec![]), (Some(4), vec![1, 2, 3, 4]));
+    assert_eq!(super::find_m(vec![1, 2], |_| None::<bool>), None);
+    assert_eq!(
+      super::find_m(0..100_000, |&n| Ok::<_, ()>(n == 99_999)),
+      Ok(Some(99_999))
+    );
+  }
+
+  #[test]
+  fn any_all_m() {
+    let calls = Cell::new(0);
+    let positive = |n: i32| {
+      calls.set(calls.get() + 1);
+      Some(n > 0)
+    };
+
+    assert_eq!(any_m(vec![-1, 2, 3], positive), Some(true));
+    assert_eq!(all_m(vec![1, -2, 3], positive), Some(false));
+    assert_eq!(calls.get(), 4);
+
+    assert_eq!(any_m(Vec::<i32>::new(), positive), Some(false));
+    assert_eq!(all_m(Vec::<i32>::new(), positive), Some(true));
+    assert_eq!(any_m(vec![1], |_| None), None);
+    assert_eq!(all_m(0..100_000, |n| Some(n < 100_000)), Some(true));
+  }
+
+  #[test]
+  fn partition_m() {
+    let r: State<i32, (Vec<i32>, Vec<i32>)> = super::partition_m(1..=5, |&n| {
+      m! {
+        limit <- get::<i32, IdentityKind>();
+        put(limit + 1);
+        return n > 2 * limit;
+      }
+    });
+
+    assert_eq!(run_state(r, 0), ((vec![1], vec![2, 3, 4, 5]), 5));
+    assert_eq!(super::partition_m(vec![1], |_| None), None);
+  }
+
+  #[test]
+  fn partition_results() {
+    let ran = Cell::new(0);
+    let check = |n: i32| {
+      ran.set(ran.get() + 1);
+      if n % 2 == 0 {
+        Ok(n)
+      } else {
+        Err(n)
+      }
+    };
+
+    assert_eq!(
+      super::partition_results((1..=5).map(check)),
+      (vec![2, 4], vec![1, 3, 5])
+    );
+    assert_eq!(ran.get(), 5);
+
+    assert_eq!(
+      super::collect_errors((1..=5).map(check)),
+      Err(vec![1, 3, 5])
+    );
+    assert_eq!(ran.get(), 10);
+    assert_eq!(
+      super::collect_errors(vec![2, 4].into_iter().map(check)),
+      Ok(vec![2, 4])
+    );
+    assert_eq!(
+      super::collect_errors(Vec::<Result<(), ()>>::new()),
+      Ok(vec![])
+    );
+  }
+
+  #[test]
+  fn concat_map_m() {
+    let r: State<i32, Vec<i32>> = super::concat_map_m(1..=3, |n| {
+      m! {
+        modify(move |s: i32| s + n);
+        return vec![n; n as usize];
+      }
+    });
+
+    assert_eq!(run_state(r, 0), (vec![1, 2, 2, 3, 3, 3], 6));
+    assert_eq!(
+      super::concat_map_m(vec![1, 2], |n| Some(Some(n))),
+      Some(vec![1, 2])
+    );
+    assert_eq!(super::concat_map_m(vec![1, 2], |_| None::<Vec<i32>>), None);
+  }
+
+  #[test]
+  fn take_drop_while_m() {
+    let calls = Cell::new(0);
+    let small = |n: &i32| {
+      calls.set(calls.get() + 1);
+      Some(*n < 3)
+    };
+
+    assert_eq!(take_while_m(1..10, small), Some(vec![1, 2]));
+    assert_eq!(drop_while_m(1..6, small), Some(vec![3, 4, 5]));
+    assert_eq!(calls.get(), 6);
+
+    assert_eq!(take_while_m(vec![1, 2], small), Some(vec![1, 2]));
+    assert_eq!(drop_while_m(vec![1, 2], small), Some(vec![]));
+    assert_eq!(take_while_m(vec![1], |_| None), None);
+
+    let below = |n: &u32| Ok::<_, ()>(*n < 99_999);
+    assert_eq!(take_while_m(0..100_000, below).map(|v| v.len()), Ok(99_999));
+    assert_eq!(drop_while_m(0..100_000, below), Ok(vec![99_999]));
+  }
+
+  #[test]
+  fn unfold_m() {
+    let r: State<Vec<u32>, Vec<u32>> = super::unfold_m(10, |n: u32| {
+      m! {
+        modify(move |mut s: Vec<u32>| {
+          s.push(n);
+          s
+        });
+        return if n > 0 { Some((n % 3, n / 3)) } else { None };
+      }
+    });
+
+    assert_eq!(run_state(r, vec![]), (vec![1, 0, 1], vec![10, 3, 1, 0]));
+    assert_eq!(super::unfold_m(0, |_| None::<Option<(i32, i32)>>), None);
+    assert_eq!(
+      super::unfold_m(0, |n| Some((n < 100_000).then_some((n, n + 1)))).map(|v| v.len()),
+      Some(100_000)
+    );
+  }
+
+  #[test]
+  fn while_until_m() {
+    let below = |limit: i32| move || gets(move |n: &i32| *n < limit);
+    let tick = || {
+      m! {
+        n <- get::<i32, IdentityKind>();
+        put(n + 1);
+        return n;
+      }
+    };
+
+    let r: State<i32, Vec<i32>> = while_m(below(3), tick);
+    assert_eq!(run_state(r, 0), (vec![0, 1, 2], 3));
+
+    let r: State<i32, Vec<i32>> = while_m(below(3), tick);
+    assert_eq!(run_state(r, 5), (vec![], 5));
+
+    let r: State<i32, ()> = while_m_(below(100_000), tick);
+    assert_eq!(run_state(r, 0), ((), 100_000));
+
+    let r: State<i32, Vec<i32>> = until_m(tick, || gets(|n: &i32| *n >= 3));
+    assert_eq!(run_state(r, 5), (vec![5], 6));
+
+    let r: State<i32, ()> = until_m_(tick, || gets(|n: &i32| *n >= 100_000));
+    assert_eq!(run_state(r, 0), ((), 100_000));
+
+    let mut fuel = 3;
+    let r = while_m(
+      move || {
+        fuel -= 1;
+        if fuel > 0 {
+          Ok(true)
+        } else {
+          Err("out of fuel")
+        }
+      },
+      || Ok(()),
+    );
+    assert_eq!(r, Err("out of fuel"));
+  }
+
+  #[test]
+  fn if_m() {
+    assert_eq!(super::if_m(Some(true), Some(1), Some(2)), Some(1));
+    assert_eq!(super::if_m(Some(false), Some(1), Some(2)), Some(2));
+    assert_eq!(super::if_m(None, Some(1), Some(2)), None);
+    assert_eq!(super::if_m(Ok(true), Err("then"), Ok(2)), Err("then"));
+  }
+
+  #[test]
+  fn guard() {
+    type P = StateTKind<i32, OptionKind>;
+    let positive: StateT<i32, OptionKind, i32> = m! {
+      n <- get::<i32, OptionKind>();
+      super::guard::<P>(n > 0);
+      return n;
+    };
+
+    assert_eq!(run_state_t(positive, 2), Some((2, 2)));
+    assert_eq!(super::guard::<VecKind>(true), vec![()]);
+    assert!(super::guard::<VecKind>(false).is_empty());
+  }
+}
@@ -0,0 +1,249 @@
+//! Comonads and the codo-notation.
+//!
+//! A [`Comonad`] is the dual of a monad: instead of putting values in a context, it gets values out of a context.
+//! A comonad has a _focus_, whose value is [extracted](Comonad::extract), and can [extend](Comonad::extend)
+//! a computation working on the whole context to every focus — think of applying a kernel to every pixel of an
+//! image, or the rule of a cellular automaton to every cell.
+//!
+//! The [`co!`](crate::co) macro provides a dual of [`m!`](crate::m) for comonadic pipelines. It builds a
+//! function from a context to a value, made of statements evaluated at every focus of the context:
+//!
+//! ```rust
+//! use do_notation::{co, comonad::{Comonad, Store}};
+//!
+//! // a one-dimensional image
+//! let image = Store::new(|x: i64| if x == 0 { 90. } else { 0. }, 0);
+//!
+//! fn blur(w: &Store<i64, f64>) -> f64 {
+//!   (w.peek(w.pos() - 1) + w.extract() + w.peek(w.pos() + 1)) / 3.
+//! }
+//!
+//! let blurred = image.extend(co! { w =>
+//!   once <- blur(w);
+//!   blur(once)
+//! });
+//!
+//! assert_eq!(
+//!   (-3..=3).map(|x| blurred.peek(x)).collect::<Vec<_>>(),
+//!   vec![0., 10., 20., 30., 20., 10., 0.]
+//! );
+//! ```
+
+use crate::non_empty::NonEmpty;
+use std::rc::Rc;
+
+/// Comonads, getting values out of a context.
+///
+/// Implementors must ensure that:
+///
+/// - `w.extend(Comonad::extract)` is equivalent to `w`.
+/// - `w.extend(f).extract()` is equivalent to `f(&w)`.
+/// - `w.extend(f).extend(g)` is equivalent to `w.extend(|w| g(&w.extend(f)))`.
+pub trait Comonad<'a>: Sized + 'a {
+  /// Type of the values in the context.
+  type Item: 'a;
+
+  /// Same comonad, with values of type `B`.
+  type Of<B: 'a>;
+
+  /// Extract the value at the focus.
+  fn extract(&self) -> Self::Item;
+
+  /// Apply `f` to the context at every focus.
+  fn extend<B: 'a>(&self, f: impl Fn(&Self) -> B + 'a) -> Self::Of<B>;
+
+  /// Replace every value with the context at its focus.
+  fn duplicate(&self) -> Self::Of<Self>
+  where
+    Self: Clone,
+  {
+    self.extend(Self::clone)
+  }
+}
+
+/// Values of type `A` indexed by positions of type `S`, focused on a position.
+pub struct Store<'a, S, A> {
+  peek: Rc<dyn Fn(S) -> A + 'a>,
+  pos: S,
+}
+
+impl<'a, S, A> Clone for Store<'a, S, A>
+where
+  S: Clone,
+{
+  fn clone(&self) -> Self {
+    Store {
+      peek: self.peek.clone(),
+      pos: self.pos.clone(),
+    }
+  }
+}
+
+impl<'a, S, A> Store<'a, S, A> {
+  /// Create a store from the value at every position, focused on `pos`.
+  pub fn new(peek: impl Fn(S) -> A + 'a, pos: S) -> Self {
+    Store {
+      peek: Rc::new(peek),
+      pos,
+    }
+  }
+
+  /// Position of the focus.
+  pub fn pos(&self) -> S
+  where
+    S: Clone,
+  {
+    self.pos.clone()
+  }
+
+  /// Value at position `s`.
+  pub fn peek(&self, s: S) -> A {
+    (self.peek)(s)
+  }
+
+  /// Move the focus to `s`.
+  pub fn seek(&self, s: S) -> Self {
+    Store {
+      peek: self.peek.clone(),
+      pos: s,
+    }
+  }
+
+  /// Move the focus relatively to its current position.
+  pub fn seeks(&self, f: impl FnOnce(&S) -> S) -> Self {
+    self.seek(f(&self.pos))
+  }
+}
+
+impl<'a, S, A> Comonad<'a> for Store<'a, S, A>
+where
+  S: Clone + 'a,
+  A: 'a,
+{
+  type Item = A;
+  type Of<B: 'a> = Store<'a, S, B>;
+
+  fn extract(&self) -> A {
+    self.peek(self.pos.clone())
+  }
+
+  fn extend<B: 'a>(&self, f: impl Fn(&Self) -> B + 'a) -> Store<'a, S, B> {
+    let peek = self.peek.clone();
+    Store::new(
+      move |pos| {
+        f(&Store {
+          peek: peek.clone(),
+          pos,
+        })
+      },
+      self.pos.clone(),
+    )
+  }
+}
+
+/// The focus of a [`NonEmpty`] is its head. Extending a computation applies it to every suffix.
+impl<'a, A> Comonad<'a> for NonEmpty<A>
+where
+  A: Clone + 'a,
+{
+  type Item = A;
+  type Of<B: 'a> = NonEmpty<B>;
+
+  fn extract(&self) -> A {
+    self.head.clone()
+  }
+
+  fn extend<B: 'a>(&self, f: impl Fn(&Self) -> B + 'a) -> NonEmpty<B> {
+    let head = f(self);
+    let tail = (0..self.tail.len())
+      .map(|i| {
+        f(&NonEmpty {
+          head: self.tail[i].clone(),
+          tail: self.tail[i + 1..].to_vec(),
+        })
+      })
+      .collect();
+
+    NonEmpty { head, tail }
+  }
+}
+
+/// Comonadic codo-notation.
+///
+/// `co! { w => statements… }` builds a function taking a reference to a comonad `w` — the context — and
+/// evaluating the statements, suitable to be passed to [`Comonad::extend`](crate::comonad::Comonad::extend):
+///
+/// - `binding <- expr;` evaluates `expr` at every focus of the current context, and binds the resulting comonad
+///   to `binding`, which becomes the current context of the next statements.
+/// - `let` bindings have their regular Rust meaning.
+/// - The last line is an expression, the value at the focus.
+///
+/// The type of the context can be specified with `co! { w: &Type => … }`.
+///
+/// Only the current context is focused: because the statements are stored in the extended comonads, they can’t
+/// borrow from the previous contexts, and should only use the current one.
+///
+/// See the [`comonad`](crate::comonad) module for further information.
+#[macro_export]
+macro_rules! co {
+  // bind
+  (@stmts $w:ident; $binding:ident <- $x:expr ; $($r:tt)*) => {{
+    let $binding = &$crate::comonad::Comonad::extend($w, move |$w| $x);
+    $crate::co!(@stmts $binding; $($r)*)
+  }};
+
+  // let-binding
+  (@stmts $w:ident; let $p:pat = $e:expr ; $($r:tt)*) => {{
+    let $p = $e;
+    $crate::co!(@stmts $w; $($r)*)
+  }};
+
+  // extract
+  (@stmts $w:ident; $a:expr) => {
+    $a
+  };
+
+  ($w:ident $(: $t:ty)? => $($r:tt)*) => {
+    move |$w $(: $t)?| $crate::co!(@stmts $w; $($r)*)
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn store() {
+    let w = Store::new(|x: i32| x * 2, 3);
+    assert_eq!(w.extract(), 6);
+    assert_eq!(w.seeks(|x| x + 1).extract(), 8);
+
+    // rule 90
+    let cells = Store::new(|x: i32| x == 0, 0);
+    let step = co! { w: &Store<i32, bool> => w.peek(w.pos() - 1) != w.peek(w.pos() + 1) };
+    let next = cells.extend(step).extend(step);
+
+    assert_eq!(
+      (-3..=3).map(|x| next.peek(x)).collect::<Vec<_>>(),
+      vec![false, true, false, false, false, true, false]
+    );
+  }
+
+  #[test]
+  fn non_empty() {
+    let w = NonEmpty::from_vec(vec![1, 2, 3, 4]).unwrap();
+
+    let r = w.extend(co! { w =>
+      sums <- w.iter().sum::<i32>();
+      let tail_sum = sums.tail.first().copied().unwrap_or(0);
+      sums.extract() - tail_sum
+    });
+
+    assert_eq!(r, w);
+    assert_eq!(w.duplicate().extract(), w);
+    assert_eq!(
+      w.extend(NonEmpty::len),
+      NonEmpty::from_vec(vec![4, 3, 2, 1]).unwrap()
+    );
+  }
+}
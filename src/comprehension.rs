@@ -0,0 +1,151 @@
+//! Lazy list comprehensions over iterators.
+//!
+//! > This module requires the `itertools` feature.
+//!
+//! [`Each`] wraps an iterator as a list monad: binding it binds every item in turn, like binding a `Vec` in
+//! `gen_m!`. Unlike a `Vec`, nothing is collected: a do-block builds an iterator, whose items are
+//! produced on demand. Cartesian products — built with [`product`], [`multi_product`] or `itertools::iproduct!` —
+//! can be bound in a single statement:
+//!
+//! ```rust
+//! use do_notation::{comprehension::{each, product, when, Each}, m};
+//! use itertools::iproduct;
+//!
+//! let triples = m! {
+//!   ab <- product(1..20, 1..20);
+//!   let (a, b) = ab;
+//!   c <- each(b..20);
+//!   _ <- when(a < b && a * a + b * b == c * c);
+//!   return (a, b, c);
+//! };
+//!
+//! assert_eq!(triples.take(2).collect::<Vec<_>>(), vec![(3, 4, 5), (5, 12, 13)]);
+//!
+//! let sums = m! {
+//!   xyz <- Each(iproduct!(0..2, 0..2, 0..2));
+//!   let (x, y, z) = xyz;
+//!   return x + y + z;
+//! };
+//!
+//! assert_eq!(sums.collect::<Vec<_>>(), vec![0, 1, 1, 2, 1, 2, 2, 3]);
+//! ```
+//!
+//! As the rest of the do-block runs once per item, the values it captures must be [`Copy`].
+
+use crate::Lift;
+use itertools::{Itertools, MultiProduct, Product};
+use std::iter::{FlatMap, Once};
+
+/// Iterators, as list monads.
+#[derive(Clone, Debug)]
+pub struct Each<I>(pub I);
+
+impl<I: Iterator> Each<I> {
+  /// Bind every item to `f`, chaining the results lazily.
+  pub fn and_then<J, F>(self, f: F) -> Each<FlatMap<I, Each<J>, F>>
+  where
+    J: Iterator,
+    F: FnMut(I::Item) -> Each<J>,
+  {
+    Each(self.0.flat_map(f))
+  }
+}
+
+impl<I: Iterator> Iterator for Each<I> {
+  type Item = I::Item;
+
+  fn next(&mut self) -> Option<I::Item> {
+    self.0.next()
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.0.size_hint()
+  }
+}
+
+impl<A> Lift<A> for Each<Once<A>> {
+  fn lift(a: A) -> Self {
+    Each(std::iter::once(a))
+  }
+}
+
+/// Bind every item of `iter`.
+pub fn each<I: IntoIterator>(iter: I) -> Each<I::IntoIter> {
+  Each(iter.into_iter())
+}
+
+/// Continue with `()` if `cond` holds, and with nothing otherwise.
+pub fn when(cond: bool) -> Each<std::option::IntoIter<()>> {
+  Each(cond.then_some(()).into_iter())
+}
+
+/// Bind every pair of items of `a` and `b`, like `itertools::iproduct!`.
+pub fn product<I, J>(a: I, b: J) -> Each<Product<I::IntoIter, J::IntoIter>>
+where
+  I: IntoIterator,
+  I::Item: Clone,
+  J: IntoIterator,
+  J::IntoIter: Clone,
+{
+  Each(a.into_iter().cartesian_product(b))
+}
+
+/// Bind every combination of one item of each of `iters`, as a `Vec`.
+pub fn multi_product<I, J>(iters: I) -> Each<MultiProduct<J::IntoIter>>
+where
+  I: IntoIterator<Item = J>,
+  J: IntoIterator,
+  J::IntoIter: Clone,
+  J::Item: Clone,
+{
+  Each(
+    iters
+      .into_iter()
+      .map(J::into_iter)
+      .multi_cartesian_product(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::cell::Cell;
+
+  #[test]
+  fn comprehension() {
+    let r = m! {
+      x <- each(vec![1, 2]);
+      y <- each(vec!['a', 'b']);
+      return (x, y);
+    };
+
+    assert_eq!(
+      r.collect::<Vec<_>>(),
+      vec![(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]
+    );
+
+    let r = m! {
+      xs <- multi_product(vec![0..2, 0..3]);
+      _ <- when(xs.iter().sum::<i32>() == 2);
+      return xs.clone();
+    };
+
+    assert_eq!(r.collect::<Vec<_>>(), vec![vec![0, 2], vec![1, 1]]);
+  }
+
+  #[test]
+  fn lazy() {
+    let produced = Cell::new(0);
+    let produced = &produced;
+
+    let mut r = m! {
+      xy <- product(0.., 0..10);
+      let (x, y) = xy;
+      _ <- when({ produced.set(produced.get() + 1); true });
+      return x * 10 + y;
+    };
+
+    assert_eq!(r.nth(12), Some(12));
+    assert_eq!(produced.get(), 13);
+  }
+}
@@ -0,0 +1,346 @@
+//! Reading configurations.
+//!
+//! [`EnvReader<A>`] reads a configuration of type `A` from a key/value [`Source`] — the environment variables by
+//! default. It reads every key, and reports all the missing and invalid ones at once, accumulated in a
+//! [`Validation`], instead of stopping at the first one. The keys are read from an [`Env`], whose methods produce
+//! [`Validation`]s, so a configuration is a [`validate!`](crate::validate) block:
+//!
+//! ```rust
+//! use do_notation::{config::{EnvError, EnvReader}, validate};
+//! use std::collections::HashMap;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Config {
+//!   host: String,
+//!   port: u16,
+//!   workers: Option<usize>,
+//! }
+//!
+//! fn config<'a>() -> EnvReader<'a, Config> {
+//!   EnvReader::new(|env| validate! {
+//!     host <- env.var("HOST");
+//!     port <- env.parse("PORT");
+//!     workers <- env.optional("WORKERS");
+//!     return Config { host, port, workers };
+//!   })
+//! }
+//!
+//! let vars = HashMap::from([("HOST", "localhost"), ("PORT", "8080")]);
+//! assert_eq!(
+//!   config().read(&vars),
+//!   Ok(Config { host: "localhost".to_owned(), port: 8080, workers: None })
+//! );
+//!
+//! let vars = HashMap::from([("PORT", "http"), ("WORKERS", "4")]);
+//! let errors = Vec::from(config().read(&vars).unwrap_err());
+//! assert_eq!(
+//!   errors.iter().map(EnvError::to_string).collect::<Vec<_>>(),
+//!   vec!["missing HOST", "invalid PORT \"http\": invalid digit found in string"]
+//! );
+//! ```
+//!
+//! [`EnvReader::read_env`] reads the environment variables of the process. Configurations made of smaller ones read
+//! them with [`Env::read`], or combine them as applicative functors, with [`EnvReaderKind`].
+
+use crate::{
+  applicative::{Applicative, Applied, Functor},
+  non_empty::NonEmpty,
+  validation::Validation,
+  Lift,
+};
+use std::{
+  borrow::Borrow,
+  collections::{BTreeMap, HashMap},
+  env,
+  error::Error,
+  fmt,
+  hash::{BuildHasher, Hash},
+  str::FromStr,
+};
+
+/// Key/value sources of configurations.
+pub trait Source {
+  /// Value of `key`, if any.
+  fn get(&self, key: &str) -> Option<String>;
+}
+
+/// The environment variables of the process.
+#[derive(Clone, Copy, Debug)]
+pub struct Vars;
+
+impl Source for Vars {
+  fn get(&self, key: &str) -> Option<String> {
+    env::var(key).ok()
+  }
+}
+
+impl<K, V, S> Source for HashMap<K, V, S>
+where
+  K: Borrow<str> + Eq + Hash,
+  V: AsRef<str>,
+  S: BuildHasher,
+{
+  fn get(&self, key: &str) -> Option<String> {
+    HashMap::get(self, key).map(|v| v.as_ref().to_owned())
+  }
+}
+
+impl<K, V> Source for BTreeMap<K, V>
+where
+  K: Borrow<str> + Ord,
+  V: AsRef<str>,
+{
+  fn get(&self, key: &str) -> Option<String> {
+    BTreeMap::get(self, key).map(|v| v.as_ref().to_owned())
+  }
+}
+
+/// Errors of [`EnvReader`]s.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum EnvError {
+  /// The key has no value.
+  Missing {
+    /// Missing key.
+    key: String,
+  },
+  /// The value of the key can’t be parsed.
+  Invalid {
+    /// Invalid key.
+    key: String,
+    /// Value of the key.
+    value: String,
+    /// Why the value is invalid.
+    message: String,
+  },
+}
+
+impl fmt::Display for EnvError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      EnvError::Missing { key } => write!(f, "missing {}", key),
+      EnvError::Invalid {
+        key,
+        value,
+        message,
+      } => write!(f, "invalid {} {:?}: {}", key, value, message),
+    }
+  }
+}
+
+impl Error for EnvError {}
+
+/// Access to a [`Source`], reading keys as [`Validation`]s.
+pub struct Env<'s>(&'s dyn Source);
+
+impl Env<'_> {
+  /// Read `key`.
+  pub fn var(&self, key: &str) -> Validation<EnvError, String> {
+    match self.0.get(key) {
+      Some(value) => Validation::Valid(value),
+      None => Validation::invalid(EnvError::Missing {
+        key: key.to_owned(),
+      }),
+    }
+  }
+
+  /// Read and parse `key`.
+  pub fn parse<T>(&self, key: &str) -> Validation<EnvError, T>
+  where
+    T: FromStr,
+    T::Err: fmt::Display,
+  {
+    match self.optional(key) {
+      Validation::Valid(Some(t)) => Validation::Valid(t),
+      Validation::Valid(None) => Validation::invalid(EnvError::Missing {
+        key: key.to_owned(),
+      }),
+      Validation::Invalid(es) => Validation::Invalid(es),
+    }
+  }
+
+  /// Read and parse `key`, if it has a value.
+  pub fn optional<T>(&self, key: &str) -> Validation<EnvError, Option<T>>
+  where
+    T: FromStr,
+    T::Err: fmt::Display,
+  {
+    match self.0.get(key) {
+      Some(value) => match value.parse() {
+        Ok(t) => Validation::Valid(Some(t)),
+        Err(e) => Validation::invalid(EnvError::Invalid {
+          key: key.to_owned(),
+          message: e.to_string(),
+          value,
+        }),
+      },
+      None => Validation::Valid(None),
+    }
+  }
+
+  /// Read the configuration of `reader`.
+  pub fn read<A>(&self, reader: EnvReader<'_, A>) -> Validation<EnvError, A> {
+    (reader.0)(self)
+  }
+}
+
+/// Configurations of type `A`, read from a [`Source`].
+#[allow(clippy::type_complexity)]
+pub struct EnvReader<'a, A>(Box<dyn FnOnce(&Env) -> Validation<EnvError, A> + 'a>);
+
+impl<'a, A: 'a> EnvReader<'a, A> {
+  /// Build a configuration out of a function reading an [`Env`].
+  pub fn new(f: impl FnOnce(&Env) -> Validation<EnvError, A> + 'a) -> Self {
+    EnvReader(Box::new(f))
+  }
+
+  /// Read the configuration from `source`, or all the errors.
+  pub fn read(self, source: &impl Source) -> Result<A, NonEmpty<EnvError>> {
+    Env(source).read(self).into_result()
+  }
+
+  /// Read the configuration from the environment variables, or all the errors.
+  pub fn read_env(self) -> Result<A, NonEmpty<EnvError>> {
+    self.read(&Vars)
+  }
+
+  /// Apply `f` to the configuration.
+  pub fn map<B: 'a>(self, f: impl FnOnce(A) -> B + 'a) -> EnvReader<'a, B> {
+    EnvReader::new(move |env| env.read(self).map(f))
+  }
+
+  /// Combine the configuration with the one of `other` with `f`, or the errors of both.
+  pub fn zip_with<B: 'a, C: 'a>(
+    self,
+    other: EnvReader<'a, B>,
+    f: impl FnOnce(A, B) -> C + 'a,
+  ) -> EnvReader<'a, C> {
+    EnvReader::new(move |env| env.read(self).zip_with(env.read(other), f))
+  }
+}
+
+impl<'a, A: 'a> Lift<A> for EnvReader<'a, A> {
+  fn lift(a: A) -> Self {
+    EnvReader::new(move |_| Validation::Valid(a))
+  }
+}
+
+/// [`Applicative`] of `EnvReader<'_, _>`.
+#[derive(Debug)]
+pub struct EnvReaderKind;
+
+impl<'a> Functor<'a> for EnvReaderKind {
+  type Of<A: 'a> = EnvReader<'a, A>;
+
+  fn map<A: 'a, B: 'a>(fa: EnvReader<'a, A>, f: impl FnOnce(A) -> B + 'a) -> EnvReader<'a, B> {
+    fa.map(f)
+  }
+}
+
+impl<'a> Applicative<'a> for EnvReaderKind {
+  fn pure<A: 'a>(a: A) -> EnvReader<'a, A> {
+    Lift::lift(a)
+  }
+
+  fn zip_with<A: 'a, B: 'a, C: 'a>(
+    fa: EnvReader<'a, A>,
+    fb: EnvReader<'a, B>,
+    f: impl FnOnce(A, B) -> C + 'a,
+  ) -> EnvReader<'a, C> {
+    fa.zip_with(fb, f)
+  }
+}
+
+impl<'a, A: 'a> Applied<'a> for EnvReader<'a, A> {
+  type Item = A;
+  type Kind = EnvReaderKind;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{combinators::map_m, validate};
+
+  #[derive(Debug, PartialEq)]
+  struct Db {
+    url: String,
+    pool: u32,
+  }
+
+  fn db<'a>() -> EnvReader<'a, Db> {
+    EnvReader::new(|env| {
+      validate! {
+        url <- env.var("DB_URL");
+        pool <- env.parse("DB_POOL");
+        return Db { url, pool };
+      }
+    })
+  }
+
+  #[test]
+  fn nested() {
+    let app = EnvReader::new(|env| {
+      validate! {
+        db <- env.read(db());
+        debug <- env.optional::<bool>("DEBUG");
+        return (db, debug.unwrap_or(false));
+      }
+    });
+
+    let vars = BTreeMap::from([("DB_URL", "sqlite::memory:"), ("DB_POOL", "4")]);
+    let expected = Db {
+      url: "sqlite::memory:".to_owned(),
+      pool: 4,
+    };
+    assert_eq!(app.read(&vars), Ok((expected, false)));
+
+    let vars = HashMap::from([
+      ("DB_POOL".to_owned(), "-1".to_owned()),
+      ("DEBUG".to_owned(), "yes".to_owned()),
+    ]);
+    let errors = Vec::from(
+      db()
+        .zip_with(Lift::lift(()), |db, ()| db)
+        .read(&vars)
+        .unwrap_err(),
+    );
+    assert_eq!(
+      errors,
+      vec![
+        EnvError::Missing {
+          key: "DB_URL".to_owned()
+        },
+        EnvError::Invalid {
+          key: "DB_POOL".to_owned(),
+          value: "-1".to_owned(),
+          message: "invalid digit found in string".to_owned()
+        }
+      ]
+    );
+  }
+
+  #[test]
+  fn applicative() {
+    let vars = HashMap::from([("A", "1"), ("C", "x")]);
+    let keys = map_m(["A", "B", "C"], |key| {
+      EnvReader::new(move |env| env.parse::<i32>(key))
+    });
+
+    let errors = keys.read(&vars).unwrap_err();
+    assert_eq!(
+      Vec::from(errors)
+        .iter()
+        .map(EnvError::to_string)
+        .collect::<Vec<_>>(),
+      vec![
+        "missing B",
+        "invalid C \"x\": invalid digit found in string"
+      ]
+    );
+
+    let unset = EnvReader::new(|env| env.optional::<String>("DO_NOTATION_UNSET"));
+    assert_eq!(
+      EnvReaderKind::zip(unset, Lift::lift(1)).read_env(),
+      Ok((None, 1))
+    );
+  }
+}
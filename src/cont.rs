@@ -0,0 +1,337 @@
+//! The continuation monad and monad transformer.
+//!
+//! [`ContT<R, M, A>`] represents computations in continuation-passing style: instead of
+//! returning a value of type `A`, they pass it to a continuation producing the final result of
+//! type `R` in the monad `M`. [`Cont<R, A>`] is the pure version, over
+//! [`Identity`](crate::identity::Identity).
+//!
+//! The interesting bit of continuations is [`call_cc`], which captures the current
+//! continuation as an [`Escape`]. Escaping with it aborts the rest of the computation started by
+//! [`call_cc`], allowing early exits:
+//!
+//! ```rust
+//! use do_notation::{cont::{call_cc, eval_cont, Cont}, m, Lift};
+//!
+//! fn first_negative(xs: Vec<i32>) -> Cont<'static, Option<i32>, Option<i32>> {
+//!   call_cc(move |exit| {
+//!     let search = xs.into_iter().fold(Lift::lift(()), move |acc: Cont<_, ()>, x| {
+//!       let exit = exit.clone();
+//!       m! {
+//!         _ <- acc;
+//!         if x < 0 { exit.escape(Some(x)) } else { Lift::lift(()) }
+//!       }
+//!     });
+//!
+//!     m! {
+//!       _ <- search;
+//!       return None;
+//!     }
+//!   })
+//! }
+//!
+//! assert_eq!(eval_cont(first_negative(vec![1, -2, 3, -4])), Some(-2));
+//! assert_eq!(eval_cont(first_negative(vec![1, 2])), None);
+//! ```
+//!
+//! Continuations passed around in this crate are one-shot: each one can be called at most once.
+
+use crate::{
+  identity::IdentityKind,
+  io::MonadIo,
+  monad::{Kind, Monad},
+  reader::MonadReader,
+  state::MonadState,
+  trans::MonadTrans,
+  Lift,
+};
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+
+/// Continuations of type `A` producing the final result of type `R` in the monad `M`.
+type Continuation<'a, R, M, A> = Box<dyn FnOnce(A) -> <M as Kind<'a>>::Of<R> + 'a>;
+
+/// Computations in continuation-passing style, producing a value of type `A` to a continuation
+/// producing the final result of type `R` in the monad `M`.
+#[allow(clippy::type_complexity)]
+pub struct ContT<'a, R: 'a, M: Kind<'a>, A: 'a> {
+  run: Box<dyn FnOnce(Continuation<'a, R, M, A>) -> M::Of<R> + 'a>,
+}
+
+/// Pure computations in continuation-passing style.
+pub type Cont<'a, R, A> = ContT<'a, R, IdentityKind, A>;
+
+impl<'a, R, M, A> ContT<'a, R, M, A>
+where
+  R: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  /// Create a computation from a function of its continuation.
+  pub fn new(f: impl FnOnce(Continuation<'a, R, M, A>) -> M::Of<R> + 'a) -> Self {
+    ContT { run: Box::new(f) }
+  }
+
+  /// Bind the value produced by this computation, passing the current continuation along.
+  pub fn and_then<B, F>(self, f: F) -> ContT<'a, R, M, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> ContT<'a, R, M, B> + 'a,
+  {
+    ContT::new(move |c| (self.run)(Box::new(move |a| (f(a).run)(c))))
+  }
+}
+
+impl<'a, R, M, A> Lift<A> for ContT<'a, R, M, A>
+where
+  R: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  fn lift(a: A) -> Self {
+    ContT::new(move |c| c(a))
+  }
+}
+
+/// [`Kind`] of `ContT<'_, R, M, _>`.
+#[derive(Debug)]
+pub struct ContTKind<R, M>(PhantomData<(R, M)>);
+
+impl<'a, R, M> Kind<'a> for ContTKind<R, M>
+where
+  R: 'a,
+  M: Kind<'a>,
+{
+  type Of<A: 'a> = ContT<'a, R, M, A>;
+}
+
+impl<'a, R, M, A> Monad<'a> for ContT<'a, R, M, A>
+where
+  R: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  type Item = A;
+  type Kind = ContTKind<R, M>;
+
+  fn and_then<B, F>(self, f: F) -> ContT<'a, R, M, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> ContT<'a, R, M, B> + 'a,
+  {
+    ContT::and_then(self, f)
+  }
+}
+
+impl<'a, R, M> MonadTrans<'a, M> for ContT<'a, R, M::Kind, M::Item>
+where
+  R: 'a,
+  M: Monad<'a>,
+{
+  fn lift(m: M) -> Self {
+    ContT::new(move |c| m.and_then::<R, _>(c))
+  }
+}
+
+/// Escape continuation captured by [`call_cc`].
+pub struct Escape<'a, R: 'a, M: Kind<'a>, A: 'a> {
+  continuation: Rc<RefCell<Option<Continuation<'a, R, M, A>>>>,
+}
+
+impl<'a, R, M, A> Clone for Escape<'a, R, M, A>
+where
+  R: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  fn clone(&self) -> Self {
+    Escape {
+      continuation: self.continuation.clone(),
+    }
+  }
+}
+
+impl<'a, R, M, A> Escape<'a, R, M, A>
+where
+  R: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  /// Escape with the value `a`, aborting the rest of the computation started by [`call_cc`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if the continuation was already called, either by escaping or by the computation
+  /// started by [`call_cc`] normally returning.
+  pub fn escape<B: 'a>(&self, a: A) -> ContT<'a, R, M, B> {
+    let escape = self.clone();
+    ContT::new(move |_| escape.take()(a))
+  }
+
+  fn take(&self) -> Continuation<'a, R, M, A> {
+    self
+      .continuation
+      .borrow_mut()
+      .take()
+      .expect("continuation already called")
+  }
+}
+
+/// Call `f` with the current continuation.
+pub fn call_cc<'a, R, M, A>(
+  f: impl FnOnce(Escape<'a, R, M, A>) -> ContT<'a, R, M, A> + 'a,
+) -> ContT<'a, R, M, A>
+where
+  R: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  ContT::new(move |c| {
+    let escape = Escape {
+      continuation: Rc::new(RefCell::new(Some(c))),
+    };
+    let returned = escape.clone();
+
+    (f(escape).run)(Box::new(move |a| returned.take()(a)))
+  })
+}
+
+/// Run a computation with the continuation `c`.
+pub fn run_cont_t<'a, R, M, A>(m: ContT<'a, R, M, A>, c: Continuation<'a, R, M, A>) -> M::Of<R>
+where
+  M: Kind<'a>,
+{
+  (m.run)(c)
+}
+
+/// Run a computation with the final continuation, lifting its value into the inner monad.
+pub fn eval_cont_t<'a, R, M>(m: ContT<'a, R, M, R>) -> M::Of<R>
+where
+  R: 'a,
+  M: Kind<'a>,
+{
+  run_cont_t(m, Box::new(Lift::lift))
+}
+
+/// Run a pure computation with the continuation `c`.
+pub fn run_cont<'a, R, A>(m: Cont<'a, R, A>, c: impl FnOnce(A) -> R + 'a) -> R
+where
+  R: 'a,
+  A: 'a,
+{
+  run_cont_t(m, Box::new(move |a| Lift::lift(c(a)))).run()
+}
+
+/// Run a pure computation with the final continuation.
+pub fn eval_cont<'a, R: 'a>(m: Cont<'a, R, R>) -> R {
+  eval_cont_t(m).run()
+}
+
+impl<'a, R, S, M> MonadState<'a, S> for ContTKind<R, M>
+where
+  R: 'a,
+  S: 'a,
+  M: MonadState<'a, S>,
+{
+  fn state<A: 'a>(f: impl FnOnce(S) -> (A, S) + 'a) -> ContT<'a, R, M, A> {
+    MonadTrans::lift(M::state(f))
+  }
+}
+
+impl<'a, R, E, M> MonadReader<'a, E> for ContTKind<R, M>
+where
+  R: 'a,
+  E: 'a,
+  M: MonadReader<'a, E>,
+{
+  fn ask() -> ContT<'a, R, M, E> {
+    MonadTrans::lift(M::ask())
+  }
+
+  /// The continuation runs in the original environment.
+  fn local<A: 'a>(f: impl FnOnce(E) -> E + 'a, m: ContT<'a, R, M, A>) -> ContT<'a, R, M, A> {
+    ContT::new(move |c| {
+      M::ask().and_then::<R, _>(move |e| {
+        M::local(
+          f,
+          run_cont_t(m, Box::new(move |a| M::local(move |_| e, c(a)))),
+        )
+      })
+    })
+  }
+}
+
+impl<'a, R, M> MonadIo<'a> for ContTKind<R, M>
+where
+  R: 'a,
+  M: MonadIo<'a>,
+{
+  fn lift_io<A: 'a>(f: impl FnOnce() -> A + 'a) -> ContT<'a, R, M, A> {
+    MonadTrans::lift(M::lift_io(f))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    monad::ResultKind,
+    reader::{ask, run_reader_t, ReaderTKind},
+    state::{get, modify, put, run_state, StateTKind},
+  };
+
+  #[test]
+  fn cont() {
+    let r: Cont<String, i32> = m! {
+      x <- <Cont<_, _> as Lift<_>>::lift(1);
+      y <- ContT::new(|c| c(2));
+      return x + y;
+    };
+
+    assert_eq!(run_cont(r, |x| x.to_string()), "3");
+  }
+
+  #[test]
+  fn call_cc_escape() {
+    let safe_div = |x: i32, y: i32| -> Cont<Result<i32, String>, Result<i32, String>> {
+      call_cc(move |exit| {
+        m! {
+          _ <- if y == 0 { exit.escape(Err("division by zero".to_owned())) } else { Lift::lift(()) };
+          return Ok(x / y);
+        }
+      })
+    };
+
+    assert_eq!(eval_cont(safe_div(6, 3)), Ok(2));
+    assert_eq!(
+      eval_cont(safe_div(6, 0)),
+      Err("division by zero".to_owned())
+    );
+  }
+
+  #[test]
+  fn call_cc_state() {
+    type Stack = StateTKind<Vec<i32>, IdentityKind>;
+    let r: ContT<i32, Stack, i32> = m! {
+      x <- call_cc(|exit| m! {
+        _ <- lift modify(|mut v: Vec<i32>| { v.push(1); v });
+        _ <- exit.escape::<()>(10);
+        lift put(vec![])
+      }.and_then(|_| Lift::lift(0)));
+      v <- lift get::<Vec<i32>, IdentityKind>();
+      return x + v.len() as i32;
+    };
+
+    assert_eq!(run_state(eval_cont_t(r), vec![]), (11, vec![1]));
+  }
+
+  #[test]
+  fn monad_reader_local() {
+    type Stack = ContTKind<(i32, i32), ReaderTKind<i32, ResultKind<()>>>;
+    let r = m! {
+      x <- Stack::local(|e| e * 2, Stack::ask());
+      y <- lift ask();
+      return (x, y);
+    };
+
+    assert_eq!(run_reader_t(eval_cont_t(r), 2), Ok((4, 2)));
+  }
+}
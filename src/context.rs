@@ -0,0 +1,92 @@
+//! Annotating errors with context.
+//!
+//! > This module requires the `anyhow` feature.
+//!
+//! In [`m!`](crate::m), a statement can end with `context <message>`, which calls the `context` method of the bound
+//! computation with the message. With [`Context`] in scope, computations producing an [`anyhow::Result`] wrap their
+//! error with the message, so that the error says which step of the block failed. [`ensure`] and [`bail`] fail with
+//! a message, as statements of a block.
+//!
+//! ```rust
+//! use do_notation::{context::{ensure, Context}, m};
+//! use std::collections::HashMap;
+//!
+//! fn port(config: &HashMap<&str, &str>) -> anyhow::Result<u16> {
+//!   m! {
+//!     raw <- config.get("port").copied() context "missing port";
+//!     port <- raw.parse::<u16>() context format!("invalid port {:?}", raw);
+//!     ensure(port >= 1024, "privileged port");
+//!     return port;
+//!   }
+//! }
+//!
+//! let config = HashMap::from([("port", "80x")]);
+//! let e = port(&config).unwrap_err();
+//! assert_eq!(e.to_string(), "invalid port \"80x\"");
+//! assert_eq!(e.root_cause().to_string(), "invalid digit found in string");
+//!
+//! assert_eq!(port(&HashMap::from([("port", "80")])).unwrap_err().to_string(), "privileged port");
+//! assert_eq!(port(&HashMap::from([("port", "8080")])).unwrap(), 8080);
+//! ```
+
+use std::fmt::{Debug, Display};
+
+pub use anyhow::Context;
+
+/// Fail with `message` unless `cond` holds.
+pub fn ensure<M>(cond: bool, message: M) -> anyhow::Result<()>
+where
+  M: Debug + Display + Send + Sync + 'static,
+{
+  if cond {
+    Ok(())
+  } else {
+    Err(anyhow::Error::msg(message))
+  }
+}
+
+/// Fail with `message`.
+pub fn bail<A, M>(message: M) -> anyhow::Result<A>
+where
+  M: Debug + Display + Send + Sync + 'static,
+{
+  Err(anyhow::Error::msg(message))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(s: &str) -> anyhow::Result<i32> {
+    Ok(s.parse()?)
+  }
+
+  #[test]
+  fn context() {
+    let r: anyhow::Result<i32> = m! {
+      x <- parse("1") context "first";
+      _ <- parse("2") context "second";
+      parse("3") context "third";
+      let y = x + 1;
+      parse(&y.to_string()) context "last"
+    };
+
+    assert_eq!(r.unwrap(), 2);
+
+    let r: anyhow::Result<i32> = m! {
+      x <- parse("1") context "first";
+      y <- parse("two") context format!("second, after {}", x);
+      return x + y;
+    };
+
+    assert_eq!(r.unwrap_err().to_string(), "second, after 1");
+
+    let r: anyhow::Result<()> = m! {
+      ensure(true, "never");
+      x <- Some(1) context "missing";
+      if x > 0 { bail("positive") } else { Ok(()) }
+    };
+
+    assert_eq!(r.unwrap_err().to_string(), "positive");
+  }
+}
@@ -0,0 +1,109 @@
+//! Diagnostic-rich errors.
+//!
+//! > This module requires the `miette` feature.
+//!
+//! Blocks producing a [`miette::Result`] keep the pretty, labeled output of `miette`: in [`m!`](crate::m), a
+//! statement can end with `wrap_err <message>`, which calls the `wrap_err` method of the bound computation — from
+//! [`WrapErr`], which must be in scope — adding the message on top of the error. [`report`] converts the results of
+//! any [`Diagnostic`] error, and [`fail_at`] fails with a diagnostic pointing at a span of the source code:
+//!
+//! ```rust
+//! use do_notation::{diagnostics::{fail_at, report, WrapErr}, m};
+//!
+//! #[derive(Debug, miette::Diagnostic, thiserror::Error)]
+//! #[error("empty input")]
+//! struct Empty;
+//!
+//! fn non_empty(source: &str) -> Result<&str, Empty> {
+//!   if source.is_empty() { Err(Empty) } else { Ok(source) }
+//! }
+//!
+//! fn parse(source: &str) -> miette::Result<u8> {
+//!   m! {
+//!     source <- report(non_empty(source)) wrap_err "invalid configuration";
+//!     let digits = source.trim_end();
+//!     match digits.find(|c: char| !c.is_ascii_digit()) {
+//!       Some(i) => fail_at(source.to_owned(), (i, 1), "not a digit", "invalid number"),
+//!       None => digits.parse::<u8>().map_err(|e| miette::miette!("{}", e)),
+//!     }
+//!   }
+//! }
+//!
+//! assert_eq!(parse("42").unwrap(), 42);
+//!
+//! let e = parse("").unwrap_err();
+//! assert_eq!(e.to_string(), "invalid configuration");
+//! assert_eq!(e.root_cause().to_string(), "empty input");
+//!
+//! let e = parse("4x2").unwrap_err();
+//! assert_eq!(e.to_string(), "invalid number");
+//! assert_eq!(e.labels().unwrap().next().unwrap().offset(), 1);
+//! ```
+
+use miette::{Diagnostic, LabeledSpan, MietteDiagnostic, Report, SourceCode, SourceSpan};
+use std::fmt::Display;
+
+pub use miette::WrapErr;
+
+/// Convert the error of `r` to a [`Report`].
+pub fn report<A, E>(r: Result<A, E>) -> miette::Result<A>
+where
+  E: Diagnostic + Send + Sync + 'static,
+{
+  r.map_err(Report::new)
+}
+
+/// Fail with `message`, labeling `span` of `source` with `label`.
+pub fn fail_at<A>(
+  source: impl SourceCode + 'static,
+  span: impl Into<SourceSpan>,
+  label: impl Into<String>,
+  message: impl Display,
+) -> miette::Result<A> {
+  let diagnostic =
+    MietteDiagnostic::new(message.to_string()).with_label(LabeledSpan::at(span, label.into()));
+  Err(Report::new(diagnostic).with_source_code(source))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn digit(source: &str, i: usize) -> miette::Result<u32> {
+    match source[i..].chars().next().and_then(|c| c.to_digit(10)) {
+      Some(d) => Ok(d),
+      None => super::fail_at(source.to_owned(), (i, 1), "not a digit", "invalid digit"),
+    }
+  }
+
+  #[test]
+  fn wrap_err() {
+    let r: miette::Result<u32> = m! {
+      a <- digit("12", 0) wrap_err "first digit";
+      b <- digit("1x", 1) wrap_err format!("second digit, after {}", a);
+      return a + b;
+    };
+
+    let e = r.unwrap_err();
+    assert_eq!(e.to_string(), "second digit, after 1");
+    assert_eq!(e.root_cause().to_string(), "invalid digit");
+
+    let r: miette::Result<u32> = m! {
+      a <- digit("12", 0);
+      digit("12", 1) wrap_err "second digit";
+      digit("12", a as usize) wrap_err "last digit"
+    };
+
+    assert_eq!(r.unwrap(), 2);
+  }
+
+  #[test]
+  fn fail_at() {
+    let e = super::fail_at::<()>("abc", (1, 2), "here", "failed").unwrap_err();
+    let label = e.labels().unwrap().next().unwrap();
+
+    assert_eq!(e.to_string(), "failed");
+    assert_eq!((label.offset(), label.len()), (1, 2));
+    assert_eq!(label.label(), Some("here"));
+  }
+}
@@ -0,0 +1,697 @@
+//! Extensible effects.
+//!
+//! [`Eff<U, A>`] is a computation producing a value of type `A` and performing operations of the effects listed in
+//! the row `U`. Unlike with monad transformers, effects are not stacked in a fixed order: a row is an open union
+//! of effects, so effects defined independently — possibly in different crates — can be mixed in one computation
+//! and handled one at a time, in any order.
+//!
+//! An [`Effect`] is a functor of operations: each operation carries what it needs, and a continuation of type `K`
+//! waiting for its answer. Rows are built with [`Cons`] and [`Nil`], and the [`Member`] trait [injects](Member::inject)
+//! operations into a row and [projects](Member::project) them out of it. Effects are handled with [`handle`] and
+//! [`handle_with`], or with handlers defined by [`handler!`](crate::handler).
+//!
+//! ```rust
+//! use do_notation::{
+//!   eff::{handle, handle_with, run, send, Cons, Eff, Effect, Member, Nil, Resume},
+//!   m,
+//! };
+//! use std::marker::PhantomData;
+//!
+//! // A state effect.
+//! enum StateOp<'a, S, K> {
+//!   Get(Resume<'a, S, K>),
+//!   Put(S, K),
+//! }
+//!
+//! struct State<S>(PhantomData<S>);
+//!
+//! impl<'a, S: 'a> Effect<'a> for State<S> {
+//!   type Op<K: 'a> = StateOp<'a, S, K>;
+//!
+//!   fn map<K: 'a, L: 'a>(op: StateOp<'a, S, K>, f: impl FnOnce(K) -> L + 'a) -> StateOp<'a, S, L> {
+//!     match op {
+//!       StateOp::Get(k) => StateOp::Get(Box::new(move |s| f(k(s)))),
+//!       StateOp::Put(s, k) => StateOp::Put(s, f(k)),
+//!     }
+//!   }
+//! }
+//!
+//! fn get<'a, U: Member<'a, State<i32>, I>, I>() -> Eff<'a, U, i32> {
+//!   send::<State<i32>, _, _, _>(StateOp::Get(Box::new(Eff::Pure)))
+//! }
+//!
+//! fn put<'a, U: Member<'a, State<i32>, I>, I>(s: i32) -> Eff<'a, U, ()> {
+//!   send::<State<i32>, _, _, _>(StateOp::Put(s, Eff::Pure(())))
+//! }
+//!
+//! // A logging effect.
+//! struct Log;
+//!
+//! impl<'a> Effect<'a> for Log {
+//!   type Op<K: 'a> = (String, K);
+//!
+//!   fn map<K: 'a, L: 'a>((msg, k): (String, K), f: impl FnOnce(K) -> L + 'a) -> (String, L) {
+//!     (msg, f(k))
+//!   }
+//! }
+//!
+//! fn log<'a, U: Member<'a, Log, I>, I>(msg: String) -> Eff<'a, U, ()> {
+//!   send::<Log, _, _, _>((msg, Eff::Pure(())))
+//! }
+//!
+//! // A computation using both effects, and two independent handlers.
+//! type Row = Cons<Log, Cons<State<i32>, Nil>>;
+//!
+//! let counter: Eff<Row, i32> = m! {
+//!   n <- get();
+//!   log(format!("n = {}", n));
+//!   put(n + 1);
+//!   return n * 10;
+//! };
+//!
+//! let logged = handle::<Log, _, _, _, _>(counter, |a| Eff::Pure((a, Vec::new())), |(msg, k)| {
+//!   k.and_then(move |(a, mut msgs)| {
+//!     msgs.insert(0, msg);
+//!     Eff::Pure((a, msgs))
+//!   })
+//! });
+//!
+//! let stateful = handle_with::<State<i32>, _, _, _, _, _>(
+//!   logged,
+//!   1,
+//!   |s, a| Eff::Pure((a, s)),
+//!   |s, op| match op {
+//!     StateOp::Get(k) => k(s)(s),
+//!     StateOp::Put(s, k) => k(s),
+//!   },
+//! );
+//!
+//! assert_eq!(run(stateful), ((10, vec!["n = 1".to_owned()]), 2));
+//! ```
+
+use crate::{
+  monad::{Kind, Monad},
+  Lift,
+};
+use std::{marker::PhantomData, rc::Rc};
+
+/// Effects, seen as functors of operations.
+///
+/// An operation of type `Op<K>` carries a continuation of type `K`, to call with the answer of the operation.
+///
+/// Implementors must ensure that `map` only changes the continuations.
+pub trait Effect<'a>: 'a {
+  /// Operations of the effect, with continuations of type `K`.
+  type Op<K: 'a>: 'a;
+
+  /// Apply `f` to the continuation of `op`.
+  fn map<K: 'a, L: 'a>(op: Self::Op<K>, f: impl FnOnce(K) -> L + 'a) -> Self::Op<L>;
+}
+
+/// Continuation of an operation answering values of type `S`.
+pub type Resume<'a, S, K> = Box<dyn FnOnce(S) -> K + 'a>;
+
+/// Rows of effects.
+///
+/// A row is either [`Nil`] or a [`Cons`] of an effect and another row. Its union holds an operation of any of its
+/// effects.
+pub trait Effects<'a>: 'a {
+  /// Operations of the effects of the row, with continuations of type `K`.
+  type Union<K: 'a>: 'a;
+
+  /// Apply `f` to the continuation of the operation in `u`.
+  fn map<K: 'a, L: 'a>(u: Self::Union<K>, f: impl FnOnce(K) -> L + 'a) -> Self::Union<L>;
+}
+
+/// The row without effects.
+#[derive(Debug)]
+pub struct Nil;
+
+/// The row of the effect `E` and the effects of `U`.
+#[derive(Debug)]
+pub struct Cons<E, U>(PhantomData<(E, U)>);
+
+/// The union of no operations.
+#[derive(Debug)]
+pub enum Void {}
+
+/// The union of the operation `H` and the operations of `T`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Union<H, T> {
+  /// Operation of the first effect.
+  Here(H),
+  /// Operation of one of the other effects.
+  There(T),
+}
+
+impl<'a> Effects<'a> for Nil {
+  type Union<K: 'a> = Void;
+
+  fn map<K: 'a, L: 'a>(u: Void, _: impl FnOnce(K) -> L + 'a) -> Void {
+    match u {}
+  }
+}
+
+impl<'a, E, U> Effects<'a> for Cons<E, U>
+where
+  E: Effect<'a>,
+  U: Effects<'a>,
+{
+  type Union<K: 'a> = Union<E::Op<K>, U::Union<K>>;
+
+  fn map<K: 'a, L: 'a>(u: Self::Union<K>, f: impl FnOnce(K) -> L + 'a) -> Self::Union<L> {
+    match u {
+      Union::Here(op) => Union::Here(E::map(op, f)),
+      Union::There(u) => Union::There(U::map(u, f)),
+    }
+  }
+}
+
+/// Index of the first effect of a row.
+#[derive(Debug)]
+pub struct Head;
+
+/// Index of an effect in the tail of a row, at index `I` in the tail.
+#[derive(Debug)]
+pub struct Tail<I>(PhantomData<I>);
+
+/// Rows with the effect `E`, at index `I`.
+///
+/// The index is always inferred, and is only there to tell the implementations at different positions apart.
+pub trait Member<'a, E, I>: Effects<'a>
+where
+  E: Effect<'a>,
+{
+  /// The row without `E`.
+  type Rest: Effects<'a>;
+
+  /// Embed an operation of `E` into the union of the row.
+  fn inject<K: 'a>(op: E::Op<K>) -> Self::Union<K>;
+
+  /// Extract an operation of `E` from the union of the row, or the operation of another effect.
+  fn project<K: 'a>(u: Self::Union<K>) -> Result<E::Op<K>, <Self::Rest as Effects<'a>>::Union<K>>;
+}
+
+impl<'a, E, U> Member<'a, E, Head> for Cons<E, U>
+where
+  E: Effect<'a>,
+  U: Effects<'a>,
+{
+  type Rest = U;
+
+  fn inject<K: 'a>(op: E::Op<K>) -> Self::Union<K> {
+    Union::Here(op)
+  }
+
+  fn project<K: 'a>(u: Self::Union<K>) -> Result<E::Op<K>, U::Union<K>> {
+    match u {
+      Union::Here(op) => Ok(op),
+      Union::There(u) => Err(u),
+    }
+  }
+}
+
+impl<'a, E, F, U, I> Member<'a, E, Tail<I>> for Cons<F, U>
+where
+  E: Effect<'a>,
+  F: Effect<'a>,
+  U: Member<'a, E, I>,
+{
+  type Rest = Cons<F, U::Rest>;
+
+  fn inject<K: 'a>(op: E::Op<K>) -> Self::Union<K> {
+    Union::There(U::inject(op))
+  }
+
+  fn project<K: 'a>(
+    u: Self::Union<K>,
+  ) -> Result<E::Op<K>, Union<F::Op<K>, <U::Rest as Effects<'a>>::Union<K>>> {
+    match u {
+      Union::Here(op) => Err(Union::Here(op)),
+      Union::There(u) => U::project(u).map_err(Union::There),
+    }
+  }
+}
+
+/// Computations producing a value of type `A` and performing operations of the effects of `U`.
+pub enum Eff<'a, U: Effects<'a>, A: 'a> {
+  /// Computation producing its value without any operation.
+  Pure(A),
+  /// Computation performing an operation, and continuing with its answer.
+  Impure(Box<U::Union<Eff<'a, U, A>>>),
+}
+
+impl<'a, U, A> Eff<'a, U, A>
+where
+  U: Effects<'a>,
+  A: 'a,
+{
+  /// Sequence `f` after this computation.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(A) -> Eff<'a, U, B> + 'a) -> Eff<'a, U, B> {
+    match self {
+      Eff::Pure(a) => f(a),
+      Eff::Impure(u) => Eff::Impure(Box::new(U::map(*u, move |eff| eff.and_then(f)))),
+    }
+  }
+}
+
+impl<'a, U, A> Lift<A> for Eff<'a, U, A>
+where
+  U: Effects<'a>,
+  A: 'a,
+{
+  fn lift(a: A) -> Self {
+    Eff::Pure(a)
+  }
+}
+
+/// [`Kind`] of `Eff<U, _>`.
+#[derive(Debug)]
+pub struct EffKind<U>(PhantomData<U>);
+
+impl<'a, U: Effects<'a>> Kind<'a> for EffKind<U> {
+  type Of<A: 'a> = Eff<'a, U, A>;
+}
+
+impl<'a, U, A> Monad<'a> for Eff<'a, U, A>
+where
+  U: Effects<'a>,
+  A: 'a,
+{
+  type Item = A;
+  type Kind = EffKind<U>;
+
+  fn and_then<B, F>(self, f: F) -> Eff<'a, U, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Eff<'a, U, B> + 'a,
+  {
+    Eff::and_then(self, f)
+  }
+}
+
+/// Perform an operation of the effect `E`.
+///
+/// The continuation of the operation is usually [`Eff::Pure`], which returns the answer of the operation.
+pub fn send<'a, E, U, I, A>(op: E::Op<Eff<'a, U, A>>) -> Eff<'a, U, A>
+where
+  E: Effect<'a>,
+  U: Member<'a, E, I>,
+  A: 'a,
+{
+  Eff::Impure(Box::new(U::inject(op)))
+}
+
+/// Extract the value of a computation without effects.
+pub fn run<'a, A>(eff: Eff<'a, Nil, A>) -> A {
+  match eff {
+    Eff::Pure(a) => a,
+    Eff::Impure(u) => match *u {},
+  }
+}
+
+/// Handle the effect `E` of a computation.
+///
+/// `ret` turns the value of the computation into the value of the handled computation, and `h` handles every
+/// operation of `E`, whose continuation is already handled. Operations of the other effects are left as is.
+pub fn handle<'a, E, U, I, A, B>(
+  eff: Eff<'a, U, A>,
+  ret: impl FnOnce(A) -> Eff<'a, U::Rest, B> + 'a,
+  h: impl Fn(E::Op<Eff<'a, U::Rest, B>>) -> Eff<'a, U::Rest, B> + 'a,
+) -> Eff<'a, U::Rest, B>
+where
+  E: Effect<'a>,
+  U: Member<'a, E, I>,
+  I: 'a,
+  A: 'a,
+  B: 'a,
+{
+  handle_with::<E, U, I, (), A, B>(
+    eff,
+    (),
+    move |(), a| ret(a),
+    move |(), op| h(E::map(op, |k| k(()))),
+  )
+}
+
+/// Handle the effect `E` of a computation, threading a state of type `S` through the handler.
+///
+/// `ret` turns the final state and the value of the computation into the value of the handled computation, and `h`
+/// handles every operation of `E` with the current state. The continuation of the operation takes the next state.
+/// Operations of the other effects are left as is.
+pub fn handle_with<'a, E, U, I, S, A, B>(
+  eff: Eff<'a, U, A>,
+  s: S,
+  ret: impl FnOnce(S, A) -> Eff<'a, U::Rest, B> + 'a,
+  h: impl Fn(S, E::Op<Resume<'a, S, Eff<'a, U::Rest, B>>>) -> Eff<'a, U::Rest, B> + 'a,
+) -> Eff<'a, U::Rest, B>
+where
+  E: Effect<'a>,
+  U: Member<'a, E, I>,
+  I: 'a,
+  S: 'a,
+  A: 'a,
+  B: 'a,
+{
+  fn go<'a, E, U, I, S, A, B, R, H>(
+    eff: Eff<'a, U, A>,
+    s: S,
+    ret: R,
+    h: Rc<H>,
+  ) -> Eff<'a, U::Rest, B>
+  where
+    E: Effect<'a>,
+    U: Member<'a, E, I>,
+    I: 'a,
+    S: 'a,
+    A: 'a,
+    B: 'a,
+    R: FnOnce(S, A) -> Eff<'a, U::Rest, B> + 'a,
+    H: Fn(S, E::Op<Resume<'a, S, Eff<'a, U::Rest, B>>>) -> Eff<'a, U::Rest, B> + 'a,
+  {
+    match eff {
+      Eff::Pure(a) => ret(s, a),
+
+      Eff::Impure(u) => match U::project(*u) {
+        Ok(op) => {
+          let h_ = h.clone();
+          let op = E::map(op, move |eff| -> Resume<'a, S, _> {
+            Box::new(move |s| go::<E, U, I, S, A, B, R, H>(eff, s, ret, h_))
+          });
+
+          h(s, op)
+        }
+
+        Err(u) => Eff::Impure(Box::new(U::Rest::map(u, move |eff| {
+          go::<E, U, I, S, A, B, R, H>(eff, s, ret, h)
+        }))),
+      },
+    }
+  }
+
+  go::<E, U, I, S, A, B, _, _>(eff, s, ret, Rc::new(h))
+}
+
+/// Define a handler of an effect.
+///
+/// The handler is a function taking a computation of type `Eff<U, A>`, where the row `U` has the effect, and
+/// returning the computation without the effect. Its body matches the operations of the effect, whose
+/// continuations are already handled, and a `return` arm turns the value of type `A` into the value of the handled
+/// computation:
+///
+/// ```rust
+/// use do_notation::{eff::{run, send, Cons, Eff, Effect, Member, Nil, Resume}, handler, m};
+///
+/// enum Console<'a, K> {
+///   Read(Resume<'a, String, K>),
+///   Write(String, K),
+/// }
+///
+/// struct ConsoleEffect;
+///
+/// impl<'a> Effect<'a> for ConsoleEffect {
+///   type Op<K: 'a> = Console<'a, K>;
+///
+///   fn map<K: 'a, L: 'a>(op: Console<'a, K>, f: impl FnOnce(K) -> L + 'a) -> Console<'a, L> {
+///     match op {
+///       Console::Read(k) => Console::Read(Box::new(move |line| f(k(line)))),
+///       Console::Write(line, k) => Console::Write(line, f(k)),
+///     }
+///   }
+/// }
+///
+/// handler! {
+///   /// Write to a vector of lines.
+///   fn capture<'a>(eff: Eff<ConsoleEffect, A>) -> (A, Vec<String>) {
+///     return a => Eff::Pure((a, Vec::new())),
+///     Console::Read(k) => k("Dimitri".to_owned()),
+///     Console::Write(line, k) => k.and_then(move |(a, mut lines)| {
+///       lines.insert(0, line);
+///       Eff::Pure((a, lines))
+///     }),
+///   }
+/// }
+///
+/// let greet: Eff<Cons<ConsoleEffect, Nil>, ()> = m! {
+///   name <- send::<ConsoleEffect, _, _, _>(Console::Read(Box::new(Eff::Pure)));
+///   send::<ConsoleEffect, _, _, _>(Console::Write(format!("Hello, {}!", name), Eff::Pure(())))
+/// };
+///
+/// assert_eq!(run(capture(greet)), ((), vec!["Hello, Dimitri!".to_owned()]));
+/// ```
+///
+/// The handler can also thread a state, introduced after the computation. The state is then bound in every arm, and
+/// the continuations of the operations take the next state — see [`handle_with`](crate::eff::handle_with):
+///
+/// ```rust
+/// # use do_notation::{eff::{run, send, Cons, Eff, Effect, Member, Nil, Resume}, handler, m};
+/// # enum Console<'a, K> {
+/// #   Read(Resume<'a, String, K>),
+/// #   Write(String, K),
+/// # }
+/// # struct ConsoleEffect;
+/// # impl<'a> Effect<'a> for ConsoleEffect {
+/// #   type Op<K: 'a> = Console<'a, K>;
+/// #   fn map<K: 'a, L: 'a>(op: Console<'a, K>, f: impl FnOnce(K) -> L + 'a) -> Console<'a, L> {
+/// #     match op {
+/// #       Console::Read(k) => Console::Read(Box::new(move |line| f(k(line)))),
+/// #       Console::Write(line, k) => Console::Write(line, f(k)),
+/// #     }
+/// #   }
+/// # }
+/// handler! {
+///   /// Read from, and write to, a vector of lines.
+///   fn replay<'a>(eff: Eff<ConsoleEffect, A>, lines: Vec<String>) -> (A, Vec<String>) {
+///     return a => Eff::Pure((a, lines)),
+///     Console::Read(k) => {
+///       let mut lines = lines;
+///       let line = lines.remove(0);
+///       k(line)(lines)
+///     },
+///     Console::Write(line, k) => k(lines.into_iter().chain(Some(line)).collect()),
+///   }
+/// }
+///
+/// let echo: Eff<Cons<ConsoleEffect, Nil>, ()> = m! {
+///   line <- send::<ConsoleEffect, _, _, _>(Console::Read(Box::new(Eff::Pure)));
+///   send::<ConsoleEffect, _, _, _>(Console::Write(line.to_uppercase(), Eff::Pure(())))
+/// };
+///
+/// assert_eq!(run(replay(echo, vec!["hey".to_owned()])), ((), vec!["HEY".to_owned()]));
+/// ```
+///
+/// The generated function is generic over the row `U` and the index `I` of the effect in the row. The type parameters
+/// of the handler are bounded by its lifetime; other bounds go into a `where` clause, one bound per predicate.
+#[macro_export]
+macro_rules! handler {
+  (
+    $(#[$attr:meta])*
+    $vis:vis fn $name:ident<$lt:lifetime $(, $g:ident)*>($eff:ident: Eff<$e:ty, $a:ident>) -> $out:ty
+    $(where $($wt:ident: $wb:path),+ $(,)?)?
+    {
+      return $ret:pat => $ret_body:expr,
+      $($op:pat => $op_body:expr),* $(,)?
+    }
+  ) => {
+    $(#[$attr])*
+    $vis fn $name<$lt, U, I, $a $(, $g)*>(
+      $eff: $crate::eff::Eff<$lt, U, $a>,
+    ) -> $crate::eff::Eff<$lt, <U as $crate::eff::Member<$lt, $e, I>>::Rest, $out>
+    where
+      U: $crate::eff::Member<$lt, $e, I>,
+      I: $lt,
+      $a: $lt,
+      $($g: $lt,)*
+      $($($wt: $wb,)+)?
+    {
+      $crate::eff::handle::<$e, U, I, $a, $out>(
+        $eff,
+        move |$ret| $ret_body,
+        move |op| match op {
+          $($op => $op_body),*
+        },
+      )
+    }
+  };
+
+  (
+    $(#[$attr:meta])*
+    $vis:vis fn $name:ident<$lt:lifetime $(, $g:ident)*>($eff:ident: Eff<$e:ty, $a:ident>, $s:ident: $st:ty) -> $out:ty
+    $(where $($wt:ident: $wb:path),+ $(,)?)?
+    {
+      return $ret:pat => $ret_body:expr,
+      $($op:pat => $op_body:expr),* $(,)?
+    }
+  ) => {
+    $(#[$attr])*
+    $vis fn $name<$lt, U, I, $a $(, $g)*>(
+      $eff: $crate::eff::Eff<$lt, U, $a>,
+      $s: $st,
+    ) -> $crate::eff::Eff<$lt, <U as $crate::eff::Member<$lt, $e, I>>::Rest, $out>
+    where
+      U: $crate::eff::Member<$lt, $e, I>,
+      I: $lt,
+      $a: $lt,
+      $($g: $lt,)*
+      $($($wt: $wb,)+)?
+    {
+      $crate::eff::handle_with::<$e, U, I, $st, $a, $out>(
+        $eff,
+        $s,
+        move |$s, $ret| $ret_body,
+        move |$s, op| match op {
+          $($op => $op_body),*
+        },
+      )
+    }
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  enum AskOp<'a, K> {
+    Ask(Resume<'a, i32, K>),
+  }
+
+  struct Ask;
+
+  impl<'a> Effect<'a> for Ask {
+    type Op<K: 'a> = AskOp<'a, K>;
+
+    fn map<K: 'a, L: 'a>(op: AskOp<'a, K>, f: impl FnOnce(K) -> L + 'a) -> AskOp<'a, L> {
+      match op {
+        AskOp::Ask(k) => AskOp::Ask(Box::new(move |n| f(k(n)))),
+      }
+    }
+  }
+
+  struct Fail;
+
+  impl<'a> Effect<'a> for Fail {
+    type Op<K: 'a> = String;
+
+    fn map<K: 'a, L: 'a>(op: String, _: impl FnOnce(K) -> L + 'a) -> String {
+      op
+    }
+  }
+
+  fn ask<'a, U: Member<'a, Ask, I>, I>() -> Eff<'a, U, i32> {
+    send::<Ask, _, _, _>(AskOp::Ask(Box::new(Eff::Pure)))
+  }
+
+  fn fail<'a, U: Member<'a, Fail, I>, I, A>(e: &str) -> Eff<'a, U, A> {
+    send::<Fail, _, _, _>(e.to_owned())
+  }
+
+  fn run_ask<'a, U: Member<'a, Ask, I>, I: 'a, A: 'a>(
+    eff: Eff<'a, U, A>,
+    n: i32,
+  ) -> Eff<'a, U::Rest, A> {
+    handle::<Ask, _, _, _, _>(eff, Eff::Pure, move |AskOp::Ask(k)| k(n))
+  }
+
+  fn run_fail<'a, U: Member<'a, Fail, I>, I: 'a, A: 'a>(
+    eff: Eff<'a, U, A>,
+  ) -> Eff<'a, U::Rest, Result<A, String>> {
+    handle::<Fail, _, _, _, _>(eff, |a| Eff::Pure(Ok(a)), |e| Eff::Pure(Err(e)))
+  }
+
+  fn half<'a, U, I, J>() -> Eff<'a, U, i32>
+  where
+    U: Member<'a, Ask, I> + Member<'a, Fail, J>,
+  {
+    m! {
+      n <- ask();
+      if n % 2 == 0 { Eff::Pure(n / 2) } else { fail("odd") }
+    }
+  }
+
+  #[test]
+  fn union() {
+    type Row = Cons<Ask, Cons<Fail, Nil>>;
+
+    let u = <Row as Member<Fail, _>>::inject::<()>("failure".to_owned());
+    assert!(matches!(u, Union::There(Union::Here(_))));
+
+    let u = <Row as Member<Fail, _>>::project(u);
+    assert_eq!(u.ok(), Some("failure".to_owned()));
+
+    let u = <Row as Member<Ask, _>>::inject(AskOp::Ask(Box::new(|n| n + 1)));
+    assert!(<Row as Member<Fail, _>>::project(u).is_err());
+  }
+
+  #[test]
+  fn handlers_in_any_order() {
+    assert_eq!(
+      run(run_fail(run_ask(
+        half::<Cons<Ask, Cons<Fail, Nil>>, _, _>(),
+        4
+      ))),
+      Ok(2)
+    );
+    assert_eq!(
+      run(run_fail(run_ask(
+        half::<Cons<Fail, Cons<Ask, Nil>>, _, _>(),
+        3
+      ))),
+      Err("odd".to_owned())
+    );
+    assert_eq!(
+      run(run_ask(
+        run_fail(half::<Cons<Ask, Cons<Fail, Nil>>, _, _>()),
+        3
+      )),
+      Err("odd".to_owned())
+    );
+  }
+
+  handler! {
+    fn run_ask_from<'a>(eff: Eff<Ask, A>, n: i32) -> (A, i32) {
+      return a => Eff::Pure((a, n)),
+      AskOp::Ask(k) => k(n)(n + 1),
+    }
+  }
+
+  handler! {
+    fn run_fail_with<'a, E>(eff: Eff<Fail, A>) -> Result<A, E>
+    where
+      E: From<String>,
+    {
+      return a => Eff::Pure(Ok(a)),
+      e => Eff::Pure(Err(E::from(e))),
+    }
+  }
+
+  #[test]
+  fn handler() {
+    let r = run_ask_from(
+      run_fail_with::<_, _, _, String>(half::<Cons<Fail, Cons<Ask, Nil>>, _, _>()),
+      2,
+    );
+    assert_eq!(run(r), (Ok(1), 3));
+
+    let r =
+      run_fail_with::<_, _, _, String>(run_ask_from(half::<Cons<Ask, Cons<Fail, Nil>>, _, _>(), 3));
+    assert_eq!(run(r), Err("odd".to_owned()));
+  }
+
+  #[test]
+  fn handle_with() {
+    let r: Eff<Cons<Ask, Nil>, _> = m! {
+      a <- ask();
+      b <- ask();
+      c <- ask();
+      return vec![a, b, c];
+    };
+
+    let r = super::handle_with::<Ask, _, _, _, _, _>(
+      r,
+      0,
+      |_, a| Eff::Pure(a),
+      |n, AskOp::Ask(k)| k(n)(n + 1),
+    );
+    assert_eq!(run(r), vec![0, 1, 2]);
+  }
+}
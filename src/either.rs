@@ -0,0 +1,250 @@
+//! The either monad.
+//!
+//! [`Either<L, R>`] holds a value of one of two types. Unlike [`Result`], none of the sides mean failure; the monad
+//! is right-biased, though: binding an [`Either`] short-circuits on [`Either::Left`].
+//!
+//! ```rust
+//! use do_notation::{either::Either, m};
+//!
+//! fn parity(n: i32) -> Either<&'static str, i32> {
+//!   if n % 2 == 0 { Either::Right(n) } else { Either::Left("odd") }
+//! }
+//!
+//! let r = m! {
+//!   a <- parity(2);
+//!   b <- parity(4);
+//!   return a + b;
+//! };
+//!
+//! assert_eq!(r, Either::Right(6));
+//! assert_eq!(m! { a <- parity(1); parity(a) }, Either::Left("odd"));
+//! ```
+//!
+//! With the `either` feature, the `Either` type of the `either` crate can be used in do-blocks too: it is lifted with
+//! [`Lift`], and bound with `RightBind`, which must be in scope. It converts to and from this [`Either`], and
+//! `into_result` and `from_result` convert it to and from [`Result`].
+
+use crate::{
+  monad::{Kind, Monad},
+  rec::MonadRec,
+  Lift,
+};
+use std::marker::PhantomData;
+
+/// Values of either type `L` or type `R`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Either<L, R> {
+  /// Left value.
+  Left(L),
+  /// Right value.
+  Right(R),
+}
+
+impl<L, R> Either<L, R> {
+  /// Whether the value is on the left side.
+  pub fn is_left(&self) -> bool {
+    matches!(self, Either::Left(_))
+  }
+
+  /// Whether the value is on the right side.
+  pub fn is_right(&self) -> bool {
+    matches!(self, Either::Right(_))
+  }
+
+  /// Left value, if any.
+  pub fn left(self) -> Option<L> {
+    match self {
+      Either::Left(l) => Some(l),
+      Either::Right(_) => None,
+    }
+  }
+
+  /// Right value, if any.
+  pub fn right(self) -> Option<R> {
+    match self {
+      Either::Left(_) => None,
+      Either::Right(r) => Some(r),
+    }
+  }
+
+  /// Swap the sides.
+  pub fn flip(self) -> Either<R, L> {
+    match self {
+      Either::Left(l) => Either::Right(l),
+      Either::Right(r) => Either::Left(r),
+    }
+  }
+
+  /// Eliminate the value with the function of its side.
+  pub fn either<A>(self, f: impl FnOnce(L) -> A, g: impl FnOnce(R) -> A) -> A {
+    match self {
+      Either::Left(l) => f(l),
+      Either::Right(r) => g(r),
+    }
+  }
+
+  /// Bind the right value to `f`.
+  pub fn and_then<B>(self, f: impl FnOnce(R) -> Either<L, B>) -> Either<L, B> {
+    match self {
+      Either::Left(l) => Either::Left(l),
+      Either::Right(r) => f(r),
+    }
+  }
+}
+
+impl<L, R> From<Result<R, L>> for Either<L, R> {
+  fn from(r: Result<R, L>) -> Self {
+    match r {
+      Ok(r) => Either::Right(r),
+      Err(l) => Either::Left(l),
+    }
+  }
+}
+
+impl<L, R> From<Either<L, R>> for Result<R, L> {
+  fn from(e: Either<L, R>) -> Self {
+    match e {
+      Either::Left(l) => Err(l),
+      Either::Right(r) => Ok(r),
+    }
+  }
+}
+
+impl<L, R> Lift<R> for Either<L, R> {
+  fn lift(r: R) -> Self {
+    Either::Right(r)
+  }
+}
+
+/// [`Kind`] of `Either<L, _>`.
+#[derive(Debug)]
+pub struct EitherKind<L>(PhantomData<L>);
+
+impl<'a, L: 'a> Kind<'a> for EitherKind<L> {
+  type Of<A: 'a> = Either<L, A>;
+
+  fn loop_m<A: 'a, B: 'a>(a: A, f: impl FnMut(A) -> Either<L, Either<A, B>> + 'a) -> Either<L, B> {
+    Self::tail_rec_m(a, f)
+  }
+}
+
+impl<'a, L: 'a, R: 'a> Monad<'a> for Either<L, R> {
+  type Item = R;
+  type Kind = EitherKind<L>;
+
+  fn and_then<B, F>(self, f: F) -> Either<L, B>
+  where
+    B: 'a,
+    F: FnOnce(R) -> Either<L, B> + 'a,
+  {
+    Either::and_then(self, f)
+  }
+}
+
+/// Right-biased bind of the `Either` type of the `either` crate, which doesn’t have an `and_then`
+/// method.
+#[cfg(feature = "either")]
+pub trait RightBind<L, R>: Sized {
+  /// Bind the right value to `f`.
+  fn and_then<B>(self, f: impl FnOnce(R) -> ::either::Either<L, B>) -> ::either::Either<L, B>;
+}
+
+#[cfg(feature = "either")]
+impl<L, R> RightBind<L, R> for ::either::Either<L, R> {
+  fn and_then<B>(self, f: impl FnOnce(R) -> ::either::Either<L, B>) -> ::either::Either<L, B> {
+    self.right_and_then(f)
+  }
+}
+
+#[cfg(feature = "either")]
+impl<L, R> Lift<R> for ::either::Either<L, R> {
+  fn lift(r: R) -> Self {
+    ::either::Either::Right(r)
+  }
+}
+
+#[cfg(feature = "either")]
+impl<L, R> From<::either::Either<L, R>> for Either<L, R> {
+  fn from(e: ::either::Either<L, R>) -> Self {
+    match e {
+      ::either::Either::Left(l) => Either::Left(l),
+      ::either::Either::Right(r) => Either::Right(r),
+    }
+  }
+}
+
+#[cfg(feature = "either")]
+impl<L, R> From<Either<L, R>> for ::either::Either<L, R> {
+  fn from(e: Either<L, R>) -> Self {
+    match e {
+      Either::Left(l) => ::either::Either::Left(l),
+      Either::Right(r) => ::either::Either::Right(r),
+    }
+  }
+}
+
+/// Convert an `Either` of the `either` crate to a [`Result`], the left side being the error.
+#[cfg(feature = "either")]
+pub fn into_result<L, R>(e: ::either::Either<L, R>) -> Result<R, L> {
+  match e {
+    ::either::Either::Left(l) => Err(l),
+    ::either::Either::Right(r) => Ok(r),
+  }
+}
+
+/// Convert a [`Result`] to an `Either` of the `either` crate, the error being the left side.
+#[cfg(feature = "either")]
+pub fn from_result<L, R>(r: Result<R, L>) -> ::either::Either<L, R> {
+  match r {
+    Ok(r) => ::either::Either::Right(r),
+    Err(l) => ::either::Either::Left(l),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn either() {
+    let l: Either<i32, &str> = Either::Left(1);
+    let r: Either<i32, &str> = Either::Right("a");
+
+    assert!(l.is_left() && r.is_right());
+    assert_eq!(l.flip(), Either::Right(1));
+    assert_eq!(r.either(|_| 0, str::len), 1);
+    assert_eq!(Result::from(r), Ok("a"));
+    assert_eq!(Either::from(Err::<&str, _>(1)), l);
+  }
+  #[cfg(feature = "either")]
+  #[test]
+  fn either_crate() {
+    fn parity(n: i32) -> ::either::Either<&'static str, i32> {
+      if n % 2 == 0 {
+        ::either::Either::Right(n)
+      } else {
+        ::either::Either::Left("odd")
+      }
+    }
+
+    let r = m! {
+      a <- parity(2);
+      b <- parity(a + 2);
+      return a + b;
+    };
+
+    assert_eq!(r, ::either::Either::Right(6));
+    assert_eq!(
+      m! { a <- parity(1); parity(a) },
+      ::either::Either::Left("odd")
+    );
+
+    assert_eq!(into_result(parity(1)), Err("odd"));
+    assert_eq!(from_result(Ok::<_, &str>(2)), parity(2));
+    assert_eq!(Either::from(parity(1)), Either::Left("odd"));
+    assert_eq!(
+      ::either::Either::from(Either::<&str, _>::Right(2)),
+      parity(2)
+    );
+  }
+}
@@ -0,0 +1,210 @@
+//! Emitting domain events.
+//!
+//! [`Emit<E, A>`] is a writer specialised for event sourcing: a computation emits events of type `E`, in order, and
+//! produces a value of type `A`. Command handlers are then pure do-blocks, returning the events to append to the
+//! event store along with their result:
+//!
+//! ```rust
+//! use do_notation::{emit::{emit, Emit}, m, Lift};
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! enum Event {
+//!   Deposited(u32),
+//!   Withdrawn(u32),
+//!   Overdrawn,
+//! }
+//!
+//! fn withdraw(balance: u32, amount: u32) -> Emit<Event, u32> {
+//!   m! {
+//!     emit(Event::Withdrawn(amount));
+//!     if amount > balance { emit(Event::Overdrawn).map(|()| 0) } else { Lift::lift(balance - amount) }
+//!   }
+//! }
+//!
+//! let handler = m! {
+//!   emit(Event::Deposited(10));
+//!   balance <- withdraw(10, 30);
+//!   return balance;
+//! };
+//!
+//! assert_eq!(
+//!   handler.run(),
+//!   (vec![Event::Deposited(10), Event::Withdrawn(30), Event::Overdrawn], 0)
+//! );
+//! ```
+//!
+//! [`Emit::listen`] exposes the events emitted by a sub-block to the rest of the block, e.g. to decide on further
+//! events.
+
+use crate::{
+  monad::{Kind, Monad},
+  Lift,
+};
+use std::marker::PhantomData;
+
+/// Computations emitting events of type `E`, producing a value of type `A`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Emit<E, A> {
+  events: Vec<E>,
+  value: A,
+}
+
+impl<E, A> Emit<E, A> {
+  /// Produce `value` after emitting `events`.
+  pub fn new(events: Vec<E>, value: A) -> Self {
+    Emit { events, value }
+  }
+
+  /// Events emitted so far.
+  pub fn events(&self) -> &[E] {
+    &self.events
+  }
+
+  /// Bind the value to `f`, whose events are emitted after the ones of this computation.
+  pub fn and_then<B>(mut self, f: impl FnOnce(A) -> Emit<E, B>) -> Emit<E, B> {
+    let Emit { events, value } = f(self.value);
+    self.events.extend(events);
+
+    Emit {
+      events: self.events,
+      value,
+    }
+  }
+
+  /// Apply `f` to the value.
+  pub fn map<B>(self, f: impl FnOnce(A) -> B) -> Emit<E, B> {
+    Emit {
+      events: self.events,
+      value: f(self.value),
+    }
+  }
+
+  /// Produce the emitted events along with the value, still emitting them.
+  pub fn listen(self) -> Emit<E, (A, Vec<E>)>
+  where
+    E: Clone,
+  {
+    Emit {
+      value: (self.value, self.events.clone()),
+      events: self.events,
+    }
+  }
+
+  /// Return the emitted events, in order, and the value.
+  pub fn run(self) -> (Vec<E>, A) {
+    (self.events, self.value)
+  }
+}
+
+impl<E, A> Lift<A> for Emit<E, A> {
+  fn lift(value: A) -> Self {
+    Emit {
+      events: Vec::new(),
+      value,
+    }
+  }
+}
+
+/// [`Kind`] of `Emit<E, _>`.
+#[derive(Debug)]
+pub struct EmitKind<E>(PhantomData<E>);
+
+impl<'a, E: 'a> Kind<'a> for EmitKind<E> {
+  type Of<A: 'a> = Emit<E, A>;
+}
+
+impl<'a, E: 'a, A: 'a> Monad<'a> for Emit<E, A> {
+  type Item = A;
+  type Kind = EmitKind<E>;
+
+  fn and_then<B, F>(self, f: F) -> Emit<E, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Emit<E, B> + 'a,
+  {
+    Emit::and_then(self, f)
+  }
+}
+
+/// Emit `event`.
+pub fn emit<E>(event: E) -> Emit<E, ()> {
+  Emit {
+    events: vec![event],
+    value: (),
+  }
+}
+
+/// Emit `events`, in order.
+pub fn emit_all<E>(events: impl IntoIterator<Item = E>) -> Emit<E, ()> {
+  Emit {
+    events: events.into_iter().collect(),
+    value: (),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::combinators::for_m;
+
+  #[derive(Clone, Debug, PartialEq)]
+  enum Event {
+    Added(&'static str),
+    Removed(&'static str),
+    Emptied,
+  }
+
+  fn clear(items: Vec<&'static str>) -> Emit<Event, usize> {
+    let n = items.len();
+    m! {
+      for_m(items, |item| emit(Event::Removed(item)));
+      emit(Event::Emptied);
+      return n;
+    }
+  }
+
+  #[test]
+  fn events_in_order() {
+    let r = m! {
+      emit_all(vec![Event::Added("a"), Event::Added("b")]);
+      n <- clear(vec!["a", "b"]);
+      return n * 10;
+    };
+
+    assert_eq!(
+      r.events(),
+      &[
+        Event::Added("a"),
+        Event::Added("b"),
+        Event::Removed("a"),
+        Event::Removed("b"),
+        Event::Emptied
+      ]
+    );
+    assert_eq!(r.run().1, 20);
+  }
+
+  #[test]
+  fn listen() {
+    let r = m! {
+      emit(Event::Added("a"));
+      listened <- clear(vec!["a"]).listen();
+      let (n, events) = listened;
+      emit_all(events.into_iter().filter(|e| *e != Event::Emptied).map(|_| Event::Added("undo")));
+      return n;
+    };
+
+    assert_eq!(
+      r.run(),
+      (
+        vec![
+          Event::Added("a"),
+          Event::Removed("a"),
+          Event::Emptied,
+          Event::Added("undo")
+        ],
+        1
+      )
+    );
+  }
+}
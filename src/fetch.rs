@@ -0,0 +1,297 @@
+//! Batching data requests.
+//!
+//! [`Fetch<S, A>`] is the computation of a value of type `A` out of requests to a [`DataSource`] `S`, in the fashion
+//! of Haskell’s Haxl. Computations combined as applicative functors — with [`Fetch::zip_with`], or the
+//! [`combinators`](crate::combinators) over [`FetchKind`] — are independent, so their requests are collected and
+//! sent together, in a single batch. Binds wait for the requests of the bound computation, and start a new batch.
+//! Requests are deduplicated, and their responses cached while running a computation, so naive code fetching the
+//! same data several times requests it once:
+//!
+//! ```rust
+//! use do_notation::{combinators::map_m, fetch::{fetch, DataSource, Fetch}, m};
+//!
+//! // posts have authors, and users have names
+//! #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+//! enum Request {
+//!   Author(u32),
+//!   Name(u32),
+//! }
+//!
+//! #[derive(Default)]
+//! struct Db {
+//!   batches: Vec<Vec<Request>>,
+//! }
+//!
+//! impl DataSource for Db {
+//!   type Request = Request;
+//!   type Response = String;
+//!
+//!   fn fetch(&mut self, requests: &[Request]) -> Vec<String> {
+//!     self.batches.push(requests.to_vec());
+//!     requests
+//!       .iter()
+//!       .map(|r| match r {
+//!         Request::Author(post) => (post % 2).to_string(),
+//!         Request::Name(user) => format!("user{}", user),
+//!       })
+//!       .collect()
+//!   }
+//! }
+//!
+//! fn author_name<'a>(post: u32) -> Fetch<'a, Db, String> {
+//!   m! {
+//!     author <- fetch::<Db>(Request::Author(post));
+//!     fetch(Request::Name(author.parse().unwrap()))
+//!   }
+//! }
+//!
+//! let mut db = Db::default();
+//! let names = map_m([1, 2, 3], author_name).run(&mut db);
+//!
+//! assert_eq!(names, vec!["user1", "user0", "user1"]);
+//! assert_eq!(
+//!   db.batches,
+//!   vec![
+//!     vec![Request::Author(1), Request::Author(2), Request::Author(3)],
+//!     vec![Request::Name(1), Request::Name(0)],
+//!   ]
+//! );
+//! ```
+//!
+//! Binds of a do-block are sequential: bind independent computations together — with [`Fetch::zip_with`] or
+//! [`map_m`](crate::combinators::map_m) — to have their requests batched.
+
+use crate::{
+  applicative::{Applicative, Applied, Functor},
+  Lift,
+};
+use std::{
+  collections::{HashMap, HashSet},
+  hash::Hash,
+  marker::PhantomData,
+};
+
+/// Sources of data, answering batches of requests.
+pub trait DataSource {
+  /// Requests.
+  type Request: Clone + Eq + Hash;
+
+  /// Responses to requests.
+  type Response: Clone;
+
+  /// Answer a batch of distinct requests, with one response per request, in the same order.
+  fn fetch(&mut self, requests: &[Self::Request]) -> Vec<Self::Response>;
+}
+
+/// Responses to the requests sent so far.
+type Cache<S> = HashMap<<S as DataSource>::Request, <S as DataSource>::Response>;
+
+/// A computation, done or waiting for requests.
+enum Step<'a, S: DataSource, A> {
+  Done(A),
+  #[allow(clippy::type_complexity)]
+  Blocked(
+    Vec<S::Request>,
+    Box<dyn FnOnce(&Cache<S>) -> Fetch<'a, S, A> + 'a>,
+  ),
+}
+
+/// Computations requesting data to a [`DataSource`] `S`, producing a value of type `A`.
+pub struct Fetch<'a, S: DataSource, A>(Step<'a, S, A>);
+
+impl<'a, S, A> Fetch<'a, S, A>
+where
+  S: DataSource + 'a,
+  S::Request: 'a,
+  A: 'a,
+{
+  /// Bind the value to `f`, once the requests of the computation are answered.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(A) -> Fetch<'a, S, B> + 'a) -> Fetch<'a, S, B> {
+    match self.0 {
+      Step::Done(a) => f(a),
+      Step::Blocked(requests, k) => Fetch(Step::Blocked(
+        requests,
+        Box::new(move |cache| k(cache).and_then(f)),
+      )),
+    }
+  }
+
+  /// Apply `f` to the value.
+  pub fn map<B: 'a>(self, f: impl FnOnce(A) -> B + 'a) -> Fetch<'a, S, B> {
+    self.and_then(move |a| Lift::lift(f(a)))
+  }
+
+  /// Combine the value with the one of `other` with `f`, batching the requests of both computations.
+  pub fn zip_with<B: 'a, C: 'a>(
+    self,
+    other: Fetch<'a, S, B>,
+    f: impl FnOnce(A, B) -> C + 'a,
+  ) -> Fetch<'a, S, C> {
+    match (self.0, other.0) {
+      (Step::Done(a), fb) => Fetch(fb).map(move |b| f(a, b)),
+      (fa, Step::Done(b)) => Fetch(fa).map(move |a| f(a, b)),
+      (Step::Blocked(mut requests, ka), Step::Blocked(requests_b, kb)) => {
+        requests.extend(requests_b);
+        Fetch(Step::Blocked(
+          requests,
+          Box::new(move |cache| ka(cache).zip_with(kb(cache), f)),
+        ))
+      }
+    }
+  }
+
+  /// Run the computation, sending its requests to `source` in batches.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `source` doesn’t answer every request of a batch.
+  pub fn run(self, source: &mut S) -> A {
+    let mut cache = Cache::<S>::new();
+    let mut fetch = self;
+
+    loop {
+      match fetch.0 {
+        Step::Done(a) => return a,
+
+        Step::Blocked(requests, k) => {
+          let mut seen = HashSet::new();
+          let batch: Vec<_> = requests
+            .into_iter()
+            .filter(|request| !cache.contains_key(request) && seen.insert(request.clone()))
+            .collect();
+
+          if !batch.is_empty() {
+            let responses = source.fetch(&batch);
+            assert_eq!(
+              responses.len(),
+              batch.len(),
+              "the data source must answer every request"
+            );
+            cache.extend(batch.into_iter().zip(responses));
+          }
+
+          fetch = k(&cache);
+        }
+      }
+    }
+  }
+}
+
+impl<'a, S: DataSource, A> Lift<A> for Fetch<'a, S, A> {
+  fn lift(a: A) -> Self {
+    Fetch(Step::Done(a))
+  }
+}
+
+/// [`Applicative`] of `Fetch<'_, S, _>`, batching requests.
+#[derive(Debug)]
+pub struct FetchKind<S>(PhantomData<S>);
+
+impl<'a, S> Functor<'a> for FetchKind<S>
+where
+  S: DataSource + 'a,
+  S::Request: 'a,
+{
+  type Of<A: 'a> = Fetch<'a, S, A>;
+
+  fn map<A: 'a, B: 'a>(fa: Fetch<'a, S, A>, f: impl FnOnce(A) -> B + 'a) -> Fetch<'a, S, B> {
+    fa.map(f)
+  }
+}
+
+impl<'a, S> Applicative<'a> for FetchKind<S>
+where
+  S: DataSource + 'a,
+  S::Request: 'a,
+{
+  fn pure<A: 'a>(a: A) -> Fetch<'a, S, A> {
+    Lift::lift(a)
+  }
+
+  fn zip_with<A: 'a, B: 'a, C: 'a>(
+    fa: Fetch<'a, S, A>,
+    fb: Fetch<'a, S, B>,
+    f: impl FnOnce(A, B) -> C + 'a,
+  ) -> Fetch<'a, S, C> {
+    fa.zip_with(fb, f)
+  }
+}
+
+impl<'a, S, A> Applied<'a> for Fetch<'a, S, A>
+where
+  S: DataSource + 'a,
+  S::Request: 'a,
+  A: 'a,
+{
+  type Item = A;
+  type Kind = FetchKind<S>;
+}
+
+/// Request `request`.
+pub fn fetch<'a, S>(request: S::Request) -> Fetch<'a, S, S::Response>
+where
+  S: DataSource + 'a,
+  S::Request: 'a,
+{
+  Fetch(Step::Blocked(
+    vec![request.clone()],
+    Box::new(move |cache: &Cache<S>| Lift::lift(cache[&request].clone())),
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{ado, combinators::map_m};
+
+  /// Squares numbers, recording the batches.
+  #[derive(Default)]
+  struct Squares(Vec<Vec<i32>>);
+
+  impl DataSource for Squares {
+    type Request = i32;
+    type Response = i32;
+
+    fn fetch(&mut self, requests: &[i32]) -> Vec<i32> {
+      self.0.push(requests.to_vec());
+      requests.iter().map(|n| n * n).collect()
+    }
+  }
+
+  #[test]
+  fn batches_and_cache() {
+    let mut squares = Squares::default();
+    let r = m! {
+      a <- fetch(2).zip_with(fetch(3), |a, b| a + b);
+      b <- map_m([a, 2, 3, a], fetch);
+      c <- fetch(4);
+      return (a, b, c);
+    };
+
+    assert_eq!(r.run(&mut squares), (13, vec![169, 4, 9, 169], 16));
+    assert_eq!(squares.0, vec![vec![2, 3], vec![13], vec![4]]);
+  }
+
+  #[test]
+  fn applicative_block() {
+    let mut squares = Squares::default();
+    let r = ado! {
+      a <- fetch(1);
+      b <- fetch(2);
+      c <- fetch(1);
+      return a + b + c;
+    };
+
+    assert_eq!(r.run(&mut squares), 6);
+    assert_eq!(squares.0, vec![vec![1, 2]]);
+  }
+
+  #[test]
+  fn pure_computations_send_nothing() {
+    let mut squares = Squares::default();
+    let r = FetchKind::<Squares>::zip(Lift::lift(1), Lift::lift(2));
+
+    assert_eq!(r.run(&mut squares), (1, 2));
+    assert!(squares.0.is_empty());
+  }
+}
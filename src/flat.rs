@@ -0,0 +1,162 @@
+//! Flat do-notation for short-circuiting monads.
+//!
+//! [`m!`](crate::m) desugars a do-block into nested closures, which the optimizer usually sees through, but which
+//! hinder inlining in debug builds and make deep blocks slow to compile. For monads which can only either continue
+//! with a single value or short-circuit — like `Option` and `Result` — [`flat_m!`](crate::flat_m) desugars the block
+//! into flat code instead, matching on every bound value and breaking out of the block on failure, like the `?`
+//! operator does:
+//!
+//! ```rust
+//! use do_notation::flat_m;
+//!
+//! fn parse_pair(a: &str, b: &str) -> Result<(i32, i32), std::num::ParseIntError> {
+//!   flat_m! {
+//!     x <- a.parse::<i32>();
+//!     y <- b.parse::<i32>();
+//!     return (x, y);
+//!   }
+//! }
+//!
+//! assert_eq!(parse_pair("1", "2"), Ok((1, 2)));
+//! assert!(parse_pair("1", "two").is_err());
+//! ```
+//!
+//! The binding protocol is [`Branch`]. As the block isn’t split into closures, it doesn’t move the values it uses.
+
+use std::ops::ControlFlow;
+
+/// The bind protocol of [`flat_m!`](crate::flat_m).
+///
+/// Binding a value either continues the block with a value of type [`Branch::Item`], or breaks out of the block with
+/// its result of type `R`.
+pub trait Branch<R> {
+  /// Type of the bound value.
+  type Item;
+
+  /// Continue with the value, or break out of the block.
+  fn branch(self) -> ControlFlow<R, Self::Item>;
+}
+
+impl<A, B> Branch<Option<B>> for Option<A> {
+  type Item = A;
+
+  #[inline]
+  fn branch(self) -> ControlFlow<Option<B>, A> {
+    match self {
+      Some(a) => ControlFlow::Continue(a),
+      None => ControlFlow::Break(None),
+    }
+  }
+}
+
+impl<A, B, E> Branch<Result<B, E>> for Result<A, E> {
+  type Item = A;
+
+  #[inline]
+  fn branch(self) -> ControlFlow<Result<B, E>, A> {
+    match self {
+      Ok(a) => ControlFlow::Continue(a),
+      Err(e) => ControlFlow::Break(Err(e)),
+    }
+  }
+}
+
+/// Flat monadic do-notation.
+///
+/// The syntax is the one of [`m!`](crate::m), without the `lift`, `for`, `tell` and `catch` statements. Bound values
+/// are matched according to the [`Branch`](crate::flat::Branch) protocol. See the [`flat`](crate::flat) module.
+#[macro_export]
+macro_rules! flat_m {
+  // return
+  (@stmts $l:lifetime return $r:expr ;) => {
+    $crate::Lift::lift($r)
+  };
+
+  // return followed by other statements, which would be parsed as an expression returning from the function
+  (@stmts $l:lifetime return $r:expr ; $($t:tt)+) => {
+    $crate::misplaced_return($r)
+  };
+
+  // let-binding
+  (@stmts $l:lifetime let $p:pat = $e:expr ; $($r:tt)*) => {{
+    let $p = $e;
+    $crate::flat_m!(@stmts $l $($r)*)
+  }};
+
+  // const-bind
+  (@stmts $l:lifetime _ <- $x:expr ; $($r:tt)*) => {{
+    let _ = $crate::flat_m!(@bind $l $x);
+    $crate::flat_m!(@stmts $l $($r)*)
+  }};
+
+  // bind
+  (@stmts $l:lifetime $binding:ident <- $x:expr ; $($r:tt)*) => {{
+    let $binding = $crate::flat_m!(@bind $l $x);
+    $crate::flat_m!(@stmts $l $($r)*)
+  }};
+
+  // const-bind
+  (@stmts $l:lifetime $e:expr ; $($r:tt)*) => {{
+    let _ = $crate::flat_m!(@bind $l $e);
+    $crate::flat_m!(@stmts $l $($r)*)
+  }};
+
+  // pure
+  (@stmts $l:lifetime $a:expr) => {
+    $a
+  };
+
+  (@bind $l:lifetime $x:expr) => {
+    match $crate::flat::Branch::branch($x) {
+      ::core::ops::ControlFlow::Continue(a) => a,
+      ::core::ops::ControlFlow::Break(r) => break $l r,
+    }
+  };
+
+  ($($t:tt)*) => {
+    'flat_m: { $crate::flat_m!(@stmts 'flat_m $($t)*) }
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  #[test]
+  fn option() {
+    let n: Option<i32> = None;
+    let v = vec![1, 2];
+
+    let r = flat_m! {
+      x <- Some(1);
+      let y = x + 1;
+      _ <- v.first();
+      z <- Some(v.len());
+      return (x, y, z);
+    };
+
+    assert_eq!(r, Some((1, 2, 2)));
+    assert_eq!(v, vec![1, 2]);
+
+    let r: Option<i32> = flat_m! {
+      x <- Some(1);
+      n;
+      Some(x)
+    };
+
+    assert_eq!(r, None);
+  }
+
+  #[test]
+  fn result() {
+    let r: Result<i32, &str> = flat_m! {
+      x <- Ok(1);
+      y <- flat_m! {
+        a <- Ok::<_, &str>(x + 1);
+        Err::<(), _>("inner");
+        return a;
+      };
+      return x + y;
+    };
+
+    assert_eq!(r, Err("inner"));
+  }
+}
@@ -0,0 +1,167 @@
+//! Foldable containers.
+//!
+//! A [`Foldable`] container can be consumed element by element, in order. On top of plain folds, it provides
+//! [monoidal folds](Foldable::fold_map) and [monadic folds](Foldable::fold_m), whose steps can have effects:
+//!
+//! ```rust
+//! use do_notation::{foldable::Foldable, monad::OptionKind};
+//!
+//! let safe_div = |acc: i32, n: i32| acc.checked_div(n);
+//!
+//! assert_eq!(vec![2, 5].fold_m::<OptionKind, _>(100, safe_div), Some(10));
+//! assert_eq!(vec![0, 5].fold_m::<OptionKind, _>(100, safe_div), None);
+//! assert_eq!(Some(3).fold_map(|n| vec![n; n]), vec![3, 3, 3]);
+//! ```
+
+use crate::{
+  either::Either,
+  identity::Identity,
+  monad::{Kind, Monad},
+  monoid::Monoid,
+  non_empty::NonEmpty,
+  these::These,
+  Lift,
+};
+
+/// Containers of elements of type [`Foldable::Item`] that can be consumed element by element.
+pub trait Foldable: Sized {
+  /// Type of the elements.
+  type Item;
+
+  /// Combine the elements, from the first to the last one, with `f`.
+  fn fold<B>(self, init: B, f: impl FnMut(B, Self::Item) -> B) -> B;
+
+  /// Map every element into a monoid, and combine the results.
+  fn fold_map<W: Monoid>(self, mut f: impl FnMut(Self::Item) -> W) -> W {
+    self.fold(W::empty(), |w, a| w.combine(f(a)))
+  }
+
+  /// Collect the elements.
+  fn to_vec(self) -> Vec<Self::Item> {
+    self.fold(Vec::new(), |mut v, a| {
+      v.push(a);
+      v
+    })
+  }
+
+  /// Combine the elements, from the first to the last one, with the monadic function `f`.
+  ///
+  /// The fold stops as soon as the monad short-circuits.
+  fn fold_m<'a, M, B>(self, init: B, f: impl FnMut(B, Self::Item) -> M::Of<B> + 'a) -> M::Of<B>
+  where
+    M: Kind<'a>,
+    B: 'a,
+    Self::Item: 'a,
+  {
+    fn go<'a, M, A, B, F>(mut elements: std::vec::IntoIter<A>, b: B, mut f: F) -> M::Of<B>
+    where
+      M: Kind<'a>,
+      A: 'a,
+      B: 'a,
+      F: FnMut(B, A) -> M::Of<B> + 'a,
+    {
+      match elements.next() {
+        Some(a) => f(b, a).and_then(move |b| go::<M, A, B, F>(elements, b, f)),
+        None => Lift::lift(b),
+      }
+    }
+
+    go::<M, _, _, _>(self.to_vec().into_iter(), init, f)
+  }
+}
+
+impl<A> Foldable for Vec<A> {
+  type Item = A;
+
+  fn fold<B>(self, init: B, f: impl FnMut(B, A) -> B) -> B {
+    self.into_iter().fold(init, f)
+  }
+
+  fn to_vec(self) -> Vec<A> {
+    self
+  }
+}
+
+impl<A> Foldable for Option<A> {
+  type Item = A;
+
+  fn fold<B>(self, init: B, f: impl FnMut(B, A) -> B) -> B {
+    self.into_iter().fold(init, f)
+  }
+}
+
+/// Only the value in [`Ok`] is an element.
+impl<A, E> Foldable for Result<A, E> {
+  type Item = A;
+
+  fn fold<B>(self, init: B, f: impl FnMut(B, A) -> B) -> B {
+    self.into_iter().fold(init, f)
+  }
+}
+
+impl<A> Foldable for NonEmpty<A> {
+  type Item = A;
+
+  fn fold<B>(self, init: B, f: impl FnMut(B, A) -> B) -> B {
+    self.into_iter().fold(init, f)
+  }
+}
+
+/// Only the value in [`Either::Right`] is an element.
+impl<L, R> Foldable for Either<L, R> {
+  type Item = R;
+
+  fn fold<B>(self, init: B, f: impl FnMut(B, R) -> B) -> B {
+    self.right().into_iter().fold(init, f)
+  }
+}
+
+/// Only the value of type `B` is an element.
+impl<A, B> Foldable for These<A, B> {
+  type Item = B;
+
+  fn fold<C>(self, init: C, f: impl FnMut(C, B) -> C) -> C {
+    self.that().into_iter().fold(init, f)
+  }
+}
+
+impl<A> Foldable for Identity<A> {
+  type Item = A;
+
+  fn fold<B>(self, init: B, mut f: impl FnMut(B, A) -> B) -> B {
+    f(init, self.run())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    identity::IdentityKind,
+    state::{modify, run_state, StateTKind},
+  };
+
+  #[test]
+  fn fold() {
+    assert_eq!(vec![1, 2, 3].fold(0, |a, b| a * 10 + b), 123);
+    assert_eq!(NonEmpty::new(1).fold_map(|n| n.to_string()), "1");
+    // with serde_json, `Vec<i32>` is also comparable to a `Value`, so `vec![]` would be ambiguous
+    assert_eq!(Err::<i32, _>(()).to_vec(), Vec::<i32>::new());
+    assert_eq!(These::<(), _>::Both((), 1).to_vec(), vec![1]);
+    assert_eq!(Either::<(), _>::Right(1).to_vec(), vec![1]);
+    assert_eq!(Identity(1).to_vec(), vec![1]);
+  }
+
+  #[test]
+  fn fold_m() {
+    let r = vec![1, 2, 3].fold_m::<StateTKind<Vec<i32>, IdentityKind>, _>(0, |acc, n| {
+      modify(move |mut v: Vec<i32>| {
+        v.push(acc);
+        v
+      })
+      .and_then(move |()| Lift::lift(acc + n))
+    });
+
+    assert_eq!(run_state(r, vec![]), (6, vec![0, 1, 3]));
+  }
+}
@@ -0,0 +1,629 @@
+//! Asynchronous do-notation.
+//!
+//! The [`async_m!`](crate::async_m) macro is the asynchronous counterpart of [`m!`](crate::m): it builds a future,
+//! and the values bound with `<-` are awaited. What happens with the awaited values is controlled by the [`Bind`]
+//! protocol:
+//!
+//! - Plain futures are simply awaited, and their output is bound.
+//! - Futures producing an `Option` wrapped in an [`OptionT`] bind the value in `Some`, or short-circuit the whole
+//!   block on `None`.
+//! - Futures producing a `Result` wrapped in a [`ResultT`] bind the value in `Ok`, or short-circuit the whole block
+//!   with the error.
+//!
+//! ```rust
+//! use do_notation::{async_m, future::OptionT};
+//!
+//! async fn user_id(name: &str) -> Option<u32> {
+//!   if name == "alice" { Some(1) } else { None }
+//! }
+//!
+//! async fn score(id: u32) -> Option<u32> {
+//!   Some(id * 10)
+//! }
+//!
+//! async fn user_score(name: &str) -> Option<u32> {
+//!   async_m! {
+//!     id <- OptionT(user_id(name));
+//!     score <- OptionT(score(id));
+//!     return score + 1;
+//!   }
+//!   .await
+//! }
+//! #
+//! # fn block_on<F: std::future::Future>(f: F) -> F::Output {
+//! #   use std::task::{Context, Poll, Waker};
+//! #   let mut f = Box::pin(f);
+//! #   let mut cx = Context::from_waker(Waker::noop());
+//! #   loop {
+//! #     if let Poll::Ready(a) = f.as_mut().poll(&mut cx) {
+//! #       return a;
+//! #     }
+//! #   }
+//! # }
+//!
+//! assert_eq!(block_on(user_score("alice")), Some(11));
+//! assert_eq!(block_on(user_score("bob")), None);
+//! ```
+//!
+//! Asynchronous monads, whose continuations are asynchronous too, implement [`AsyncBind`]. They are futures or
+//! [`Bind`] adapters, so they can be bound in [`async_m!`](crate::async_m) as well: [`Async`] is the monad of boxed
+//! futures, and [`OptionT`] and [`ResultT`] the ones of futures producing an `Option` or a `Result`. Boxed futures
+//! — [`BoxFuture`] — are asynchronous monads as well, so that the futures returned by trait objects can be bound
+//! without any wrapper.
+//!
+//! # Concurrency
+//!
+//! Binds are awaited one after the other, even when they don’t depend on each other: a macro only sees tokens, so it
+//! can’t tell whether an expression uses a value bound before it — it may be hidden in another macro, or shadowed.
+//! Concurrency is explicit instead: the binds of a `join { … };` statement are awaited concurrently, and the compiler
+//! rejects the groups whose binds depend on each other, as their values are only bound once all of them are
+//! available.
+//!
+//! ```rust
+//! use do_notation::{async_m, future::OptionT};
+//!
+//! async fn score(name: &str) -> Option<u32> {
+//!   Some(name.len() as u32)
+//! }
+//!
+//! let total = async_m! {
+//!   join {
+//!     a <- OptionT(score("alice"));
+//!     b <- OptionT(score("bob"));
+//!   };
+//!   return a + b;
+//! };
+//! #
+//! # fn block_on<F: std::future::Future>(f: F) -> F::Output {
+//! #   use std::task::{Context, Poll, Waker};
+//! #   let mut f = Box::pin(f);
+//! #   let mut cx = Context::from_waker(Waker::noop());
+//! #   loop {
+//! #     if let Poll::Ready(a) = f.as_mut().poll(&mut cx) {
+//! #       return a;
+//! #     }
+//! #   }
+//! # }
+//!
+//! assert_eq!(block_on(total), Some(8));
+//! ```
+
+use crate::Lift;
+use std::{
+  future::{poll_fn, ready, Future},
+  ops::ControlFlow,
+  pin::{pin, Pin},
+  task::{Context, Poll},
+};
+
+/// A boxed future.
+pub type BoxFuture<'a, A> = Pin<Box<dyn Future<Output = A> + 'a>>;
+
+/// The bind protocol of [`async_m!`](crate::async_m).
+///
+/// Binding a value awaits the future returned by [`Bind::bind`], which either continues the block with a value of
+/// type [`Bind::Item`], or breaks out of the block with its result of type `R`.
+pub trait Bind<R> {
+  /// Type of the bound value.
+  type Item;
+
+  /// Await the value, continuing or breaking out of the block.
+  fn bind(self) -> impl Future<Output = ControlFlow<R, Self::Item>>;
+}
+
+impl<R, F> Bind<R> for F
+where
+  F: Future,
+{
+  type Item = F::Output;
+
+  async fn bind(self) -> ControlFlow<R, F::Output> {
+    ControlFlow::Continue(self.await)
+  }
+}
+
+/// Futures producing an optional value.
+///
+/// Binding an [`OptionT`] in [`async_m!`](crate::async_m) binds the value in `Some`, or short-circuits the block with
+/// `None`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct OptionT<F>(pub F);
+
+impl<A, B, F> Bind<Option<B>> for OptionT<F>
+where
+  F: Future<Output = Option<A>>,
+{
+  type Item = A;
+
+  async fn bind(self) -> ControlFlow<Option<B>, A> {
+    match self.0.await {
+      Some(a) => ControlFlow::Continue(a),
+      None => ControlFlow::Break(None),
+    }
+  }
+}
+
+/// Futures producing a fallible value.
+///
+/// Binding a [`ResultT`] in [`async_m!`](crate::async_m) binds the value in `Ok`, or short-circuits the block with the
+/// error. Every fallible bind of a block must have the same error type.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ResultT<F>(pub F);
+
+impl<A, B, E, F> Bind<Result<B, E>> for ResultT<F>
+where
+  F: Future<Output = Result<A, E>>,
+{
+  type Item = A;
+
+  async fn bind(self) -> ControlFlow<Result<B, E>, A> {
+    match self.0.await {
+      Ok(a) => ControlFlow::Continue(a),
+      Err(e) => ControlFlow::Break(Err(e)),
+    }
+  }
+}
+
+/// Asynchronous monads.
+///
+/// The continuations passed to [`AsyncBind::and_then`] return futures — typically `async` blocks — so they can await
+/// before producing the rest of the computation. The `'a` lifetime is the lifetime of the continuations, as with
+/// [`Monad`](crate::Monad).
+pub trait AsyncBind<'a>: Sized + 'a {
+  /// Type of the value passed to the continuations.
+  type Item: 'a;
+
+  /// The asynchronous monad carrying values of type `B`.
+  type Of<B: 'a>: AsyncBind<'a, Item = B> + Lift<B>;
+
+  /// Sequentially compose two asynchronous actions, awaiting the future returned by `f` with the value produced by
+  /// the first one.
+  fn and_then<B, F, Fut>(self, f: F) -> Self::Of<B>
+  where
+    B: 'a,
+    F: FnOnce(Self::Item) -> Fut + 'a,
+    Fut: Future<Output = Self::Of<B>> + 'a;
+}
+
+/// The monad of boxed futures.
+///
+/// Binding an [`Async`] in [`async_m!`](crate::async_m) awaits it, as any other future.
+pub struct Async<'a, A>(pub BoxFuture<'a, A>);
+
+impl<'a, A> Async<'a, A> {
+  /// Box a future.
+  pub fn new(f: impl Future<Output = A> + 'a) -> Self {
+    Async(Box::pin(f))
+  }
+}
+
+impl<A> Future for Async<'_, A> {
+  type Output = A;
+
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<A> {
+    self.0.as_mut().poll(cx)
+  }
+}
+
+impl<'a, A: 'a> Lift<A> for Async<'a, A> {
+  fn lift(a: A) -> Self {
+    Async::new(ready(a))
+  }
+}
+
+impl<'a, A: 'a> AsyncBind<'a> for Async<'a, A> {
+  type Item = A;
+  type Of<B: 'a> = Async<'a, B>;
+
+  fn and_then<B, F, Fut>(self, f: F) -> Async<'a, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Fut + 'a,
+    Fut: Future<Output = Async<'a, B>> + 'a,
+  {
+    Async::new(async move { f(self.await).await.await })
+  }
+}
+
+impl<'a, A: 'a> Lift<A> for BoxFuture<'a, A> {
+  fn lift(a: A) -> Self {
+    Box::pin(ready(a))
+  }
+}
+
+impl<'a, A: Send + 'a> Lift<A> for Pin<Box<dyn Future<Output = A> + Send + 'a>> {
+  fn lift(a: A) -> Self {
+    Box::pin(ready(a))
+  }
+}
+
+impl<'a, A: 'a> AsyncBind<'a> for BoxFuture<'a, A> {
+  type Item = A;
+  type Of<B: 'a> = BoxFuture<'a, B>;
+
+  fn and_then<B, F, Fut>(self, f: F) -> BoxFuture<'a, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Fut + 'a,
+    Fut: Future<Output = BoxFuture<'a, B>> + 'a,
+  {
+    Box::pin(async move { f(self.await).await.await })
+  }
+}
+
+impl<'a, A: 'a> Lift<A> for OptionT<BoxFuture<'a, Option<A>>> {
+  fn lift(a: A) -> Self {
+    OptionT(Box::pin(ready(Some(a))))
+  }
+}
+
+impl<'a, A, F> AsyncBind<'a> for OptionT<F>
+where
+  A: 'a,
+  F: Future<Output = Option<A>> + 'a,
+{
+  type Item = A;
+  type Of<B: 'a> = OptionT<BoxFuture<'a, Option<B>>>;
+
+  fn and_then<B, G, Fut>(self, f: G) -> Self::Of<B>
+  where
+    B: 'a,
+    G: FnOnce(A) -> Fut + 'a,
+    Fut: Future<Output = Self::Of<B>> + 'a,
+  {
+    OptionT(Box::pin(async move {
+      match self.0.await {
+        Some(a) => f(a).await.0.await,
+        None => None,
+      }
+    }))
+  }
+}
+
+impl<'a, A: 'a, E: 'a> Lift<A> for ResultT<BoxFuture<'a, Result<A, E>>> {
+  fn lift(a: A) -> Self {
+    ResultT(Box::pin(ready(Ok(a))))
+  }
+}
+
+impl<'a, A, E, F> AsyncBind<'a> for ResultT<F>
+where
+  A: 'a,
+  E: 'a,
+  F: Future<Output = Result<A, E>> + 'a,
+{
+  type Item = A;
+  type Of<B: 'a> = ResultT<BoxFuture<'a, Result<B, E>>>;
+
+  fn and_then<B, G, Fut>(self, f: G) -> Self::Of<B>
+  where
+    B: 'a,
+    G: FnOnce(A) -> Fut + 'a,
+    Fut: Future<Output = Self::Of<B>> + 'a,
+  {
+    ResultT(Box::pin(async move {
+      match self.0.await {
+        Ok(a) => f(a).await.0.await,
+        Err(e) => Err(e),
+      }
+    }))
+  }
+}
+
+/// Await two binds concurrently, pairing their values.
+///
+/// As soon as one of them breaks out of the block, the other one is dropped and the joined bind breaks as well.
+pub async fn join_bind<R, A, B>(
+  a: impl Future<Output = ControlFlow<R, A>>,
+  b: impl Future<Output = ControlFlow<R, B>>,
+) -> ControlFlow<R, (A, B)> {
+  let (mut a, mut b) = (pin!(a), pin!(b));
+  let (mut ra, mut rb) = (None, None);
+
+  poll_fn(|cx| {
+    if ra.is_none() {
+      match a.as_mut().poll(cx) {
+        Poll::Ready(ControlFlow::Continue(x)) => ra = Some(x),
+        Poll::Ready(ControlFlow::Break(r)) => return Poll::Ready(ControlFlow::Break(r)),
+        Poll::Pending => (),
+      }
+    }
+
+    if rb.is_none() {
+      match b.as_mut().poll(cx) {
+        Poll::Ready(ControlFlow::Continue(x)) => rb = Some(x),
+        Poll::Ready(ControlFlow::Break(r)) => return Poll::Ready(ControlFlow::Break(r)),
+        Poll::Pending => (),
+      }
+    }
+
+    match (ra.take(), rb.take()) {
+      (Some(x), Some(y)) => Poll::Ready(ControlFlow::Continue((x, y))),
+      (x, y) => {
+        ra = x;
+        rb = y;
+        Poll::Pending
+      }
+    }
+  })
+  .await
+}
+
+/// Asynchronous monadic do-notation.
+///
+/// This macro builds a future out of statements following the syntax of [`m!`](crate::m) — `let` bindings,
+/// `binding <- expr;`, `_ <- expr;`, `expr;` and a final expression or `return expr;`. Bound values are awaited
+/// according to the [`Bind`](crate::future::Bind) protocol, and the final expression is the output of the future.
+/// The future captures its environment by move.
+///
+/// Binds are awaited one after the other: independent binds aren’t detected. Consecutive binds which don’t depend on
+/// each other can be grouped in a `join { binding <- expr; ... };` statement, which awaits them concurrently — see
+/// [`join_bind`](crate::future::join_bind). The values of the group are only bound once all of them are available,
+/// so the compiler rejects groups whose binds depend on each other.
+///
+/// See the [`future`](crate::future) module for further information.
+#[macro_export]
+macro_rules! async_m {
+  // return
+  (@stmts return $r:expr ;) => {
+    $crate::Lift::lift($r)
+  };
+
+  // let-binding
+  (@stmts let $p:pat = $e:expr ; $($r:tt)*) => {{
+    let $p = $e;
+    $crate::async_m!(@stmts $($r)*)
+  }};
+
+  // join
+  (@stmts join { $($binding:tt <- $x:expr ;)+ } ; $($r:tt)*) => {{
+    let $crate::async_m!(@join_pat $($binding)+) = match $crate::async_m!(@join $($x),+).await {
+      ::core::ops::ControlFlow::Continue(a) => a,
+      ::core::ops::ControlFlow::Break(r) => return r,
+    };
+    $crate::async_m!(@stmts $($r)*)
+  }};
+
+  // const-bind
+  (@stmts _ <- $x:expr ; $($r:tt)*) => {{
+    let _ = $crate::async_m!(@bind $x);
+    $crate::async_m!(@stmts $($r)*)
+  }};
+
+  // bind
+  (@stmts $binding:ident <- $x:expr ; $($r:tt)*) => {{
+    let $binding = $crate::async_m!(@bind $x);
+    $crate::async_m!(@stmts $($r)*)
+  }};
+
+  // const-bind
+  (@stmts $e:expr ; $($r:tt)*) => {{
+    let _ = $crate::async_m!(@bind $e);
+    $crate::async_m!(@stmts $($r)*)
+  }};
+
+  // pure
+  (@stmts $a:expr) => {
+    $a
+  };
+
+  (@bind $x:expr) => {
+    match $crate::future::Bind::bind($x).await {
+      ::core::ops::ControlFlow::Continue(a) => a,
+      ::core::ops::ControlFlow::Break(r) => return r,
+    }
+  };
+
+  (@join $x:expr $(, $xs:expr)*) => {
+    $crate::future::join_bind($crate::future::Bind::bind($x), $crate::async_m!(@join $($xs),*))
+  };
+
+  (@join) => {
+    ::core::future::ready(::core::ops::ControlFlow::Continue(()))
+  };
+
+  (@join_pat $binding:tt $($bindings:tt)*) => {
+    ($binding, $crate::async_m!(@join_pat $($bindings)*))
+  };
+
+  (@join_pat) => {
+    ()
+  };
+
+  ($($t:tt)*) => {
+    async move { $crate::async_m!(@stmts $($t)*) }
+  };
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::*;
+  use std::{
+    cell::RefCell,
+    future::ready,
+    pin::pin,
+    task::{Context, Poll, Waker},
+  };
+
+  pub(crate) fn block_on<F: Future>(f: F) -> F::Output {
+    let mut f = pin!(f);
+    let mut cx = Context::from_waker(Waker::noop());
+
+    loop {
+      if let Poll::Ready(a) = f.as_mut().poll(&mut cx) {
+        return a;
+      }
+    }
+  }
+
+  #[test]
+  fn futures() {
+    let r = async_m! {
+      x <- ready(1);
+      let y = x + 1;
+      z <- async move { y * 2 };
+      x + z
+    };
+
+    assert_eq!(block_on(r), 5);
+  }
+
+  #[test]
+  fn option_t() {
+    let log = &RefCell::new(Vec::new());
+
+    let r = async_m! {
+      x <- OptionT(ready(Some(1)));
+      async { log.borrow_mut().push(x) };
+      y <- OptionT(ready(Some(2)));
+      return x + y;
+    };
+
+    assert_eq!(block_on(r), Some(3));
+
+    let r = async_m! {
+      x <- OptionT(ready(Some(1)));
+      _ <- OptionT(ready(None::<()>));
+      async { log.borrow_mut().push(x) };
+      return x;
+    };
+
+    assert_eq!(block_on(r), None);
+    assert_eq!(*log.borrow(), vec![1]);
+  }
+
+  #[test]
+  fn result_t() {
+    async fn parse(s: &str) -> Result<i32, String> {
+      s.parse().map_err(|_| format!("cannot parse {}", s))
+    }
+
+    let log = &RefCell::new(Vec::new());
+    let sum = |a, b| {
+      async_m! {
+        x <- ResultT(parse(a));
+        async { log.borrow_mut().push(x) };
+        y <- ResultT(parse(b));
+        return x + y;
+      }
+    };
+
+    assert_eq!(block_on(sum("1", "2")), Ok(3));
+    assert_eq!(
+      block_on(sum("1", "two")),
+      Err("cannot parse two".to_owned())
+    );
+    assert_eq!(
+      block_on(sum("one", "2")),
+      Err("cannot parse one".to_owned())
+    );
+    assert_eq!(*log.borrow(), vec![1, 1]);
+
+    let r = double(ResultT(ready(Ok::<_, ()>(3))));
+    assert_eq!(block_on(r.0), Ok(6));
+  }
+
+  #[test]
+  fn box_future() {
+    trait Service {
+      fn call(&self, x: i32) -> BoxFuture<'_, i32>;
+    }
+
+    struct Double;
+
+    impl Service for Double {
+      fn call(&self, x: i32) -> BoxFuture<'_, i32> {
+        Box::pin(async move { x * 2 })
+      }
+    }
+
+    let service: &dyn Service = &Double;
+    let r: BoxFuture<i32> = service
+      .call(1)
+      .and_then(move |x| ready(service.call(x + 1)));
+    assert_eq!(block_on(r), 6);
+
+    let r: BoxFuture<i32> = Box::pin(async_m! {
+      x <- service.call(1);
+      y <- BoxFuture::lift(x + 1);
+      service.call(y).await
+    });
+    assert_eq!(block_on(r), 6);
+
+    let r = Pin::<Box<dyn Future<Output = i32> + Send>>::lift(1);
+    assert_eq!(block_on(r), 1);
+  }
+
+  fn double<'a, M>(m: M) -> M::Of<i32>
+  where
+    M: AsyncBind<'a, Item = i32>,
+  {
+    m.and_then(|x| async move { Lift::lift(x * 2) })
+  }
+
+  #[test]
+  fn async_bind() {
+    let r = double(Async::new(async { 3 }));
+    assert_eq!(block_on(r), 6);
+
+    assert_eq!(block_on(double(OptionT(ready(Some(3)))).0), Some(6));
+    assert_eq!(block_on(double(OptionT(ready(None))).0), None);
+
+    let r = async_m! {
+      x <- Async::lift(1);
+      y <- double(OptionT(async move { Some(x + 1) }));
+      return x + y;
+    };
+
+    assert_eq!(block_on(r), Some(5));
+  }
+
+  /// A future which is pending `n` times, logging its polls.
+  fn yield_n<'a>(
+    log: &'a RefCell<Vec<&'static str>>,
+    name: &'static str,
+    mut n: usize,
+  ) -> impl Future<Output = ()> + 'a {
+    poll_fn(move |cx| {
+      log.borrow_mut().push(name);
+
+      if n == 0 {
+        Poll::Ready(())
+      } else {
+        n -= 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+      }
+    })
+  }
+
+  #[test]
+  fn join() {
+    let log = &RefCell::new(Vec::new());
+
+    let r = async_m! {
+      x <- ready(1);
+      join {
+        y <- async move { yield_n(log, "a", 1).await; x + 1 };
+        _ <- yield_n(log, "b", 2);
+        z <- OptionT(async move { yield_n(log, "c", 0).await; Some(x + 2) });
+      };
+      return x + y + z;
+    };
+
+    assert_eq!(block_on(r), Some(6));
+    assert_eq!(*log.borrow(), vec!["a", "b", "c", "a", "b", "b"]);
+
+    log.borrow_mut().clear();
+    let r = async_m! {
+      join {
+        _ <- yield_n(log, "a", 5);
+        _ <- OptionT(async { yield_n(log, "b", 1).await; None::<()> });
+      };
+      return ();
+    };
+
+    assert_eq!(block_on(r), None);
+    assert_eq!(*log.borrow(), vec!["a", "b", "a", "b"]);
+  }
+}
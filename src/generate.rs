@@ -0,0 +1,264 @@
+//! Composing test-data generators.
+//!
+//! > This module requires the `proptest` or the `quickcheck` feature.
+//!
+//! Generators of dependent data — a length, then a vector of that length; a kind, then the fields of that kind —
+//! are do-blocks, instead of nested `prop_flat_map` calls or hand-written [`Arbitrary`] implementations:
+//!
+//! - With the `proptest` feature, `StrategyExt` binds the values generated by any [`Strategy`], producing a
+//!   [`BoxedStrategy`] — so a block ending with a strategy must box it. proptest calls the continuations again while
+//!   shrinking, so they must be [`Fn`]: clone the values they move out.
+//! - With the `quickcheck` feature, `Generator` is the monad of computations drawing random values from a
+//!   [`Gen`], to implement [`Arbitrary::arbitrary`].
+//!
+//! [`Strategy`]: https://docs.rs/proptest/latest/proptest/strategy/trait.Strategy.html
+//! [`BoxedStrategy`]: https://docs.rs/proptest/latest/proptest/strategy/struct.BoxedStrategy.html
+//! [`Arbitrary`]: https://docs.rs/quickcheck/latest/quickcheck/trait.Arbitrary.html
+//! [`Arbitrary::arbitrary`]: https://docs.rs/quickcheck/latest/quickcheck/trait.Arbitrary.html#tymethod.arbitrary
+//! [`Gen`]: https://docs.rs/quickcheck/latest/quickcheck/struct.Gen.html
+
+#[cfg(feature = "quickcheck")]
+use crate::monad::{Kind, Monad};
+use crate::Lift;
+#[cfg(feature = "proptest")]
+use proptest::strategy::{BoxedStrategy, Just, Strategy};
+#[cfg(feature = "quickcheck")]
+use quickcheck::{Arbitrary, Gen};
+#[cfg(feature = "proptest")]
+use std::fmt::Debug;
+
+/// Binding the values of proptest strategies.
+///
+/// ```rust
+/// use do_notation::{generate::StrategyExt, m};
+/// use proptest::{collection::vec, prelude::*, strategy::ValueTree, test_runner::TestRunner};
+///
+/// fn matrix() -> BoxedStrategy<(usize, Vec<Vec<u8>>)> {
+///   m! {
+///     rows <- 1..4usize;
+///     cols <- 1..4usize;
+///     cells <- vec(vec(any::<u8>(), cols), rows);
+///     return (cols, cells);
+///   }
+/// }
+///
+/// let mut runner = TestRunner::default();
+///
+/// for _ in 0..100 {
+///   let (cols, cells) = matrix().new_tree(&mut runner).unwrap().current();
+///   assert!(cells.iter().all(|row| row.len() == cols));
+/// }
+/// ```
+#[cfg(feature = "proptest")]
+pub trait StrategyExt: Strategy + Sized + 'static {
+  /// Generate a value, then a value of the strategy built by `f`.
+  ///
+  /// This is [`prop_flat_map`](Strategy::prop_flat_map), boxed so that every bind of a block has the same type. The
+  /// strategy built by `f` is boxed too: it’s the rest of the block, or a strategy ending it, boxed with
+  /// [`boxed`](Strategy::boxed).
+  fn and_then<B, F>(self, f: F) -> BoxedStrategy<B>
+  where
+    B: Debug + 'static,
+    F: Fn(Self::Value) -> BoxedStrategy<B> + 'static;
+}
+
+#[cfg(feature = "proptest")]
+impl<T> StrategyExt for T
+where
+  T: Strategy + 'static,
+{
+  fn and_then<B, F>(self, f: F) -> BoxedStrategy<B>
+  where
+    B: Debug + 'static,
+    F: Fn(T::Value) -> BoxedStrategy<B> + 'static,
+  {
+    self.prop_flat_map(f).boxed()
+  }
+}
+
+#[cfg(feature = "proptest")]
+impl<A> Lift<A> for BoxedStrategy<A>
+where
+  A: Clone + Debug + 'static,
+{
+  fn lift(a: A) -> Self {
+    Just(a).boxed()
+  }
+}
+
+/// Computations drawing random values from a quickcheck [`Gen`], producing a value of type `A`.
+///
+/// ```rust
+/// use do_notation::{generate::{arbitrary, choose, Generator}, m};
+/// use quickcheck::{Arbitrary, Gen};
+///
+/// #[derive(Clone, Debug)]
+/// enum Shape {
+///   Circle(u8),
+///   Rect(u8, u8),
+/// }
+///
+/// impl Arbitrary for Shape {
+///   fn arbitrary(g: &mut Gen) -> Self {
+///     let shape: Generator<Shape> = m! {
+///       kind <- choose(vec!["circle", "rect"]);
+///       w <- arbitrary();
+///       if kind == "circle" {
+///         Generator::from(Shape::Circle(w))
+///       } else {
+///         arbitrary().map(move |h| Shape::Rect(w, h))
+///       }
+///     };
+///
+///     shape.run(g)
+///   }
+/// }
+///
+/// let shape = Shape::arbitrary(&mut Gen::new(10));
+/// assert!(matches!(shape, Shape::Circle(_) | Shape::Rect(..)));
+/// ```
+#[cfg(feature = "quickcheck")]
+pub struct Generator<'a, A>(Box<dyn FnOnce(&mut Gen) -> A + 'a>);
+
+#[cfg(feature = "quickcheck")]
+impl<'a, A: 'a> Generator<'a, A> {
+  /// Build a generator out of a function.
+  pub fn new(f: impl FnOnce(&mut Gen) -> A + 'a) -> Self {
+    Generator(Box::new(f))
+  }
+
+  /// Generate a value, drawing from `g`.
+  pub fn run(self, g: &mut Gen) -> A {
+    (self.0)(g)
+  }
+
+  /// Generate a value, then a value with the generator built by `f`.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(A) -> Generator<'a, B> + 'a) -> Generator<'a, B> {
+    Generator::new(move |g| {
+      let a = self.run(g);
+      f(a).run(g)
+    })
+  }
+
+  /// Apply `f` to the generated value.
+  pub fn map<B: 'a>(self, f: impl FnOnce(A) -> B + 'a) -> Generator<'a, B> {
+    Generator::new(move |g| f(self.run(g)))
+  }
+}
+
+#[cfg(feature = "quickcheck")]
+impl<'a, A: 'a> From<A> for Generator<'a, A> {
+  fn from(a: A) -> Self {
+    Generator::new(move |_| a)
+  }
+}
+
+#[cfg(feature = "quickcheck")]
+impl<'a, A: 'a> Lift<A> for Generator<'a, A> {
+  fn lift(a: A) -> Self {
+    Generator::from(a)
+  }
+}
+
+/// [`Kind`] of `Generator<'_, _>`.
+#[cfg(feature = "quickcheck")]
+#[derive(Debug)]
+pub struct GeneratorKind;
+
+#[cfg(feature = "quickcheck")]
+impl<'a> Kind<'a> for GeneratorKind {
+  type Of<A: 'a> = Generator<'a, A>;
+}
+
+#[cfg(feature = "quickcheck")]
+impl<'a, A: 'a> Monad<'a> for Generator<'a, A> {
+  type Item = A;
+  type Kind = GeneratorKind;
+
+  fn and_then<B, F>(self, f: F) -> Generator<'a, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Generator<'a, B> + 'a,
+  {
+    Generator::and_then(self, f)
+  }
+}
+
+/// Generate an arbitrary value.
+#[cfg(feature = "quickcheck")]
+pub fn arbitrary<'a, A: Arbitrary>() -> Generator<'a, A> {
+  Generator::new(A::arbitrary)
+}
+
+/// Pick one of `items`.
+///
+/// # Panics
+///
+/// The generator panics if `items` is empty.
+#[cfg(feature = "quickcheck")]
+pub fn choose<'a, A: 'a>(mut items: Vec<A>) -> Generator<'a, A> {
+  Generator::new(move |g| {
+    let indices = (0..items.len()).collect::<Vec<_>>();
+    let i = *g.choose(&indices).expect("cannot choose among no items");
+    items.swap_remove(i)
+  })
+}
+
+/// Build a generator from the size of the [`Gen`].
+#[cfg(feature = "quickcheck")]
+pub fn sized<'a, A: 'a>(f: impl FnOnce(usize) -> Generator<'a, A> + 'a) -> Generator<'a, A> {
+  Generator::new(move |g| {
+    let size = g.size();
+    f(size).run(g)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(feature = "proptest")]
+  #[test]
+  fn strategy() {
+    use proptest::{collection::vec, strategy::ValueTree, test_runner::TestRunner};
+
+    let strategy: BoxedStrategy<(Vec<u32>, usize)> = m! {
+      len <- 0..10usize;
+      xs <- vec(0..100u32, len);
+      i <- 0..=len;
+      return (xs.clone(), i);
+    };
+
+    let mut runner = TestRunner::deterministic();
+
+    for _ in 0..100 {
+      let mut tree = strategy.new_tree(&mut runner).unwrap();
+      let (xs, i) = tree.current();
+      assert!(i <= xs.len());
+
+      while tree.simplify() {
+        let (xs, i) = tree.current();
+        assert!(i <= xs.len());
+      }
+    }
+  }
+
+  #[cfg(feature = "quickcheck")]
+  #[test]
+  fn generator() {
+    let g = &mut Gen::new(20);
+
+    for _ in 0..100 {
+      let r = m! {
+        n <- sized(|size| choose((0..=size).collect()));
+        xs <- crate::combinators::replicate_m(n, arbitrary::<bool>);
+        b <- choose(vec![true, false]);
+        return (n, xs, b);
+      };
+
+      let (n, xs, _) = r.run(g);
+      assert!(n <= 20);
+      assert_eq!(xs.len(), n);
+    }
+  }
+}
@@ -0,0 +1,250 @@
+//! Coroutine-based do-notation.
+//!
+//! > This module requires a nightly compiler and the `nightly` feature. Crates using [`gen_m!`](crate::gen_m) must
+//! > enable the `coroutines` and `coroutine_clone` features.
+//!
+//! [`m!`](crate::m) desugars a do-block into nested closures, one per statement, which are consumed by `and_then`.
+//! [`gen_m!`](crate::gen_m) compiles the whole block into a single coroutine instead: binding a value suspends the
+//! coroutine, and the bound computation resumes it with each of its values. Because a suspended coroutine can be
+//! cloned, it can be resumed more than once, so computations producing several values — like `Vec`, the list
+//! monad — can be bound without requiring the rest of the block to be an `Fn` closure:
+//!
+//! ```rust
+//! #![feature(coroutines, coroutine_clone)]
+//!
+//! use do_notation::gen_m;
+//!
+//! let r = gen_m! {
+//!   x <- vec![1, 2, 3];
+//!   y <- vec![x, x * 10];
+//!   let z = x + y;
+//!   return z;
+//! };
+//!
+//! assert_eq!(r, vec![2, 11, 4, 22, 6, 33]);
+//! ```
+//!
+//! The binding protocol is [`GenBind`]. As the values cross the coroutine boundary, the bound values must be
+//! `'static`, and everything live across a binding must be [`Clone`].
+
+use crate::non_empty::NonEmpty;
+use std::{
+  any::Any,
+  cell::Cell,
+  ops::{Coroutine, CoroutineState},
+  pin::Pin,
+  rc::Rc,
+};
+
+/// Value a do-block coroutine is resumed with.
+pub type Resumed = Box<dyn Any>;
+
+/// Suspension of a do-block coroutine on a bound computation.
+///
+/// Given the rest of the block, which resumes the coroutine, it produces the result of the block.
+///
+/// A coroutine can only be cloned if the values it yields can, so the suspension is shared, and copies of a
+/// suspension that was already run are empty.
+#[allow(clippy::type_complexity)]
+pub struct Step<R>(Rc<Cell<Option<Box<dyn FnOnce(&mut dyn FnMut(Resumed) -> R) -> R>>>>);
+
+impl<R> Clone for Step<R> {
+  fn clone(&self) -> Self {
+    Step(self.0.clone())
+  }
+}
+
+/// The bind protocol of [`gen_m!`](crate::gen_m).
+///
+/// A computation of type `Self` bound in a block producing `R` calls the rest of the block with every value of type
+/// [`GenBind::Item`] it has, and combines the results.
+pub trait GenBind<R>: Sized {
+  /// Type of the bound values.
+  type Item;
+
+  /// Call `k` with every value, combining the results.
+  fn gen_bind(self, k: impl FnMut(Self::Item) -> R) -> R;
+}
+
+impl<A, B> GenBind<Vec<B>> for Vec<A> {
+  type Item = A;
+
+  fn gen_bind(self, k: impl FnMut(A) -> Vec<B>) -> Vec<B> {
+    self.into_iter().flat_map(k).collect()
+  }
+}
+
+impl<A, B> GenBind<NonEmpty<B>> for NonEmpty<A> {
+  type Item = A;
+
+  fn gen_bind(self, k: impl FnMut(A) -> NonEmpty<B>) -> NonEmpty<B> {
+    self.and_then(k)
+  }
+}
+
+impl<A, B> GenBind<Option<B>> for Option<A> {
+  type Item = A;
+
+  fn gen_bind(self, k: impl FnMut(A) -> Option<B>) -> Option<B> {
+    self.and_then(k)
+  }
+}
+
+impl<A, B, E> GenBind<Result<B, E>> for Result<A, E> {
+  type Item = A;
+
+  fn gen_bind(self, k: impl FnMut(A) -> Result<B, E>) -> Result<B, E> {
+    self.and_then(k)
+  }
+}
+
+/// Suspend a block on `m`.
+///
+/// Returns the suspension, and the function extracting the bound value the block is resumed with.
+#[doc(hidden)]
+#[allow(clippy::type_complexity)]
+pub fn step<R, M>(m: M) -> (Step<R>, fn(Resumed) -> M::Item)
+where
+  M: GenBind<R> + 'static,
+  M::Item: 'static,
+{
+  let step = Step(Rc::new(Cell::new(Some(Box::new(
+    move |k: &mut dyn FnMut(Resumed) -> R| m.gen_bind(|a| k(Box::new(a))),
+  )))));
+  let unbox = |a: Resumed| match a.downcast() {
+    Ok(a) => *a,
+    Err(_) => unreachable!("do-block coroutine resumed with a value of the wrong type"),
+  };
+
+  (step, unbox)
+}
+
+/// Run a do-block coroutine.
+///
+/// The coroutine is resumed with the values of the computation it is suspended on, from a fresh copy for each
+/// value.
+#[doc(hidden)]
+pub fn run<R, G>(co: G) -> R
+where
+  G: Coroutine<Resumed, Yield = Step<R>, Return = R> + Clone + Unpin,
+{
+  fn go<R, G>(mut co: G, resumed: Resumed) -> R
+  where
+    G: Coroutine<Resumed, Yield = Step<R>, Return = R> + Clone + Unpin,
+  {
+    match Pin::new(&mut co).resume(resumed) {
+      CoroutineState::Yielded(step) => match step.0.take() {
+        Some(step) => step(&mut |a| go(co.clone(), a)),
+        None => unreachable!("do-block coroutine suspended twice on the same computation"),
+      },
+      CoroutineState::Complete(r) => r,
+    }
+  }
+
+  go(co, Box::new(()))
+}
+
+/// Coroutine-based do-notation.
+///
+/// The syntax is the one of [`m!`](crate::m), without the `lift`, `tell` and `catch` statements. See the
+/// [`generator`](crate::generator) module.
+#[macro_export]
+macro_rules! gen_m {
+  // return
+  (@stmts return $r:expr ;) => {
+    $crate::Lift::lift($r)
+  };
+
+  // let-binding
+  (@stmts let $p:pat = $e:expr ; $($r:tt)*) => {{
+    let $p = $e;
+    $crate::gen_m!(@stmts $($r)*)
+  }};
+
+  // const-bind
+  (@stmts _ <- $x:expr ; $($r:tt)*) => {{
+    let _ = $crate::gen_m!(@bind $x);
+    $crate::gen_m!(@stmts $($r)*)
+  }};
+
+  // bind
+  (@stmts $binding:ident <- $x:expr ; $($r:tt)*) => {{
+    let $binding = $crate::gen_m!(@bind $x);
+    $crate::gen_m!(@stmts $($r)*)
+  }};
+
+  // const-bind
+  (@stmts $e:expr ; $($r:tt)*) => {{
+    let _ = $crate::gen_m!(@bind $e);
+    $crate::gen_m!(@stmts $($r)*)
+  }};
+
+  // pure
+  (@stmts $a:expr) => {
+    $a
+  };
+
+  (@bind $x:expr) => {{
+    let (step, unbox) = $crate::generator::step($x);
+    unbox(yield step)
+  }};
+
+  ($($t:tt)*) => {
+    $crate::generator::run(#[coroutine] move |resumed: $crate::generator::Resumed| {
+      ::core::mem::drop(resumed);
+      $crate::gen_m!(@stmts $($t)*)
+    })
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  #[test]
+  fn list() {
+    let r = gen_m! {
+      x <- vec![1, 2];
+      y <- vec!['a', 'b'];
+      return (x, y);
+    };
+
+    assert_eq!(r, vec![(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]);
+
+    let r: Vec<i32> = gen_m! {
+      x <- vec![1, 2, 3, 4];
+      _ <- if x % 2 == 0 { vec![()] } else { vec![] };
+      vec![x; 2]
+    };
+
+    assert_eq!(r, vec![2, 2, 4, 4]);
+  }
+
+  #[test]
+  fn short_circuit() {
+    let r = gen_m! {
+      x <- Some(1);
+      None::<i32>;
+      return x;
+    };
+
+    assert_eq!(r, None);
+
+    let r: Result<i32, String> = gen_m! {
+      x <- "1".parse::<i32>().map_err(|e| e.to_string());
+      y <- Ok(x + 1);
+      return x + y;
+    };
+
+    assert_eq!(r, Ok(3));
+  }
+
+  #[test]
+  fn stack() {
+    let r = gen_m! {
+      x <- (0..10_000).collect::<Vec<_>>();
+      y <- vec![x];
+      return y;
+    };
+
+    assert_eq!(r.len(), 10_000);
+  }
+}
@@ -0,0 +1,192 @@
+//! Interoperability with `frunk`.
+//!
+//! > This module requires the `frunk` feature.
+//!
+//! [`sequence`] runs the computations of a heterogeneous list — an [`HList`] — in any [`Applicative`], collecting
+//! their values into an [`HList`] of the same length. Unlike [`traversable::sequence`](crate::traversable::sequence),
+//! the computations can produce values of different types:
+//!
+//! ```rust
+//! use do_notation::{hlist::sequence, monad::OptionKind};
+//! use frunk::{hlist, hlist_pat};
+//!
+//! let hlist_pat![port, host] = sequence::<OptionKind, _, _>(hlist![Some(8080), Some("localhost")]).unwrap();
+//! assert_eq!((port, host), (8080, "localhost"));
+//!
+//! assert_eq!(sequence::<OptionKind, _, _>(hlist![Some(8080), None::<&str>]), None);
+//! ```
+//!
+//! The [`Sum`], [`Product`], [`First`], [`Last`], [`Min`] and [`Max`] monoids and [`NonEmpty`] implement the
+//! semigroup and monoid traits of `frunk`, and [`Frunk`] wraps the semigroups and monoids of `frunk` so that they
+//! implement the ones of this crate — and can be the output of a [`Writer`](crate::writer::Writer):
+//!
+//! ```rust
+//! use do_notation::{hlist::Frunk, m, writer::run_writer};
+//! use frunk::semigroup::All;
+//!
+//! let r = m! {
+//!   tell Frunk(Some(All(true)));
+//!   tell Frunk(Some(All(false)));
+//!   return ();
+//! };
+//!
+//! assert_eq!(run_writer(r), ((), Frunk(Some(All(false)))));
+//! ```
+
+use crate::{
+  applicative::Applicative,
+  monoid::{First, Last, Max, Min, Monoid, Product, Semigroup, Sum},
+  non_empty::NonEmpty,
+};
+use frunk::{HCons, HNil};
+
+#[doc(no_inline)]
+pub use frunk::HList;
+
+/// Heterogeneous lists of computations in the applicative `F`, producing the list of values `Out`.
+pub trait Sequence<'a, F: Applicative<'a>, Out: 'a> {
+  /// Run every computation, in order, collecting the values.
+  fn sequence(self) -> F::Of<Out>;
+}
+
+impl<'a, F: Applicative<'a>> Sequence<'a, F, HNil> for HNil {
+  fn sequence(self) -> F::Of<HNil> {
+    F::pure(HNil)
+  }
+}
+
+impl<'a, F, H, T, Out> Sequence<'a, F, HCons<H, Out>> for HCons<F::Of<H>, T>
+where
+  F: Applicative<'a>,
+  H: 'a,
+  T: Sequence<'a, F, Out>,
+  Out: 'a,
+{
+  fn sequence(self) -> F::Of<HCons<H, Out>> {
+    F::zip_with(self.head, self.tail.sequence(), |head, tail| HCons {
+      head,
+      tail,
+    })
+  }
+}
+
+/// Run the computations of `l`, in order, collecting their values in the applicative `F`.
+pub fn sequence<'a, F, L, Out>(l: L) -> F::Of<Out>
+where
+  F: Applicative<'a>,
+  L: Sequence<'a, F, Out>,
+  Out: 'a,
+{
+  l.sequence()
+}
+
+/// Semigroups and monoids of `frunk`, as semigroups and monoids of this crate.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Frunk<T>(pub T);
+
+impl<T: frunk::Semigroup> Semigroup for Frunk<T> {
+  fn combine(self, other: Self) -> Self {
+    Frunk(self.0.combine(&other.0))
+  }
+}
+
+impl<T: frunk::Monoid> Monoid for Frunk<T> {
+  fn empty() -> Self {
+    Frunk(T::empty())
+  }
+}
+
+macro_rules! impl_frunk_semigroups {
+  ($($t:ident),*) => {
+    $(
+      impl<T> frunk::Semigroup for $t<T>
+      where
+        $t<T>: Semigroup + Clone,
+      {
+        fn combine(&self, other: &Self) -> Self {
+          Semigroup::combine(self.clone(), other.clone())
+        }
+      }
+
+      impl<T> frunk::Monoid for $t<T>
+      where
+        $t<T>: Monoid + Clone,
+      {
+        fn empty() -> Self {
+          Monoid::empty()
+        }
+      }
+    )*
+  };
+}
+
+impl_frunk_semigroups!(Sum, Product, First, Last, Min, Max);
+
+impl<A: Clone> frunk::Semigroup for NonEmpty<A> {
+  fn combine(&self, other: &Self) -> Self {
+    let mut r = self.clone();
+    for a in other.iter() {
+      r.push(a.clone());
+    }
+
+    r
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    monad::ResultKind,
+    writer::{run_writer, Writer},
+  };
+  use frunk::{hlist, monoid::combine_all, semigroup::Any};
+
+  #[test]
+  fn sequence_hlist() {
+    let parse = |s: &str| {
+      s.parse::<i32>()
+        .map_err(|_| format!("{:?} is not a number", s))
+    };
+
+    let r = sequence::<ResultKind<_>, _, _>(hlist![parse("1"), Ok::<_, String>('a'), Ok(true)]);
+    assert_eq!(r, Ok(hlist![1, 'a', true]));
+
+    let r = sequence::<ResultKind<_>, _, _>(hlist![parse("1"), parse("a"), parse("b")]);
+    assert_eq!(r, Err("\"a\" is not a number".to_owned()));
+
+    assert_eq!(sequence::<ResultKind<()>, _, _>(HNil), Ok(HNil));
+  }
+
+  #[test]
+  fn frunk_monoids() {
+    fn check(n: i32) -> Writer<'static, Frunk<Option<Any<bool>>>, i32> {
+      m! {
+        tell Frunk(Some(Any(n < 0)));
+        return n;
+      }
+    }
+
+    let r = m! {
+      a <- check(1);
+      b <- check(-2);
+      return a + b;
+    };
+
+    assert_eq!(run_writer(r), (-1, Frunk(Some(Any(true)))));
+  }
+
+  #[test]
+  fn crate_monoids() {
+    assert_eq!(combine_all(&[Sum(1), Sum(2), Sum(3)]), Sum(6));
+    assert_eq!(combine_all(&[Max(1u8), Max(3), Max(2)]), Max(3));
+    assert_eq!(
+      combine_all(&[First(None), First(Some(1)), First(Some(2))]),
+      First(Some(1))
+    );
+    assert_eq!(combine_all::<Last<i32>>(&[]), Last(None));
+
+    let xs = frunk::Semigroup::combine(&NonEmpty::new(1), &NonEmpty::new(2));
+    assert_eq!(xs.iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+  }
+}
@@ -0,0 +1,75 @@
+//! The identity monad.
+//!
+//! [`Identity`] doesn’t have any effect. It is mostly useful as the inner monad of monad
+//! transformers — e.g. [`State`](crate::state::State) is [`StateT`](crate::state::StateT) over
+//! [`Identity`].
+
+use crate::{
+  either::Either,
+  monad::{Kind, Monad},
+  rec::MonadRec,
+  Lift,
+};
+
+/// The monad without any effect.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Identity<A>(pub A);
+
+impl<A> Identity<A> {
+  /// Extract the value.
+  pub fn run(self) -> A {
+    self.0
+  }
+
+  /// Pass the value to `f`.
+  pub fn and_then<B>(self, f: impl FnOnce(A) -> Identity<B>) -> Identity<B> {
+    f(self.0)
+  }
+}
+
+impl<A> Lift<A> for Identity<A> {
+  fn lift(a: A) -> Self {
+    Identity(a)
+  }
+}
+
+/// [`Kind`] of `Identity<_>`.
+#[derive(Debug)]
+pub struct IdentityKind;
+
+impl<'a> Kind<'a> for IdentityKind {
+  type Of<A: 'a> = Identity<A>;
+
+  fn loop_m<A: 'a, B: 'a>(a: A, f: impl FnMut(A) -> Identity<Either<A, B>> + 'a) -> Identity<B> {
+    Self::tail_rec_m(a, f)
+  }
+}
+
+impl<'a, A: 'a> Monad<'a> for Identity<A> {
+  type Item = A;
+  type Kind = IdentityKind;
+
+  fn and_then<B, F>(self, f: F) -> Identity<B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Identity<B> + 'a,
+  {
+    Identity::and_then(self, f)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn identity() {
+    let r = m! {
+      x <- Identity(1);
+      y <- Identity(2);
+      return x + y;
+    };
+
+    assert_eq!(r.run(), 3);
+  }
+}
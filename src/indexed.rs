@@ -0,0 +1,272 @@
+//! Parameterised monads.
+//!
+//! `m!` only relies on an `and_then` method, which doesn’t have to keep the type of the monad. A parameterised
+//! monad — also known as an indexed monad — carries two type-level indices, `I` and `J`: a computation starts at
+//! index `I` and ends at index `J`, and binding it to a computation starting at `J` yields a computation going from
+//! `I` to wherever the second one ends. The indices are checked at compile time, so they can track permissions,
+//! units or the phases of a protocol.
+//!
+//! [`IxBind`] is the parameterised counterpart of [`Monad`](crate::Monad), and [`IxKind`] the one of
+//! [`Kind`](crate::Kind). [`IxState`] is a state monad whose state can change type along the computation. When
+//! the states are the phases of a protocol, the protocol can only be followed in order:
+//!
+//! ```rust
+//! use do_notation::{indexed::{modify, run_ix_state, IxState}, m};
+//!
+//! struct Closed;
+//! struct Open(Vec<String>);
+//!
+//! fn open() -> IxState<'static, Closed, Open, ()> {
+//!   modify(|Closed| Open(Vec::new()))
+//! }
+//!
+//! fn write(line: &'static str) -> IxState<'static, Open, Open, ()> {
+//!   modify(move |Open(mut lines)| {
+//!     lines.push(line.to_owned());
+//!     Open(lines)
+//!   })
+//! }
+//!
+//! fn close() -> IxState<'static, Open, Closed, Vec<String>> {
+//!   IxState::new(|Open(lines)| (lines, Closed))
+//! }
+//!
+//! let session = m! {
+//!   open();
+//!   write("hello");
+//!   write("world");
+//!   close()
+//! };
+//!
+//! let (lines, Closed) = run_ix_state(session, Closed);
+//! assert_eq!(lines, vec!["hello", "world"]);
+//! ```
+//!
+//! Writing before opening doesn’t compile:
+//!
+//! ```rust,compile_fail
+//! # use do_notation::{indexed::{modify, run_ix_state, IxState}, m};
+//! # struct Closed;
+//! # struct Open(Vec<String>);
+//! # fn write(line: &'static str) -> IxState<'static, Open, Open, ()> {
+//! #   modify(move |Open(mut lines)| {
+//! #     lines.push(line.to_owned());
+//! #     Open(lines)
+//! #   })
+//! # }
+//! let session = m! {
+//!   write("hello");
+//!   return ();
+//! };
+//!
+//! run_ix_state(session, Closed);
+//! ```
+
+use crate::Lift;
+
+/// Type constructors of parameterised monads.
+///
+/// Like [`Kind`](crate::Kind), this trait is implemented by marker types.
+pub trait IxKind<'a>: Sized + 'a {
+  /// The parameterised monad going from the index `I` to the index `J`, producing a value of type `A`.
+  type Of<I: 'a, J: 'a, A: 'a>: IxBind<'a, Pre = I, Post = J, Item = A, Kind = Self>;
+
+  /// Embed a value, without changing the index.
+  fn pure<I: 'a, A: 'a>(a: A) -> Self::Of<I, I, A>;
+}
+
+/// Parameterised monads.
+///
+/// Implementors must ensure that the monad laws hold, with [`IxKind::pure`] as `return`.
+pub trait IxBind<'a>: Sized + 'a {
+  /// Index at the start of the computation.
+  type Pre: 'a;
+
+  /// Index at the end of the computation.
+  type Post: 'a;
+
+  /// Type of the value produced by the computation.
+  type Item: 'a;
+
+  /// [`IxKind`] of the monad.
+  type Kind: IxKind<'a>;
+
+  /// Sequence `f` after this computation, `f` starting at the index this computation ends at.
+  fn and_then<K, B, F>(self, f: F) -> <Self::Kind as IxKind<'a>>::Of<Self::Pre, K, B>
+  where
+    K: 'a,
+    B: 'a,
+    F: FnOnce(Self::Item) -> <Self::Kind as IxKind<'a>>::Of<Self::Post, K, B> + 'a;
+}
+
+/// Computations turning a state of type `I` into a state of type `J`, producing a value of type `A`.
+#[allow(clippy::type_complexity)]
+pub struct IxState<'a, I, J, A> {
+  run: Box<dyn FnOnce(I) -> (A, J) + 'a>,
+}
+
+impl<'a, I, J, A> IxState<'a, I, J, A>
+where
+  I: 'a,
+  J: 'a,
+  A: 'a,
+{
+  /// Create a computation from a state transition.
+  pub fn new(f: impl FnOnce(I) -> (A, J) + 'a) -> Self {
+    IxState { run: Box::new(f) }
+  }
+
+  /// Bind the value produced by this computation, passing along the updated state.
+  pub fn and_then<K, B>(
+    self,
+    f: impl FnOnce(A) -> IxState<'a, J, K, B> + 'a,
+  ) -> IxState<'a, I, K, B>
+  where
+    K: 'a,
+    B: 'a,
+  {
+    IxState::new(move |i| {
+      let (a, j) = (self.run)(i);
+      (f(a).run)(j)
+    })
+  }
+}
+
+impl<'a, S, A> Lift<A> for IxState<'a, S, S, A>
+where
+  S: 'a,
+  A: 'a,
+{
+  fn lift(a: A) -> Self {
+    IxState::new(move |s| (a, s))
+  }
+}
+
+/// [`IxKind`] of `IxState<'_, _, _, _>`.
+#[derive(Debug)]
+pub struct IxStateKind;
+
+impl<'a> IxKind<'a> for IxStateKind {
+  type Of<I: 'a, J: 'a, A: 'a> = IxState<'a, I, J, A>;
+
+  fn pure<I: 'a, A: 'a>(a: A) -> IxState<'a, I, I, A> {
+    Lift::lift(a)
+  }
+}
+
+impl<'a, I, J, A> IxBind<'a> for IxState<'a, I, J, A>
+where
+  I: 'a,
+  J: 'a,
+  A: 'a,
+{
+  type Pre = I;
+  type Post = J;
+  type Item = A;
+  type Kind = IxStateKind;
+
+  fn and_then<K, B, F>(self, f: F) -> IxState<'a, I, K, B>
+  where
+    K: 'a,
+    B: 'a,
+    F: FnOnce(A) -> IxState<'a, J, K, B> + 'a,
+  {
+    IxState::and_then(self, f)
+  }
+}
+
+/// Get the state.
+pub fn get<'a, S>() -> IxState<'a, S, S, S>
+where
+  S: Clone + 'a,
+{
+  IxState::new(|s: S| (s.clone(), s))
+}
+
+/// Get a value computed from the state.
+pub fn gets<'a, S, A>(f: impl FnOnce(&S) -> A + 'a) -> IxState<'a, S, S, A>
+where
+  S: 'a,
+  A: 'a,
+{
+  IxState::new(move |s| (f(&s), s))
+}
+
+/// Replace the state, possibly with a state of another type.
+pub fn put<'a, I, J>(j: J) -> IxState<'a, I, J, ()>
+where
+  I: 'a,
+  J: 'a,
+{
+  IxState::new(move |_| ((), j))
+}
+
+/// Modify the state, possibly into a state of another type.
+pub fn modify<'a, I, J>(f: impl FnOnce(I) -> J + 'a) -> IxState<'a, I, J, ()>
+where
+  I: 'a,
+  J: 'a,
+{
+  IxState::new(move |i| ((), f(i)))
+}
+
+/// Run a computation with an initial state, returning the produced value and the final state.
+pub fn run_ix_state<'a, I, J, A>(m: IxState<'a, I, J, A>, i: I) -> (A, J) {
+  (m.run)(i)
+}
+
+/// Run a computation with an initial state, returning the produced value.
+pub fn eval_ix_state<'a, I, J, A>(m: IxState<'a, I, J, A>, i: I) -> A {
+  run_ix_state(m, i).0
+}
+
+/// Run a computation with an initial state, returning the final state.
+pub fn exec_ix_state<'a, I, J, A>(m: IxState<'a, I, J, A>, i: I) -> J {
+  run_ix_state(m, i).1
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[derive(Debug, PartialEq)]
+  struct Meters(f64);
+
+  #[derive(Debug, PartialEq)]
+  struct Feet(f64);
+
+  fn to_feet<'a>() -> IxState<'a, Meters, Feet, ()> {
+    modify(|Meters(m)| Feet(m / 0.3048))
+  }
+
+  fn double<'a>() -> IxState<'a, Meters, Meters, ()> {
+    modify(|Meters(m)| Meters(m * 2.))
+  }
+
+  fn both<'a, K, I, J, A, B>(a: K::Of<I, J, A>, b: K::Of<J, J, B>) -> K::Of<I, J, (A, B)>
+  where
+    K: IxKind<'a>,
+    I: 'a,
+    J: 'a,
+    A: 'a,
+    B: 'a,
+  {
+    a.and_then(move |a| b.and_then(move |b| K::pure((a, b))))
+  }
+
+  #[test]
+  fn ix_state() {
+    let r = m! {
+      double();
+      to_feet();
+      gets(|Feet(ft): &Feet| *ft)
+    };
+
+    let (ft, q) = run_ix_state(r, Meters(0.1524));
+    assert!((ft - 1.).abs() < 1e-9);
+    assert_eq!(q, Feet(ft));
+
+    let r = both::<IxStateKind, _, _, _, _>(put("a"), get());
+    assert_eq!(run_ix_state(r, 1), (((), "a"), "a"));
+  }
+}
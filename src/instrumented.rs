@@ -0,0 +1,227 @@
+//! Instrumented computations.
+//!
+//! [`Instrumented`] wraps the computations of another monad, and counts what happens while a do-block runs: the
+//! binds, the values lifted — e.g. with `return` — and the binds whose continuation never ran because the computation
+//! short-circuited. This is mostly useful to test the behavior of algorithms, e.g. to check that a pipeline stops at
+//! its first failure.
+//!
+//! ```rust
+//! use do_notation::{instrumented::{Instrumented, Stats}, m};
+//!
+//! let parse = |s: &str| Instrumented::new(s.parse::<i32>().ok());
+//!
+//! let (r, stats) = m! {
+//!   a <- parse("1");
+//!   b <- parse("two");
+//!   c <- parse("3");
+//!   return a + b + c;
+//! }
+//! .run();
+//!
+//! assert_eq!(r, None);
+//! assert_eq!(stats, Stats { binds: 2, lifts: 0, short_circuits: 1 });
+//! ```
+//!
+//! The statistics are gathered while the computation is built, so the inner monad must be strict: its `and_then`
+//! must call the continuation, if ever, before returning — like `Option`, `Result` or
+//! [`Identity`](crate::identity::Identity).
+
+use crate::{
+  either::Either,
+  monad::{Kind, Monad},
+  monoid::{Monoid, Semigroup},
+  Lift,
+};
+use std::{cell::Cell, marker::PhantomData, rc::Rc};
+
+/// Statistics of an [`Instrumented`] computation.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Stats {
+  /// Number of binds.
+  pub binds: usize,
+
+  /// Number of lifted values.
+  pub lifts: usize,
+
+  /// Number of binds whose continuation wasn’t called.
+  pub short_circuits: usize,
+}
+
+impl Semigroup for Stats {
+  fn combine(self, other: Self) -> Self {
+    Stats {
+      binds: self.binds + other.binds,
+      lifts: self.lifts + other.lifts,
+      short_circuits: self.short_circuits + other.short_circuits,
+    }
+  }
+}
+
+impl Monoid for Stats {
+  fn empty() -> Self {
+    Stats::default()
+  }
+}
+
+/// A computation of the monad `M`, along with the [`Stats`] of its construction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Instrumented<M> {
+  inner: M,
+  stats: Stats,
+}
+
+impl<M> Instrumented<M> {
+  /// Wrap a computation, without any statistics.
+  pub fn new(inner: M) -> Self {
+    Instrumented {
+      inner,
+      stats: Stats::default(),
+    }
+  }
+
+  /// Statistics of the computation so far.
+  pub fn stats(&self) -> Stats {
+    self.stats
+  }
+
+  /// Extract the computation and its statistics.
+  pub fn run(self) -> (M, Stats) {
+    (self.inner, self.stats)
+  }
+
+  /// Bind the value produced by the computation, counting the bind and whether `f` was called.
+  pub fn and_then<'a, B, F>(self, f: F) -> Instrumented<<M::Kind as Kind<'a>>::Of<B>>
+  where
+    M: Monad<'a>,
+    B: 'a,
+    F: FnOnce(M::Item) -> Instrumented<<M::Kind as Kind<'a>>::Of<B>> + 'a,
+  {
+    let continued = Rc::new(Cell::new(None));
+    let continued_ = continued.clone();
+    let inner = self.inner.and_then::<B, _>(move |a| {
+      let r = f(a);
+      continued_.set(Some(r.stats));
+      r.inner
+    });
+    let stats = Stats {
+      binds: 1,
+      ..Stats::default()
+    };
+    let stats = match continued.take() {
+      Some(continued) => stats.combine(continued),
+      None => Stats {
+        short_circuits: 1,
+        ..stats
+      },
+    };
+
+    Instrumented {
+      inner,
+      stats: self.stats.combine(stats),
+    }
+  }
+}
+
+impl<A, M> Lift<A> for Instrumented<M>
+where
+  M: Lift<A>,
+{
+  fn lift(a: A) -> Self {
+    Instrumented {
+      inner: M::lift(a),
+      stats: Stats {
+        lifts: 1,
+        ..Stats::default()
+      },
+    }
+  }
+}
+
+/// [`Kind`] of `Instrumented<K::Of<_>>`.
+#[derive(Debug)]
+pub struct InstrumentedKind<K>(PhantomData<K>);
+
+impl<'a, K> Kind<'a> for InstrumentedKind<K>
+where
+  K: Kind<'a>,
+{
+  type Of<A: 'a> = Instrumented<K::Of<A>>;
+
+  /// Loop with the inner monad, gathering the statistics of every step.
+  fn loop_m<A: 'a, B: 'a>(
+    a: A,
+    mut f: impl FnMut(A) -> Instrumented<K::Of<Either<A, B>>> + 'a,
+  ) -> Instrumented<K::Of<B>> {
+    let stats = Rc::new(Cell::new(Stats::default()));
+    let stats_ = stats.clone();
+    let inner = K::loop_m(a, move |a| {
+      let step = f(a);
+      stats_.set(stats_.get().combine(step.stats));
+      step.inner
+    });
+
+    Instrumented {
+      inner,
+      stats: stats.get(),
+    }
+  }
+}
+
+impl<'a, M> Monad<'a> for Instrumented<M>
+where
+  M: Monad<'a>,
+{
+  type Item = M::Item;
+  type Kind = InstrumentedKind<M::Kind>;
+
+  fn and_then<B, F>(self, f: F) -> Instrumented<<M::Kind as Kind<'a>>::Of<B>>
+  where
+    B: 'a,
+    F: FnOnce(M::Item) -> Instrumented<<M::Kind as Kind<'a>>::Of<B>> + 'a,
+  {
+    Instrumented::and_then::<B, F>(self, f)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{combinators::map_m, identity::Identity};
+
+  #[test]
+  fn instrumented() {
+    let r = m! {
+      a <- Instrumented::new(Ok::<_, &str>(1));
+      b <- Instrumented::new(Ok(2));
+      return a + b;
+    };
+
+    assert_eq!(
+      r.run(),
+      (
+        Ok(3),
+        Stats {
+          binds: 2,
+          lifts: 1,
+          short_circuits: 0
+        }
+      )
+    );
+
+    let r = m! {
+      _ <- Instrumented::new(Identity(()));
+      Instrumented::<Identity<i32>>::lift(1)
+    };
+
+    assert_eq!(r.stats().lifts, 1);
+  }
+
+  #[test]
+  fn short_circuit() {
+    let checked = |n: i32| Instrumented::new(if n < 3 { Some(n) } else { None });
+    let (r, stats) = map_m(1..10, checked).run();
+
+    assert_eq!(r, None);
+    assert_eq!(stats.short_circuits, 1);
+  }
+}
@@ -0,0 +1,184 @@
+//! Side effects.
+//!
+//! [`Io`] describes a side-effecting action, performed only when the action is [run](Io::run).
+//! Effectful leaf operations are embedded into any monad implementing [`MonadIo`] with
+//! [`MonadIo::lift_io`].
+//!
+//! ```rust
+//! use do_notation::{io::{Io, IoKind, MonadIo}, m, reader::{ask, run_reader_t, ReaderTKind}};
+//!
+//! type App = ReaderTKind<String, IoKind>;
+//!
+//! let r = m! {
+//!   prefix <- ask::<String, IoKind>();
+//!   line <- App::lift_io(move || format!("{}: hello", prefix));
+//!   return line.len();
+//! };
+//!
+//! assert_eq!(run_reader_t(r, "info".to_owned()).run(), 11);
+//! ```
+
+use crate::{
+  lazy::{Lazy, LazyKind},
+  monad::{Kind, Monad},
+  monoid::Monoid,
+  reader::ReaderTKind,
+  result::ResultTKind,
+  state::StateTKind,
+  trans::MonadTrans,
+  writer::WriterTKind,
+  Lift,
+};
+
+/// Side-effecting actions producing a value of type `A`.
+pub struct Io<'a, A> {
+  action: Box<dyn FnOnce() -> A + 'a>,
+}
+
+impl<'a, A: 'a> Io<'a, A> {
+  /// Create an action performing the side effects of `f`.
+  pub fn new(f: impl FnOnce() -> A + 'a) -> Self {
+    Io {
+      action: Box::new(f),
+    }
+  }
+
+  /// Perform the action.
+  pub fn run(self) -> A {
+    (self.action)()
+  }
+
+  /// Sequence the action with the one produced by `f`.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(A) -> Io<'a, B> + 'a) -> Io<'a, B> {
+    Io::new(move || f(self.run()).run())
+  }
+}
+
+impl<'a, A: 'a> Lift<A> for Io<'a, A> {
+  fn lift(a: A) -> Self {
+    Io::new(move || a)
+  }
+}
+
+/// [`Kind`] of `Io<'_, _>`.
+#[derive(Debug)]
+pub struct IoKind;
+
+impl<'a> Kind<'a> for IoKind {
+  type Of<A: 'a> = Io<'a, A>;
+}
+
+impl<'a, A: 'a> Monad<'a> for Io<'a, A> {
+  type Item = A;
+  type Kind = IoKind;
+
+  fn and_then<B, F>(self, f: F) -> Io<'a, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Io<'a, B> + 'a,
+  {
+    Io::and_then(self, f)
+  }
+}
+
+/// Monads able to perform side effects.
+///
+/// This trait is implemented by the [`Kind`] of the monads, so that functions can be
+/// written against any monad able to perform side effects.
+pub trait MonadIo<'a>: Kind<'a> {
+  /// Embed the side effects of `f` into the monad.
+  fn lift_io<A: 'a>(f: impl FnOnce() -> A + 'a) -> Self::Of<A>;
+}
+
+impl<'a> MonadIo<'a> for IoKind {
+  fn lift_io<A: 'a>(f: impl FnOnce() -> A + 'a) -> Io<'a, A> {
+    Io::new(f)
+  }
+}
+
+impl<'a> MonadIo<'a> for LazyKind {
+  fn lift_io<A: 'a>(f: impl FnOnce() -> A + 'a) -> Lazy<'a, A> {
+    Lazy::new(f)
+  }
+}
+
+impl<'a, R, M> MonadIo<'a> for ReaderTKind<R, M>
+where
+  R: Clone + 'a,
+  M: MonadIo<'a>,
+{
+  fn lift_io<A: 'a>(f: impl FnOnce() -> A + 'a) -> Self::Of<A> {
+    MonadTrans::lift(M::lift_io(f))
+  }
+}
+
+impl<'a, S, M> MonadIo<'a> for StateTKind<S, M>
+where
+  S: 'a,
+  M: MonadIo<'a>,
+{
+  fn lift_io<A: 'a>(f: impl FnOnce() -> A + 'a) -> Self::Of<A> {
+    MonadTrans::lift(M::lift_io(f))
+  }
+}
+
+impl<'a, W, M> MonadIo<'a> for WriterTKind<W, M>
+where
+  W: Monoid + 'a,
+  M: MonadIo<'a>,
+{
+  fn lift_io<A: 'a>(f: impl FnOnce() -> A + 'a) -> Self::Of<A> {
+    MonadTrans::lift(M::lift_io(f))
+  }
+}
+
+impl<'a, E, M> MonadIo<'a> for ResultTKind<E, M>
+where
+  E: 'a,
+  M: MonadIo<'a>,
+{
+  fn lift_io<A: 'a>(f: impl FnOnce() -> A + 'a) -> Self::Of<A> {
+    MonadTrans::lift(M::lift_io(f))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    result::{run_result_t, throw_error},
+    state::{modify, run_state_t},
+  };
+  use std::cell::RefCell;
+
+  #[test]
+  fn io() {
+    let log = &RefCell::new(Vec::new());
+    let r = m! {
+      _ <- Io::new(move || log.borrow_mut().push(1));
+      x <- Io::new(|| 2);
+      _ <- Io::new(move || log.borrow_mut().push(x));
+      return x;
+    };
+
+    assert!(log.borrow().is_empty());
+    assert_eq!(r.run(), 2);
+    assert_eq!(*log.borrow(), vec![1, 2]);
+  }
+
+  #[test]
+  fn lift_io_stack() {
+    type App = StateTKind<i32, ResultTKind<&'static str, IoKind>>;
+
+    let log = &RefCell::new(Vec::new());
+    let r = m! {
+      _ <- modify(|n| n + 1);
+      _ <- App::lift_io(move || log.borrow_mut().push("modified"));
+      _ <- lift throw_error::<_, _, ()>("nope");
+      App::lift_io(move || log.borrow_mut().push("unreachable"))
+    };
+
+    assert_eq!(run_result_t(run_state_t(r, 0)).run(), Err("nope"));
+    assert_eq!(*log.borrow(), vec!["modified"]);
+  }
+}
@@ -0,0 +1,73 @@
+//! JavaScript promises.
+//!
+//! > This module requires the `wasm-bindgen` feature.
+//!
+//! [`JsPromise`] wraps a [`Promise`] — or a [`JsFuture`] — so that it can be bound in
+//! [`async_m!`](crate::async_m): the value the promise resolves to is bound, and a rejected promise short-circuits
+//! the block with the rejection, as a `Result<_, JsValue>`. JavaScript interop calls can then be sequenced without
+//! matching on each awaited result:
+//!
+//! ```rust,no_run
+//! use do_notation::{async_m, js::JsPromise};
+//! use js_sys::{Function, Promise};
+//! use wasm_bindgen::JsValue;
+//!
+//! async fn load(fetch_user: &Function, fetch_avatar: &Function) -> Result<JsValue, JsValue> {
+//!   async_m! {
+//!     user <- JsPromise(Promise::from(fetch_user.call0(&JsValue::NULL)?));
+//!     avatar <- JsPromise(Promise::from(fetch_avatar.call1(&JsValue::NULL, &user)?));
+//!     return avatar;
+//!   }
+//!   .await
+//! }
+//! ```
+//!
+//! The block is a Rust future; [`wasm_bindgen_futures::future_to_promise`] turns it back into a [`Promise`] for
+//! JavaScript.
+
+use crate::future::Bind;
+use js_sys::Promise;
+use std::ops::ControlFlow;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+
+/// JavaScript promises, or the futures awaiting them.
+///
+/// Binding a [`JsPromise`] in [`async_m!`](crate::async_m) binds the resolved value, or short-circuits the block with
+/// the rejection. The block must produce a `Result<_, JsValue>`.
+#[derive(Clone, Debug)]
+pub struct JsPromise<P = Promise>(pub P);
+
+impl<B, P> Bind<Result<B, JsValue>> for JsPromise<P>
+where
+  P: Into<JsFuture>,
+{
+  type Item = JsValue;
+
+  async fn bind(self) -> ControlFlow<Result<B, JsValue>, JsValue> {
+    match self.0.into().await {
+      Ok(a) => ControlFlow::Continue(a),
+      Err(e) => ControlFlow::Break(Err(e)),
+    }
+  }
+}
+
+impl From<Promise> for JsPromise {
+  fn from(promise: Promise) -> Self {
+    JsPromise(promise)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assert_bind<P: Bind<Result<(), JsValue>, Item = JsValue>>() {}
+
+  // promises can only be created and awaited on wasm targets, where JavaScript runs
+  #[test]
+  fn bind() {
+    assert_bind::<JsPromise>();
+    assert_bind::<JsPromise<JsFuture>>();
+  }
+}
@@ -0,0 +1,246 @@
+//! Decoding dynamic JSON.
+//!
+//! > This module requires the `serde_json` feature.
+//!
+//! [`Decoder`] is the monad of decoders of [`Value`]s: a computation extracts a value from a JSON document, or fails
+//! with a [`DecodeError`]. [`field`] and [`index`] run a decoder on a part of the document, and the errors say where
+//! the decoding failed, so that nested data is extracted with a do-block instead of chains of
+//! `get(…).and_then(…).ok_or(…)`:
+//!
+//! ```rust
+//! use do_notation::{json::{field, index, int, optional, string, Decoder}, m};
+//! use serde_json::json;
+//!
+//! fn user<'a>() -> Decoder<'a, (String, i64, Option<String>)> {
+//!   m! {
+//!     name <- field("name", string());
+//!     age <- field("age", int());
+//!     email <- field("emails", index(0, optional(string())));
+//!     return (name, age, email);
+//!   }
+//! }
+//!
+//! let doc = json!({ "name": "Ada", "age": 36, "emails": ["ada@example.com"] });
+//! assert_eq!(user().decode(&doc).unwrap(), ("Ada".to_owned(), 36, Some("ada@example.com".to_owned())));
+//!
+//! let doc = json!({ "name": "Ada", "age": "36", "emails": [] });
+//! assert_eq!(user().decode(&doc).unwrap_err().to_string(), "at $.age: expected an integer, found \"36\"");
+//! ```
+//!
+//! [`optional`] decodes `null` as [`None`]; in a [`field`], a missing field is decoded as `null`.
+
+use crate::Lift;
+use serde_json::Value;
+use std::{error::Error, fmt};
+
+/// Step of the path to a part of a JSON document.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Segment {
+  /// Field of an object.
+  Field(String),
+  /// Element of an array.
+  Index(usize),
+}
+
+/// Errors of [`Decoder`]s.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecodeError {
+  /// Path to the part of the document which couldn’t be decoded, from the root.
+  pub path: Vec<Segment>,
+  /// What went wrong.
+  pub message: String,
+}
+
+impl DecodeError {
+  /// Error at the root of the document.
+  pub fn new(message: impl Into<String>) -> Self {
+    DecodeError {
+      path: Vec::new(),
+      message: message.into(),
+    }
+  }
+
+  fn at(mut self, segment: Segment) -> Self {
+    self.path.insert(0, segment);
+    self
+  }
+}
+
+impl fmt::Display for DecodeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("at $")?;
+
+    for segment in &self.path {
+      match segment {
+        Segment::Field(name) => write!(f, ".{}", name)?,
+        Segment::Index(i) => write!(f, "[{}]", i)?,
+      }
+    }
+
+    write!(f, ": {}", self.message)
+  }
+}
+
+impl Error for DecodeError {}
+
+/// A decoder of JSON documents, producing a value of type `A`.
+#[allow(clippy::type_complexity)]
+pub struct Decoder<'a, A>(Box<dyn FnOnce(&Value) -> Result<A, DecodeError> + 'a>);
+
+impl<'a, A: 'a> Decoder<'a, A> {
+  /// Build a decoder out of a function.
+  pub fn new(f: impl FnOnce(&Value) -> Result<A, DecodeError> + 'a) -> Self {
+    Decoder(Box::new(f))
+  }
+
+  /// Decode `value`.
+  pub fn decode(self, value: &Value) -> Result<A, DecodeError> {
+    (self.0)(value)
+  }
+
+  /// Decode the document, then decode it again with the decoder built by `f` from the value.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(A) -> Decoder<'a, B> + 'a) -> Decoder<'a, B> {
+    Decoder::new(move |value| {
+      let a = self.decode(value)?;
+      f(a).decode(value)
+    })
+  }
+
+  /// Apply `f` to the decoded value.
+  pub fn map<B: 'a>(self, f: impl FnOnce(A) -> B + 'a) -> Decoder<'a, B> {
+    Decoder::new(move |value| self.decode(value).map(f))
+  }
+
+  /// Fail with `message`, whatever the document.
+  pub fn fail(message: impl Into<String>) -> Self {
+    let e = DecodeError::new(message);
+    Decoder::new(move |_| Err(e))
+  }
+}
+
+impl<'a, A: 'a> Lift<A> for Decoder<'a, A> {
+  fn lift(a: A) -> Self {
+    Decoder::new(move |_| Ok(a))
+  }
+}
+
+fn expected(what: &str, value: &Value) -> DecodeError {
+  DecodeError::new(format!("expected {}, found {}", what, value))
+}
+
+/// Decode the field `name` of an object with `d`.
+///
+/// A missing field is decoded as `null`, so that it can be decoded with [`optional`].
+pub fn field<'a, A: 'a>(name: &'a str, d: Decoder<'a, A>) -> Decoder<'a, A> {
+  Decoder::new(move |value| match value {
+    Value::Object(fields) => match fields.get(name) {
+      Some(value) => d.decode(value),
+      None => d
+        .decode(&Value::Null)
+        .map_err(|_| DecodeError::new("missing field")),
+    }
+    .map_err(|e| e.at(Segment::Field(name.to_owned()))),
+    _ => Err(expected("an object", value)),
+  })
+}
+
+/// Decode the element `i` of an array with `d`.
+pub fn index<'a, A: 'a>(i: usize, d: Decoder<'a, A>) -> Decoder<'a, A> {
+  Decoder::new(move |value| match value {
+    Value::Array(elements) => match elements.get(i) {
+      Some(value) => d.decode(value),
+      None => Err(DecodeError::new(format!(
+        "missing element, the array has {} elements",
+        elements.len()
+      ))),
+    }
+    .map_err(|e| e.at(Segment::Index(i))),
+    _ => Err(expected("an array", value)),
+  })
+}
+
+/// Decode a string.
+pub fn string<'a>() -> Decoder<'a, String> {
+  Decoder::new(|value| match value {
+    Value::String(s) => Ok(s.clone()),
+    _ => Err(expected("a string", value)),
+  })
+}
+
+/// Decode an integer.
+pub fn int<'a>() -> Decoder<'a, i64> {
+  Decoder::new(|value| value.as_i64().ok_or_else(|| expected("an integer", value)))
+}
+
+/// Decode `null` as [`None`], and anything else with `d`.
+pub fn optional<'a, A: 'a>(d: Decoder<'a, A>) -> Decoder<'a, Option<A>> {
+  Decoder::new(move |value| match value {
+    Value::Null => Ok(None),
+    _ => d.decode(value).map(Some),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn point<'a>() -> Decoder<'a, (i64, i64)> {
+    m! {
+      x <- field("x", int());
+      y <- field("y", int());
+      return (x, y);
+    }
+  }
+
+  #[test]
+  fn decoder() {
+    let doc = json!({ "points": [{ "x": 1, "y": 2 }, { "x": 3 }], "label": null });
+
+    let r = m! {
+      p <- field("points", index(0, point()));
+      label <- field("label", optional(string()));
+      color <- field("color", optional(string()));
+      return (p, label, color);
+    };
+
+    assert_eq!(r.decode(&doc), Ok(((1, 2), None, None)));
+
+    let e = field("points", index(1, point())).decode(&doc).unwrap_err();
+    assert_eq!(
+      e.path,
+      vec![
+        Segment::Field("points".to_owned()),
+        Segment::Index(1),
+        Segment::Field("y".to_owned())
+      ]
+    );
+    assert_eq!(e.to_string(), "at $.points[1].y: missing field");
+  }
+
+  #[test]
+  fn errors() {
+    let doc = json!({ "points": [1], "label": 3 });
+
+    let e = field("points", index(2, int())).decode(&doc).unwrap_err();
+    assert_eq!(
+      e.to_string(),
+      "at $.points[2]: missing element, the array has 1 elements"
+    );
+
+    let e = field("label", optional(string())).decode(&doc).unwrap_err();
+    assert_eq!(e.to_string(), "at $.label: expected a string, found 3");
+
+    let e = index(0, int()).decode(&doc).unwrap_err();
+    assert_eq!(
+      e.to_string(),
+      "at $: expected an array, found {\"label\":3,\"points\":[1]}"
+    );
+
+    let e = m! {
+      n <- field("points", index(0, int()));
+      if n > 0 { Decoder::<()>::fail("positive") } else { Lift::lift(()) }
+    };
+    assert_eq!(e.decode(&doc), Err(DecodeError::new("positive")));
+  }
+}
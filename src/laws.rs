@@ -0,0 +1,291 @@
+//! Property tests of the laws of functors, applicative functors and monads.
+//!
+//! > This module requires the `proptest` feature.
+//!
+//! [`Lift`] and `and_then` can be implemented for any type, but a monad is only useful if they obey the monad laws
+//! — otherwise, refactoring a do-block, like extracting some of its statements into a function, changes its
+//! meaning. The functions of this module check the laws with [`proptest`], for values generated by strategies, and
+//! panic with the minimal failing input if a law is broken, so that they can be called from any test suite.
+//!
+//! Computations can’t always be compared — e.g. [`State`](crate::state::State) is a function — so each check takes
+//! an `observe` function, running the computations to produce comparable values.
+//!
+//! ```rust
+//! use do_notation::{
+//!   identity::IdentityKind,
+//!   laws::{check_associativity, check_left_identity, check_right_identity},
+//!   monad::OptionKind,
+//!   state::{modify, run_state, State, StateTKind},
+//!   Lift,
+//! };
+//!
+//! type S = StateTKind<i32, IdentityKind>;
+//!
+//! let push = |n: i32| -> State<'static, i32, i32> {
+//!   modify(move |s: i32| s * 10 + n).and_then(move |()| Lift::lift(n + 1))
+//! };
+//! let observe = |m| run_state(m, 1);
+//!
+//! check_left_identity::<S, _, _, _>(0..10, push, observe);
+//! check_right_identity::<S, _, _, _>(0..10, push, observe);
+//! check_associativity::<S, _, _, _, _, _>(0..10, push, push, push, observe);
+//!
+//! let half = |n: i32| if n % 2 == 0 { Some(n / 2) } else { None };
+//! check_associativity::<OptionKind, _, _, _, _, _>(0..100, half, half, half, |m| m);
+//! ```
+
+use crate::{
+  applicative::{Applicative, Functor},
+  monad::{Kind, Monad},
+  Lift,
+};
+use proptest::{
+  prop_assert_eq,
+  strategy::Strategy,
+  test_runner::{TestCaseError, TestRunner},
+};
+use std::fmt::Debug;
+
+/// Run `test` on the values generated by `strategy`, panicking with the minimal failing value, if any.
+fn check<S>(strategy: S, test: impl Fn(S::Value) -> Result<(), TestCaseError>)
+where
+  S: Strategy,
+{
+  if let Err(e) = TestRunner::default().run(&strategy, test) {
+    panic!("{}", e);
+  }
+}
+
+/// Check that `lift(a).and_then(f)` is equivalent to `f(a)`, for the values generated by `a`.
+pub fn check_left_identity<'a, K, A, B, O>(
+  a: impl Strategy<Value = A>,
+  f: impl Fn(A) -> K::Of<B> + Clone + 'a,
+  observe: impl Fn(K::Of<B>) -> O,
+) where
+  K: Kind<'a>,
+  A: Clone + Debug + 'a,
+  B: 'a,
+  O: Debug + PartialEq,
+{
+  check(a, |a| {
+    let lhs = <K::Of<A> as Lift<A>>::lift(a.clone()).and_then::<B, _>(f.clone());
+    prop_assert_eq!(observe(lhs), observe(f(a)));
+    Ok(())
+  })
+}
+
+/// Check that `m.and_then(lift)` is equivalent to `m`, for the computations built by `m` from the values generated
+/// by `seed`.
+pub fn check_right_identity<'a, K, S, A, O>(
+  seed: impl Strategy<Value = S>,
+  m: impl Fn(S) -> K::Of<A>,
+  observe: impl Fn(K::Of<A>) -> O,
+) where
+  K: Kind<'a>,
+  S: Clone + Debug,
+  A: 'a,
+  O: Debug + PartialEq,
+{
+  check(seed, |s| {
+    let lhs = m(s.clone()).and_then::<A, _>(Lift::lift);
+    prop_assert_eq!(observe(lhs), observe(m(s)));
+    Ok(())
+  })
+}
+
+/// Check that `m.and_then(f).and_then(g)` is equivalent to `m.and_then(|a| f(a).and_then(g))`, for the computations
+/// built by `m` from the values generated by `seed`.
+pub fn check_associativity<'a, K, S, A, B, C, O>(
+  seed: impl Strategy<Value = S>,
+  m: impl Fn(S) -> K::Of<A>,
+  f: impl Fn(A) -> K::Of<B> + Clone + 'a,
+  g: impl Fn(B) -> K::Of<C> + Clone + 'a,
+  observe: impl Fn(K::Of<C>) -> O,
+) where
+  K: Kind<'a>,
+  S: Clone + Debug,
+  A: 'a,
+  B: 'a,
+  C: 'a,
+  O: Debug + PartialEq,
+{
+  check(seed, |s| {
+    let lhs = m(s.clone())
+      .and_then::<B, _>(f.clone())
+      .and_then::<C, _>(g.clone());
+    let (f, g) = (f.clone(), g.clone());
+    let rhs = m(s).and_then::<C, _>(move |a| f(a).and_then::<C, _>(g));
+    prop_assert_eq!(observe(lhs), observe(rhs));
+    Ok(())
+  })
+}
+
+/// Check that `F::map(x, |a| a)` is equivalent to `x`, for the computations built by `x` from the values generated
+/// by `seed`.
+pub fn check_functor_identity<'a, F, S, A, O>(
+  seed: impl Strategy<Value = S>,
+  x: impl Fn(S) -> F::Of<A>,
+  observe: impl Fn(F::Of<A>) -> O,
+) where
+  F: Functor<'a>,
+  S: Clone + Debug,
+  A: 'a,
+  O: Debug + PartialEq,
+{
+  check(seed, |s| {
+    prop_assert_eq!(observe(F::map(x(s.clone()), |a| a)), observe(x(s)));
+    Ok(())
+  })
+}
+
+/// Check that `F::map(F::map(x, f), g)` is equivalent to `F::map(x, |a| g(f(a)))`, for the computations built by
+/// `x` from the values generated by `seed`.
+pub fn check_functor_composition<'a, F, S, A, B, C, O>(
+  seed: impl Strategy<Value = S>,
+  x: impl Fn(S) -> F::Of<A>,
+  f: impl Fn(A) -> B + Clone + 'a,
+  g: impl Fn(B) -> C + Clone + 'a,
+  observe: impl Fn(F::Of<C>) -> O,
+) where
+  F: Functor<'a>,
+  S: Clone + Debug,
+  A: 'a,
+  B: 'a,
+  C: 'a,
+  O: Debug + PartialEq,
+{
+  check(seed, |s| {
+    let lhs = F::map(F::map(x(s.clone()), f.clone()), g.clone());
+    let (f, g) = (f.clone(), g.clone());
+    let rhs = F::map(x(s), move |a| g(f(a)));
+    prop_assert_eq!(observe(lhs), observe(rhs));
+    Ok(())
+  })
+}
+
+/// Check that `F::pure` is neutral for [`Applicative::zip`]: `F::zip(F::pure(()), x)` and `F::zip(x, F::pure(()))`
+/// are equivalent to `x`, for the computations built by `x` from the values generated by `seed`.
+pub fn check_applicative_identity<'a, F, S, A, O>(
+  seed: impl Strategy<Value = S>,
+  x: impl Fn(S) -> F::Of<A>,
+  observe: impl Fn(F::Of<A>) -> O,
+) where
+  F: Applicative<'a>,
+  S: Clone + Debug,
+  A: 'a,
+  O: Debug + PartialEq,
+{
+  check(seed, |s| {
+    let left = F::zip_with(F::pure(()), x(s.clone()), |(), a| a);
+    prop_assert_eq!(observe(left), observe(x(s.clone())));
+
+    let right = F::zip_with(x(s.clone()), F::pure(()), |a, ()| a);
+    prop_assert_eq!(observe(right), observe(x(s)));
+    Ok(())
+  })
+}
+
+/// Check that `F::zip(F::zip(x, y), z)` is equivalent to `F::zip(x, F::zip(y, z))`, up to the nesting of the pairs,
+/// for the computations built by `x`, `y` and `z` from the values generated by `seed`.
+pub fn check_applicative_associativity<'a, F, S, A, B, C, O>(
+  seed: impl Strategy<Value = S>,
+  x: impl Fn(S) -> F::Of<A>,
+  y: impl Fn(S) -> F::Of<B>,
+  z: impl Fn(S) -> F::Of<C>,
+  observe: impl Fn(F::Of<(A, B, C)>) -> O,
+) where
+  F: Applicative<'a>,
+  S: Clone + Debug,
+  A: 'a,
+  B: 'a,
+  C: 'a,
+  O: Debug + PartialEq,
+{
+  check(seed, |s| {
+    let lhs = F::zip(F::zip(x(s.clone()), y(s.clone())), z(s.clone()));
+    let lhs = F::map(lhs, |((a, b), c)| (a, b, c));
+    let rhs = F::zip(x(s.clone()), F::zip(y(s.clone()), z(s)));
+    let rhs = F::map(rhs, |(a, (b, c))| (a, b, c));
+    prop_assert_eq!(observe(lhs), observe(rhs));
+    Ok(())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    identity::IdentityKind,
+    monad::{OptionKind, ResultKind},
+    writer::{run_writer, tell, Writer, WriterTKind},
+  };
+
+  type W = WriterTKind<Vec<i32>, IdentityKind>;
+
+  fn log(n: i32) -> Writer<'static, Vec<i32>, i32> {
+    tell(vec![n]).and_then(move |()| Lift::lift(n * 2))
+  }
+
+  fn parse(n: i32) -> Result<i32, String> {
+    if n % 3 == 0 {
+      Err(format!("{} is a multiple of 3", n))
+    } else {
+      Ok(n + 1)
+    }
+  }
+
+  #[test]
+  fn lawful() {
+    check_left_identity::<W, _, _, _>(0..100, log, run_writer);
+    check_right_identity::<W, _, _, _>(0..100, log, run_writer);
+    check_associativity::<W, _, _, _, _, _>(0..100, log, log, log, run_writer);
+    check_functor_identity::<W, _, _, _>(0..100, log, run_writer);
+    check_functor_composition::<W, _, _, _, _, _>(0..100, log, |a| a + 1, |b| b * 3, run_writer);
+    check_applicative_identity::<W, _, _, _>(0..100, log, run_writer);
+    check_applicative_associativity::<W, _, _, _, _, _>(0..100, log, log, log, run_writer);
+
+    type R = ResultKind<String>;
+    check_associativity::<R, _, _, _, _, _>(0..100, parse, parse, parse, |m| m);
+    check_applicative_associativity::<R, _, _, _, _, _>(0..100, parse, parse, parse, |m| m);
+
+    let even = |n: i32| Some(n).filter(|n| n % 2 == 0);
+    check_left_identity::<OptionKind, _, _, _>(0..100, even, |m| m);
+    check_right_identity::<OptionKind, _, _, _>(0..100, even, |m| m);
+  }
+
+  /// Computations counting the binds and lifts they are made of, which isn’t lawful.
+  #[derive(Debug, PartialEq)]
+  struct Counted<A>(A, u32);
+
+  impl<A> Lift<A> for Counted<A> {
+    fn lift(a: A) -> Self {
+      Counted(a, 1)
+    }
+  }
+
+  struct CountedKind;
+
+  impl<'a> Kind<'a> for CountedKind {
+    type Of<A: 'a> = Counted<A>;
+  }
+
+  impl<'a, A: 'a> Monad<'a> for Counted<A> {
+    type Item = A;
+    type Kind = CountedKind;
+
+    fn and_then<B, F>(self, f: F) -> Counted<B>
+    where
+      B: 'a,
+      F: FnOnce(A) -> Counted<B> + 'a,
+    {
+      let Counted(b, n) = f(self.0);
+      Counted(b, self.1 + n)
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "Test failed")]
+  fn unlawful() {
+    check_left_identity::<CountedKind, _, _, _>(0..100, |n| Counted(n, 0), |m| m);
+  }
+}
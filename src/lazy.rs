@@ -0,0 +1,153 @@
+//! The lazy monad.
+//!
+//! [`Lazy`] defers a computation until its value is [forced](Lazy::force). Binding a lazy
+//! value doesn’t compute anything: the whole do-block is evaluated when the result is forced.
+//!
+//! ```rust
+//! use do_notation::{lazy::Lazy, m};
+//!
+//! let r = m! {
+//!   x <- Lazy::new(|| 1);
+//!   y <- Lazy::new(|| 2);
+//!   return x + y;
+//! };
+//!
+//! assert_eq!(r.force(), 3);
+//! ```
+//!
+//! # Deferred blocks
+//!
+//! [`Lazy`] runs a computation once. [`m_lazy!`](crate::m_lazy) defers a whole block of any monad instead: it builds
+//! a closure taking no argument, desugaring the block when called, so the block can be stored, passed around, and
+//! run several times:
+//!
+//! ```rust
+//! use do_notation::m_lazy;
+//!
+//! let parse_id = |s: &str| s.strip_prefix("id-").and_then(|n| n.parse::<u32>().ok());
+//! let prefix = "id-".to_owned();
+//!
+//! let next_id = m_lazy! {
+//!   clone(prefix);
+//!   id <- parse_id("id-41");
+//!   return format!("{}{}", prefix, id + 1);
+//! };
+//!
+//! assert_eq!(next_id(), Some("id-42".to_owned()));
+//! assert_eq!(next_id(), Some("id-42".to_owned()));
+//! ```
+//!
+//! The closure moves the variables the block uses, and is a [`Fn`] as long as the block doesn’t move them out: use
+//! references, [`Copy`] values, or start the block with `clone(<ident>, ...);` to clone them at every run.
+
+use crate::{
+  monad::{Kind, Monad},
+  Lift,
+};
+
+/// Deferred computations producing a value of type `A`.
+pub struct Lazy<'a, A> {
+  thunk: Box<dyn FnOnce() -> A + 'a>,
+}
+
+impl<'a, A: 'a> Lazy<'a, A> {
+  /// Defer the computation `f`.
+  pub fn new(f: impl FnOnce() -> A + 'a) -> Self {
+    Lazy { thunk: Box::new(f) }
+  }
+
+  /// Compute the value.
+  pub fn force(self) -> A {
+    (self.thunk)()
+  }
+
+  /// Defer passing the value to `f`.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(A) -> Lazy<'a, B> + 'a) -> Lazy<'a, B> {
+    Lazy::new(move || f(self.force()).force())
+  }
+}
+
+impl<'a, A: 'a> Lift<A> for Lazy<'a, A> {
+  fn lift(a: A) -> Self {
+    Lazy::new(move || a)
+  }
+}
+
+/// [`Kind`] of `Lazy<'_, _>`.
+#[derive(Debug)]
+pub struct LazyKind;
+
+impl<'a> Kind<'a> for LazyKind {
+  type Of<A: 'a> = Lazy<'a, A>;
+}
+
+impl<'a, A: 'a> Monad<'a> for Lazy<'a, A> {
+  type Item = A;
+  type Kind = LazyKind;
+
+  fn and_then<B, F>(self, f: F) -> Lazy<'a, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Lazy<'a, B> + 'a,
+  {
+    Lazy::and_then(self, f)
+  }
+}
+
+/// Deferred do-notation.
+///
+/// The syntax is the one of [`m!`](crate::m). The block is wrapped in a closure taking no argument, which desugars
+/// and evaluates it when called. See the [`lazy`](crate::lazy) module.
+#[macro_export]
+macro_rules! m_lazy {
+  ($($t:tt)*) => {
+    move || $crate::m!($($t)*)
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::cell::Cell;
+
+  #[test]
+  fn deferred() {
+    let forced = Cell::new(false);
+    let r = m! {
+      x <- Lazy::new(|| { forced.set(true); 1 });
+      return x + 1;
+    };
+
+    assert!(!forced.get());
+    assert_eq!(r.force(), 2);
+    assert!(forced.get());
+  }
+
+  #[test]
+  fn deferred_block() {
+    fn twice<M>(block: impl Fn() -> M) -> (M, M) {
+      (block(), block())
+    }
+
+    let runs = Cell::new(0);
+    let runs = &runs;
+    let names = vec!["a", "b"];
+    let block = m_lazy! {
+      clone(names);
+      let _ = runs.replace(runs.get() + 1);
+      n <- names.first().copied();
+      return format!("{}{}", n, names.len());
+    };
+
+    assert_eq!(runs.get(), 0);
+    assert_eq!(twice(block), (Some("a2".to_owned()), Some("a2".to_owned())));
+    assert_eq!(runs.get(), 2);
+
+    let state = m_lazy! {
+      x <- crate::state::get();
+      crate::state::put(x * 2)
+    };
+    assert_eq!(crate::state::exec_state(state(), 2), 4);
+    assert_eq!(crate::state::exec_state(state(), 3), 6);
+  }
+}
@@ -14,6 +14,76 @@
 //! - You can use `return` nowhere but on the last line.
 //! - A line containing a single expression with a semicolon is a valid statement and has the same effect as `_ <- expr`.
 //! - `let` bindings are allowed in the form `let <pattern> = <expr>;` and have the regular Rust meaning.
+//! - `lift <expr>` lifts a computation from the inner monad of a monad transformer, and can be used anywhere an
+//!   expression is expected — e.g. `x <- lift <expr>;`. See [`MonadTrans`](trans::MonadTrans).
+//! - `for <pattern> in <expr> => { <statements> };` runs the inner statements, each ending with `;`, for every
+//!   element of an iterator, and `x <- for <acc> = <init>, <pattern> in <expr> => { <statements> };` folds the
+//!   elements into an accumulator, the inner statements producing the next accumulator. See
+//!   [`fold_m`](combinators::fold_m). As the inner statements can run several times, the values they move must be
+//!   [`Copy`].
+//! - `tell <expr>;` appends to the output of a monad with an output. See [`MonadWriter`](writer::MonadWriter).
+//!   Likewise, `x <- listen <expr>;` binds the value of `<expr>` along with its output, and
+//!   `x <- censor <expr> => <f>;` binds the value of `<expr>`, transforming its output with `<f>`. `censor` can also
+//!   end a statement without binding, and both can be the last line. See the [`writer`] module.
+//! - `x <- <expr> context <message>;` binds `<expr>.context(<message>)`, annotating its error with a message — e.g.
+//!   with [`anyhow::Context`](https://docs.rs/anyhow/latest/anyhow/trait.Context.html), which must be in scope.
+//!   Likewise, `x <- <expr> wrap_err <message>;` binds `<expr>.wrap_err(<message>)`, as with `miette`. Annotations can
+//!   also end a statement without binding, or the last line. See the `context` and `diagnostics` modules.
+//! - In `Result` blocks, `x <- <option> else <error>;` binds an `Option`, failing with `<error>` on `None` — it
+//!   desugars to `<option>.ok_or_else(|| <error>)`.
+//!   Conversely, in `Option` blocks, `x <- ok <result>;` binds a `Result`, discarding its error with `ok()`. Both can
+//!   also end a statement without binding, or the last line.
+//! - `catch <expr> => <handler>` recovers from the error of a fallible monad, and can be used anywhere an expression is
+//!   expected — e.g. `x <- catch <expr> => <handler>;`. See [`MonadError`](result::MonadError).
+//! - The closures of a block capture the variables they use by move. Starting a block with `ref;` makes them borrow
+//!   the variables instead, so that they can still be used after the block, and a bind can be prefixed with `move` —
+//!   e.g. `move x <- <expr>;` — to move its closure anyway. Starting a block with `move;` explicitly asks for the
+//!   default behavior. Starting a block with `clone(<ident>, ...);` clones the listed variables before every closure
+//!   is built, so that every statement can use them — e.g. shared clients or configurations. Borrowing and cloning
+//!   blocks only support `let` bindings, binds and `return`.
+//! - In `Result` blocks, starting a block with `into;` converts the error of every bound computation with [`Into`],
+//!   like the `?` operator does, so that computations failing with different error types can be bound in the same
+//!   block. The trailing expression must fail with the error type of the block, and, like borrowing and cloning
+//!   blocks, converting blocks only support `let` bindings, binds and `return`.
+//! - In methods, `&self` is copied in every closure. However, `&mut self` is moved into the first closure, so it can’t
+//!   be used after the block anymore: start the block with `ref;` to borrow it instead.
+//! - The `do` notation syntax does not extend into inner code blocks; however, it can have its own `m!` block. For example:
+//!   `m! { outer_do... if exp { m! { inner_do... } } else { ... } ... }`.
+//!
+//! The asynchronous counterpart of `m!` is [`async_m!`], which builds a future and awaits the bound values. See
+//! the [`future`] module. With the `futures` feature, `stream_m!` sequences streams, producing a new stream. See the
+//! `stream` module. With the `wasm-bindgen` feature, JavaScript promises can be bound too. See the `js` module.
+//!
+//! Every statement is expanded by a recursive call of the macro, so blocks with more than about 120 statements reach
+//! the recursion limit of the compiler: split them into several blocks, or raise the limit with
+//! `#![recursion_limit = "256"]`.
+//!
+//! A block expands to code keeping the tokens of its statements as they are written — bindings, patterns and
+//! expressions aren’t rewritten, and the macro calls itself through its full path — so IDEs can resolve the names of
+//! every statement, and `m!` can be invoked by path, or from other macros, without being imported:
+//!
+//! ```rust
+//! let r = do_notation::m! {
+//!   x <- Some(1);
+//!   let y = x + 1;
+//!   return x + y;
+//! };
+//!
+//! assert_eq!(r, Some(3));
+//! ```
+//!
+//! For `Option` and `Result`, [`flat_m!`] desugars a block into flat code, without any closure. See the [`flat`]
+//! module.
+//!
+//! [`m_lazy!`] defers a block, building a closure which runs it on every call. See the [`lazy`] module.
+//!
+//! [`validate!`] checks independent validations, accumulating all their errors instead of stopping at the first one.
+//! See the [`validation`] module. More generally, [`ado!`] binds independent computations of any applicative
+//! functor. See the [`applicative`] module.
+//!
+//! On a nightly compiler, the `nightly` feature adds `gen_m!`, which compiles a do-block into a single coroutine
+//! instead of nested closures, allowing to bind computations with several values, like `Vec`. See the
+//! `generator` module.
 //!
 //! ## How do I make my monad works with `m!`?
 //!
@@ -79,38 +149,569 @@
 //! assert_eq!(r, Some([1, 2, 3]));
 //! ```
 
+#![cfg_attr(feature = "nightly", feature(coroutine_trait))]
+#![cfg_attr(all(test, feature = "nightly"), feature(coroutine_clone, coroutines))]
+
 #[macro_export]
 macro_rules! m {
+  // capture modes
+  (move ; $($t:tt)*) => {
+    $crate::m!($($t)*)
+  };
+
+  (ref ; $($t:tt)*) => {
+    $crate::m!(@ref $($t)*)
+  };
+
+  (clone ( $($c:ident),* $(,)? ) ; $($t:tt)*) => {
+    $crate::m!(@clone [$($c),*] $($t)*)
+  };
+
+  (into ; $($t:tt)*) => {
+    $crate::m!(@into $($t)*)
+  };
+
+  // annotated statements — `context`, `wrap_err` or `else` — found by scanning the tokens of the first statement up
+  // to its semicolon
+  (@context [$($s:tt)*] context $($r:tt)*) => {
+    $crate::m!(@annotate context [$($s)*] $($r)*)
+  };
+
+  (@context [$($s:tt)*] wrap_err $($r:tt)*) => {
+    $crate::m!(@annotate wrap_err [$($s)*] $($r)*)
+  };
+
+  // the `else` of an `if` expression isn’t an annotation
+  (@context [$b:tt <- if $($s:tt)*] else { $($e:tt)* } $($r:tt)*) => {
+    $crate::m!(@context [$b <- if $($s)* else { $($e)* }] $($r)*)
+  };
+
+  (@context [$b:tt <- if $($s:tt)*] else if $($r:tt)*) => {
+    $crate::m!(@context [$b <- if $($s)* else if] $($r)*)
+  };
+
+  (@context [if $($s:tt)*] else { $($e:tt)* } $($r:tt)*) => {
+    $crate::m!(@context [if $($s)* else { $($e)* }] $($r)*)
+  };
+
+  (@context [if $($s:tt)*] else if $($r:tt)*) => {
+    $crate::m!(@context [if $($s)* else if] $($r)*)
+  };
+
+  (@context [$($s:tt)*] else $($r:tt)*) => {
+    $crate::m!(@annotate ok_or_else [$($s)*] || $($r)*)
+  };
+
+  (@context [$($s:tt)*] $(; $($r:tt)*)?) => {
+    $crate::m!(@malformed $($s)* $(; $($r)*)?)
+  };
+
+  (@context [$($s:tt)*] $t:tt $($r:tt)*) => {
+    $crate::m!(@context [$($s)* $t] $($r)*)
+  };
+
+  (@annotate $m:ident [$b:tt <- $($x:tt)*] $c:expr) => {
+    $crate::m!(@malformed $b <- ($($x)*).$m($c))
+  };
+
+  (@annotate $m:ident [$($x:tt)*] $c:expr) => {
+    ($($x)*).$m($c)
+  };
+
+  (@annotate $m:ident [_ <- $($x:tt)*] $c:expr ; $($r:tt)*) => {
+    ($($x)*).$m($c).and_then(move |_| { $crate::m!($($r)*) })
+  };
+
+  (@annotate $m:ident [$binding:ident <- $($x:tt)*] $c:expr ; $($r:tt)*) => {
+    ($($x)*).$m($c).and_then(move |$binding| { $crate::m!($($r)*) })
+  };
+
+  (@annotate $m:ident [$($x:tt)*] $c:expr ; $($r:tt)*) => {
+    ($($x)*).$m($c).and_then(move |_| { $crate::m!($($r)*) })
+  };
+
+  (@annotate $m:ident [$($s:tt)*] $($r:tt)*) => {
+    $crate::m!(@malformed $($s)* $m $($r)*)
+  };
+
+  // diagnostics of the malformed blocks, reached once no other rule matches
+  (@malformed) => {
+    compile_error!(
+      "missing trailing expression: an `m!` block must end with an expression producing its result, like `return x;`"
+    )
+  };
+
+  (@malformed let $($t:tt)*) => {
+    $crate::m!(@malformed_stmt [let] $($t)*)
+  };
+
+  (@malformed $b:tt <- $(; $($t:tt)*)?) => {
+    compile_error!(concat!("dangling `<-`: `", stringify!($b), " <-` has no computation to bind"))
+  };
+
+  (@malformed $b:tt <- $($t:tt)*) => {
+    $crate::m!(@malformed_stmt [$b <-] $($t)*)
+  };
+
+  (@malformed $($t:tt)*) => {
+    $crate::m!(@malformed_stmt [] $($t)*)
+  };
+
+  // the tokens of the first statement are scanned up to its semicolon, looking for the start of another statement;
+  // the second error points at it
+  (@malformed_stmt [$($s:tt)*] $b:tt <- $($t:tt)*) => {{
+    compile_error!(concat!("missing `;` after `", stringify!($($s)*), "`"));
+    $crate::__m_missing_semicolon!($b)
+  }};
+
+  (@malformed_stmt [$($s:tt)*] let $($t:tt)*) => {{
+    compile_error!(concat!("missing `;` after `", stringify!($($s)*), "`"));
+    $crate::__m_missing_semicolon!(let)
+  }};
+
+  (@malformed_stmt [let $($s:tt)*]) => {
+    compile_error!(
+      "missing trailing expression: an `m!` block must end with an expression producing its result, like `return x;`"
+    )
+  };
+
+  (@malformed_stmt [$b:tt <- $($s:tt)*]) => {
+    compile_error!(
+      "`<-` on the last line: the last statement of an `m!` block can’t bind a value, as nothing could use it; end the block with the computation itself, or `return` its value"
+    )
+  };
+
+  (@malformed_stmt [$($s:tt)*] $(; $($r:tt)*)?) => {
+    compile_error!(
+      "malformed `m!` block: expected `x <- m;`, `let x = e;`, `m;` or `return e;` statements, followed by a trailing expression"
+    )
+  };
+
+  (@malformed_stmt [$($s:tt)*] $t:tt $($r:tt)*) => {
+    $crate::m!(@malformed_stmt [$($s)* $t] $($r)*)
+  };
+
+  // cloning return
+  (@clone [$($c:ident),*] return $r:expr ;) => {
+    $crate::Lift::lift($r)
+  };
+
+  (@clone [$($c:ident),*] return $r:expr ; $($t:tt)+) => {
+    $crate::misplaced_return($r)
+  };
+
+  // cloning let-binding
+  (@clone [$($c:ident),*] let $p:pat = $e:expr ; $($r:tt)*) => {{
+    let $p = $e;
+    $crate::m!(@clone [$($c),*] $($r)*)
+  }};
+
+  // cloning const-bind
+  (@clone [$($c:ident),*] _ <- $x:expr ; $($r:tt)*) => {{
+    $(let $c = $c.clone();)*
+    $x.and_then(move |_| { $crate::m!(@clone [$($c),*] $($r)*) })
+  }};
+
+  // cloning bind
+  (@clone [$($c:ident),*] $binding:ident <- $x:expr ; $($r:tt)*) => {{
+    $(let $c = $c.clone();)*
+    $x.and_then(move |$binding| { $crate::m!(@clone [$($c),*] $($r)*) })
+  }};
+
+  // binds that didn’t match, which mustn’t be parsed as expressions
+  (@clone [$($c:ident),*] $binding:tt <- $($t:tt)*) => {
+    $crate::m!(@malformed $binding <- $($t)*)
+  };
+
+  // cloning const-bind
+  (@clone [$($c:ident),*] $e:expr ; $($r:tt)*) => {{
+    $(let $c = $c.clone();)*
+    $e.and_then(move |_| { $crate::m!(@clone [$($c),*] $($r)*) })
+  }};
+
+  // cloning pure
+  (@clone [$($c:ident),*] $a:expr) => {
+    $a
+  };
+
+  (@clone [$($c:ident),*] $($t:tt)*) => {
+    $crate::m!(@malformed $($t)*)
+  };
+
+  // borrowing return
+  (@ref return $r:expr ;) => {
+    $crate::Lift::lift($r)
+  };
+
+  (@ref return $r:expr ; $($t:tt)+) => {
+    $crate::misplaced_return($r)
+  };
+
+  // borrowing let-binding
+  (@ref let $p:pat = $e:expr ; $($r:tt)*) => {{
+    let $p = $e;
+    $crate::m!(@ref $($r)*)
+  }};
+
+  // moving const-bind
+  (@ref move _ <- $x:expr ; $($r:tt)*) => {
+    $x.and_then(move |_| { $crate::m!(@ref $($r)*) })
+  };
+
+  // moving bind
+  (@ref move $binding:ident <- $x:expr ; $($r:tt)*) => {
+    $x.and_then(move |$binding| { $crate::m!(@ref $($r)*) })
+  };
+
+  // borrowing const-bind
+  (@ref _ <- $x:expr ; $($r:tt)*) => {
+    $x.and_then(|_| { $crate::m!(@ref $($r)*) })
+  };
+
+  // borrowing bind
+  (@ref $binding:ident <- $x:expr ; $($r:tt)*) => {
+    $x.and_then(|$binding| { $crate::m!(@ref $($r)*) })
+  };
+
+  // binds that didn’t match, which mustn’t be parsed as expressions
+  (@ref $binding:tt <- $($t:tt)*) => {
+    $crate::m!(@malformed $binding <- $($t)*)
+  };
+
+  // borrowing const-bind
+  (@ref $e:expr ; $($r:tt)*) => {
+    $e.and_then(|_| $crate::m!(@ref $($r)*))
+  };
+
+  // borrowing pure
+  (@ref $a:expr) => {
+    $a
+  };
+
+  (@ref $($t:tt)*) => {
+    $crate::m!(@malformed $($t)*)
+  };
+
+  // converting return
+  (@into return $r:expr ;) => {
+    $crate::Lift::lift($r)
+  };
+
+  (@into return $r:expr ; $($t:tt)+) => {
+    $crate::misplaced_return($r)
+  };
+
+  // converting let-binding
+  (@into let $p:pat = $e:expr ; $($r:tt)*) => {{
+    let $p = $e;
+    $crate::m!(@into $($r)*)
+  }};
+
+  // converting const-bind
+  (@into _ <- $x:expr ; $($r:tt)*) => {
+    ::core::result::Result::map_err($x, ::core::convert::Into::into)
+      .and_then(move |_| { $crate::m!(@into $($r)*) })
+  };
+
+  // converting bind
+  (@into $binding:ident <- $x:expr ; $($r:tt)*) => {
+    ::core::result::Result::map_err($x, ::core::convert::Into::into)
+      .and_then(move |$binding| { $crate::m!(@into $($r)*) })
+  };
+
+  // binds that didn’t match, which mustn’t be parsed as expressions
+  (@into $binding:tt <- $($t:tt)*) => {
+    $crate::m!(@malformed $binding <- $($t)*)
+  };
+
+  // converting const-bind
+  (@into $e:expr ; $($r:tt)*) => {
+    ::core::result::Result::map_err($e, ::core::convert::Into::into)
+      .and_then(move |_| { $crate::m!(@into $($r)*) })
+  };
+
+  // the trailing expression isn’t converted, as its error type would be ambiguous with `Ok(…)`
+  (@into $a:expr) => {
+    $a
+  };
+
+  (@into $($t:tt)*) => {
+    $crate::m!(@malformed $($t)*)
+  };
+
   // return
   (return $r:expr ;) => {
     $crate::Lift::lift($r)
   };
 
+  // return followed by other statements, which would be parsed as an expression returning from the function
+  (return $r:expr ; $($t:tt)+) => {
+    $crate::misplaced_return($r)
+  };
+
   // let-binding
   (let $p:pat = $e:expr ; $($r:tt)*) => {{
     let $p = $e;
-    m!($($r)*)
+    $crate::m!($($r)*)
   }};
 
+  // lift-bind
+  ($binding:ident <- lift $x:expr ; $($r:tt)*) => {
+    $crate::trans::lift_and_then($x, move |$binding| { $crate::m!($($r)*) })
+  };
+
+  // const-lift-bind
+  (_ <- lift $x:expr ; $($r:tt)*) => {
+    $crate::trans::lift_and_then($x, move |_| { $crate::m!($($r)*) })
+  };
+
+  // const-lift-bind
+  (lift $x:expr ; $($r:tt)*) => {
+    $crate::trans::lift_and_then($x, move |_| { $crate::m!($($r)*) })
+  };
+
+  // lift
+  (lift $x:expr) => {
+    $crate::trans::lift($x)
+  };
+
+  // catch-bind
+  ($binding:ident <- catch $x:expr => $h:expr ; $($r:tt)*) => {
+    $crate::result::catch_and_then($x, $h, move |$binding| { $crate::m!($($r)*) })
+  };
+
+  // const-catch-bind
+  (_ <- catch $x:expr => $h:expr ; $($r:tt)*) => {
+    $crate::result::catch_and_then($x, $h, move |_| { $crate::m!($($r)*) })
+  };
+
+  // const-catch-bind
+  (catch $x:expr => $h:expr ; $($r:tt)*) => {
+    $crate::result::catch_and_then($x, $h, move |_| { $crate::m!($($r)*) })
+  };
+
+  // catch
+  (catch $x:expr => $h:expr) => {
+    $crate::result::catch_and_then($x, $h, $crate::Lift::lift)
+  };
+
+  // for
+  (for $p:pat in $iter:expr => { $($body:tt)* } ; $($r:tt)*) => {
+    $crate::combinators::fold_m((), $iter, move |(), $p| $crate::m! { $($body)* return (); })
+      .and_then(move |()| $crate::m!($($r)*))
+  };
+
+  // final for
+  (for $p:pat in $iter:expr => { $($body:tt)* }) => {
+    $crate::combinators::fold_m((), $iter, move |(), $p| $crate::m! { $($body)* return (); })
+  };
+
+  // fold-bind
+  ($binding:ident <- for $acc:pat = $init:expr , $p:pat in $iter:expr => { $($body:tt)* } ; $($r:tt)*) => {
+    $crate::combinators::fold_m($init, $iter, move |$acc, $p| $crate::m! { $($body)* })
+      .and_then(move |$binding| $crate::m!($($r)*))
+  };
+
+  // tell
+  (tell $w:expr ; $($r:tt)*) => {
+    $crate::writer::tell_and_then($w, move |_| { $crate::m!($($r)*) })
+  };
+
   // const-bind
   (_ <- $x:expr ; $($r:tt)*) => {
-    $x.and_then(move |_| { m!($($r)*) })
+    $x.and_then(move |_| { $crate::m!($($r)*) })
   };
 
   // bind
   ($binding:ident <- $x:expr ; $($r:tt)*) => {
-    $x.and_then(move |$binding| { m!($($r)*) })
+    $x.and_then(move |$binding| { $crate::m!($($r)*) })
+  };
+
+  // ok-bind, after the binds so that expressions starting with an `ok` variable are still bound
+  ($binding:ident <- ok $x:expr ; $($r:tt)*) => {
+    ($x).ok().and_then(move |$binding| { $crate::m!($($r)*) })
+  };
+
+  // const-ok-bind
+  (_ <- ok $x:expr ; $($r:tt)*) => {
+    ($x).ok().and_then(move |_| { $crate::m!($($r)*) })
+  };
+
+  // listen-bind, after the binds so that calls to the `listen` function are still bound
+  ($binding:ident <- listen $x:expr ; $($r:tt)*) => {
+    $crate::writer::listen_and_then($x, move |$binding| { $crate::m!($($r)*) })
+  };
+
+  // censor-bind
+  ($binding:ident <- censor $x:expr => $f:expr ; $($r:tt)*) => {
+    $crate::writer::censor_and_then($x, $f, move |$binding| { $crate::m!($($r)*) })
+  };
+
+  // const-censor-bind
+  (_ <- censor $x:expr => $f:expr ; $($r:tt)*) => {
+    $crate::writer::censor_and_then($x, $f, move |_| { $crate::m!($($r)*) })
+  };
+
+  // binds that didn’t match, which mustn’t be parsed as expressions: context-bind, else-bind, or malformed
+  ($binding:tt <- $($t:tt)*) => {
+    $crate::m!(@context [$binding <-] $($t)*)
   };
 
   // const-bind
   ($e:expr ; $($a:tt)*) => {
-    $e.and_then(move |_| m!($($a)*))
+    $e.and_then(move |_| $crate::m!($($a)*))
+  };
+
+  // const-ok-bind
+  (ok $x:expr ; $($r:tt)*) => {
+    ($x).ok().and_then(move |_| { $crate::m!($($r)*) })
+  };
+
+  // const-censor-bind
+  (censor $x:expr => $f:expr ; $($r:tt)*) => {
+    $crate::writer::censor_and_then($x, $f, move |_| { $crate::m!($($r)*) })
   };
 
   // pure
   ($a:expr) => {
     $a
-  }
+  };
+
+  // ok
+  (ok $x:expr) => {
+    ($x).ok()
+  };
+
+  // listen
+  (listen $x:expr) => {
+    $crate::writer::listen_and_then($x, $crate::Lift::lift)
+  };
+
+  // censor
+  (censor $x:expr => $f:expr) => {
+    $crate::writer::censor_and_then($x, $f, $crate::Lift::lift)
+  };
+
+  // context, or malformed
+  ($($t:tt)*) => {
+    $crate::m!(@context [] $($t)*)
+  };
+}
+
+/// Point at the statement following a missing semicolon in a [`m!`] block, expecting the semicolon instead.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __m_missing_semicolon {
+  (;) => {};
+}
+
+pub mod alternative;
+pub mod applicative;
+pub mod arrow;
+pub mod bifunctor;
+pub mod binary;
+#[cfg(feature = "higher")]
+pub mod bridge;
+pub mod build;
+pub mod channel;
+pub mod checkpoint;
+#[cfg(feature = "combine")]
+pub mod combinator;
+pub mod combinators;
+pub mod comonad;
+#[cfg(feature = "itertools")]
+pub mod comprehension;
+pub mod config;
+pub mod cont;
+#[cfg(feature = "anyhow")]
+pub mod context;
+#[cfg(feature = "miette")]
+pub mod diagnostics;
+pub mod eff;
+pub mod either;
+pub mod emit;
+pub mod fetch;
+pub mod flat;
+pub mod foldable;
+pub mod future;
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+pub mod generate;
+#[cfg(feature = "nightly")]
+pub mod generator;
+#[cfg(feature = "frunk")]
+pub mod hlist;
+pub mod identity;
+pub mod indexed;
+pub mod instrumented;
+pub mod io;
+#[cfg(feature = "wasm-bindgen")]
+pub mod js;
+#[cfg(feature = "serde_json")]
+pub mod json;
+#[cfg(feature = "proptest")]
+pub mod laws;
+pub mod lazy;
+#[cfg(feature = "log")]
+pub mod logging;
+pub mod memo;
+#[cfg(feature = "metrics")]
+pub mod metered;
+pub mod monad;
+pub mod monoid;
+pub mod non_empty;
+pub mod option;
+#[cfg(feature = "rayon")]
+pub mod par;
+#[cfg(feature = "nom")]
+pub mod parse;
+#[cfg(feature = "im")]
+pub mod persistent;
+pub mod railway;
+pub mod reader;
+pub mod rec;
+pub mod result;
+pub mod retry;
+pub mod runtime;
+pub mod saga;
+mod stack;
+pub mod state;
+#[cfg(feature = "futures")]
+pub mod stream;
+#[cfg(feature = "tokio")]
+pub mod task;
+pub mod these;
+pub mod timed;
+#[cfg(feature = "tracing")]
+pub mod traced;
+pub mod trans;
+pub mod traversable;
+#[cfg(feature = "sqlx")]
+pub mod tx;
+pub mod validation;
+pub mod writer;
+
+pub use monad::{Kind, Monad};
+
+/// Report a `return` statement which isn’t the last statement of a [`m!`] or [`flat_m!`] block, pointing at the
+/// returned value.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+  message = "misplaced `return`: `return` is only allowed on the last line of a do-block",
+  label = "returned before the end of the block",
+  note = "early returns aren’t supported; move the statements after `return` in another block, or end the block with `return`"
+)]
+pub trait ReturnOnLastLine: sealed::Sealed {}
+
+#[doc(hidden)]
+mod sealed {
+  /// Prevents [`ReturnOnLastLine`](super::ReturnOnLastLine) from being implemented, so that misplaced `return`
+  /// statements never compile.
+  pub trait Sealed {}
+}
+
+#[doc(hidden)]
+pub fn misplaced_return<T: ReturnOnLastLine, R>(_: T) -> R {
+  unreachable!("misplaced `return` in a do-block")
 }
 
 /// Lift a value inside a monad.
@@ -131,6 +732,12 @@ impl<A, E> Lift<A> for Result<A, E> {
   }
 }
 
+impl<A> Lift<A> for Vec<A> {
+  fn lift(a: A) -> Self {
+    vec![a]
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -230,6 +837,185 @@ mod tests {
     assert_eq!(r, Err("meh"));
   }
 
+  #[test]
+  fn capture_modes() {
+    let name = String::from("alice");
+
+    let r = m! {
+      ref;
+      x <- Some(name.len());
+      let y = x * 2;
+      c <- name.chars().next();
+      // moves `c` and `y`
+      move z <- Some(y + 1);
+      return (c, z);
+    };
+
+    assert_eq!(r, Some(('a', 11)));
+
+    let r = m! {
+      move;
+      x <- Some(name);
+      Some(x.len())
+    };
+
+    assert_eq!(r, Some(5));
+  }
+
+  #[test]
+  fn clone_mode() {
+    use std::rc::Rc;
+
+    let config = Rc::new(String::from("config"));
+
+    let r = m! {
+      clone(config);
+      x <- Some(config.len());
+      let n = Rc::strong_count(&config);
+      y <- Some(config);
+      return (x, n, y.len());
+    };
+
+    assert_eq!(r, Some((6, 2, 6)));
+    assert_eq!(Rc::strong_count(&config), 1);
+  }
+
+  #[test]
+  fn option_result_interop() {
+    use std::collections::HashMap;
+
+    let config = &HashMap::from([("port", "80"), ("host", "localhost"), ("retries", "x")]);
+
+    let port = |key| -> Result<u16, String> {
+      m! {
+        raw <- config.get(key) else format!("missing {}", key);
+        port <- raw.parse::<u16>().map_err(|e| e.to_string());
+        if port > 0 { Some(()) } else { None } else "zero port".to_owned();
+        return port;
+      }
+    };
+
+    assert_eq!(port("port"), Ok(80));
+    assert_eq!(port("timeout"), Err("missing timeout".to_owned()));
+
+    let r: Result<u16, String> = m! {
+      x <- if config.is_empty() { None } else { Some(1) } else "empty".to_owned();
+      _ <- Some(x) else "unreachable".to_owned();
+      config.get("host").map(|h| h.len() as u16 + x) else "no host".to_owned()
+    };
+
+    assert_eq!(r, Ok(10));
+
+    let retries = |key| {
+      m! {
+        raw <- config.get(key).copied();
+        ok raw.parse::<u8>();
+        n <- ok raw.parse::<u8>();
+        _ <- ok raw.parse::<u8>();
+        ok raw.parse::<u8>().map(|m| m + n)
+      }
+    };
+
+    assert_eq!(retries("port"), Some(160));
+    assert_eq!(retries("retries"), None);
+  }
+
+  #[test]
+  fn into_mode() {
+    use std::{
+      convert::TryFrom,
+      num::{ParseIntError, TryFromIntError},
+    };
+
+    #[derive(Debug, PartialEq)]
+    enum Error {
+      Parse(ParseIntError),
+      Range(TryFromIntError),
+      Zero,
+    }
+
+    impl From<ParseIntError> for Error {
+      fn from(e: ParseIntError) -> Self {
+        Error::Parse(e)
+      }
+    }
+
+    impl From<TryFromIntError> for Error {
+      fn from(e: TryFromIntError) -> Self {
+        Error::Range(e)
+      }
+    }
+
+    fn parse(s: &str) -> Result<u8, Error> {
+      m! {
+        into;
+        n <- s.parse::<i32>();
+        let n = n * 2;
+        b <- u8::try_from(n);
+        if b == 0 { Err(Error::Zero) } else { Ok(()) };
+        return b;
+      }
+    }
+
+    assert_eq!(parse("21"), Ok(42));
+    assert!(matches!(parse("x"), Err(Error::Parse(_))));
+    assert!(matches!(parse("200"), Err(Error::Range(_))));
+    assert_eq!(parse("0"), Err(Error::Zero));
+  }
+
+  #[test]
+  fn methods() {
+    struct Counter {
+      count: u32,
+      max: u32,
+    }
+
+    impl Counter {
+      fn incr(&mut self) -> Option<u32> {
+        if self.count < self.max {
+          self.count += 1;
+          Some(self.count)
+        } else {
+          None
+        }
+      }
+
+      fn peek(&self) -> Option<u32> {
+        Some(self.count)
+      }
+
+      // `&self` is copied in every closure
+      fn twice_peek(&self) -> Option<u32> {
+        let r = m! {
+          x <- self.peek();
+          y <- self.peek();
+          Some(x + y)
+        };
+
+        r.map(|r| r + self.max)
+      }
+
+      // `&mut self` is borrowed by the closures of a borrowing block, and is usable again after the block
+      fn incr_twice(&mut self) -> Option<u32> {
+        let r = m! {
+          ref;
+          x <- self.incr();
+          y <- self.incr();
+          Some(x + y)
+        };
+
+        self.max += 1;
+        r
+      }
+    }
+
+    let mut counter = Counter { count: 0, max: 3 };
+    assert_eq!(counter.incr_twice(), Some(3));
+    assert_eq!(counter.twice_peek(), Some(8));
+    assert_eq!(counter.incr_twice(), Some(7));
+    assert_eq!(counter.incr_twice(), None);
+  }
+
   #[test]
   fn instruction_counter() {
     struct IC<A> {
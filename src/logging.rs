@@ -0,0 +1,196 @@
+//! Forwarding the output of writers to the `log` crate.
+//!
+//! > This module requires the `log` feature.
+//!
+//! [`Logs`] is a [`Monoid`] of log records, to be accumulated with the `tell` statement of [`m!`](crate::m) in a
+//! [`Writer`] or any monad implementing [`MonadWriter`](crate::writer::MonadWriter). Each
+//! record has its own level, set with [`error`], [`warn`], [`info`], [`debug`], [`trace`] or [`record`]. The
+//! accumulated records can be inspected, which is convenient in tests, or forwarded to the logger installed with
+//! the [`log`] crate with [`Logs::forward`] or [`run_logged`], without changing the do-blocks:
+//!
+//! ```rust
+//! use do_notation::{m, logging::{self, run_logged, Logs}, writer::{run_writer, Writer}};
+//!
+//! fn half(n: u32) -> Writer<'static, Logs, u32> {
+//!   m! {
+//!     tell logging::debug(format!("halving {}", n));
+//!     tell if n % 2 == 1 { logging::warn("rounding down") } else { Logs::default() };
+//!     return n / 2;
+//!   }
+//! }
+//!
+//! // in tests
+//! let (r, logs) = run_writer(half(3));
+//! assert_eq!(r, 1);
+//! assert_eq!(logs.records().len(), 2);
+//!
+//! // in production, the records go to the logger
+//! assert_eq!(run_logged(half(3)), 1);
+//! ```
+
+use crate::{
+  monad::{Kind, Monad},
+  monoid::{Monoid, Semigroup},
+  writer::{run_writer, run_writer_t, Writer, WriterT},
+  Lift,
+};
+use ::log::Level;
+
+/// A log record, accumulated in [`Logs`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Record {
+  /// Level of the record.
+  pub level: Level,
+
+  /// Message of the record.
+  pub message: String,
+}
+
+/// Log records accumulated by a writer, in order.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Logs(Vec<Record>);
+
+impl Logs {
+  /// The accumulated records.
+  pub fn records(&self) -> &[Record] {
+    &self.0
+  }
+
+  /// Forward the records to the logger, in order.
+  pub fn forward(self) {
+    for record in self.0 {
+      ::log::log!(record.level, "{}", record.message);
+    }
+  }
+}
+
+impl Semigroup for Logs {
+  fn combine(mut self, other: Self) -> Self {
+    self.0.extend(other.0);
+    self
+  }
+}
+
+impl Monoid for Logs {
+  fn empty() -> Self {
+    Logs::default()
+  }
+}
+
+/// A single record with the given level.
+pub fn record(level: Level, message: impl Into<String>) -> Logs {
+  Logs(vec![Record {
+    level,
+    message: message.into(),
+  }])
+}
+
+/// A single record with the [`Level::Error`] level.
+pub fn error(message: impl Into<String>) -> Logs {
+  record(Level::Error, message)
+}
+
+/// A single record with the [`Level::Warn`] level.
+pub fn warn(message: impl Into<String>) -> Logs {
+  record(Level::Warn, message)
+}
+
+/// A single record with the [`Level::Info`] level.
+pub fn info(message: impl Into<String>) -> Logs {
+  record(Level::Info, message)
+}
+
+/// A single record with the [`Level::Debug`] level.
+pub fn debug(message: impl Into<String>) -> Logs {
+  record(Level::Debug, message)
+}
+
+/// A single record with the [`Level::Trace`] level.
+pub fn trace(message: impl Into<String>) -> Logs {
+  record(Level::Trace, message)
+}
+
+/// Run a pure computation, forwarding its records to the logger and returning its value.
+pub fn run_logged<'a, A>(m: Writer<'a, Logs, A>) -> A {
+  let (a, logs) = run_writer(m);
+  logs.forward();
+  a
+}
+
+/// Run a computation, forwarding its records to the logger and returning its value in the inner monad.
+///
+/// The records are only forwarded if the inner monad produces a value.
+pub fn run_logged_t<'a, M, A>(m: WriterT<'a, Logs, M, A>) -> M::Of<A>
+where
+  M: Kind<'a>,
+  A: 'a,
+{
+  run_writer_t(m).and_then::<A, _>(|(a, logs)| {
+    logs.forward();
+    Lift::lift(a)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::monad::OptionKind;
+  use std::sync::Mutex;
+
+  /// Logger storing the records it receives.
+  struct Store(Mutex<Vec<(Level, String)>>);
+
+  impl ::log::Log for Store {
+    fn enabled(&self, _: &::log::Metadata) -> bool {
+      true
+    }
+
+    fn log(&self, record: &::log::Record) {
+      let message = record.args().to_string();
+      self.0.lock().unwrap().push((record.level(), message));
+    }
+
+    fn flush(&self) {}
+  }
+
+  static STORE: Store = Store(Mutex::new(Vec::new()));
+
+  #[test]
+  fn forward() {
+    ::log::set_logger(&STORE).unwrap();
+    ::log::set_max_level(::log::LevelFilter::Trace);
+
+    let r: Writer<Logs, i32> = m! {
+      tell info("a");
+      x <- Writer::lift(1);
+      tell error(format!("b{}", x));
+      tell Logs::empty();
+      return x + 1;
+    };
+
+    assert_eq!(run_logged(r), 2);
+    assert_eq!(
+      *STORE.0.lock().unwrap(),
+      vec![
+        (Level::Info, "a".to_owned()),
+        (Level::Error, "b1".to_owned())
+      ]
+    );
+
+    let r: WriterT<Logs, OptionKind, i32> = m! {
+      tell trace("c");
+      lift None::<i32>
+    };
+
+    assert_eq!(run_logged_t(r), None);
+    assert_eq!(STORE.0.lock().unwrap().len(), 2);
+
+    let r: WriterT<Logs, OptionKind, i32> = m! {
+      tell trace("c");
+      lift Some(3)
+    };
+
+    assert_eq!(run_logged_t(r), Some(3));
+    assert_eq!(STORE.0.lock().unwrap()[2], (Level::Trace, "c".to_owned()));
+  }
+}
@@ -0,0 +1,153 @@
+//! Memoisation in the state monad.
+//!
+//! [`memo`] caches the values of computations in the state of a monad with a state — see
+//! [`MonadState`] — keyed by an argument: the first time a key is seen, the computation runs and its value is
+//! stored; afterwards, the stored value is reused without running the computation again. The state must be a
+//! [`MemoStore`], such as a [`HashMap`] or a [`BTreeMap`]. [`Memo`] is the state monad over a [`HashMap`]:
+//!
+//! ```rust
+//! use do_notation::{m, memo::{memo, run_memo, Memo}, Lift};
+//!
+//! fn fib(n: u64) -> Memo<'static, u64, u64, u64> {
+//!   memo(n, move || {
+//!     if n < 2 {
+//!       Lift::lift(n)
+//!     } else {
+//!       m! {
+//!         a <- fib(n - 1);
+//!         b <- fib(n - 2);
+//!         return a + b;
+//!       }
+//!     }
+//!   })
+//! }
+//!
+//! assert_eq!(run_memo(fib(90)), 2_880_067_194_370_816_120);
+//! ```
+
+use crate::{
+  identity::IdentityKind,
+  monad::Monad,
+  state::{eval_state, MonadState, StateT},
+  Lift,
+};
+use std::{
+  collections::{BTreeMap, HashMap},
+  hash::Hash,
+};
+
+/// Stores of values of type `V`, keyed by values of type `K`.
+pub trait MemoStore<K, V> {
+  /// Retrieve a copy of the value stored for `key`, if any.
+  fn lookup(&self, key: &K) -> Option<V>;
+
+  /// Store `value` for `key`.
+  fn store(&mut self, key: K, value: V);
+}
+
+impl<K, V> MemoStore<K, V> for HashMap<K, V>
+where
+  K: Eq + Hash,
+  V: Clone,
+{
+  fn lookup(&self, key: &K) -> Option<V> {
+    self.get(key).cloned()
+  }
+
+  fn store(&mut self, key: K, value: V) {
+    self.insert(key, value);
+  }
+}
+
+impl<K, V> MemoStore<K, V> for BTreeMap<K, V>
+where
+  K: Ord,
+  V: Clone,
+{
+  fn lookup(&self, key: &K) -> Option<V> {
+    self.get(key).cloned()
+  }
+
+  fn store(&mut self, key: K, value: V) {
+    self.insert(key, value);
+  }
+}
+
+/// Pure computations memoising values of type `V` by keys of type `K`, producing a value of type `A`.
+pub type Memo<'a, K, V, A> = StateT<'a, HashMap<K, V>, IdentityKind, A>;
+
+/// Run a computation with an empty store, returning the produced value.
+pub fn run_memo<'a, K, V, A>(m: Memo<'a, K, V, A>) -> A {
+  eval_state(m, HashMap::new())
+}
+
+/// Reuse the value stored for `key`, or run the computation built by `m` and store its value for `key`.
+///
+/// The store is the state of the monad.
+pub fn memo<'a, S, K, N>(key: K, m: impl FnOnce() -> N + 'a) -> N
+where
+  S: MemoStore<K, N::Item> + 'a,
+  K: Clone + 'a,
+  N: Monad<'a>,
+  N::Item: Clone,
+  N::Kind: MonadState<'a, S>,
+{
+  let key_ = key.clone();
+
+  N::Kind::gets(move |store: &S| store.lookup(&key_)).and_then::<N::Item, _>(move |stored| {
+    match stored {
+      Some(value) => Lift::lift(value),
+      None => m().and_then::<N::Item, _>(move |value| {
+        let stored = value.clone();
+
+        <N::Kind as MonadState<'a, S>>::modify(move |mut store| {
+          store.store(key, stored);
+          store
+        })
+        .and_then::<N::Item, _>(move |()| Lift::lift(value))
+      }),
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    monad::OptionKind,
+    state::{run_state_t, StateT},
+  };
+  use std::cell::Cell;
+
+  #[test]
+  fn memo() {
+    let runs = &Cell::new(0);
+    let square = |n: i32| -> Memo<i32, i32, i32> {
+      super::memo(n, move || {
+        runs.set(runs.get() + 1);
+        Lift::lift(n * n)
+      })
+    };
+
+    let r = m! {
+      a <- square(3);
+      b <- square(4);
+      c <- square(3);
+      return a + b + c;
+    };
+
+    assert_eq!(run_memo(r), 34);
+    assert_eq!(runs.get(), 2);
+
+    // failures aren’t stored
+    let parse = |s: &'static str| -> StateT<BTreeMap<&str, i32>, OptionKind, i32> {
+      super::memo(s, move || {
+        StateT::new(move |st| s.parse().ok().map(|n| (n, st)))
+      })
+    };
+
+    let (n, store) = run_state_t(parse("12"), BTreeMap::new()).unwrap();
+    assert_eq!((n, store.len()), (12, 1));
+    assert_eq!(run_state_t(parse("a"), store), None);
+  }
+}
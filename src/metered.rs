@@ -0,0 +1,207 @@
+//! Metered do-notation.
+//!
+//! > This module requires the `metrics` feature.
+//!
+//! [`metered_m!`](crate::metered_m) is [`m!`](crate::m) for a named block, reporting through the [`metrics`]
+//! facade how the block ended and how long it took to run, to whatever recorder is installed:
+//!
+//! - the [`BLOCKS`] counter is incremented, with the `block` label set to the name of the block and the `outcome`
+//!   label set to either `success` or `short_circuit`, as decided by [`Outcome`];
+//! - the duration of the block, in seconds, is recorded in the [`BLOCK_DURATION`] histogram, with the `block` label.
+//!
+//! ```rust
+//! use do_notation::metered_m;
+//!
+//! fn parse_pair(a: &str, b: &str) -> Option<(i32, i32)> {
+//!   metered_m! {
+//!     "parse_pair";
+//!     x <- a.parse().ok();
+//!     y <- b.parse().ok();
+//!     return (x, y);
+//!   }
+//! }
+//!
+//! assert_eq!(parse_pair("1", "2"), Some((1, 2)));
+//! ```
+//!
+//! The block is timed while it is built, so the monad must be strict for the duration to be meaningful.
+
+use std::time::Instant;
+
+/// Name of the counter of the blocks run by [`metered_m!`](crate::metered_m).
+pub const BLOCKS: &str = "do_notation_blocks_total";
+
+/// Name of the histogram of the durations, in seconds, of the blocks run by [`metered_m!`](crate::metered_m).
+pub const BLOCK_DURATION: &str = "do_notation_block_duration_seconds";
+
+/// Results of metered blocks, which either succeeded or short-circuited.
+pub trait Outcome {
+  /// Whether the block ran until its end.
+  fn is_success(&self) -> bool;
+}
+
+impl<A> Outcome for Option<A> {
+  fn is_success(&self) -> bool {
+    self.is_some()
+  }
+}
+
+impl<A, E> Outcome for Result<A, E> {
+  fn is_success(&self) -> bool {
+    self.is_ok()
+  }
+}
+
+/// Run the block `f` named `name`, reporting its outcome and duration.
+///
+/// This is what [`metered_m!`](crate::metered_m) desugars to.
+pub fn metered<R>(name: &'static str, f: impl FnOnce() -> R) -> R
+where
+  R: Outcome,
+{
+  let start = Instant::now();
+  let r = f();
+  let duration = start.elapsed();
+
+  let outcome = if r.is_success() {
+    "success"
+  } else {
+    "short_circuit"
+  };
+  metrics::counter!(BLOCKS, "block" => name, "outcome" => outcome).increment(1);
+  metrics::histogram!(BLOCK_DURATION, "block" => name).record(duration.as_secs_f64());
+
+  r
+}
+
+/// Metered monadic do-notation.
+///
+/// The block starts with its name, followed by a semicolon, and the statements of [`m!`](crate::m). See the
+/// [`metered`](crate::metered) module.
+#[macro_export]
+macro_rules! metered_m {
+  ($name:expr ; $($r:tt)*) => {
+    $crate::metered::metered($name, || $crate::m!($($r)*))
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use metrics::{
+    Counter, CounterFn, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+  };
+  use std::sync::{Arc, Mutex};
+
+  type Log = Arc<Mutex<Vec<(String, Vec<(String, String)>, f64)>>>;
+
+  /// Recorder logging the updates of the metrics, along with their labels.
+  #[derive(Default)]
+  struct Store(Log);
+
+  struct Handle(Key, Log);
+
+  impl Handle {
+    fn push(&self, value: f64) {
+      let labels = self
+        .0
+        .labels()
+        .map(|label| (label.key().to_owned(), label.value().to_owned()))
+        .collect();
+      self
+        .1
+        .lock()
+        .unwrap()
+        .push((self.0.name().to_owned(), labels, value));
+    }
+  }
+
+  impl CounterFn for Handle {
+    fn increment(&self, value: u64) {
+      self.push(value as f64);
+    }
+
+    fn absolute(&self, value: u64) {
+      self.push(value as f64);
+    }
+  }
+
+  impl HistogramFn for Handle {
+    fn record(&self, value: f64) {
+      self.push(value);
+    }
+  }
+
+  impl Recorder for Store {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata) -> Counter {
+      Counter::from_arc(Arc::new(Handle(key.clone(), self.0.clone())))
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata) -> Gauge {
+      Gauge::noop()
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata) -> Histogram {
+      Histogram::from_arc(Arc::new(Handle(key.clone(), self.0.clone())))
+    }
+  }
+
+  #[test]
+  fn metered_m() {
+    let store = Store::default();
+
+    let (a, b) = metrics::with_local_recorder(&store, || {
+      let a: Result<i32, &str> = metered_m! {
+        "a";
+        x <- Ok(1);
+        return x + 1;
+      };
+
+      let b = metered_m! {
+        "b";
+        x <- Some(1);
+        None::<()>;
+        return x;
+      };
+
+      (a, b)
+    });
+
+    assert_eq!(a, Ok(2));
+    assert_eq!(b, None);
+
+    let log = store.0.lock().unwrap();
+    let labels = |labels: &[(&str, &str)]| {
+      labels
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<Vec<_>>()
+    };
+    let metrics = log
+      .iter()
+      .map(|(name, labels, _)| (name.as_str(), labels.clone()))
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      metrics,
+      vec![
+        (BLOCKS, labels(&[("block", "a"), ("outcome", "success")])),
+        (BLOCK_DURATION, labels(&[("block", "a")])),
+        (
+          BLOCKS,
+          labels(&[("block", "b"), ("outcome", "short_circuit")])
+        ),
+        (BLOCK_DURATION, labels(&[("block", "b")])),
+      ]
+    );
+    assert_eq!(log[0].2, 1.);
+    assert!(log[1].2 >= 0.);
+  }
+}
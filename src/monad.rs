@@ -0,0 +1,130 @@
+//! Generic monadic interface.
+//!
+//! [`m!`](crate::m) only requires an `and_then` method, resolved syntactically. When you need to
+//! write code that is generic over _any_ monad — monad transformers, combinators, etc. — you
+//! need a way to talk about the monad itself, independently of the value it carries. Rust
+//! doesn’t have higher-kinded types, so this module emulates them with two traits:
+//!
+//! - [`Kind`], implemented by a (usually zero-sized) marker type standing for the type
+//!   constructor — e.g. [`OptionKind`] stands for `Option<_>`.
+//! - [`Monad`], implemented by the actual monadic values, e.g. `Option<A>`, which links back
+//!   to its [`Kind`].
+//!
+//! The `'a` lifetime is the lifetime of the continuations passed to [`Monad::and_then`]. Monads
+//! storing closures, like [`ReaderT`](crate::reader::ReaderT), need it; other monads are
+//! implemented for any `'a`.
+
+use crate::{either::Either, rec::MonadRec, Lift};
+use std::marker::PhantomData;
+
+/// A type constructor of monads.
+///
+/// `Self::Of<A>` is the monad carrying values of type `A`.
+pub trait Kind<'a>: Sized + 'a {
+  /// The monad carrying values of type `A`.
+  type Of<A: 'a>: Monad<'a, Item = A, Kind = Self>;
+
+  /// Apply the step `f`, starting with `a`, until it produces [`Either::Right`].
+  ///
+  /// The default implementation binds each step to the next one, which nests a closure per step. Monads supporting
+  /// [`MonadRec`] override it with their stack-safe loop, so that the traversals and the
+  /// [`combinators`](crate::combinators) built on it don’t overflow the stack on long iterators.
+  fn loop_m<A: 'a, B: 'a>(
+    a: A,
+    mut f: impl FnMut(A) -> Self::Of<Either<A, B>> + 'a,
+  ) -> Self::Of<B> {
+    f(a).and_then(move |step| match step {
+      Either::Left(a) => Self::loop_m(a, f),
+      Either::Right(b) => Lift::lift(b),
+    })
+  }
+}
+
+/// Monadic values.
+///
+/// Monads can always lift a value of their [`Item`](Monad::Item) type, hence the [`Lift`]
+/// super-trait.
+pub trait Monad<'a>: Sized + 'a + Lift<<Self as Monad<'a>>::Item> {
+  /// Type of the value carried by the monad.
+  type Item: 'a;
+
+  /// Type constructor of the monad.
+  type Kind: Kind<'a, Of<Self::Item> = Self>;
+
+  /// Sequentially compose two actions, passing the value produced by the first one to the
+  /// second one.
+  fn and_then<B, F>(self, f: F) -> <Self::Kind as Kind<'a>>::Of<B>
+  where
+    B: 'a,
+    F: FnOnce(Self::Item) -> <Self::Kind as Kind<'a>>::Of<B> + 'a;
+}
+
+/// [`Kind`] of `Option<_>`.
+#[derive(Debug)]
+pub struct OptionKind;
+
+impl<'a> Kind<'a> for OptionKind {
+  type Of<A: 'a> = Option<A>;
+
+  fn loop_m<A: 'a, B: 'a>(a: A, f: impl FnMut(A) -> Option<Either<A, B>> + 'a) -> Option<B> {
+    Self::tail_rec_m(a, f)
+  }
+}
+
+impl<'a, A: 'a> Monad<'a> for Option<A> {
+  type Item = A;
+  type Kind = OptionKind;
+
+  fn and_then<B, F>(self, f: F) -> Option<B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Option<B> + 'a,
+  {
+    Option::and_then(self, f)
+  }
+}
+
+/// [`Kind`] of `Result<_, E>`.
+#[derive(Debug)]
+pub struct ResultKind<E>(PhantomData<E>);
+
+impl<'a, E: 'a> Kind<'a> for ResultKind<E> {
+  type Of<A: 'a> = Result<A, E>;
+
+  fn loop_m<A: 'a, B: 'a>(a: A, f: impl FnMut(A) -> Result<Either<A, B>, E> + 'a) -> Result<B, E> {
+    Self::tail_rec_m(a, f)
+  }
+}
+
+impl<'a, A: 'a, E: 'a> Monad<'a> for Result<A, E> {
+  type Item = A;
+  type Kind = ResultKind<E>;
+
+  fn and_then<B, F>(self, f: F) -> Result<B, E>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Result<B, E> + 'a,
+  {
+    Result::and_then(self, f)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn double<'a, M>(m: M) -> <M::Kind as Kind<'a>>::Of<i32>
+  where
+    M: Monad<'a, Item = i32>,
+  {
+    m.and_then::<i32, _>(|x| Lift::lift(x * 2))
+  }
+
+  #[test]
+  fn generic_and_then() {
+    assert_eq!(double(Some(3)), Some(6));
+    assert_eq!(double(Option::<i32>::None), None);
+    assert_eq!(double(Result::<_, &str>::Ok(3)), Ok(6));
+    assert_eq!(double(Result::<i32, _>::Err("nope")), Err("nope"));
+  }
+}
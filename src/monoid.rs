@@ -0,0 +1,213 @@
+//! Semigroups and monoids.
+//!
+//! A [`Semigroup`] is a type with an associative way to combine two values into one; a
+//! [`Monoid`] is a semigroup with a neutral element. They are used to accumulate the output of
+//! [`Writer`](crate::writer::Writer).
+//!
+//! Some types can be combined in more than one sensible way. The [`Sum`], [`Product`], [`First`], [`Last`],
+//! [`Min`] and [`Max`] newtypes pick one:
+//!
+//! ```rust
+//! use do_notation::monoid::{Max, Monoid, Semigroup, Sum};
+//!
+//! assert_eq!(Sum(1).combine(Sum(2)), Sum(3));
+//! assert_eq!(Max(1).combine(Max(3)).combine(Max(2)), Max(3));
+//! assert_eq!(Max::<u8>::empty(), Max(0));
+//! ```
+
+use std::ops::{Add, Mul};
+
+/// Types with an associative binary operation.
+pub trait Semigroup {
+  /// Combine two values.
+  fn combine(self, other: Self) -> Self;
+}
+
+/// Semigroups with a neutral element.
+pub trait Monoid: Semigroup {
+  /// The neutral element of [`Semigroup::combine`].
+  fn empty() -> Self;
+}
+
+impl Semigroup for () {
+  fn combine(self, _: Self) -> Self {}
+}
+
+impl Monoid for () {
+  fn empty() -> Self {}
+}
+
+impl<T> Semigroup for Vec<T> {
+  fn combine(mut self, mut other: Self) -> Self {
+    self.append(&mut other);
+    self
+  }
+}
+
+impl<T> Monoid for Vec<T> {
+  fn empty() -> Self {
+    Vec::new()
+  }
+}
+
+impl Semigroup for String {
+  fn combine(mut self, other: Self) -> Self {
+    self.push_str(&other);
+    self
+  }
+}
+
+impl Monoid for String {
+  fn empty() -> Self {
+    String::new()
+  }
+}
+
+/// Monoid of the addition.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Sum<T>(pub T);
+
+impl<T: Add<Output = T>> Semigroup for Sum<T> {
+  fn combine(self, other: Self) -> Self {
+    Sum(self.0 + other.0)
+  }
+}
+
+/// Monoid of the multiplication.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Product<T>(pub T);
+
+impl<T: Mul<Output = T>> Semigroup for Product<T> {
+  fn combine(self, other: Self) -> Self {
+    Product(self.0 * other.0)
+  }
+}
+
+/// Monoid keeping the first value, if any.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct First<T>(pub Option<T>);
+
+impl<T> Semigroup for First<T> {
+  fn combine(self, other: Self) -> Self {
+    First(self.0.or(other.0))
+  }
+}
+
+impl<T> Monoid for First<T> {
+  fn empty() -> Self {
+    First(None)
+  }
+}
+
+/// Monoid keeping the last value, if any.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Last<T>(pub Option<T>);
+
+impl<T> Semigroup for Last<T> {
+  fn combine(self, other: Self) -> Self {
+    Last(other.0.or(self.0))
+  }
+}
+
+impl<T> Monoid for Last<T> {
+  fn empty() -> Self {
+    Last(None)
+  }
+}
+
+/// Semigroup keeping the smallest value.
+///
+/// It is a monoid for bounded types.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Min<T>(pub T);
+
+impl<T: Ord> Semigroup for Min<T> {
+  fn combine(self, other: Self) -> Self {
+    Min(self.0.min(other.0))
+  }
+}
+
+/// Semigroup keeping the greatest value.
+///
+/// It is a monoid for bounded types.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Max<T>(pub T);
+
+impl<T: Ord> Semigroup for Max<T> {
+  fn combine(self, other: Self) -> Self {
+    Max(self.0.max(other.0))
+  }
+}
+
+macro_rules! impl_num_monoids {
+  ($($t:ty: $zero:literal, $one:literal),*) => {
+    $(
+      impl Monoid for Sum<$t> {
+        fn empty() -> Self {
+          Sum($zero)
+        }
+      }
+
+      impl Monoid for Product<$t> {
+        fn empty() -> Self {
+          Product($one)
+        }
+      }
+    )*
+  };
+}
+
+impl_num_monoids!(
+  i8: 0, 1, i16: 0, 1, i32: 0, 1, i64: 0, 1, i128: 0, 1, isize: 0, 1,
+  u8: 0, 1, u16: 0, 1, u32: 0, 1, u64: 0, 1, u128: 0, 1, usize: 0, 1,
+  f32: 0., 1., f64: 0., 1.
+);
+
+macro_rules! impl_bounded_monoids {
+  ($($t:ty),*) => {
+    $(
+      impl Monoid for Min<$t> {
+        fn empty() -> Self {
+          Min(<$t>::MAX)
+        }
+      }
+
+      impl Monoid for Max<$t> {
+        fn empty() -> Self {
+          Max(<$t>::MIN)
+        }
+      }
+    )*
+  };
+}
+
+impl_bounded_monoids!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, char);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn combine() {
+    assert_eq!(vec![1, 2].combine(vec![3]), vec![1, 2, 3]);
+    assert_eq!(Vec::<i32>::empty().combine(vec![1]), vec![1]);
+    assert_eq!("foo".to_owned().combine("bar".to_owned()), "foobar");
+    assert_eq!(String::empty(), "");
+  }
+
+  #[test]
+  fn newtypes() {
+    assert_eq!(Sum(2).combine(Sum::empty()), Sum(2));
+    assert_eq!(
+      Product(2).combine(Product(3)).combine(Product::empty()),
+      Product(6)
+    );
+    assert_eq!(First(Some(1)).combine(First(Some(2))), First(Some(1)));
+    assert_eq!(First::empty().combine(First(Some(2))), First(Some(2)));
+    assert_eq!(Last(Some(1)).combine(Last(Some(2))), Last(Some(2)));
+    assert_eq!(Last(Some(1)).combine(Last::empty()), Last(Some(1)));
+    assert_eq!(Min(1).combine(Min(2)), Min(1));
+    assert_eq!(Min::<i32>::empty().combine(Min(2)), Min(2));
+    assert_eq!(Max('a').combine(Max('b')), Max('b'));
+  }
+}
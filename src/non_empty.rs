@@ -0,0 +1,139 @@
+//! Non-empty vectors.
+//!
+//! [`NonEmpty<A>`] is a vector with at least one element. In [`m!`](crate::m), it behaves like a list: binding a
+//! [`NonEmpty`] runs the rest of the do-block for each of its elements and concatenates the results.
+//!
+//! Because the rest of the do-block might run several times, [`NonEmpty`] doesn’t implement
+//! [`Monad`](crate::monad::Monad), whose continuations are called once.
+//!
+//! ```rust
+//! use do_notation::{m, non_empty::NonEmpty};
+//!
+//! let r = m! {
+//!   x <- NonEmpty::from_vec(vec![1, 2]).unwrap();
+//!   y <- NonEmpty::from_vec(vec![10, 20]).unwrap();
+//!   return x * y;
+//! };
+//!
+//! assert_eq!(Vec::from(r), vec![10, 20, 20, 40]);
+//! ```
+
+use crate::Lift;
+
+/// Vectors with at least one element.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NonEmpty<A> {
+  /// First element.
+  pub head: A,
+  /// Remaining elements.
+  pub tail: Vec<A>,
+}
+
+impl<A> NonEmpty<A> {
+  /// Create a vector with a single element.
+  pub fn new(head: A) -> Self {
+    NonEmpty {
+      head,
+      tail: Vec::new(),
+    }
+  }
+
+  /// Create a vector from a [`Vec`], if it’s not empty.
+  pub fn from_vec(mut v: Vec<A>) -> Option<Self> {
+    if v.is_empty() {
+      None
+    } else {
+      let head = v.remove(0);
+      Some(NonEmpty { head, tail: v })
+    }
+  }
+
+  /// Number of elements, which is at least one.
+  #[allow(clippy::len_without_is_empty)]
+  pub fn len(&self) -> usize {
+    self.tail.len() + 1
+  }
+
+  /// Iterate over the elements.
+  pub fn iter(&self) -> impl Iterator<Item = &A> {
+    std::iter::once(&self.head).chain(self.tail.iter())
+  }
+
+  /// Append an element.
+  pub fn push(&mut self, a: A) {
+    self.tail.push(a);
+  }
+
+  /// Append the elements of another vector.
+  pub fn append(&mut self, other: NonEmpty<A>) {
+    self.tail.push(other.head);
+    self.tail.extend(other.tail);
+  }
+
+  /// Apply a function to every element.
+  pub fn map<B>(self, mut f: impl FnMut(A) -> B) -> NonEmpty<B> {
+    let head = f(self.head);
+    NonEmpty {
+      head,
+      tail: self.tail.into_iter().map(f).collect(),
+    }
+  }
+
+  /// Bind every element to `f`, concatenating the results.
+  pub fn and_then<B>(self, mut f: impl FnMut(A) -> NonEmpty<B>) -> NonEmpty<B> {
+    let mut r = f(self.head);
+
+    for a in self.tail {
+      r.append(f(a));
+    }
+
+    r
+  }
+}
+
+impl<A> From<NonEmpty<A>> for Vec<A> {
+  fn from(v: NonEmpty<A>) -> Self {
+    let mut r = Vec::with_capacity(v.len());
+    r.push(v.head);
+    r.extend(v.tail);
+    r
+  }
+}
+
+impl<A> IntoIterator for NonEmpty<A> {
+  type Item = A;
+  type IntoIter = std::iter::Chain<std::iter::Once<A>, std::vec::IntoIter<A>>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    std::iter::once(self.head).chain(self.tail)
+  }
+}
+
+impl<A> Lift<A> for NonEmpty<A> {
+  fn lift(a: A) -> Self {
+    NonEmpty::new(a)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn non_empty() {
+    assert_eq!(NonEmpty::<i32>::from_vec(vec![]), None);
+
+    let mut v = NonEmpty::new(1);
+    v.push(2);
+    assert_eq!(v.len(), 2);
+    assert_eq!(v.iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+
+    let r = m! {
+      x <- v;
+      _ <- NonEmpty::from_vec(vec![(), ()]).unwrap();
+      return x;
+    };
+
+    assert_eq!(Vec::from(r), vec![1, 1, 2, 2]);
+  }
+}
@@ -0,0 +1,173 @@
+//! Helpers for [`Option`]s.
+//!
+//! [`OptionExt`] gathers the small adapters that keep binds on [`Option`]s — or converting them — on a single line
+//! in [`m!`](crate::m) blocks:
+//!
+//! ```rust
+//! use do_notation::{m, option::OptionExt};
+//! use std::collections::HashMap;
+//!
+//! let ports = HashMap::from([("http", 80), ("https", 443), ("dev", 0)]);
+//! let mut seen = Vec::new();
+//!
+//! let mut port = |name| {
+//!   m! {
+//!     port <- ports.get(name).copied().tap_some(|p| seen.push(*p));
+//!     _ <- Some(()).guard_some(port > 0);
+//!     return port;
+//!   }
+//! };
+//!
+//! assert_eq!(port("https"), Some(443));
+//! assert_eq!(port("dev"), None);
+//! assert_eq!(port("ftp"), None);
+//! assert_eq!(seen, vec![443, 0]);
+//!
+//! let r: Result<u16, String> = m! {
+//!   port <- ports.get("ftp").copied().to_result_with(|| "no ftp port");
+//!   return port;
+//! };
+//!
+//! assert_eq!(r, Err("no ftp port".to_owned()));
+//! ```
+
+use crate::applicative::{Applicative, Applied, Functor};
+
+/// Extension methods of [`Option`].
+pub trait OptionExt<T>: Sized {
+  /// Keep the value if `cond` holds.
+  ///
+  /// This is [`guard`](crate::combinators::guard), for a value already bound.
+  fn guard_some(self, cond: bool) -> Option<T>;
+
+  /// Keep the value if the monadic predicate `f` holds.
+  ///
+  /// ```rust
+  /// use do_notation::option::OptionExt;
+  ///
+  /// let is_small = |n: &i32| if *n < 100 { Ok(*n % 2 == 0) } else { Err(format!("{} is too big", n)) };
+  ///
+  /// assert_eq!(Some(2).filter_m(is_small), Ok(Some(2)));
+  /// assert_eq!(Some(3).filter_m(is_small), Ok(None));
+  /// assert_eq!(Some(200).filter_m(is_small), Err("200 is too big".to_owned()));
+  /// assert_eq!(None.filter_m(is_small), Ok(None));
+  /// ```
+  fn filter_m<'a, M>(self, f: impl FnOnce(&T) -> M) -> <M::Kind as Functor<'a>>::Of<Option<T>>
+  where
+    T: 'a,
+    M: Applied<'a, Item = bool>;
+
+  /// Call `f` with the value, if any.
+  fn tap_some(self, f: impl FnOnce(&T)) -> Option<T>;
+
+  /// Combine the value with the value of `other` with `f`, if both are present.
+  ///
+  /// [`Option`] has an unstable method with the same name, so call it as `OptionExt::zip_with(a, b, f)`.
+  ///
+  /// ```rust
+  /// use do_notation::option::OptionExt;
+  ///
+  /// assert_eq!(OptionExt::zip_with(Some(1), Some(2), |a, b| a + b), Some(3));
+  /// assert_eq!(OptionExt::zip_with(Some(1), None::<i32>, |a, b| a + b), None);
+  /// ```
+  fn zip_with<U, R>(self, other: Option<U>, f: impl FnOnce(T, U) -> R) -> Option<R>;
+
+  /// Convert to a [`Result`], failing with the error converted from `ctx()` on [`None`].
+  ///
+  /// `ctx` is only called on [`None`], and its value is converted with [`Into`], so that a message can be turned
+  /// into the error type of the block.
+  fn to_result_with<E, C>(self, ctx: impl FnOnce() -> C) -> Result<T, E>
+  where
+    C: Into<E>;
+}
+
+impl<T> OptionExt<T> for Option<T> {
+  fn guard_some(self, cond: bool) -> Option<T> {
+    self.filter(|_| cond)
+  }
+
+  fn filter_m<'a, M>(self, f: impl FnOnce(&T) -> M) -> <M::Kind as Functor<'a>>::Of<Option<T>>
+  where
+    T: 'a,
+    M: Applied<'a, Item = bool>,
+  {
+    match self {
+      Some(t) => M::Kind::map(f(&t), move |keep| if keep { Some(t) } else { None }),
+      None => M::Kind::pure(None),
+    }
+  }
+
+  fn tap_some(self, f: impl FnOnce(&T)) -> Option<T> {
+    if let Some(t) = &self {
+      f(t);
+    }
+
+    self
+  }
+
+  fn zip_with<U, R>(self, other: Option<U>, f: impl FnOnce(T, U) -> R) -> Option<R> {
+    Some(f(self?, other?))
+  }
+
+  fn to_result_with<E, C>(self, ctx: impl FnOnce() -> C) -> Result<T, E>
+  where
+    C: Into<E>,
+  {
+    self.ok_or_else(|| ctx().into())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::validation::Validation;
+
+  #[test]
+  fn option_ext() {
+    let mut tapped = Vec::new();
+
+    let r = m! {
+      a <- Some(3).tap_some(|a| tapped.push(*a));
+      b <- Some(4).guard_some(a < 4);
+      c <- OptionExt::zip_with(Some(a), Some(b), |a, b| a * a + b * b);
+      None::<i32>.tap_some(|_| unreachable!()).guard_some(true).map(|_| ()).or(Some(()));
+      return c;
+    };
+
+    assert_eq!(r, Some(25));
+    assert_eq!(tapped, vec![3]);
+    assert_eq!(Some(4).guard_some(false), None);
+    assert_eq!(
+      OptionExt::zip_with(None::<i32>, Some(1), |a, b| a + b),
+      None
+    );
+  }
+
+  #[test]
+  fn filter_m() {
+    let even = |n: &i32| {
+      if *n >= 0 {
+        Validation::Valid(n % 2 == 0)
+      } else {
+        Validation::invalid("negative")
+      }
+    };
+
+    assert_eq!(Some(2).filter_m(even), Validation::Valid(Some(2)));
+    assert_eq!(Some(1).filter_m(even), Validation::Valid(None));
+    assert_eq!(Some(-1).filter_m(even), Validation::invalid("negative"));
+    assert_eq!(None.filter_m(even), Validation::Valid(None));
+  }
+
+  #[test]
+  fn to_result_with() {
+    let r: Result<i32, Box<dyn std::error::Error>> = m! {
+      x <- Some(1).to_result_with(|| "missing x");
+      y <- None::<i32>.to_result_with(|| format!("missing y, x = {}", x));
+      return x + y;
+    };
+
+    assert_eq!(r.unwrap_err().to_string(), "missing y, x = 1");
+    assert_eq!(Some(1).to_result_with::<String, _>(|| "missing"), Ok(1));
+  }
+}
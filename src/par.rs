@@ -0,0 +1,255 @@
+//! Parallel evaluation.
+//!
+//! > This module requires the `rayon` feature.
+//!
+//! [`Par`] is a pure computation evaluated when [run](Par::run). Binding a computation with [`Par::and_then`] runs it
+//! before the rest of the block, as the rest depends on its value, but independent computations combined with
+//! [`Par::zip`], [`Par::zip_with`] or [`Par::sequence`] — or as applicative functors, with [`ado!`](crate::ado) or
+//! the [`combinators`](crate::combinators) over [`ParKind`] — run in parallel, on the [rayon](rayon) thread pool.
+//!
+//! ```rust
+//! use do_notation::{m, par::Par};
+//!
+//! fn fib(n: u64) -> u64 {
+//!   if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+//! }
+//!
+//! let r = m! {
+//!   // both computations run in parallel
+//!   p <- Par::new(|| fib(20)).zip(Par::new(|| fib(21)));
+//!   let (a, b) = p;
+//!   sums <- Par::sequence((0..4).map(move |i| Par::new(move || fib(i) + a + b)));
+//!   return sums;
+//! };
+//!
+//! assert_eq!(r.run(), vec![17711, 17712, 17712, 17713]);
+//! ```
+
+use crate::{
+  applicative::{Applicative, Applied, Functor},
+  Lift,
+};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::sync::{Arc, Mutex};
+
+/// Independent computations, evaluated in parallel.
+enum Work<'a> {
+  Done,
+  Task(Box<dyn FnOnce() + Send + 'a>),
+  Join(Box<Work<'a>>, Box<Work<'a>>),
+  All(Vec<Work<'a>>),
+}
+
+impl<'a> Work<'a> {
+  fn run(self) {
+    match self {
+      Work::Done => (),
+      Work::Task(f) => f(),
+      Work::Join(a, b) => {
+        rayon::join(move || a.run(), move || b.run());
+      }
+      Work::All(works) => works.into_par_iter().for_each(Work::run),
+    }
+  }
+}
+
+/// A computation producing a value of type `A`, whose independent parts are evaluated in parallel.
+///
+/// The independent parts are the computations built with [`Par::new`]; the functions combining their values, with
+/// [`Par::map`], [`Par::zip_with`] or [`Par::and_then`], run on the thread running the computation, so they don’t need
+/// to be [`Send`].
+pub struct Par<'a, A> {
+  work: Work<'a>,
+  finish: Box<dyn FnOnce() -> A + 'a>,
+}
+
+impl<'a, A: 'a> Par<'a, A> {
+  /// Defer a computation.
+  pub fn new(f: impl FnOnce() -> A + Send + 'a) -> Self
+  where
+    A: Send,
+  {
+    let slot = Arc::new(Mutex::new(None));
+    let result = slot.clone();
+
+    Par {
+      work: Work::Task(Box::new(move || *slot.lock().unwrap() = Some(f()))),
+      finish: Box::new(move || result.lock().unwrap().take().unwrap()),
+    }
+  }
+
+  /// Evaluate the computation.
+  pub fn run(self) -> A {
+    self.work.run();
+    (self.finish)()
+  }
+
+  /// Evaluate this computation, then the one built by `f` with its value.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(A) -> Par<'a, B> + 'a) -> Par<'a, B> {
+    let Par { work, finish } = self;
+
+    Par {
+      work,
+      finish: Box::new(move || f(finish()).run()),
+    }
+  }
+
+  /// Map the value produced by this computation.
+  pub fn map<B: 'a>(self, f: impl FnOnce(A) -> B + 'a) -> Par<'a, B> {
+    let Par { work, finish } = self;
+
+    Par {
+      work,
+      finish: Box::new(move || f(finish())),
+    }
+  }
+
+  /// Evaluate this computation and `other` in parallel, combining their values with `f`.
+  pub fn zip_with<B: 'a, C: 'a>(
+    self,
+    other: Par<'a, B>,
+    f: impl FnOnce(A, B) -> C + 'a,
+  ) -> Par<'a, C> {
+    let work = match (self.work, other.work) {
+      (Work::Done, work) | (work, Work::Done) => work,
+      (a, b) => Work::Join(Box::new(a), Box::new(b)),
+    };
+    let (fa, fb) = (self.finish, other.finish);
+
+    Par {
+      work,
+      finish: Box::new(move || f(fa(), fb())),
+    }
+  }
+
+  /// Evaluate this computation and `other` in parallel, pairing their values.
+  pub fn zip<B: 'a>(self, other: Par<'a, B>) -> Par<'a, (A, B)> {
+    self.zip_with(other, |a, b| (a, b))
+  }
+
+  /// Evaluate computations in parallel, collecting their values in order.
+  pub fn sequence(iter: impl IntoIterator<Item = Self>) -> Par<'a, Vec<A>> {
+    let (works, finishes): (Vec<_>, Vec<_>) = iter.into_iter().map(|p| (p.work, p.finish)).unzip();
+
+    Par {
+      work: Work::All(works),
+      finish: Box::new(move || finishes.into_iter().map(|finish| finish()).collect()),
+    }
+  }
+}
+
+impl<'a, A: 'a> Lift<A> for Par<'a, A> {
+  fn lift(a: A) -> Self {
+    Par {
+      work: Work::Done,
+      finish: Box::new(move || a),
+    }
+  }
+}
+
+/// [`Applicative`] of `Par<'_, _>`, evaluating independent computations in parallel.
+#[derive(Debug)]
+pub struct ParKind;
+
+impl<'a> Functor<'a> for ParKind {
+  type Of<A: 'a> = Par<'a, A>;
+
+  fn map<A: 'a, B: 'a>(fa: Par<'a, A>, f: impl FnOnce(A) -> B + 'a) -> Par<'a, B> {
+    fa.map(f)
+  }
+}
+
+impl<'a> Applicative<'a> for ParKind {
+  fn pure<A: 'a>(a: A) -> Par<'a, A> {
+    Lift::lift(a)
+  }
+
+  fn zip_with<A: 'a, B: 'a, C: 'a>(
+    fa: Par<'a, A>,
+    fb: Par<'a, B>,
+    f: impl FnOnce(A, B) -> C + 'a,
+  ) -> Par<'a, C> {
+    fa.zip_with(fb, f)
+  }
+
+  fn traverse_iter<I, B, F>(iter: I, f: F) -> Par<'a, Vec<B>>
+  where
+    I: Iterator + 'a,
+    B: 'a,
+    F: FnMut(I::Item) -> Par<'a, B> + 'a,
+  {
+    Par::sequence(iter.map(f))
+  }
+
+  fn for_each_iter<I, B, F>(iter: I, f: F) -> Par<'a, ()>
+  where
+    I: Iterator + 'a,
+    B: 'a,
+    F: FnMut(I::Item) -> Par<'a, B> + 'a,
+  {
+    Par::sequence(iter.map(f)).map(|_| ())
+  }
+}
+
+impl<'a, A: 'a> Applied<'a> for Par<'a, A> {
+  type Item = A;
+  type Kind = ParKind;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    ado,
+    combinators::{lift_m2, map_m},
+  };
+
+  #[test]
+  fn par() {
+    let r = m! {
+      x <- Par::lift(1);
+      y <- Par::new(move || x + 1).map(|y| y * 10);
+      return x + y;
+    };
+
+    assert_eq!(r.run(), 21);
+  }
+
+  #[test]
+  fn zip_on_thread_pool() {
+    let on_pool = || rayon::current_thread_index().is_some();
+
+    assert!(!Par::new(on_pool).run());
+    assert_eq!(Par::new(on_pool).zip(Par::new(on_pool)).run(), (true, true));
+
+    let r = Par::sequence((0..10).map(|i| Par::new(move || i * i)));
+    assert_eq!(r.run(), (0..10).map(|i| i * i).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn applicative() {
+    let on_pool = || Par::new(|| rayon::current_thread_index().is_some());
+
+    let r = ado! {
+      a <- on_pool();
+      b <- on_pool();
+      c <- Par::lift(2);
+      return (a, b, c);
+    };
+    assert_eq!(r.run(), (true, true, 2));
+
+    // the combining functions don’t need to be `Send`
+    let n = std::rc::Rc::new(3);
+    let r = lift_m2(|a: i32, b: i32| a + b + *n, Par::new(|| 1), Par::new(|| 2));
+    assert_eq!(r.run(), 6);
+
+    let r = map_m(0..100, |i| {
+      Par::new(move || (i, rayon::current_thread_index().is_some()))
+    });
+    assert!(r
+      .run()
+      .into_iter()
+      .enumerate()
+      .all(|(i, (j, on_pool))| i == j && on_pool));
+  }
+}
@@ -0,0 +1,152 @@
+//! Sequencing `nom` parsers.
+//!
+//! > This module requires the `nom` feature.
+//!
+//! [`Parse`] is the monad of `nom` parsers: a computation consumes some input, producing a value and the remaining
+//! input, or fails with a [`nom::Err`]. Binding a parser runs it on the input left by the previous ones, so parsers
+//! lifted with [`parse`] are sequenced in do-blocks without threading the remaining input by hand, and a block is
+//! [run](Parse::run) on the input like a parser:
+//!
+//! ```rust
+//! use do_notation::{m, parse::{parse, Parse}};
+//! use nom::{character::complete::{char, digit1}, error::Error};
+//!
+//! fn pair<'a>() -> Parse<'a, &'a str, (&'a str, &'a str), Error<&'a str>> {
+//!   m! {
+//!     x <- parse(digit1);
+//!     _ <- parse(char(','));
+//!     y <- parse(digit1);
+//!     return (x, y);
+//!   }
+//! }
+//!
+//! assert_eq!(pair().run("12,34;"), Ok((";", ("12", "34"))));
+//! assert!(pair().run("12;34").is_err());
+//! ```
+//!
+//! [`parser`] turns a block back into a `nom` parser, so that it can be used with `nom` combinators.
+
+use crate::Lift;
+use nom::{IResult, Parser};
+
+/// A parser of inputs of type `I`, producing a value of type `O` or failing with an error of type `E`.
+#[allow(clippy::type_complexity)]
+pub struct Parse<'a, I, O, E>(Box<dyn FnOnce(I) -> IResult<I, O, E> + 'a>);
+
+impl<'a, I, O, E> Parse<'a, I, O, E>
+where
+  I: 'a,
+  O: 'a,
+  E: 'a,
+{
+  /// Build a parser out of a function consuming some input.
+  pub fn new(f: impl FnOnce(I) -> IResult<I, O, E> + 'a) -> Self {
+    Parse(Box::new(f))
+  }
+
+  /// Run the parser on `input`, producing the remaining input and the value.
+  pub fn run(self, input: I) -> IResult<I, O, E> {
+    (self.0)(input)
+  }
+
+  /// Run the parser, then the parser built by `f` from its value on the remaining input.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(O) -> Parse<'a, I, B, E> + 'a) -> Parse<'a, I, B, E> {
+    Parse::new(move |input| {
+      let (input, o) = self.run(input)?;
+      f(o).run(input)
+    })
+  }
+
+  /// Apply `f` to the value of the parser.
+  pub fn map<B: 'a>(self, f: impl FnOnce(O) -> B + 'a) -> Parse<'a, I, B, E> {
+    Parse::new(move |input| self.run(input).map(|(input, o)| (input, f(o))))
+  }
+
+  /// Fail with `e`, without consuming any input.
+  pub fn fail(e: nom::Err<E>) -> Self {
+    Parse::new(move |_| Err(e))
+  }
+}
+
+impl<'a, I, O, E> Lift<O> for Parse<'a, I, O, E>
+where
+  I: 'a,
+  O: 'a,
+  E: 'a,
+{
+  fn lift(o: O) -> Self {
+    Parse::new(move |input| Ok((input, o)))
+  }
+}
+
+/// Lift a `nom` parser.
+pub fn parse<'a, I, P>(mut p: P) -> Parse<'a, I, P::Output, P::Error>
+where
+  I: 'a,
+  P: Parser<I> + 'a,
+{
+  Parse::new(move |input| p.parse(input))
+}
+
+/// Turn the blocks built by `f` into a `nom` parser, building a block every time the parser runs.
+pub fn parser<'a, I, O, E>(f: impl Fn() -> Parse<'a, I, O, E>) -> impl FnMut(I) -> IResult<I, O, E>
+where
+  I: 'a,
+  O: 'a,
+  E: 'a,
+{
+  move |input| f().run(input)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use nom::{
+    bytes::complete::tag,
+    character::complete::{alpha1, char, digit1, space0},
+    error::{Error, ErrorKind},
+    multi::separated_list1,
+  };
+
+  type P<'a, O> = Parse<'a, &'a str, O, Error<&'a str>>;
+
+  fn assignment<'a>() -> P<'a, (&'a str, u32)> {
+    m! {
+      name <- parse(alpha1);
+      _ <- parse((space0, char('='), space0));
+      value <- parse(digit1).map(|d: &str| d.parse().unwrap());
+      return (name, value);
+    }
+  }
+
+  #[test]
+  fn sequence() {
+    assert_eq!(assignment().run("x = 42"), Ok(("", ("x", 42))));
+    assert_eq!(
+      assignment().run("x = y"),
+      Err(nom::Err::Error(Error::new("y", ErrorKind::Digit)))
+    );
+
+    let r: P<u32> = m! {
+      p <- assignment();
+      let (name, value) = p;
+      if name == "x" { Lift::lift(()) } else { Parse::fail(nom::Err::Failure(Error::new(name, ErrorKind::Verify))) };
+      return value;
+    };
+
+    assert_eq!(
+      r.run("y=1"),
+      Err(nom::Err::Failure(Error::new("y", ErrorKind::Verify)))
+    );
+  }
+
+  #[test]
+  fn combinators() {
+    let mut assignments = separated_list1(tag(", "), super::parser(assignment));
+
+    assert_eq!(
+      assignments.parse("a = 1, b = 2"),
+      Ok(("", vec![("a", 1), ("b", 2)]))
+    );
+  }
+}
@@ -0,0 +1,137 @@
+//! Persistent collections as list monads.
+//!
+//! > This module requires the `im` feature.
+//!
+//! Like `Vec` or [`NonEmpty`](crate::non_empty::NonEmpty), the persistent collections of the `im` crate can be used
+//! as list monads, binding every element in turn: [`Vector`] concatenates the results, and [`OrdSet`] merges them,
+//! removing duplicates. The results are concatenated and merged by sharing their structure, instead of copying
+//! them, so the binds stay cheap with large collections. Binding requires [`VectorBind`] or [`OrdSetBind`] to be in
+//! scope.
+//!
+//! ```rust
+//! use do_notation::{m, persistent::{OrdSetBind, VectorBind}};
+//! use im::{ordset, vector};
+//!
+//! let r = m! {
+//!   x <- vector![1, 2];
+//!   y <- vector![10, 20];
+//!   return x * y;
+//! };
+//!
+//! assert_eq!(r, vector![10, 20, 20, 40]);
+//!
+//! let r = m! {
+//!   x <- ordset![1, 2];
+//!   y <- ordset![10, 20];
+//!   return x * y;
+//! };
+//!
+//! assert_eq!(r, ordset![10, 20, 40]);
+//! ```
+//!
+//! As the rest of the do-block might run several times, the values it captures must be [`Copy`].
+
+use crate::Lift;
+use im::{OrdSet, Vector};
+
+/// Bind of [`Vector`], which doesn’t have an `and_then` method.
+pub trait VectorBind<A: Clone>: Sized {
+  /// Bind every element to `f`, concatenating the results.
+  fn and_then<B: Clone>(self, f: impl FnMut(A) -> Vector<B>) -> Vector<B>;
+}
+
+impl<A: Clone> VectorBind<A> for Vector<A> {
+  fn and_then<B: Clone>(self, mut f: impl FnMut(A) -> Vector<B>) -> Vector<B> {
+    let mut r = Vector::new();
+
+    for a in self {
+      r.append(f(a));
+    }
+
+    r
+  }
+}
+
+impl<A: Clone> Lift<A> for Vector<A> {
+  fn lift(a: A) -> Self {
+    Vector::unit(a)
+  }
+}
+
+/// Bind of [`OrdSet`], which doesn’t have an `and_then` method.
+pub trait OrdSetBind<A: Ord + Clone>: Sized {
+  /// Bind every element to `f`, merging the results.
+  fn and_then<B: Ord + Clone>(self, f: impl FnMut(A) -> OrdSet<B>) -> OrdSet<B>;
+}
+
+impl<A: Ord + Clone> OrdSetBind<A> for OrdSet<A> {
+  fn and_then<B: Ord + Clone>(self, f: impl FnMut(A) -> OrdSet<B>) -> OrdSet<B> {
+    OrdSet::unions(self.into_iter().map(f))
+  }
+}
+
+impl<A: Ord + Clone> Lift<A> for OrdSet<A> {
+  fn lift(a: A) -> Self {
+    OrdSet::unit(a)
+  }
+}
+
+#[cfg(feature = "nightly")]
+impl<A: Clone, B: Clone> crate::generator::GenBind<Vector<B>> for Vector<A> {
+  type Item = A;
+
+  fn gen_bind(self, k: impl FnMut(A) -> Vector<B>) -> Vector<B> {
+    VectorBind::and_then(self, k)
+  }
+}
+
+#[cfg(feature = "nightly")]
+impl<A: Ord + Clone, B: Ord + Clone> crate::generator::GenBind<OrdSet<B>> for OrdSet<A> {
+  type Item = A;
+
+  fn gen_bind(self, k: impl FnMut(A) -> OrdSet<B>) -> OrdSet<B> {
+    OrdSetBind::and_then(self, k)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use im::{ordset, vector};
+
+  #[test]
+  fn vector() {
+    let r = m! {
+      x <- vector![1, 2, 3];
+      _ <- if x % 2 == 1 { vector![()] } else { Vector::new() };
+      y <- vector![x, x * 10];
+      return (x, y);
+    };
+
+    assert_eq!(r, vector![(1, 1), (1, 10), (3, 3), (3, 30)]);
+
+    let big: Vector<u32> = (0..10_000).collect();
+    let r = m! {
+      x <- big.clone();
+      vector![x, x]
+    };
+
+    assert_eq!(r.len(), 20_000);
+    assert_eq!(
+      r.iter().take(4).copied().collect::<Vec<_>>(),
+      vec![0, 0, 1, 1]
+    );
+  }
+
+  #[test]
+  fn ord_set() {
+    let r = m! {
+      x <- ordset![3, 1, 2];
+      y <- ordset![x % 2, x + 10];
+      return y;
+    };
+
+    assert_eq!(r, ordset![0, 1, 11, 12, 13]);
+    assert_eq!(<OrdSet<_> as Lift<_>>::lift(1), ordset![1]);
+  }
+}
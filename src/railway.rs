@@ -0,0 +1,251 @@
+//! Railway-oriented pipelines.
+//!
+//! A [`Track`] is a [`Result`] running through named stages: values continue on the success track, and the first
+//! failing stage switches the pipeline to the failure track, remembering the name of the stage. Hooks can look at
+//! the value between stages with [`Track::tee`], errors can be converted with [`Track::map_err`] or recovered from
+//! with [`Track::recover`], and [`Track::finally`] ends the pipeline, whatever the track:
+//!
+//! ```rust
+//! use do_notation::railway::Track;
+//!
+//! let mut log = Vec::new();
+//!
+//! let r = Track::ok(" 42 ")
+//!   .then("trim", |s| Ok(s.trim()))
+//!   .then("parse", |s| s.parse::<u8>().map_err(|e| e.to_string()))
+//!   .tee(|n| log.push(format!("parsed {}", n)))
+//!   .then("double", |n| n.checked_mul(2).ok_or_else(|| "overflow".to_owned()))
+//!   .then("check", |n| if n > 100 { Err(format!("{} is too large", n)) } else { Ok(n) });
+//!
+//! assert_eq!(r.failed_stage(), None);
+//! assert_eq!(r.finally(|r| log.push(format!("done: {:?}", r))), Ok(84));
+//! assert_eq!(log, vec!["parsed 42", "done: Ok(84)"]);
+//!
+//! let r = Track::ok(200).then("double", |n: u8| n.checked_mul(2).ok_or("overflow"));
+//! assert_eq!(r.failed_stage(), Some("double"));
+//! ```
+//!
+//! Tracks are monads too, so unnamed stages can be written as [`m!`](crate::m) blocks:
+//!
+//! ```rust
+//! use do_notation::{m, railway::Track};
+//!
+//! let r: Track<i32, &str> = m! {
+//!   x <- Track::ok(1);
+//!   y <- Track::from(Ok(2)).then("check", |y| if y > 0 { Ok(y) } else { Err("negative") });
+//!   return x + y;
+//! };
+//!
+//! assert_eq!(r.into_result(), Ok(3));
+//! ```
+
+use crate::{
+  monad::{Kind, Monad},
+  Lift,
+};
+use std::marker::PhantomData;
+
+/// Pipelines producing a value of type `T`, or failing with an error of type `E`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Track<T, E> {
+  result: Result<T, E>,
+  stage: Option<&'static str>,
+}
+
+impl<T, E> Track<T, E> {
+  /// Start a pipeline on the success track.
+  pub fn ok(t: T) -> Self {
+    Track {
+      result: Ok(t),
+      stage: None,
+    }
+  }
+
+  /// Start a pipeline on the failure track.
+  pub fn err(e: E) -> Self {
+    Track {
+      result: Err(e),
+      stage: None,
+    }
+  }
+
+  /// Name of the stage which switched the pipeline to the failure track, if it was a named stage.
+  pub fn failed_stage(&self) -> Option<&'static str> {
+    self.stage
+  }
+
+  /// Run the stage `stage` on the value.
+  pub fn then<U>(self, stage: &'static str, f: impl FnOnce(T) -> Result<U, E>) -> Track<U, E> {
+    match self.result {
+      Ok(t) => {
+        let result = f(t);
+        let stage = result.as_ref().err().map(|_| stage);
+        Track { result, stage }
+      }
+      Err(e) => Track {
+        result: Err(e),
+        stage: self.stage,
+      },
+    }
+  }
+
+  /// Bind the value to `f`, an unnamed stage.
+  pub fn and_then<U>(self, f: impl FnOnce(T) -> Track<U, E>) -> Track<U, E> {
+    match self.result {
+      Ok(t) => f(t),
+      Err(e) => Track {
+        result: Err(e),
+        stage: self.stage,
+      },
+    }
+  }
+
+  /// Apply `f` to the value.
+  pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Track<U, E> {
+    Track {
+      result: self.result.map(f),
+      stage: self.stage,
+    }
+  }
+
+  /// Call `f` with the value, if on the success track.
+  pub fn tee(self, f: impl FnOnce(&T)) -> Self {
+    if let Ok(t) = &self.result {
+      f(t);
+    }
+
+    self
+  }
+
+  /// Apply `f` to the error, if on the failure track.
+  pub fn map_err<F>(self, f: impl FnOnce(E) -> F) -> Track<T, F> {
+    Track {
+      result: self.result.map_err(f),
+      stage: self.stage,
+    }
+  }
+
+  /// Try to switch back to the success track with `f`, if on the failure track.
+  pub fn recover(self, f: impl FnOnce(E) -> Result<T, E>) -> Self {
+    match self.result {
+      Ok(_) => self,
+      Err(e) => match f(e) {
+        Ok(t) => Track::ok(t),
+        Err(e) => Track {
+          result: Err(e),
+          stage: self.stage,
+        },
+      },
+    }
+  }
+
+  /// End the pipeline, calling `f` with the result, whatever the track.
+  pub fn finally(self, f: impl FnOnce(&Result<T, E>)) -> Result<T, E> {
+    f(&self.result);
+    self.result
+  }
+
+  /// End the pipeline.
+  pub fn into_result(self) -> Result<T, E> {
+    self.result
+  }
+}
+
+impl<T, E> From<Result<T, E>> for Track<T, E> {
+  fn from(result: Result<T, E>) -> Self {
+    Track {
+      result,
+      stage: None,
+    }
+  }
+}
+
+impl<T, E> From<Track<T, E>> for Result<T, E> {
+  fn from(track: Track<T, E>) -> Self {
+    track.result
+  }
+}
+
+impl<T, E> Lift<T> for Track<T, E> {
+  fn lift(t: T) -> Self {
+    Track::ok(t)
+  }
+}
+
+/// [`Kind`] of `Track<_, E>`.
+#[derive(Debug)]
+pub struct TrackKind<E>(PhantomData<E>);
+
+impl<'a, E: 'a> Kind<'a> for TrackKind<E> {
+  type Of<A: 'a> = Track<A, E>;
+}
+
+impl<'a, T: 'a, E: 'a> Monad<'a> for Track<T, E> {
+  type Item = T;
+  type Kind = TrackKind<E>;
+
+  fn and_then<B, F>(self, f: F) -> Track<B, E>
+  where
+    B: 'a,
+    F: FnOnce(T) -> Track<B, E> + 'a,
+  {
+    Track::and_then(self, f)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(s: &str) -> Track<i32, String> {
+    Track::ok(s).then("parse", |s| s.parse::<i32>().map_err(|e| e.to_string()))
+  }
+
+  #[test]
+  fn stages() {
+    let r = parse("4").then("sqrt", |n| match (1..=n).find(|k| k * k == n) {
+      Some(k) => Ok(k),
+      None => Err(format!("{} isn’t a square", n)),
+    });
+    assert_eq!(r.failed_stage(), None);
+    assert_eq!(r.into_result(), Ok(2));
+
+    let r = parse("x").then("double", |n| Ok(n * 2));
+    assert_eq!(r.failed_stage(), Some("parse"));
+    assert_eq!(
+      r.into_result(),
+      Err("invalid digit found in string".to_owned())
+    );
+  }
+
+  #[test]
+  fn hooks() {
+    let mut seen = Vec::new();
+
+    let r = parse("x")
+      .tee(|n| seen.push(*n))
+      .map_err(|e| e.len())
+      .recover(|len| if len > 100 { Err(len) } else { Ok(0) })
+      .tee(|n| seen.push(*n))
+      .then("check", |n| if n > 0 { Ok(n) } else { Err(0) });
+
+    assert_eq!(r.failed_stage(), Some("check"));
+    assert_eq!(seen, vec![0]);
+
+    let mut ended = None;
+    assert_eq!(r.finally(|r| ended = Some(*r)), Err(0));
+    assert_eq!(ended, Some(Err(0)));
+  }
+
+  #[test]
+  fn track_in_m() {
+    let r = m! {
+      x <- parse("1");
+      y <- parse("y");
+      return x + y;
+    };
+
+    assert_eq!(r.failed_stage(), Some("parse"));
+    assert!(r.into_result().is_err());
+  }
+}
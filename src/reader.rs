@@ -0,0 +1,293 @@
+//! The reader monad transformer.
+//!
+//! [`ReaderT<R, M, A>`] adds a read-only environment of type `R` to the monad `M`. The
+//! environment is not threaded explicitly through the statements of a do-block: it’s made
+//! available with [`ask`], and can be locally altered with [`local`].
+//!
+//! ```rust
+//! use do_notation::{m, monad::ResultKind, reader::{ask, run_reader_t, ReaderT}};
+//!
+//! #[derive(Clone)]
+//! struct Config {
+//!   port: u16,
+//! }
+//!
+//! fn validate(port: u16) -> Result<u16, String> {
+//!   if port < 1024 {
+//!     Err(format!("port {} is privileged", port))
+//!   } else {
+//!     Ok(port)
+//!   }
+//! }
+//!
+//! fn port() -> ReaderT<'static, Config, ResultKind<String>, u16> {
+//!   m! {
+//!     config <- ask::<Config, _>();
+//!     lift validate(config.port)
+//!   }
+//! }
+//!
+//! assert_eq!(run_reader_t(port(), Config { port: 8080 }), Ok(8080));
+//! assert!(run_reader_t(port(), Config { port: 80 }).is_err());
+//! ```
+//!
+//! Functions generic over any monad with an environment can be written against the
+//! [`MonadReader`] capability, implemented by the [`Kind`] of every monad stack containing a
+//! [`ReaderT`].
+
+use crate::{
+  monad::{Kind, Monad},
+  monoid::Monoid,
+  state::{run_state_t, StateT, StateTKind},
+  trans::MonadTrans,
+  writer::{run_writer_t, WriterT, WriterTKind},
+  Lift,
+};
+use std::marker::PhantomData;
+
+/// Computations reading an environment of type `R` in the monad `M`, producing a value of type
+/// `A`.
+pub struct ReaderT<'a, R, M: Kind<'a>, A: 'a> {
+  run: Box<dyn FnOnce(R) -> M::Of<A> + 'a>,
+}
+
+impl<'a, R, M, A> ReaderT<'a, R, M, A>
+where
+  R: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  /// Create a computation from a function of the environment.
+  pub fn new(f: impl FnOnce(R) -> M::Of<A> + 'a) -> Self {
+    ReaderT { run: Box::new(f) }
+  }
+
+  /// Bind the value produced by this computation, passing it the same environment.
+  pub fn and_then<B, F>(self, f: F) -> ReaderT<'a, R, M, B>
+  where
+    R: Clone,
+    B: 'a,
+    F: FnOnce(A) -> ReaderT<'a, R, M, B> + 'a,
+  {
+    ReaderT::new(move |r: R| (self.run)(r.clone()).and_then(move |a| (f(a).run)(r)))
+  }
+}
+
+impl<'a, R, M, A> Lift<A> for ReaderT<'a, R, M, A>
+where
+  R: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  fn lift(a: A) -> Self {
+    ReaderT::new(move |_| Lift::lift(a))
+  }
+}
+
+/// [`Kind`] of `ReaderT<'_, R, M, _>`.
+#[derive(Debug)]
+pub struct ReaderTKind<R, M>(PhantomData<(R, M)>);
+
+impl<'a, R, M> Kind<'a> for ReaderTKind<R, M>
+where
+  R: Clone + 'a,
+  M: Kind<'a>,
+{
+  type Of<A: 'a> = ReaderT<'a, R, M, A>;
+}
+
+impl<'a, R, M, A> Monad<'a> for ReaderT<'a, R, M, A>
+where
+  R: Clone + 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  type Item = A;
+  type Kind = ReaderTKind<R, M>;
+
+  fn and_then<B, F>(self, f: F) -> ReaderT<'a, R, M, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> ReaderT<'a, R, M, B> + 'a,
+  {
+    ReaderT::and_then(self, f)
+  }
+}
+
+impl<'a, R, M> MonadTrans<'a, M> for ReaderT<'a, R, M::Kind, M::Item>
+where
+  R: Clone + 'a,
+  M: Monad<'a>,
+{
+  fn lift(m: M) -> Self {
+    ReaderT::new(move |_| m)
+  }
+}
+
+/// Monads with an environment of type `R`.
+///
+/// This trait is implemented by the [`Kind`] of the monads, so that functions can be
+/// written against any monad with an environment:
+///
+/// ```rust
+/// use do_notation::{m, monad::OptionKind, reader::{run_reader_t, MonadReader, ReaderTKind}, Kind};
+///
+/// fn verbose<'a, K: MonadReader<'a, u8>>() -> K::Of<bool> {
+///   K::asks(|level| level > 2)
+/// }
+///
+/// assert_eq!(run_reader_t(verbose::<ReaderTKind<u8, OptionKind>>(), 3), Some(true));
+/// ```
+pub trait MonadReader<'a, R: 'a>: Kind<'a> {
+  /// Retrieve the environment.
+  fn ask() -> Self::Of<R>;
+
+  /// Run a computation in an environment modified by `f`.
+  fn local<A: 'a>(f: impl FnOnce(R) -> R + 'a, m: Self::Of<A>) -> Self::Of<A>;
+
+  /// Retrieve a projection of the environment.
+  fn asks<A: 'a>(f: impl FnOnce(R) -> A + 'a) -> Self::Of<A> {
+    Self::ask().and_then::<A, _>(move |r| Lift::lift(f(r)))
+  }
+}
+
+impl<'a, R, M> MonadReader<'a, R> for ReaderTKind<R, M>
+where
+  R: Clone + 'a,
+  M: Kind<'a>,
+{
+  fn ask() -> ReaderT<'a, R, M, R> {
+    ask()
+  }
+
+  fn local<A: 'a>(f: impl FnOnce(R) -> R + 'a, m: ReaderT<'a, R, M, A>) -> ReaderT<'a, R, M, A> {
+    local(f, m)
+  }
+}
+
+impl<'a, R, S, M> MonadReader<'a, R> for StateTKind<S, M>
+where
+  R: 'a,
+  S: 'a,
+  M: MonadReader<'a, R>,
+{
+  fn ask() -> StateT<'a, S, M, R> {
+    StateT::new(|s| M::ask().and_then::<(R, S), _>(move |r| Lift::lift((r, s))))
+  }
+
+  fn local<A: 'a>(f: impl FnOnce(R) -> R + 'a, m: StateT<'a, S, M, A>) -> StateT<'a, S, M, A> {
+    StateT::new(move |s| M::local(f, run_state_t(m, s)))
+  }
+}
+
+impl<'a, R, W, M> MonadReader<'a, R> for WriterTKind<W, M>
+where
+  R: 'a,
+  W: Monoid + 'a,
+  M: MonadReader<'a, R>,
+{
+  fn ask() -> WriterT<'a, W, M, R> {
+    WriterT::new(M::ask().and_then::<(R, W), _>(|r| Lift::lift((r, W::empty()))))
+  }
+
+  fn local<A: 'a>(f: impl FnOnce(R) -> R + 'a, m: WriterT<'a, W, M, A>) -> WriterT<'a, W, M, A> {
+    WriterT::new(M::local(f, run_writer_t(m)))
+  }
+}
+
+/// Retrieve the environment.
+pub fn ask<'a, R, M>() -> ReaderT<'a, R, M, R>
+where
+  R: 'a,
+  M: Kind<'a>,
+{
+  ReaderT::new(Lift::lift)
+}
+
+/// Run a computation in an environment modified by `f`.
+pub fn local<'a, R, M, A>(
+  f: impl FnOnce(R) -> R + 'a,
+  m: ReaderT<'a, R, M, A>,
+) -> ReaderT<'a, R, M, A>
+where
+  R: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  ReaderT::new(move |r| (m.run)(f(r)))
+}
+
+/// Run a computation in the environment `r`.
+pub fn run_reader_t<'a, R, M, A>(m: ReaderT<'a, R, M, A>, r: R) -> M::Of<A>
+where
+  M: Kind<'a>,
+{
+  (m.run)(r)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    monad::{OptionKind, ResultKind},
+    state::put,
+  };
+
+  #[test]
+  fn ask_env() {
+    let r = m! {
+      x <- ask::<i32, OptionKind>();
+      y <- lift Some(10);
+      return x * y;
+    };
+
+    assert_eq!(run_reader_t(r, 3), Some(30));
+  }
+
+  #[test]
+  fn short_circuit() {
+    let r = m! {
+      x <- ask::<i32, ResultKind<&str>>();
+      _ <- lift if x > 0 { Ok(()) } else { Err("negative") };
+      return x;
+    };
+
+    assert_eq!(run_reader_t(r, -1), Err("negative"));
+  }
+
+  fn depth<'a, K: MonadReader<'a, u32>>() -> K::Of<(u32, u32)> {
+    m! {
+      x <- K::ask();
+      y <- K::local(|d| d + 1, K::ask());
+      return (x, y);
+    }
+  }
+
+  #[test]
+  fn monad_reader() {
+    assert_eq!(
+      run_reader_t(depth::<ReaderTKind<u32, OptionKind>>(), 0),
+      Some((0, 1))
+    );
+
+    let r = m! {
+      d <- depth::<StateTKind<u32, ReaderTKind<u32, OptionKind>>>();
+      _ <- put(d.1);
+      return d.0;
+    };
+
+    assert_eq!(run_reader_t(run_state_t(r, 0), 10), Some((10, 11)));
+  }
+
+  #[test]
+  fn local_env() {
+    let r = m! {
+      x <- ask::<i32, OptionKind>();
+      y <- local(|r| r + 1, ask());
+      z <- ask();
+      return [x, y, z];
+    };
+
+    assert_eq!(run_reader_t(r, 1), Some([1, 2, 1]));
+  }
+}
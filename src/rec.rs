@@ -0,0 +1,137 @@
+//! Stack-safe monadic recursion.
+//!
+//! A loop written with `and_then` — a function binding a computation to a call to itself — nests a closure per
+//! iteration, so long loops overflow the stack. [`MonadRec::tail_rec_m`] runs such a loop without growing the
+//! stack: the step function produces either [`Either::Left`] to loop again with a new value, or [`Either::Right`]
+//! to stop with the result.
+//!
+//! ```rust
+//! use do_notation::{either::Either, monad::OptionKind, rec::MonadRec};
+//!
+//! // the sum of 1..=n, failing on overflow
+//! let sum = |n: u32| {
+//!   OptionKind::tail_rec_m((n, 0u32), |(n, acc)| match n {
+//!     0 => Some(Either::Right(acc)),
+//!     _ => acc.checked_add(n).map(|acc| Either::Left((n - 1, acc))),
+//!   })
+//! };
+//!
+//! assert_eq!(sum(1_000_000), None);
+//! assert_eq!(sum(10_000), Some(50_005_000));
+//! ```
+
+use crate::{
+  either::{Either, EitherKind},
+  identity::{Identity, IdentityKind},
+  monad::{Kind, OptionKind, ResultKind},
+};
+
+/// Monads supporting stack-safe tail recursion.
+///
+/// Implementors must ensure that `M::tail_rec_m(a, f)` is equivalent to binding `f(a)` to `M::tail_rec_m(a, f)`
+/// as long as it produces [`Either::Left`], and to [`Lift::lift`](crate::Lift::lift) once it produces
+/// [`Either::Right`], while using a constant amount of stack.
+pub trait MonadRec<'a>: Kind<'a> {
+  /// Apply the step `f`, starting with `a`, until it produces [`Either::Right`].
+  fn tail_rec_m<A: 'a, B: 'a>(a: A, f: impl FnMut(A) -> Self::Of<Either<A, B>> + 'a)
+    -> Self::Of<B>;
+}
+
+impl<'a> MonadRec<'a> for OptionKind {
+  fn tail_rec_m<A: 'a, B: 'a>(
+    mut a: A,
+    mut f: impl FnMut(A) -> Option<Either<A, B>> + 'a,
+  ) -> Option<B> {
+    loop {
+      match f(a)? {
+        Either::Left(next) => a = next,
+        Either::Right(b) => return Some(b),
+      }
+    }
+  }
+}
+
+impl<'a, E: 'a> MonadRec<'a> for ResultKind<E> {
+  fn tail_rec_m<A: 'a, B: 'a>(
+    mut a: A,
+    mut f: impl FnMut(A) -> Result<Either<A, B>, E> + 'a,
+  ) -> Result<B, E> {
+    loop {
+      match f(a)? {
+        Either::Left(next) => a = next,
+        Either::Right(b) => return Ok(b),
+      }
+    }
+  }
+}
+
+impl<'a> MonadRec<'a> for IdentityKind {
+  fn tail_rec_m<A: 'a, B: 'a>(
+    mut a: A,
+    mut f: impl FnMut(A) -> Identity<Either<A, B>> + 'a,
+  ) -> Identity<B> {
+    loop {
+      match f(a).run() {
+        Either::Left(next) => a = next,
+        Either::Right(b) => return Identity(b),
+      }
+    }
+  }
+}
+
+impl<'a, L: 'a> MonadRec<'a> for EitherKind<L> {
+  fn tail_rec_m<A: 'a, B: 'a>(
+    mut a: A,
+    mut f: impl FnMut(A) -> Either<L, Either<A, B>> + 'a,
+  ) -> Either<L, B> {
+    loop {
+      match f(a) {
+        Either::Left(l) => return Either::Left(l),
+        Either::Right(Either::Left(next)) => a = next,
+        Either::Right(Either::Right(b)) => return Either::Right(b),
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    state::{modify, run_state, StateTKind},
+    Lift,
+  };
+
+  #[test]
+  fn tail_rec_m() {
+    let r = ResultKind::<&str>::tail_rec_m(0, |n| {
+      if n < 1_000_000 {
+        Ok(Either::Left(n + 1))
+      } else {
+        Ok(Either::Right(n))
+      }
+    });
+    assert_eq!(r, Ok(1_000_000));
+
+    let r = IdentityKind::tail_rec_m(3, |n: i32| {
+      Identity(if n > 0 {
+        Either::Left(n - 1)
+      } else {
+        Either::Right("done")
+      })
+    });
+    assert_eq!(r.run(), "done");
+
+    type S = StateTKind<u64, IdentityKind>;
+    let r = S::tail_rec_m(0u64, |n| {
+      modify(move |s: u64| s + n).and_then(move |()| {
+        Lift::lift(if n < 1_000_000 {
+          Either::Left(n + 1)
+        } else {
+          Either::Right(n)
+        })
+      })
+    });
+    assert_eq!(run_state(r, 0), (1_000_000, 500_000_500_000));
+  }
+}
@@ -0,0 +1,495 @@
+//! The fallible monad transformer.
+//!
+//! [`ResultT<E, M, A>`] adds failure with errors of type `E` to the monad `M`. It’s the
+//! transformer version of `Result<_, E>`.
+//!
+//! Errors are thrown with [`throw_error`] and recovered with [`catch_error`]. In [`m!`](crate::m),
+//! the `catch <expr> => <handler>` statement recovers from errors in any monad implementing
+//! [`MonadError`]:
+//!
+//! ```rust
+//! use do_notation::m;
+//!
+//! fn parse(s: &str) -> Result<i32, String> {
+//!   s.parse().map_err(|_| format!("cannot parse {}", s))
+//! }
+//!
+//! let r: Result<i32, String> = m! {
+//!   x <- parse("1");
+//!   y <- catch parse("nope") => |_| Ok(0);
+//!   return x + y;
+//! };
+//!
+//! assert_eq!(r, Ok(1));
+//! ```
+//!
+//! [`ResultExt`] provides a few adjustments of [`Result`]s — inspecting, annotating or recovering from their errors,
+//! and checking their values — to use on the right-hand side of binds.
+
+use crate::{
+  monad::{Kind, Monad, ResultKind},
+  monoid::Monoid,
+  reader::{run_reader_t, MonadReader, ReaderT, ReaderTKind},
+  state::{run_state_t, MonadState, StateT, StateTKind},
+  trans::MonadTrans,
+  writer::{run_writer_t, MonadWriter, WriterT, WriterTKind},
+  Lift,
+};
+use std::{fmt, marker::PhantomData};
+
+/// Computations failing with errors of type `E` in the monad `M`, producing a value of type `A`.
+pub struct ResultT<'a, E: 'a, M: Kind<'a>, A: 'a> {
+  run: M::Of<Result<A, E>>,
+}
+
+impl<'a, E, M, A> ResultT<'a, E, M, A>
+where
+  E: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  /// Create a computation from a result in the inner monad.
+  pub fn new(m: M::Of<Result<A, E>>) -> Self {
+    ResultT { run: m }
+  }
+
+  /// Bind the value produced by this computation, if it didn’t fail.
+  pub fn and_then<B, F>(self, f: F) -> ResultT<'a, E, M, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> ResultT<'a, E, M, B> + 'a,
+  {
+    ResultT::new(self.run.and_then(move |r| match r {
+      Ok(a) => f(a).run,
+      Err(e) => Lift::lift(Err(e)),
+    }))
+  }
+}
+
+impl<'a, E, M, A> Lift<A> for ResultT<'a, E, M, A>
+where
+  E: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  fn lift(a: A) -> Self {
+    ResultT::new(Lift::lift(Ok(a)))
+  }
+}
+
+/// [`Kind`] of `ResultT<'_, E, M, _>`.
+#[derive(Debug)]
+pub struct ResultTKind<E, M>(PhantomData<(E, M)>);
+
+impl<'a, E, M> Kind<'a> for ResultTKind<E, M>
+where
+  E: 'a,
+  M: Kind<'a>,
+{
+  type Of<A: 'a> = ResultT<'a, E, M, A>;
+}
+
+impl<'a, E, M, A> Monad<'a> for ResultT<'a, E, M, A>
+where
+  E: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  type Item = A;
+  type Kind = ResultTKind<E, M>;
+
+  fn and_then<B, F>(self, f: F) -> ResultT<'a, E, M, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> ResultT<'a, E, M, B> + 'a,
+  {
+    ResultT::and_then(self, f)
+  }
+}
+
+impl<'a, E, M> MonadTrans<'a, M> for ResultT<'a, E, M::Kind, M::Item>
+where
+  E: 'a,
+  M: Monad<'a>,
+{
+  fn lift(m: M) -> Self {
+    ResultT::new(m.and_then::<Result<M::Item, E>, _>(|a| Lift::lift(Ok(a))))
+  }
+}
+
+/// Monads failing with errors of type `E`.
+///
+/// This trait is implemented by the [`Kind`] of the monads, so that functions can be
+/// written against any fallible monad.
+pub trait MonadError<'a, E: 'a>: Kind<'a> {
+  /// Fail with the error `e`.
+  fn throw_error<A: 'a>(e: E) -> Self::Of<A>;
+
+  /// Run `m`, recovering from its error with `h`.
+  fn catch_error<A: 'a>(m: Self::Of<A>, h: impl FnOnce(E) -> Self::Of<A> + 'a) -> Self::Of<A>;
+}
+
+impl<'a, E: 'a> MonadError<'a, E> for ResultKind<E> {
+  fn throw_error<A: 'a>(e: E) -> Result<A, E> {
+    Err(e)
+  }
+
+  fn catch_error<A: 'a>(m: Result<A, E>, h: impl FnOnce(E) -> Result<A, E> + 'a) -> Result<A, E> {
+    m.or_else(h)
+  }
+}
+
+impl<'a, E, M> MonadError<'a, E> for ResultTKind<E, M>
+where
+  E: 'a,
+  M: Kind<'a>,
+{
+  fn throw_error<A: 'a>(e: E) -> ResultT<'a, E, M, A> {
+    ResultT::new(Lift::lift(Err(e)))
+  }
+
+  fn catch_error<A: 'a>(
+    m: ResultT<'a, E, M, A>,
+    h: impl FnOnce(E) -> ResultT<'a, E, M, A> + 'a,
+  ) -> ResultT<'a, E, M, A> {
+    ResultT::new(m.run.and_then(move |r| match r {
+      Ok(a) => Lift::lift(Ok(a)),
+      Err(e) => h(e).run,
+    }))
+  }
+}
+
+impl<'a, R, E, M> MonadError<'a, E> for ReaderTKind<R, M>
+where
+  R: Clone + 'a,
+  E: 'a,
+  M: MonadError<'a, E>,
+{
+  fn throw_error<A: 'a>(e: E) -> ReaderT<'a, R, M, A> {
+    ReaderT::new(move |_| M::throw_error(e))
+  }
+
+  fn catch_error<A: 'a>(
+    m: ReaderT<'a, R, M, A>,
+    h: impl FnOnce(E) -> ReaderT<'a, R, M, A> + 'a,
+  ) -> ReaderT<'a, R, M, A> {
+    ReaderT::new(move |r: R| {
+      let r_ = r.clone();
+      M::catch_error(run_reader_t(m, r), move |e| run_reader_t(h(e), r_))
+    })
+  }
+}
+
+/// Errors caught in a [`StateT`] are recovered from with the state as it was before running the
+/// failing computation.
+impl<'a, S, E, M> MonadError<'a, E> for StateTKind<S, M>
+where
+  S: Clone + 'a,
+  E: 'a,
+  M: MonadError<'a, E>,
+{
+  fn throw_error<A: 'a>(e: E) -> StateT<'a, S, M, A> {
+    StateT::new(move |_| M::throw_error(e))
+  }
+
+  fn catch_error<A: 'a>(
+    m: StateT<'a, S, M, A>,
+    h: impl FnOnce(E) -> StateT<'a, S, M, A> + 'a,
+  ) -> StateT<'a, S, M, A> {
+    StateT::new(move |s: S| {
+      let s_ = s.clone();
+      M::catch_error(run_state_t(m, s), move |e| run_state_t(h(e), s_))
+    })
+  }
+}
+
+impl<'a, W, E, M> MonadError<'a, E> for WriterTKind<W, M>
+where
+  W: Monoid + 'a,
+  E: 'a,
+  M: MonadError<'a, E>,
+{
+  fn throw_error<A: 'a>(e: E) -> WriterT<'a, W, M, A> {
+    WriterT::new(M::throw_error(e))
+  }
+
+  fn catch_error<A: 'a>(
+    m: WriterT<'a, W, M, A>,
+    h: impl FnOnce(E) -> WriterT<'a, W, M, A> + 'a,
+  ) -> WriterT<'a, W, M, A> {
+    WriterT::new(M::catch_error(run_writer_t(m), move |e| run_writer_t(h(e))))
+  }
+}
+
+impl<'a, E, R, M> MonadReader<'a, R> for ResultTKind<E, M>
+where
+  E: 'a,
+  R: 'a,
+  M: MonadReader<'a, R>,
+{
+  fn ask() -> ResultT<'a, E, M, R> {
+    MonadTrans::lift(M::ask())
+  }
+
+  fn local<A: 'a>(f: impl FnOnce(R) -> R + 'a, m: ResultT<'a, E, M, A>) -> ResultT<'a, E, M, A> {
+    ResultT::new(M::local(f, m.run))
+  }
+}
+
+impl<'a, E, S, M> MonadState<'a, S> for ResultTKind<E, M>
+where
+  E: 'a,
+  S: 'a,
+  M: MonadState<'a, S>,
+{
+  fn state<A: 'a>(f: impl FnOnce(S) -> (A, S) + 'a) -> ResultT<'a, E, M, A> {
+    MonadTrans::lift(M::state(f))
+  }
+}
+
+impl<'a, E, W, M> MonadWriter<'a, W> for ResultTKind<E, M>
+where
+  E: 'a,
+  W: 'a,
+  M: MonadWriter<'a, W>,
+{
+  fn tell(w: W) -> ResultT<'a, E, M, ()> {
+    MonadTrans::lift(M::tell(w))
+  }
+
+  /// The output of a failing computation is lost, as is its error.
+  fn listen<A: 'a>(m: ResultT<'a, E, M, A>) -> ResultT<'a, E, M, (A, W)> {
+    ResultT::new(
+      M::listen(m.run).and_then::<Result<(A, W), E>, _>(|(r, w)| Lift::lift(r.map(|a| (a, w)))),
+    )
+  }
+
+  fn pass<A: 'a, F: FnOnce(W) -> W + 'a>(m: ResultT<'a, E, M, (A, F)>) -> ResultT<'a, E, M, A> {
+    type Censor<'a, W> = Box<dyn FnOnce(W) -> W + 'a>;
+
+    ResultT::new(M::pass(m.run.and_then::<(Result<A, E>, Censor<'a, W>), _>(
+      |r| match r {
+        Ok((a, f)) => Lift::lift((Ok(a), Box::new(f) as Censor<'a, W>)),
+        Err(e) => Lift::lift((Err(e), Box::new(|w| w) as Censor<'a, W>)),
+      },
+    )))
+  }
+}
+
+/// Run `m`, recovering from its error with `h`, and bind to `f`.
+///
+/// This is what the `x <- catch m => h;` statement desugars to in [`m!`](crate::m). The monad
+/// stack doesn’t have to be known before `f` is.
+pub fn catch_and_then<'a, E, N, A, H, F>(m: <N::Kind as Kind<'a>>::Of<A>, h: H, f: F) -> N
+where
+  E: 'a,
+  N: Monad<'a>,
+  N::Kind: MonadError<'a, E>,
+  A: 'a,
+  H: FnOnce(E) -> <N::Kind as Kind<'a>>::Of<A> + 'a,
+  F: FnOnce(A) -> N + 'a,
+{
+  <N::Kind as MonadError<'a, E>>::catch_error::<A>(m, h).and_then::<N::Item, F>(f)
+}
+
+/// Fail with the error `e`.
+pub fn throw_error<'a, E, M, A>(e: E) -> ResultT<'a, E, M, A>
+where
+  E: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  ResultTKind::throw_error(e)
+}
+
+/// Run `m`, recovering from its error with `h`.
+pub fn catch_error<'a, E, M, A>(
+  m: ResultT<'a, E, M, A>,
+  h: impl FnOnce(E) -> ResultT<'a, E, M, A> + 'a,
+) -> ResultT<'a, E, M, A>
+where
+  E: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  ResultTKind::catch_error(m, h)
+}
+
+/// Run a computation, returning its result in the inner monad.
+pub fn run_result_t<'a, E, M, A>(m: ResultT<'a, E, M, A>) -> M::Of<Result<A, E>>
+where
+  M: Kind<'a>,
+{
+  m.run
+}
+
+/// Extension methods of [`Result`].
+///
+/// They adjust the right-hand side of a bind without leaving the [`m!`](crate::m) block:
+///
+/// ```rust
+/// use do_notation::{m, result::ResultExt};
+///
+/// let mut failures = Vec::new();
+///
+/// let mut port = |s: &str| -> Result<u16, String> {
+///   m! {
+///     port <- s.parse::<u16>().tap_err(|e| failures.push(e.to_string())).annotate("invalid port");
+///     port <- Ok(port).require(|p| *p != 0, |_| "reserved port".to_owned());
+///     return port;
+///   }
+/// };
+///
+/// assert_eq!(port("8080"), Ok(8080));
+/// assert_eq!(port("0"), Err("reserved port".to_owned()));
+/// assert_eq!(port("http"), Err("invalid port: invalid digit found in string".to_owned()));
+/// assert_eq!(failures, vec!["invalid digit found in string"]);
+/// ```
+pub trait ResultExt<T, E>: Sized {
+  /// Call `f` with the error, if any.
+  fn tap_err(self, f: impl FnOnce(&E)) -> Result<T, E>;
+
+  /// Prefix the error with `msg`, as in `<msg>: <error>`.
+  fn annotate(self, msg: impl fmt::Display) -> Result<T, String>
+  where
+    E: fmt::Display;
+
+  /// Recover from the errors for which `f` produces a value, keeping the other ones.
+  ///
+  /// ```rust
+  /// use do_notation::result::ResultExt;
+  /// use std::io::{Error, ErrorKind};
+  ///
+  /// let or_empty = |e: &Error| if e.kind() == ErrorKind::NotFound { Some(String::new()) } else { None };
+  ///
+  /// let r = Err(Error::from(ErrorKind::NotFound)).recover_with(or_empty);
+  /// assert_eq!(r.unwrap(), "");
+  ///
+  /// let r = Err(Error::from(ErrorKind::PermissionDenied)).recover_with(or_empty);
+  /// assert_eq!(r.unwrap_err().kind(), ErrorKind::PermissionDenied);
+  /// ```
+  fn recover_with(self, f: impl FnOnce(&E) -> Option<T>) -> Result<T, E>;
+
+  /// Fail with `err(&value)` if the value doesn’t satisfy `pred`.
+  fn require(self, pred: impl FnOnce(&T) -> bool, err: impl FnOnce(&T) -> E) -> Result<T, E>;
+}
+
+impl<T, E> ResultExt<T, E> for Result<T, E> {
+  fn tap_err(self, f: impl FnOnce(&E)) -> Result<T, E> {
+    if let Err(e) = &self {
+      f(e);
+    }
+
+    self
+  }
+
+  fn annotate(self, msg: impl fmt::Display) -> Result<T, String>
+  where
+    E: fmt::Display,
+  {
+    self.map_err(|e| format!("{}: {}", msg, e))
+  }
+
+  fn recover_with(self, f: impl FnOnce(&E) -> Option<T>) -> Result<T, E> {
+    match self {
+      Ok(t) => Ok(t),
+      Err(e) => f(&e).ok_or(e),
+    }
+  }
+
+  fn require(self, pred: impl FnOnce(&T) -> bool, err: impl FnOnce(&T) -> E) -> Result<T, E> {
+    match self {
+      Ok(t) if !pred(&t) => Err(err(&t)),
+      r => r,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    identity::IdentityKind,
+    reader::{ask, run_reader_t},
+    state::{get, put, run_state, run_state_t},
+  };
+
+  #[test]
+  fn result_ext() {
+    let mut seen = Vec::new();
+
+    let r: Result<i32, String> = m! {
+      y <- Err("nope").tap_err(|e| seen.push(*e)).recover_with(|e| if e.is_empty() { None } else { Some(2) }).annotate("y");
+      x <- Ok(1).tap_err(|_| unreachable!()).require(|x| *x > 0, |_| "negative").annotate("x");
+      z <- Err::<i32, _>("nope").recover_with(|_| None).annotate(format!("z, with x = {}", x));
+      return x + y + z;
+    };
+
+    assert_eq!(r, Err("z, with x = 1: nope".to_owned()));
+    assert_eq!(seen, vec!["nope"]);
+    assert_eq!(
+      Ok(-1).require(|x| *x > 0, |x| format!("{} isn’t positive", x)),
+      Err("-1 isn’t positive".to_owned())
+    );
+    assert_eq!(Err::<i32, _>(3).require(|_| false, |_| 4), Err(3));
+  }
+
+  #[test]
+  fn catch() {
+    let r: Result<i32, &str> = m! {
+      x <- catch Err("nope") => |_| Ok(1);
+      _ <- catch Ok(()) => |_| Err("unreachable");
+      catch Err("nope") => |e| if e == "nope" { Ok(()) } else { Err(e) };
+      return x;
+    };
+
+    assert_eq!(r, Ok(1));
+
+    let r: Result<i32, &str> = m! {
+      _ <- Ok(());
+      catch Err("nope") => Err
+    };
+
+    assert_eq!(r, Err("nope"));
+  }
+
+  #[test]
+  fn result_t() {
+    type Stack = StateTKind<i32, IdentityKind>;
+    let r: ResultT<&str, Stack, i32> = m! {
+      _ <- lift put(1);
+      _ <- throw_error::<_, _, ()>("nope");
+      _ <- lift put(2);
+      return 3;
+    };
+
+    assert_eq!(run_state(run_result_t(r), 0), (Err("nope"), 1));
+  }
+
+  #[test]
+  fn state_rollback() {
+    type Stack = StateTKind<i32, ResultKind<&'static str>>;
+    let r: StateT<i32, ResultKind<&str>, i32> = m! {
+      _ <- put(1);
+      x <- catch m! { _ <- put(2); <Stack as MonadError<_>>::throw_error("nope") } => |_| get();
+      return x;
+    };
+
+    assert_eq!(run_state_t(r, 0), Ok((1, 1)));
+  }
+
+  #[test]
+  fn monad_error_stack() {
+    type Stack = ReaderTKind<i32, WriterTKind<Vec<i32>, ResultTKind<String, IdentityKind>>>;
+    let r: ReaderT<i32, WriterTKind<Vec<i32>, ResultTKind<String, IdentityKind>>, i32> = m! {
+      x <- ask();
+      tell vec![x];
+      catch <Stack as MonadError<_>>::throw_error(format!("{}", x)) => |e: String| Lift::lift(e.len() as i32)
+    };
+
+    assert_eq!(
+      run_result_t(run_writer_t(run_reader_t(r, 42))).run(),
+      Ok((2, vec![42]))
+    );
+  }
+}
@@ -0,0 +1,239 @@
+//! Retrying failing computations.
+//!
+//! A [`RetryPolicy`] decides whether a failed computation is retried, and how long to wait before retrying it:
+//! the number of retries is bounded, the delays follow a [`Backoff`], and only the errors matching a predicate are
+//! retried. [`retry_m`] retries computations of any monad with errors — see [`MonadError`] — waiting by blocking the
+//! thread, and [`retry_async`] retries futures producing a [`Result`], waiting with the [`Timer`] of the asynchronous
+//! runtime.
+//!
+//! ```rust
+//! use do_notation::{monad::ResultKind, retry::{retry_m, RetryPolicy}};
+//! use std::cell::Cell;
+//!
+//! let calls = Cell::new(0);
+//! let flaky = || {
+//!   calls.set(calls.get() + 1);
+//!   if calls.get() < 3 { Err("busy") } else { Ok(calls.get()) }
+//! };
+//!
+//! assert_eq!(retry_m::<ResultKind<_>, _, _>(RetryPolicy::retries(5), flaky), Ok(3));
+//!
+//! calls.set(0);
+//! assert_eq!(retry_m::<ResultKind<_>, _, _>(RetryPolicy::retries(1), flaky), Err("busy"));
+//! ```
+
+use crate::{result::MonadError, runtime::Timer};
+use std::{convert::TryFrom, future::Future, thread, time::Duration};
+
+/// Delays between the attempts of a retried computation.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Backoff {
+  /// Retry immediately.
+  None,
+
+  /// Wait the same duration before every retry.
+  Fixed(Duration),
+
+  /// Wait `initial` before the first retry, multiplying the delay by `factor` before every further retry, up to
+  /// `max`.
+  Exponential {
+    initial: Duration,
+    factor: u32,
+    max: Duration,
+  },
+}
+
+impl Backoff {
+  /// Delay before the retry number `retry`, starting at `0`.
+  pub fn delay(&self, retry: usize) -> Duration {
+    match *self {
+      Backoff::None => Duration::ZERO,
+      Backoff::Fixed(delay) => delay,
+      Backoff::Exponential {
+        initial,
+        factor,
+        max,
+      } => {
+        let factor = u32::try_from(retry)
+          .ok()
+          .and_then(|retry| factor.checked_pow(retry));
+        factor
+          .and_then(|factor| initial.checked_mul(factor))
+          .map_or(max, |delay| delay.min(max))
+      }
+    }
+  }
+}
+
+/// Policies deciding whether and when computations failing with errors of type `E` are retried.
+pub struct RetryPolicy<'a, E> {
+  retries: usize,
+  backoff: Backoff,
+  retry_on: Box<dyn Fn(&E) -> bool + 'a>,
+}
+
+impl<'a, E> RetryPolicy<'a, E> {
+  /// Retry failing computations up to `retries` times, immediately, whatever the error.
+  pub fn retries(retries: usize) -> Self {
+    RetryPolicy {
+      retries,
+      backoff: Backoff::None,
+      retry_on: Box::new(|_| true),
+    }
+  }
+
+  /// Wait according to `backoff` before retrying.
+  pub fn backoff(self, backoff: Backoff) -> Self {
+    RetryPolicy { backoff, ..self }
+  }
+
+  /// Only retry the computations failing with errors for which `f` holds.
+  pub fn retry_on(self, f: impl Fn(&E) -> bool + 'a) -> Self {
+    RetryPolicy {
+      retry_on: Box::new(f),
+      ..self
+    }
+  }
+
+  /// Delay to wait before the retry number `retry`, starting at `0`, after failing with `e`.
+  ///
+  /// Returns [`None`] if the computation must not be retried.
+  pub fn next_delay(&self, retry: usize, e: &E) -> Option<Duration> {
+    if retry < self.retries && (self.retry_on)(e) {
+      Some(self.backoff.delay(retry))
+    } else {
+      None
+    }
+  }
+}
+
+/// Run the computation built by `m`, building and running it again when it fails, as decided by `policy`.
+///
+/// Delays are waited by blocking the current thread. As computations are consumed when they run, `m` builds a new
+/// one for every attempt.
+pub fn retry_m<'a, K, E, A>(
+  policy: RetryPolicy<'a, E>,
+  m: impl FnMut() -> K::Of<A> + 'a,
+) -> K::Of<A>
+where
+  K: MonadError<'a, E>,
+  E: 'a,
+  A: 'a,
+{
+  fn go<'a, K, E, A, F>(policy: RetryPolicy<'a, E>, mut m: F, retry: usize) -> K::Of<A>
+  where
+    K: MonadError<'a, E>,
+    E: 'a,
+    A: 'a,
+    F: FnMut() -> K::Of<A> + 'a,
+  {
+    K::catch_error(m(), move |e| match policy.next_delay(retry, &e) {
+      Some(delay) => {
+        if !delay.is_zero() {
+          thread::sleep(delay);
+        }
+
+        go::<K, E, A, F>(policy, m, retry + 1)
+      }
+      None => K::throw_error(e),
+    })
+  }
+
+  go::<K, E, A, _>(policy, m, 0)
+}
+
+/// Await the future built by `f`, building and awaiting it again when it fails, as decided by `policy`.
+///
+/// Delays are waited with `timer`, so that any asynchronous runtime can be used.
+pub async fn retry_async<'a, E, A, F>(
+  policy: RetryPolicy<'a, E>,
+  mut f: impl FnMut() -> F,
+  mut timer: impl Timer,
+) -> Result<A, E>
+where
+  F: Future<Output = Result<A, E>>,
+{
+  let mut retry = 0;
+
+  loop {
+    match f().await {
+      Ok(a) => return Ok(a),
+      Err(e) => match policy.next_delay(retry, &e) {
+        Some(delay) => {
+          timer.sleep(delay).await;
+          retry += 1;
+        }
+        None => return Err(e),
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    future::tests::block_on,
+    identity::IdentityKind,
+    result::{run_result_t, throw_error, ResultT, ResultTKind},
+    state::{get, put, run_state, StateTKind},
+    Lift,
+  };
+  use std::{cell::RefCell, future::ready};
+
+  #[test]
+  fn backoff() {
+    let ms = Duration::from_millis;
+    let exp = Backoff::Exponential {
+      initial: ms(10),
+      factor: 3,
+      max: ms(100),
+    };
+
+    assert_eq!(Backoff::Fixed(ms(5)).delay(3), ms(5));
+    assert_eq!(
+      (0..4).map(|retry| exp.delay(retry)).collect::<Vec<_>>(),
+      vec![ms(10), ms(30), ms(90), ms(100)]
+    );
+    assert_eq!(exp.delay(usize::MAX), ms(100));
+  }
+
+  #[test]
+  fn retry_m() {
+    type M = ResultTKind<&'static str, StateTKind<u32, IdentityKind>>;
+
+    // every attempt is counted in the state, which survives the failures
+    let attempt = || -> ResultT<&str, StateTKind<u32, IdentityKind>, u32> {
+      m! {
+        n <- lift get::<u32, IdentityKind>();
+        lift put(n + 1);
+        if n < 2 { throw_error("busy") } else if n < 4 { throw_error("down") } else { Lift::lift(n) }
+      }
+    };
+
+    let policy = RetryPolicy::retries(10).retry_on(|e: &&str| *e == "busy");
+    let r = super::retry_m::<M, _, _>(policy, attempt);
+    assert_eq!(run_state(run_result_t(r), 0), (Err("down"), 3));
+
+    let r = super::retry_m::<M, _, _>(RetryPolicy::retries(10), attempt);
+    assert_eq!(run_state(run_result_t(r), 0), (Ok(4), 5));
+  }
+
+  #[test]
+  fn retry_async() {
+    let sleeps = RefCell::new(Vec::new());
+    let policy = RetryPolicy::retries(3).backoff(Backoff::Fixed(Duration::from_secs(1)));
+
+    let r = super::retry_async(
+      policy,
+      || ready(Err::<(), _>("down")),
+      |delay| {
+        sleeps.borrow_mut().push(delay);
+        ready(())
+      },
+    );
+
+    assert_eq!(block_on(r), Err("down"));
+    assert_eq!(*sleeps.borrow(), vec![Duration::from_secs(1); 3]);
+  }
+}
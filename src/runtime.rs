@@ -0,0 +1,334 @@
+//! Asynchronous runtimes.
+//!
+//! The asynchronous combinators don’t depend on any runtime: they spawn tasks with a [`Spawner`] and wait with a
+//! [`Timer`]. Closures building a sleeping future from a [`Duration`] are timers, and adapters for the common runtimes
+//! are provided behind features:
+//!
+//! - `Tokio`, with the `tokio` feature.
+//! - `AsyncStd`, with the `async-std` feature.
+//! - `Smol`, with the `smol` feature.
+//!
+//! ```rust
+//! use do_notation::runtime::timeout;
+//! use std::{future::{pending, ready}, time::Duration};
+//! #
+//! # fn block_on<F: std::future::Future>(f: F) -> F::Output {
+//! #   use std::task::{Context, Poll, Waker};
+//! #   let mut f = Box::pin(f);
+//! #   let mut cx = Context::from_waker(Waker::noop());
+//! #   loop {
+//! #     if let Poll::Ready(a) = f.as_mut().poll(&mut cx) {
+//! #       return a;
+//! #     }
+//! #   }
+//! # }
+//!
+//! // a timer whose delays are elapsed immediately
+//! let timer = |_| ready(());
+//!
+//! assert_eq!(block_on(timeout(timer, Duration::from_secs(1), ready(1))), Some(1));
+//! assert_eq!(block_on(timeout(timer, Duration::from_secs(1), pending::<i32>())), None);
+//! ```
+
+use std::{
+  future::{poll_fn, Future},
+  pin::pin,
+  task::Poll,
+  time::Duration,
+};
+
+/// Runtimes able to spawn tasks.
+pub trait Spawner {
+  /// Handle of a spawned task producing a value of type `A`, awaiting the task.
+  type Handle<A: Send + 'static>: Future<Output = A>;
+
+  /// Spawn a future in a new task.
+  fn spawn<A, F>(&self, f: F) -> Self::Handle<A>
+  where
+    A: Send + 'static,
+    F: Future<Output = A> + Send + 'static;
+}
+
+/// Runtimes able to wait.
+pub trait Timer {
+  /// Wait for `delay`.
+  fn sleep(&mut self, delay: Duration) -> impl Future<Output = ()>;
+}
+
+impl<F, S> Timer for F
+where
+  F: FnMut(Duration) -> S,
+  S: Future<Output = ()>,
+{
+  fn sleep(&mut self, delay: Duration) -> impl Future<Output = ()> {
+    self(delay)
+  }
+}
+
+/// Await `f` for at most `delay`, waited with `timer`.
+///
+/// Returns [`None`] if `f` didn’t complete in time.
+pub async fn timeout<A>(
+  mut timer: impl Timer,
+  delay: Duration,
+  f: impl Future<Output = A>,
+) -> Option<A> {
+  let mut f = pin!(f);
+  let mut sleep = pin!(timer.sleep(delay));
+
+  poll_fn(|cx| {
+    if let Poll::Ready(a) = f.as_mut().poll(cx) {
+      Poll::Ready(Some(a))
+    } else if sleep.as_mut().poll(cx).is_ready() {
+      Poll::Ready(None)
+    } else {
+      Poll::Pending
+    }
+  })
+  .await
+}
+
+/// Apply `f` to every element of `iter`, running the futures concurrently in tasks spawned with `spawner`, and
+/// collect their values in order.
+pub async fn traverse_concurrent<S, I, A, F>(
+  spawner: &S,
+  iter: I,
+  mut f: impl FnMut(I::Item) -> F,
+) -> Vec<A>
+where
+  S: Spawner,
+  I: IntoIterator,
+  A: Send + 'static,
+  F: Future<Output = A> + Send + 'static,
+{
+  let handles = iter
+    .into_iter()
+    .map(|a| spawner.spawn(f(a)))
+    .collect::<Vec<_>>();
+  let mut values = Vec::with_capacity(handles.len());
+
+  for handle in handles {
+    values.push(handle.await);
+  }
+
+  values
+}
+
+/// The [tokio](tokio) runtime.
+///
+/// Tasks are spawned in the current runtime. If a task panics, awaiting its handle resumes the panic; if it is
+/// cancelled — e.g. as the runtime shuts down — awaiting its handle panics.
+#[cfg(feature = "tokio")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tokio;
+
+#[cfg(feature = "tokio")]
+impl Spawner for Tokio {
+  type Handle<A: Send + 'static> = std::pin::Pin<Box<dyn Future<Output = A> + Send>>;
+
+  fn spawn<A, F>(&self, f: F) -> Self::Handle<A>
+  where
+    A: Send + 'static,
+    F: Future<Output = A> + Send + 'static,
+  {
+    let handle = tokio::spawn(f);
+
+    Box::pin(async move {
+      match handle.await {
+        Ok(a) => a,
+        Err(e) => match e.try_into_panic() {
+          Ok(panic) => std::panic::resume_unwind(panic),
+          Err(e) => panic!("awaiting a cancelled task: {}", e),
+        },
+      }
+    })
+  }
+}
+
+#[cfg(feature = "tokio")]
+impl Timer for Tokio {
+  fn sleep(&mut self, delay: Duration) -> impl Future<Output = ()> {
+    tokio::time::sleep(delay)
+  }
+}
+
+/// The [async-std](async_std) runtime.
+#[cfg(feature = "async-std")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsyncStd;
+
+#[cfg(feature = "async-std")]
+impl Spawner for AsyncStd {
+  type Handle<A: Send + 'static> = async_std::task::JoinHandle<A>;
+
+  fn spawn<A, F>(&self, f: F) -> Self::Handle<A>
+  where
+    A: Send + 'static,
+    F: Future<Output = A> + Send + 'static,
+  {
+    async_std::task::spawn(f)
+  }
+}
+
+#[cfg(feature = "async-std")]
+impl Timer for AsyncStd {
+  fn sleep(&mut self, delay: Duration) -> impl Future<Output = ()> {
+    async_std::task::sleep(delay)
+  }
+}
+
+/// The [smol](smol) runtime.
+///
+/// Tasks are spawned on the global executor of smol.
+#[cfg(feature = "smol")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Smol;
+
+#[cfg(feature = "smol")]
+impl Spawner for Smol {
+  type Handle<A: Send + 'static> = smol::Task<A>;
+
+  fn spawn<A, F>(&self, f: F) -> Self::Handle<A>
+  where
+    A: Send + 'static,
+    F: Future<Output = A> + Send + 'static,
+  {
+    smol::spawn(f)
+  }
+}
+
+#[cfg(feature = "smol")]
+impl Timer for Smol {
+  async fn sleep(&mut self, delay: Duration) {
+    smol::Timer::after(delay).await;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::future::tests::block_on;
+  use std::{cell::Cell, future::ready, time::Duration};
+
+  /// Runs the tasks in place.
+  struct Inline;
+
+  impl Spawner for Inline {
+    type Handle<A: Send + 'static> = ::std::pin::Pin<Box<dyn Future<Output = A>>>;
+
+    fn spawn<A, F>(&self, f: F) -> Self::Handle<A>
+    where
+      A: Send + 'static,
+      F: Future<Output = A> + Send + 'static,
+    {
+      Box::pin(f)
+    }
+  }
+
+  #[test]
+  fn timeout() {
+    let polls = Cell::new(0);
+    let slow = poll_fn(|cx| {
+      polls.set(polls.get() + 1);
+      cx.waker().wake_by_ref();
+      if polls.get() < 3 {
+        Poll::Pending
+      } else {
+        Poll::Ready(())
+      }
+    });
+
+    // the timer elapses after being polled twice
+    let timer = |_| {
+      let mut n = 0;
+      poll_fn(move |cx| {
+        n += 1;
+        cx.waker().wake_by_ref();
+        if n < 2 {
+          Poll::Pending
+        } else {
+          Poll::Ready(())
+        }
+      })
+    };
+
+    let r = block_on(super::timeout(timer, Duration::from_secs(1), slow));
+    assert_eq!(r, None);
+    assert_eq!(polls.get(), 2);
+
+    let r = block_on(super::timeout(timer, Duration::from_secs(1), ready(1)));
+    assert_eq!(r, Some(1));
+  }
+
+  #[test]
+  fn traverse_concurrent() {
+    let r = super::traverse_concurrent(&Inline, 1..4, |x| async move { x * 10 });
+    assert_eq!(block_on(r), vec![10, 20, 30]);
+  }
+
+  #[cfg(feature = "tokio")]
+  #[test]
+  fn tokio() {
+    let rt = tokio::runtime::Builder::new_current_thread()
+      .enable_time()
+      .build()
+      .unwrap();
+
+    let r = rt.block_on(async {
+      let slow = async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        1
+      };
+      let squares = super::traverse_concurrent(&Tokio, 1..4, |x| async move { x * x });
+      (
+        super::timeout(Tokio, Duration::from_millis(1), slow).await,
+        squares.await,
+      )
+    });
+
+    assert_eq!(r, (None, vec![1, 4, 9]));
+
+    let handle = {
+      let _rt = rt.enter();
+      Tokio.spawn(std::future::pending::<()>())
+    };
+    drop(rt);
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+      .build()
+      .unwrap();
+    let cancelled = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| rt.block_on(handle)));
+    let message = cancelled.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.starts_with("awaiting a cancelled task"));
+  }
+
+  #[cfg(feature = "async-std")]
+  #[test]
+  fn async_std() {
+    let r = async_std::task::block_on(async {
+      let slow = async_std::future::pending::<i32>();
+      let squares = super::traverse_concurrent(&AsyncStd, 1..4, |x| async move { x * x });
+      (
+        super::timeout(AsyncStd, Duration::from_millis(1), slow).await,
+        squares.await,
+      )
+    });
+
+    assert_eq!(r, (None, vec![1, 4, 9]));
+  }
+
+  #[cfg(feature = "smol")]
+  #[test]
+  fn smol() {
+    let r = smol::block_on(async {
+      let slow = smol::future::pending::<i32>();
+      let squares = super::traverse_concurrent(&Smol, 1..4, |x| async move { x * x });
+      (
+        super::timeout(Smol, Duration::from_millis(1), slow).await,
+        squares.await,
+      )
+    });
+
+    assert_eq!(r, (None, vec![1, 4, 9]));
+  }
+}
@@ -0,0 +1,208 @@
+//! Sagas: sequences of actions with compensations.
+//!
+//! [`Saga<E, A>`] runs actions which can fail with an error of type `E`. Each [`step`] registers a compensation,
+//! undoing its action; when a later action fails, the compensations of the previous steps run in reverse order, and
+//! the error is returned. Multi-service call sequences are then do-blocks, instead of ad hoc cleanup code at each
+//! failure point:
+//!
+//! ```rust
+//! use do_notation::{m, saga::{action, step, Saga}};
+//! use std::cell::RefCell;
+//!
+//! let log = RefCell::new(Vec::new());
+//! let log = &log;
+//!
+//! let reserve = |item: &'static str| {
+//!   step(
+//!     move || {
+//!       log.borrow_mut().push(format!("reserve {}", item));
+//!       Ok(item)
+//!     },
+//!     move |item| log.borrow_mut().push(format!("release {}", item)),
+//!   )
+//! };
+//!
+//! let order: Saga<String, ()> = m! {
+//!   reserve("flight");
+//!   reserve("hotel");
+//!   action(|| Err("payment declined".to_owned()))
+//! };
+//!
+//! assert_eq!(order.run(), Err("payment declined".to_owned()));
+//! assert_eq!(
+//!   *log.borrow(),
+//!   vec!["reserve flight", "reserve hotel", "release hotel", "release flight"]
+//! );
+//! ```
+//!
+//! Compensations can’t fail: a compensation which can should handle its own errors, e.g. by logging or retrying.
+
+use crate::{
+  monad::{Kind, Monad},
+  Lift,
+};
+use std::marker::PhantomData;
+
+/// Compensations registered so far, in order.
+type Compensations<'a> = Vec<Box<dyn FnOnce() + 'a>>;
+
+/// Actions with compensations, failing with an error of type `E`, and producing a value of type `A`.
+#[allow(clippy::type_complexity)]
+pub struct Saga<'a, E, A>(Box<dyn FnOnce(&mut Compensations<'a>) -> Result<A, E> + 'a>);
+
+impl<'a, E: 'a, A: 'a> Saga<'a, E, A> {
+  fn new(f: impl FnOnce(&mut Compensations<'a>) -> Result<A, E> + 'a) -> Self {
+    Saga(Box::new(f))
+  }
+
+  /// Bind the value to `f`, if the saga didn’t fail.
+  pub fn and_then<B: 'a>(self, f: impl FnOnce(A) -> Saga<'a, E, B> + 'a) -> Saga<'a, E, B> {
+    Saga::new(move |compensations: &mut Compensations<'a>| {
+      let a = (self.0)(compensations)?;
+      (f(a).0)(compensations)
+    })
+  }
+
+  /// Apply `f` to the value.
+  pub fn map<B: 'a>(self, f: impl FnOnce(A) -> B + 'a) -> Saga<'a, E, B> {
+    Saga::new(move |compensations: &mut Compensations<'a>| (self.0)(compensations).map(f))
+  }
+
+  /// Run the saga.
+  ///
+  /// If it fails, the registered compensations run, the latest first, before returning the error.
+  pub fn run(self) -> Result<A, E> {
+    let mut compensations = Vec::new();
+    let r = (self.0)(&mut compensations);
+
+    if r.is_err() {
+      for compensation in compensations.into_iter().rev() {
+        compensation();
+      }
+    }
+
+    r
+  }
+}
+
+impl<'a, E: 'a, A: 'a> Lift<A> for Saga<'a, E, A> {
+  fn lift(a: A) -> Self {
+    Saga::new(move |_| Ok(a))
+  }
+}
+
+/// [`Kind`] of `Saga<'_, E, _>`.
+#[derive(Debug)]
+pub struct SagaKind<E>(PhantomData<E>);
+
+impl<'a, E: 'a> Kind<'a> for SagaKind<E> {
+  type Of<A: 'a> = Saga<'a, E, A>;
+}
+
+impl<'a, E: 'a, A: 'a> Monad<'a> for Saga<'a, E, A> {
+  type Item = A;
+  type Kind = SagaKind<E>;
+
+  fn and_then<B, F>(self, f: F) -> Saga<'a, E, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> Saga<'a, E, B> + 'a,
+  {
+    Saga::and_then(self, f)
+  }
+}
+
+/// Run `action`, without compensation.
+pub fn action<'a, E: 'a, A: 'a>(action: impl FnOnce() -> Result<A, E> + 'a) -> Saga<'a, E, A> {
+  Saga::new(move |_| action())
+}
+
+/// Run `action`, and register `compensation` to undo it, called with a copy of its value.
+///
+/// If `action` fails, there’s nothing to undo, and `compensation` isn’t registered.
+pub fn step<'a, E, A>(
+  action: impl FnOnce() -> Result<A, E> + 'a,
+  compensation: impl FnOnce(A) + 'a,
+) -> Saga<'a, E, A>
+where
+  E: 'a,
+  A: Clone + 'a,
+{
+  Saga::new(move |compensations: &mut Compensations<'a>| {
+    let a = action()?;
+    let value = a.clone();
+    compensations.push(Box::new(move || compensation(value)));
+    Ok(a)
+  })
+}
+
+/// Register `compensation`, to run if the saga fails afterwards.
+pub fn compensate<'a, E: 'a>(compensation: impl FnOnce() + 'a) -> Saga<'a, E, ()> {
+  Saga::new(move |compensations: &mut Compensations<'a>| {
+    compensations.push(Box::new(compensation));
+    Ok(())
+  })
+}
+
+/// Fail with `e`, running the registered compensations.
+pub fn fail<'a, E: 'a, A: 'a>(e: E) -> Saga<'a, E, A> {
+  Saga::new(move |_| Err(e))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::combinators::for_m;
+  use std::cell::RefCell;
+
+  fn call<'a>(log: &'a RefCell<Vec<String>>, service: &'static str) -> Saga<'a, String, usize> {
+    step(
+      move || {
+        let mut log = log.borrow_mut();
+        log.push(format!("call {}", service));
+        Ok(log.len())
+      },
+      move |id| log.borrow_mut().push(format!("undo {} #{}", service, id)),
+    )
+  }
+
+  #[test]
+  fn success_keeps_effects() {
+    let log = RefCell::new(Vec::new());
+    let log = &log;
+
+    let r = m! {
+      a <- call(log, "a");
+      b <- call(log, "b");
+      return a + b;
+    };
+
+    assert_eq!(r.run(), Ok(3));
+    assert_eq!(*log.borrow(), vec!["call a", "call b"]);
+  }
+
+  #[test]
+  fn compensations_in_reverse_order() {
+    let log = RefCell::new(Vec::new());
+    let log = &log;
+
+    let r: Saga<String, ()> = m! {
+      for_m(["a", "b"], |service| call(log, service));
+      compensate(move || log.borrow_mut().push("notify".to_owned()));
+      step(|| Err::<(), _>("c is down".to_owned()), |()| unreachable!());
+      call(log, "d");
+      return ();
+    };
+
+    assert_eq!(r.run(), Err("c is down".to_owned()));
+    assert_eq!(
+      *log.borrow(),
+      vec!["call a", "call b", "notify", "undo b #2", "undo a #1"]
+    );
+
+    log.borrow_mut().clear();
+    let r = call(log, "a").and_then(|_| fail::<_, ()>("nope".to_owned()));
+    assert_eq!(r.run(), Err("nope".to_owned()));
+    assert_eq!(*log.borrow(), vec!["call a", "undo a #1"]);
+  }
+}
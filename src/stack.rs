@@ -0,0 +1,283 @@
+//! Monad transformer stacks. See [`stack!`](crate::stack).
+
+/// Declare a monad transformer stack.
+///
+/// Spelling out a stack of monad transformers by hand quickly gets unreadable. This macro declares a stack
+/// from its layers, outermost first, and generates a module containing:
+///
+/// - `StackKind`, the [`Kind`](crate::monad::Kind) of the stack.
+/// - `Stack<'a, A>`, the type of computations in the stack producing a value of type `A`.
+/// - `new`, creating a computation from a function of the arguments of the layers.
+/// - `run`, running a computation by unwinding the layers in order.
+///
+/// The supported layers are:
+///
+/// - `Reader<R>`, a [`ReaderT`](crate::reader::ReaderT), taking an environment of type `R` as argument.
+/// - `State<S>`, a [`StateT`](crate::state::StateT), taking an initial state of type `S` as argument and producing
+///   the final state alongside the value.
+/// - `Writer<W>`, a [`WriterT`](crate::writer::WriterT), producing the output of type `W` alongside the value.
+/// - `Result<E>`, a [`ResultT`](crate::result::ResultT), producing a `Result<_, E>`.
+///
+/// The stack is built over [`Identity`](crate::identity::Identity) by default, in which case `run` returns the
+/// unwound value directly. Another base monad can be picked by passing its [`Kind`](crate::monad::Kind) with
+/// `over <kind>`.
+///
+/// ```rust
+/// use do_notation::{m, reader::MonadReader, stack, state::MonadState};
+///
+/// #[derive(Clone)]
+/// pub struct Config {
+///   step: i32,
+/// }
+///
+/// stack! {
+///   /// Stack of our application.
+///   pub mod app = Reader<Config>, State<i32>, Result<String>;
+/// }
+///
+/// fn step<'a>() -> app::Stack<'a, i32> {
+///   m! {
+///     config <- app::StackKind::ask();
+///     app::StackKind::modify(move |n| n + config.step);
+///     n <- app::StackKind::get();
+///     app::new(move |_, _| if n > 2 { Err("overflow".to_owned()) } else { Ok((n, n)) })
+///   }
+/// }
+///
+/// fn main() {
+///   let config = Config { step: 2 };
+///   assert_eq!(app::run(step(), config.clone(), 0), Ok((2, 2)));
+///   assert_eq!(app::run(step(), config, 1), Err("overflow".to_owned()));
+/// }
+/// ```
+///
+/// Because the generated module refers to the types of the layers through `use super::*`, the macro must be invoked
+/// at module level.
+#[macro_export]
+macro_rules! stack {
+  // layers
+  (@kind [$base:ty]) => {
+    $base
+  };
+
+  (@kind [$base:ty] Reader<$r:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::reader::ReaderTKind<$r, $crate::stack!(@kind [$base] $($layer<$arg>),*)>
+  };
+
+  (@kind [$base:ty] State<$s:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::state::StateTKind<$s, $crate::stack!(@kind [$base] $($layer<$arg>),*)>
+  };
+
+  (@kind [$base:ty] Writer<$w:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::writer::WriterTKind<$w, $crate::stack!(@kind [$base] $($layer<$arg>),*)>
+  };
+
+  (@kind [$base:ty] Result<$e:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::result::ResultTKind<$e, $crate::stack!(@kind [$base] $($layer<$arg>),*)>
+  };
+
+  // unwound value
+  (@unwound [$a:ty]) => {
+    $a
+  };
+
+  (@unwound [$a:ty] Reader<$r:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::stack!(@unwound [$a] $($layer<$arg>),*)
+  };
+
+  (@unwound [$a:ty] State<$s:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::stack!(@unwound [($a, $s)] $($layer<$arg>),*)
+  };
+
+  (@unwound [$a:ty] Writer<$w:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::stack!(@unwound [($a, $w)] $($layer<$arg>),*)
+  };
+
+  (@unwound [$a:ty] Result<$e:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::stack!(@unwound [::core::result::Result<$a, $e>] $($layer<$arg>),*)
+  };
+
+  // base monad
+  (@base_kind []) => {
+    $crate::identity::IdentityKind
+  };
+
+  (@base_kind [over $base:ty]) => {
+    $base
+  };
+
+  (@base_of [$lt:lifetime] [] [$a:ty]) => {
+    $a
+  };
+
+  (@base_of [$lt:lifetime] [over $base:ty] [$a:ty]) => {
+    <$base as $crate::monad::Kind<$lt>>::Of<$a>
+  };
+
+  (@base_wrap [] $e:expr) => {
+    $crate::identity::Identity($e)
+  };
+
+  (@base_wrap [over $base:ty] $e:expr) => {
+    $e
+  };
+
+  (@base_unwrap [] $e:expr) => {
+    $crate::identity::Identity::run($e)
+  };
+
+  (@base_unwrap [over $base:ty] $e:expr) => {
+    $e
+  };
+
+  // constructor
+  (@new [$($base:tt)*] [$e:expr] []) => {
+    $crate::stack!(@base_wrap [$($base)*] $e)
+  };
+
+  (@new [$($base:tt)*] [$e:expr] [$id:ident $(, $ids:ident)*] Reader<$r:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::reader::ReaderT::new(move |$id| {
+      $crate::stack!(@new [$($base)*] [$e] [$($ids),*] $($layer<$arg>),*)
+    })
+  };
+
+  (@new [$($base:tt)*] [$e:expr] [$id:ident $(, $ids:ident)*] State<$s:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::state::StateT::new(move |$id| {
+      $crate::stack!(@new [$($base)*] [$e] [$($ids),*] $($layer<$arg>),*)
+    })
+  };
+
+  (@new [$($base:tt)*] [$e:expr] [$($ids:ident),*] Writer<$w:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::writer::WriterT::new($crate::stack!(@new [$($base)*] [$e] [$($ids),*] $($layer<$arg>),*))
+  };
+
+  (@new [$($base:tt)*] [$e:expr] [$($ids:ident),*] Result<$e_:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::result::ResultT::new($crate::stack!(@new [$($base)*] [$e] [$($ids),*] $($layer<$arg>),*))
+  };
+
+  // runner
+  (@run [$($base:tt)*] [$e:expr] []) => {
+    $crate::stack!(@base_unwrap [$($base)*] $e)
+  };
+
+  (@run [$($base:tt)*] [$e:expr] [$id:ident $(, $ids:ident)*] Reader<$r:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::stack!(@run [$($base)*] [$crate::reader::run_reader_t($e, $id)] [$($ids),*] $($layer<$arg>),*)
+  };
+
+  (@run [$($base:tt)*] [$e:expr] [$id:ident $(, $ids:ident)*] State<$s:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::stack!(@run [$($base)*] [$crate::state::run_state_t($e, $id)] [$($ids),*] $($layer<$arg>),*)
+  };
+
+  (@run [$($base:tt)*] [$e:expr] [$($ids:ident),*] Writer<$w:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::stack!(@run [$($base)*] [$crate::writer::run_writer_t($e)] [$($ids),*] $($layer<$arg>),*)
+  };
+
+  (@run [$($base:tt)*] [$e:expr] [$($ids:ident),*] Result<$e_:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::stack!(@run [$($base)*] [$crate::result::run_result_t($e)] [$($ids),*] $($layer<$arg>),*)
+  };
+
+  // arguments of the layers, collected before generating the functions
+  (@args [$($base:tt)*] [$($id:ident: $t:ty,)*] [$($layers:tt)*]) => {
+    /// Create a computation from a function of the arguments of the layers.
+    pub fn new<'a, A: 'a>(
+      f: impl FnOnce($($t),*) -> $crate::stack!(@base_of ['a] [$($base)*] [$crate::stack!(@unwound [A] $($layers)*)])
+        + 'a,
+    ) -> Stack<'a, A> {
+      $crate::stack!(@new [$($base)*] [f($($id),*)] [$($id),*] $($layers)*)
+    }
+
+    /// Run a computation, unwinding the layers in order.
+    pub fn run<'a, A: 'a>(
+      m: Stack<'a, A>,
+      $($id: $t),*
+    ) -> $crate::stack!(@base_of ['a] [$($base)*] [$crate::stack!(@unwound [A] $($layers)*)]) {
+      $crate::stack!(@run [$($base)*] [m] [$($id),*] $($layers)*)
+    }
+  };
+
+  (@args [$($base:tt)*] [$($args:tt)*] [$($layers:tt)*] Reader<$r:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::stack! { @args [$($base)*] [$($args)* env: $r,] [$($layers)*] $($layer<$arg>),* }
+  };
+
+  (@args [$($base:tt)*] [$($args:tt)*] [$($layers:tt)*] State<$s:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::stack! { @args [$($base)*] [$($args)* state: $s,] [$($layers)*] $($layer<$arg>),* }
+  };
+
+  (@args [$($base:tt)*] [$($args:tt)*] [$($layers:tt)*] $other:ident<$o:ty> $(, $layer:ident<$arg:ty>)*) => {
+    $crate::stack! { @args [$($base)*] [$($args)*] [$($layers)*] $($layer<$arg>),* }
+  };
+
+  // entry point
+  ($(#[$attr:meta])* $vis:vis mod $name:ident = $($layer:ident<$arg:ty>),+ $(over $base:ty)?;) => {
+    $(#[$attr])*
+    $vis mod $name {
+      #[allow(unused_imports)]
+      use super::*;
+
+      /// Kind of the stack.
+      pub type StackKind =
+        $crate::stack!(@kind [$crate::stack!(@base_kind [$(over $base)?])] $($layer<$arg>),+);
+
+      /// Computations in the stack, producing a value of type `A`.
+      pub type Stack<'a, A> = <StackKind as $crate::monad::Kind<'a>>::Of<A>;
+
+      $crate::stack!(@args [$(over $base)?] [] [$($layer<$arg>),+] $($layer<$arg>),+);
+    }
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    io::{Io, IoKind, MonadIo},
+    reader::ask,
+    state::{get, MonadState},
+  };
+
+  crate::stack! {
+    mod app = Reader<i32>, Writer<Vec<i32>>, State<i32>, Result<&'static str>;
+  }
+
+  crate::stack! {
+    mod app_io = State<i32>, Reader<i32> over IoKind;
+  }
+
+  #[test]
+  fn stack() {
+    let r: app::Stack<i32> = m! {
+      x <- ask();
+      tell vec![x];
+      y <- app::StackKind::get();
+      app::StackKind::put(x + y);
+      app::new(|env, n| Ok(((env * n, vec![0]), n)))
+    };
+
+    assert_eq!(app::run(r, 2, 3), Ok(((10, vec![2, 0]), 5)));
+
+    let r: app::Stack<()> = m! {
+      tell vec![1];
+      throw_error("nope")
+    };
+
+    assert_eq!(app::run(r, 2, 3), Err("nope"));
+  }
+
+  fn throw_error<'a, A: 'a>(e: &'static str) -> app::Stack<'a, A> {
+    app::new(move |_, _| Err(e))
+  }
+
+  #[test]
+  fn stack_over() {
+    let r: app_io::Stack<i32> = m! {
+      n <- get();
+      x <- lift ask();
+      app_io::StackKind::lift_io(move || n * x)
+    };
+
+    assert_eq!(app_io::run(r, 2, 3).run(), (6, 2));
+    assert_eq!(
+      app_io::run(app_io::new(|n, x| Io::new(move || (n, x))), 1, 2).run(),
+      (1, 2)
+    );
+  }
+}
@@ -0,0 +1,405 @@
+//! The state monad and monad transformer.
+//!
+//! [`StateT<S, M, A>`] threads a state of type `S` through the statements of a do-block, in the
+//! monad `M`. [`State<S, A>`] is the pure version, over
+//! [`Identity`](crate::identity::Identity).
+//!
+//! ```rust
+//! use do_notation::{m, state::{get, put, run_state, State}};
+//!
+//! fn tick() -> State<'static, u32, u32> {
+//!   m! {
+//!     n <- get();
+//!     _ <- put(n + 1);
+//!     return n;
+//!   }
+//! }
+//!
+//! let r = m! {
+//!   a <- tick();
+//!   b <- tick();
+//!   return a + b;
+//! };
+//!
+//! assert_eq!(run_state(r, 10), (21, 12));
+//! ```
+//!
+//! Functions generic over any monad with a state can be written against the [`MonadState`]
+//! capability, implemented by the [`Kind`] of every monad stack containing a [`StateT`].
+
+use crate::{
+  alternative::MonadPlus,
+  either::Either,
+  identity::IdentityKind,
+  monad::{Kind, Monad},
+  monoid::Monoid,
+  reader::{ReaderT, ReaderTKind},
+  rec::MonadRec,
+  trans::MonadTrans,
+  writer::{WriterT, WriterTKind},
+  Lift,
+};
+use std::marker::PhantomData;
+
+/// Computations threading a state of type `S` in the monad `M`, producing a value of type `A`.
+#[allow(clippy::type_complexity)]
+pub struct StateT<'a, S: 'a, M: Kind<'a>, A: 'a> {
+  run: Box<dyn FnOnce(S) -> M::Of<(A, S)> + 'a>,
+}
+
+/// Pure computations threading a state of type `S`.
+pub type State<'a, S, A> = StateT<'a, S, IdentityKind, A>;
+
+impl<'a, S, M, A> StateT<'a, S, M, A>
+where
+  S: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  /// Create a computation from a state transition in the inner monad.
+  pub fn new(f: impl FnOnce(S) -> M::Of<(A, S)> + 'a) -> Self {
+    StateT { run: Box::new(f) }
+  }
+
+  /// Bind the value produced by this computation, passing along the updated state.
+  pub fn and_then<B, F>(self, f: F) -> StateT<'a, S, M, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> StateT<'a, S, M, B> + 'a,
+  {
+    StateT::new(move |s| (self.run)(s).and_then(move |(a, s)| (f(a).run)(s)))
+  }
+}
+
+impl<'a, S, M, A> Lift<A> for StateT<'a, S, M, A>
+where
+  S: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  fn lift(a: A) -> Self {
+    StateT::new(move |s| Lift::lift((a, s)))
+  }
+}
+
+/// [`Kind`] of `StateT<'_, S, M, _>`.
+#[derive(Debug)]
+pub struct StateTKind<S, M>(PhantomData<(S, M)>);
+
+impl<'a, S, M> Kind<'a> for StateTKind<S, M>
+where
+  S: 'a,
+  M: Kind<'a>,
+{
+  type Of<A: 'a> = StateT<'a, S, M, A>;
+
+  /// The loop runs in the inner monad, like [`MonadRec::tail_rec_m`].
+  fn loop_m<A: 'a, B: 'a>(
+    a: A,
+    mut f: impl FnMut(A) -> StateT<'a, S, M, Either<A, B>> + 'a,
+  ) -> StateT<'a, S, M, B> {
+    StateT::new(move |s| M::loop_m((a, s), move |(a, s)| step(&mut f, a, s)))
+  }
+}
+
+impl<'a, S, M, A> Monad<'a> for StateT<'a, S, M, A>
+where
+  S: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  type Item = A;
+  type Kind = StateTKind<S, M>;
+
+  fn and_then<B, F>(self, f: F) -> StateT<'a, S, M, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> StateT<'a, S, M, B> + 'a,
+  {
+    StateT::and_then(self, f)
+  }
+}
+
+/// Both alternatives start with the same state.
+impl<'a, S, M> MonadPlus<'a> for StateTKind<S, M>
+where
+  S: Clone + 'a,
+  M: MonadPlus<'a>,
+{
+  fn mzero<A: 'a>() -> StateT<'a, S, M, A> {
+    StateT::new(|_| M::mzero())
+  }
+
+  fn mplus<A: 'a>(fa: StateT<'a, S, M, A>, fb: StateT<'a, S, M, A>) -> StateT<'a, S, M, A> {
+    StateT::new(move |s: S| M::mplus((fa.run)(s.clone()), (fb.run)(s)))
+  }
+}
+
+/// The loop runs in the inner monad, so it is stack-safe if the inner monad is.
+impl<'a, S, M> MonadRec<'a> for StateTKind<S, M>
+where
+  S: 'a,
+  M: MonadRec<'a>,
+{
+  fn tail_rec_m<A: 'a, B: 'a>(
+    a: A,
+    mut f: impl FnMut(A) -> StateT<'a, S, M, Either<A, B>> + 'a,
+  ) -> StateT<'a, S, M, B> {
+    StateT::new(move |s| M::tail_rec_m((a, s), move |(a, s)| step(&mut f, a, s)))
+  }
+}
+
+/// Run the step `f` of a loop with `a` and the state `s`, in the inner monad.
+#[allow(clippy::type_complexity)]
+fn step<'a, S, M, A, B>(
+  f: &mut impl FnMut(A) -> StateT<'a, S, M, Either<A, B>>,
+  a: A,
+  s: S,
+) -> M::Of<Either<(A, S), (B, S)>>
+where
+  S: 'a,
+  M: Kind<'a>,
+  A: 'a,
+  B: 'a,
+{
+  (f(a).run)(s).and_then(|(step, s)| {
+    Lift::lift(match step {
+      Either::Left(a) => Either::Left((a, s)),
+      Either::Right(b) => Either::Right((b, s)),
+    })
+  })
+}
+
+impl<'a, S, M> MonadTrans<'a, M> for StateT<'a, S, M::Kind, M::Item>
+where
+  S: 'a,
+  M: Monad<'a>,
+{
+  fn lift(m: M) -> Self {
+    StateT::new(move |s| m.and_then::<(M::Item, S), _>(move |a| Lift::lift((a, s))))
+  }
+}
+
+/// Monads with a state of type `S`.
+///
+/// This trait is implemented by the [`Kind`] of the monads, so that functions can be
+/// written against any monad with a state:
+///
+/// ```rust
+/// use do_notation::{identity::IdentityKind, m, state::{run_state, MonadState, StateTKind}};
+/// use do_notation::{Kind, Monad};
+///
+/// fn incr<'a, K: MonadState<'a, u32>>() -> K::Of<()> {
+///   K::modify(|n| n + 1)
+/// }
+///
+/// fn incr_twice<'a, K: MonadState<'a, u32>>() -> K::Of<u32> {
+///   m! {
+///     _ <- incr::<K>();
+///     _ <- incr::<K>();
+///     K::get()
+///   }
+/// }
+///
+/// assert_eq!(run_state(incr_twice::<StateTKind<u32, IdentityKind>>(), 0), (2, 2));
+/// ```
+pub trait MonadState<'a, S: 'a>: Kind<'a> {
+  /// Embed a state transition into the monad.
+  fn state<A: 'a>(f: impl FnOnce(S) -> (A, S) + 'a) -> Self::Of<A>;
+
+  /// Retrieve the state.
+  fn get() -> Self::Of<S>
+  where
+    S: Clone,
+  {
+    Self::state(|s: S| (s.clone(), s))
+  }
+
+  /// Retrieve a projection of the state.
+  fn gets<A: 'a>(f: impl FnOnce(&S) -> A + 'a) -> Self::Of<A> {
+    Self::state(move |s| (f(&s), s))
+  }
+
+  /// Replace the state.
+  fn put(s: S) -> Self::Of<()> {
+    Self::state(move |_| ((), s))
+  }
+
+  /// Modify the state with `f`.
+  fn modify(f: impl FnOnce(S) -> S + 'a) -> Self::Of<()> {
+    Self::state(move |s| ((), f(s)))
+  }
+}
+
+impl<'a, S, M> MonadState<'a, S> for StateTKind<S, M>
+where
+  S: 'a,
+  M: Kind<'a>,
+{
+  fn state<A: 'a>(f: impl FnOnce(S) -> (A, S) + 'a) -> StateT<'a, S, M, A> {
+    StateT::new(move |s| Lift::lift(f(s)))
+  }
+}
+
+impl<'a, R, S, M> MonadState<'a, S> for ReaderTKind<R, M>
+where
+  R: Clone + 'a,
+  S: 'a,
+  M: MonadState<'a, S>,
+{
+  fn state<A: 'a>(f: impl FnOnce(S) -> (A, S) + 'a) -> ReaderT<'a, R, M, A> {
+    ReaderT::new(move |_| M::state(f))
+  }
+}
+
+impl<'a, W, S, M> MonadState<'a, S> for WriterTKind<W, M>
+where
+  W: Monoid + 'a,
+  S: 'a,
+  M: MonadState<'a, S>,
+{
+  fn state<A: 'a>(f: impl FnOnce(S) -> (A, S) + 'a) -> WriterT<'a, W, M, A> {
+    WriterT::new(M::state(f).and_then::<(A, W), _>(|a| Lift::lift((a, W::empty()))))
+  }
+}
+
+/// Embed a state transition into the monad.
+pub fn state<'a, S, M, A>(f: impl FnOnce(S) -> (A, S) + 'a) -> StateT<'a, S, M, A>
+where
+  S: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  StateTKind::state(f)
+}
+
+/// Retrieve the state.
+pub fn get<'a, S, M>() -> StateT<'a, S, M, S>
+where
+  S: Clone + 'a,
+  M: Kind<'a>,
+{
+  StateTKind::get()
+}
+
+/// Retrieve a projection of the state.
+pub fn gets<'a, S, M, A>(f: impl FnOnce(&S) -> A + 'a) -> StateT<'a, S, M, A>
+where
+  S: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  StateTKind::gets(f)
+}
+
+/// Replace the state.
+pub fn put<'a, S, M>(s: S) -> StateT<'a, S, M, ()>
+where
+  S: 'a,
+  M: Kind<'a>,
+{
+  StateTKind::put(s)
+}
+
+/// Modify the state with `f`.
+pub fn modify<'a, S, M>(f: impl FnOnce(S) -> S + 'a) -> StateT<'a, S, M, ()>
+where
+  S: 'a,
+  M: Kind<'a>,
+{
+  StateTKind::modify(f)
+}
+
+/// Run a computation with the initial state `s`, returning its value and the final state.
+pub fn run_state_t<'a, S, M, A>(m: StateT<'a, S, M, A>, s: S) -> M::Of<(A, S)>
+where
+  M: Kind<'a>,
+{
+  (m.run)(s)
+}
+
+/// Run a pure computation with the initial state `s`, returning its value and the final state.
+pub fn run_state<'a, S, A>(m: State<'a, S, A>, s: S) -> (A, S) {
+  run_state_t(m, s).run()
+}
+
+/// Run a pure computation with the initial state `s`, returning its value.
+pub fn eval_state<'a, S, A>(m: State<'a, S, A>, s: S) -> A {
+  run_state(m, s).0
+}
+
+/// Run a pure computation with the initial state `s`, returning the final state.
+pub fn exec_state<'a, S, A>(m: State<'a, S, A>, s: S) -> S {
+  run_state(m, s).1
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    monad::{OptionKind, ResultKind},
+    reader::{ask, run_reader_t},
+  };
+
+  #[test]
+  fn state() {
+    let r: State<Vec<i32>, usize> = m! {
+      _ <- modify(|mut v: Vec<i32>| { v.push(1); v });
+      _ <- modify(|mut v: Vec<i32>| { v.push(2); v });
+      gets(Vec::len)
+    };
+
+    assert_eq!(run_state(r, vec![0]), (3, vec![0, 1, 2]));
+  }
+
+  #[test]
+  fn state_t() {
+    let pop = || -> StateT<Vec<i32>, OptionKind, i32> {
+      m! {
+        v <- get::<Vec<i32>, _>();
+        let mut v = v;
+        x <- lift v.pop();
+        _ <- put(v);
+        return x;
+      }
+    };
+
+    let r = m! {
+      x <- pop();
+      y <- pop();
+      return x + y;
+    };
+
+    assert_eq!(run_state_t(r, vec![1, 2, 3]), Some((5, vec![1])));
+
+    let r = m! {
+      x <- pop();
+      y <- pop();
+      return x + y;
+    };
+
+    assert_eq!(run_state_t(r, vec![1]), None);
+  }
+
+  fn incr<'a, K: MonadState<'a, i32>>() -> K::Of<i32> {
+    K::state(|n| (n, n + 1))
+  }
+
+  #[test]
+  fn monad_state() {
+    assert_eq!(
+      run_state(incr::<StateTKind<i32, IdentityKind>>(), 1),
+      (1, 2)
+    );
+
+    type Stack = ReaderTKind<i32, StateTKind<i32, ResultKind<()>>>;
+    let r = m! {
+      step <- ask();
+      n <- incr::<Stack>();
+      _ <- <Stack as MonadState<_>>::modify(move |n| n + step);
+      return n;
+    };
+
+    assert_eq!(run_state_t(run_reader_t(r, 10), 1), Ok((1, 12)));
+  }
+}
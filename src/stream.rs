@@ -0,0 +1,105 @@
+//! Stream do-notation.
+//!
+//! > This module requires the `futures` feature.
+//!
+//! The [`stream_m!`](crate::stream_m) macro sequences [`Stream`](futures::Stream)s the way the list monad sequences vectors: binding a
+//! stream runs the rest of the block for each of its items, and the block produces a new stream, concatenating the
+//! streams produced by the rest of the block, in order. Futures can be bound as streams with a single item with
+//! [`stream::once`].
+//!
+//! ```rust
+//! use do_notation::stream_m;
+//! use futures::{executor::block_on, stream::{self, StreamExt}};
+//!
+//! let s = stream_m! {
+//!   x <- stream::iter(vec![1, 2, 3]);
+//!   y <- stream::once(async move { x * 10 });
+//!   let z = x + y;
+//!   return z;
+//! };
+//!
+//! assert_eq!(block_on(s.collect::<Vec<_>>()), vec![11, 22, 33]);
+//! ```
+//!
+//! As the rest of the block can run several times, the values it moves must be [`Copy`].
+
+use futures::stream::{self, Iter};
+use std::iter::Once;
+
+#[doc(hidden)]
+pub use futures::stream::StreamExt;
+
+/// Lift a value into a stream with a single item.
+pub fn lift<A>(a: A) -> Iter<Once<A>> {
+  stream::iter(std::iter::once(a))
+}
+
+/// Stream do-notation.
+///
+/// The syntax is the one of [`m!`](crate::m), without the `lift`, `for`, `tell` and `catch` statements. Bound values
+/// are the items of streams, and `return` lifts a value into a stream with a single item. See the
+/// [`stream`](crate::stream) module.
+#[macro_export]
+macro_rules! stream_m {
+  // return
+  (return $r:expr ;) => {
+    $crate::stream::lift($r)
+  };
+
+  // let-binding
+  (let $p:pat = $e:expr ; $($r:tt)*) => {{
+    let $p = $e;
+    $crate::stream_m!($($r)*)
+  }};
+
+  // const-bind
+  (_ <- $x:expr ; $($r:tt)*) => {
+    $crate::stream::StreamExt::flat_map($x, move |_| $crate::stream_m!($($r)*))
+  };
+
+  // bind
+  ($binding:ident <- $x:expr ; $($r:tt)*) => {
+    $crate::stream::StreamExt::flat_map($x, move |$binding| $crate::stream_m!($($r)*))
+  };
+
+  // const-bind
+  ($e:expr ; $($r:tt)*) => {
+    $crate::stream::StreamExt::flat_map($e, move |_| $crate::stream_m!($($r)*))
+  };
+
+  // pure
+  ($a:expr) => {
+    $a
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use futures::{
+    executor::block_on,
+    stream::{self, StreamExt},
+  };
+
+  #[test]
+  fn stream_m() {
+    let s = stream_m! {
+      x <- stream::iter(1..=3);
+      _ <- stream::iter(0..x);
+      y <- stream::once(async move { x * 10 });
+      stream::iter(vec![x, y])
+    };
+
+    assert_eq!(
+      block_on(s.collect::<Vec<_>>()),
+      vec![1, 10, 2, 20, 2, 20, 3, 30, 3, 30, 3, 30]
+    );
+
+    let s = stream_m! {
+      x <- stream::iter(vec![1, 2]);
+      stream::empty::<()>();
+      return x;
+    };
+
+    assert_eq!(block_on(s.collect::<Vec<i32>>()), Vec::<i32>::new());
+  }
+}
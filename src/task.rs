@@ -0,0 +1,159 @@
+//! Tokio tasks.
+//!
+//! > This module requires the `tokio` feature.
+//!
+//! [`Task`] is the monad of tasks spawned on the [tokio](tokio) runtime. Binding a task awaits it in a new task,
+//! which runs the rest of the block, so a do-block reads as the orchestration of the tasks it spawns. As a task can
+//! panic or be cancelled, awaiting a task produces a `Result`, with the error of the first task of the block which
+//! failed; the rest of the block doesn’t run.
+//!
+//! ```rust
+//! use do_notation::{m, task::Task};
+//!
+//! let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//!
+//! let r = rt.block_on(async {
+//!   let task = m! {
+//!     // both tasks run concurrently
+//!     p <- Task::spawn(async { 1 }).zip(Task::spawn(async { 2 }));
+//!     let (x, y) = p;
+//!     z <- Task::spawn(async move { x + y });
+//!     return z * 10;
+//!   };
+//!
+//!   task.await
+//! });
+//!
+//! assert_eq!(r.unwrap(), 30);
+//! ```
+//!
+//! Spawning tasks requires to be in the context of a tokio runtime, including when lifting a value.
+
+use crate::Lift;
+use std::{
+  future::Future,
+  pin::Pin,
+  task::{Context, Poll},
+};
+use tokio::task::{JoinError, JoinHandle};
+
+/// A task spawned on the tokio runtime, producing a value of type `A`.
+///
+/// Awaiting a task produces its value, or the [`JoinError`] of the first task of the block which failed.
+#[derive(Debug)]
+pub struct Task<A>(JoinHandle<Result<A, JoinError>>);
+
+impl<A> Task<A>
+where
+  A: Send + 'static,
+{
+  /// Spawn a future in a new task.
+  pub fn spawn(f: impl Future<Output = A> + Send + 'static) -> Self {
+    Task(tokio::spawn(async move { Ok(f.await) }))
+  }
+
+  /// Once this task is done, spawn the task built by `f` with its value.
+  pub fn and_then<B, F>(self, f: F) -> Task<B>
+  where
+    B: Send + 'static,
+    F: FnOnce(A) -> Task<B> + Send + 'static,
+  {
+    Task(tokio::spawn(async move { f(self.await?).await }))
+  }
+
+  /// Map the value produced by this task.
+  pub fn map<B, F>(self, f: F) -> Task<B>
+  where
+    B: Send + 'static,
+    F: FnOnce(A) -> B + Send + 'static,
+  {
+    Task(tokio::spawn(async move { self.await.map(f) }))
+  }
+
+  /// Wait for this task and `other`, which run concurrently, pairing their values.
+  pub fn zip<B>(self, other: Task<B>) -> Task<(A, B)>
+  where
+    B: Send + 'static,
+  {
+    Task(tokio::spawn(async move { Ok((self.await?, other.await?)) }))
+  }
+
+  /// Abort the task.
+  pub fn abort(&self) {
+    self.0.abort();
+  }
+}
+
+impl<A> Lift<A> for Task<A>
+where
+  A: Send + 'static,
+{
+  fn lift(a: A) -> Self {
+    Task(tokio::spawn(async move { Ok(a) }))
+  }
+}
+
+impl<A> Future for Task<A> {
+  type Output = Result<A, JoinError>;
+
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+    Pin::new(&mut self.0).poll(cx).map(|r| r.and_then(|a| a))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tokio::runtime::{Builder, Runtime};
+
+  fn runtime() -> Runtime {
+    Builder::new_current_thread().build().unwrap()
+  }
+
+  #[test]
+  fn task() {
+    let r = runtime().block_on(async {
+      let task = m! {
+        x <- Task::spawn(async { 1 });
+        let y = x + 1;
+        p <- Task::lift(x).zip(Task::spawn(async move { y * 10 }));
+        let (a, b) = p;
+        Task::lift(a + b).map(|z| z.to_string())
+      };
+
+      task.await
+    });
+
+    assert_eq!(r.unwrap(), "21");
+  }
+
+  #[test]
+  fn failing_task() {
+    let r = runtime().block_on(async {
+      let task = m! {
+        x <- Task::spawn(async { 1 });
+        _ <- Task::spawn(async { panic!("boom") });
+        return x;
+      };
+
+      task.await
+    });
+
+    assert!(r.unwrap_err().is_panic());
+
+    let r = runtime().block_on(async {
+      let pending = Task::spawn(std::future::pending::<()>());
+      pending.abort();
+
+      let task = m! {
+        x <- Task::spawn(async { 1 });
+        _ <- pending;
+        return x;
+      };
+
+      task.await
+    });
+
+    assert!(r.unwrap_err().is_cancelled());
+  }
+}
@@ -0,0 +1,139 @@
+//! The these monad.
+//!
+//! [`These<A, B>`] holds a value of type `A`, a value of type `B`, or both. As a monad, it accumulates the values
+//! on the left side — which must be a [`Semigroup`] — and short-circuits only on [`These::This`]. This is useful
+//! to carry warnings alongside a result, and fail only on errors.
+//!
+//! ```rust
+//! use do_notation::{m, these::These};
+//!
+//! fn parse(s: &str) -> These<Vec<String>, i32> {
+//!   match s.trim().parse() {
+//!     Ok(n) if s.trim() != s => These::Both(vec![format!("{:?} has spaces", s)], n),
+//!     Ok(n) => These::That(n),
+//!     Err(_) => These::This(vec![format!("{:?} is not a number", s)]),
+//!   }
+//! }
+//!
+//! let r = m! {
+//!   a <- parse(" 1");
+//!   b <- parse("2 ");
+//!   return a + b;
+//! };
+//!
+//! assert_eq!(r, These::Both(vec!["\" 1\" has spaces".to_owned(), "\"2 \" has spaces".to_owned()], 3));
+//! ```
+
+use crate::{
+  monad::{Kind, Monad},
+  monoid::Semigroup,
+  Lift,
+};
+use std::marker::PhantomData;
+
+/// A value of type `A`, a value of type `B`, or both.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum These<A, B> {
+  /// Only a value of type `A`.
+  This(A),
+  /// Only a value of type `B`.
+  That(B),
+  /// Both values.
+  Both(A, B),
+}
+
+impl<A, B> These<A, B> {
+  /// Value of type `A`, if any.
+  pub fn this(self) -> Option<A> {
+    match self {
+      These::This(a) | These::Both(a, _) => Some(a),
+      These::That(_) => None,
+    }
+  }
+
+  /// Value of type `B`, if any.
+  pub fn that(self) -> Option<B> {
+    match self {
+      These::That(b) | These::Both(_, b) => Some(b),
+      These::This(_) => None,
+    }
+  }
+
+  /// Swap the sides.
+  pub fn flip(self) -> These<B, A> {
+    match self {
+      These::This(a) => These::That(a),
+      These::That(b) => These::This(b),
+      These::Both(a, b) => These::Both(b, a),
+    }
+  }
+
+  /// Bind the value of type `B` to `f`, combining the values of type `A`.
+  pub fn and_then<C>(self, f: impl FnOnce(B) -> These<A, C>) -> These<A, C>
+  where
+    A: Semigroup,
+  {
+    match self {
+      These::This(a) => These::This(a),
+      These::That(b) => f(b),
+      These::Both(a, b) => match f(b) {
+        These::This(a_) => These::This(a.combine(a_)),
+        These::That(c) => These::Both(a, c),
+        These::Both(a_, c) => These::Both(a.combine(a_), c),
+      },
+    }
+  }
+}
+
+impl<A, B> Lift<B> for These<A, B> {
+  fn lift(b: B) -> Self {
+    These::That(b)
+  }
+}
+
+/// [`Kind`] of `These<A, _>`.
+#[derive(Debug)]
+pub struct TheseKind<A>(PhantomData<A>);
+
+impl<'a, A: Semigroup + 'a> Kind<'a> for TheseKind<A> {
+  type Of<B: 'a> = These<A, B>;
+}
+
+impl<'a, A: Semigroup + 'a, B: 'a> Monad<'a> for These<A, B> {
+  type Item = B;
+  type Kind = TheseKind<A>;
+
+  fn and_then<C, F>(self, f: F) -> These<A, C>
+  where
+    C: 'a,
+    F: FnOnce(B) -> These<A, C> + 'a,
+  {
+    These::and_then(self, f)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn these() {
+    let r: These<String, i32> = m! {
+      a <- These::Both("a".to_owned(), 1);
+      b <- These::That(2);
+      c <- These::Both("b".to_owned(), 3);
+      return a + b + c;
+    };
+
+    assert_eq!(r, These::Both("ab".to_owned(), 6));
+
+    let r: These<String, i32> = m! {
+      a <- These::Both("a".to_owned(), 1);
+      _ <- These::This::<_, ()>("b".to_owned());
+      return a;
+    };
+
+    assert_eq!(r.clone().this(), Some("ab".to_owned()));
+    assert_eq!(r.flip(), These::That("ab".to_owned()));
+  }
+}
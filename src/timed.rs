@@ -0,0 +1,192 @@
+//! Timed computations.
+//!
+//! [`Timed`] wraps the computations of another monad, and measures how long it takes to build each of them: the
+//! steps of a do-block are timed with [`Timed::new`] or [`Timed::labeled`], and the block produces the report of
+//! its steps, in order, along with the computation. This allows to profile a pipeline without any external tooling.
+//!
+//! ```rust
+//! use do_notation::{m, timed::Timed};
+//!
+//! let (r, steps) = m! {
+//!   a <- Timed::labeled("parse a", || "1".parse::<i32>());
+//!   b <- Timed::labeled("parse b", || "2".parse::<i32>());
+//!   Timed::new(|| Ok(a + b))
+//! }
+//! .run();
+//!
+//! assert_eq!(r, Ok(3));
+//! assert_eq!(
+//!   steps.iter().map(|step| step.label).collect::<Vec<_>>(),
+//!   vec![Some("parse a"), Some("parse b"), None]
+//! );
+//! ```
+//!
+//! As with [`Instrumented`](crate::instrumented::Instrumented), the inner monad must be strict: its `and_then` must
+//! call the continuation, if ever, before returning. The steps that didn’t run because the computation
+//! short-circuited aren’t reported.
+
+use crate::{
+  monad::{Kind, Monad},
+  Lift,
+};
+use std::{
+  cell::Cell,
+  marker::PhantomData,
+  rc::Rc,
+  time::{Duration, Instant},
+};
+
+/// A timed step of a [`Timed`] computation.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Step {
+  /// Label of the step, if any.
+  pub label: Option<&'static str>,
+
+  /// Wall-clock duration of the step.
+  pub duration: Duration,
+}
+
+/// A computation of the monad `M`, along with the timed steps of its construction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Timed<M> {
+  inner: M,
+  steps: Vec<Step>,
+}
+
+impl<M> Timed<M> {
+  /// Time the construction of a computation.
+  pub fn new(m: impl FnOnce() -> M) -> Self {
+    Self::time(None, m)
+  }
+
+  /// Time the construction of a computation, labeling the step with `label`.
+  pub fn labeled(label: &'static str, m: impl FnOnce() -> M) -> Self {
+    Self::time(Some(label), m)
+  }
+
+  fn time(label: Option<&'static str>, m: impl FnOnce() -> M) -> Self {
+    let start = Instant::now();
+    let inner = m();
+    let duration = start.elapsed();
+
+    Timed {
+      inner,
+      steps: vec![Step { label, duration }],
+    }
+  }
+
+  /// Steps of the computation so far.
+  pub fn steps(&self) -> &[Step] {
+    &self.steps
+  }
+
+  /// Total duration of the steps of the computation so far.
+  pub fn total(&self) -> Duration {
+    self.steps.iter().map(|step| step.duration).sum()
+  }
+
+  /// Extract the computation and its steps.
+  pub fn run(self) -> (M, Vec<Step>) {
+    (self.inner, self.steps)
+  }
+
+  /// Bind the value produced by the computation, appending the steps of the computation built by `f`.
+  pub fn and_then<'a, B, F>(self, f: F) -> Timed<<M::Kind as Kind<'a>>::Of<B>>
+  where
+    M: Monad<'a>,
+    B: 'a,
+    F: FnOnce(M::Item) -> Timed<<M::Kind as Kind<'a>>::Of<B>> + 'a,
+  {
+    let continued = Rc::new(Cell::new(Vec::new()));
+    let continued_ = continued.clone();
+    let inner = self.inner.and_then::<B, _>(move |a| {
+      let r = f(a);
+      continued_.set(r.steps);
+      r.inner
+    });
+    let mut steps = self.steps;
+    steps.append(&mut continued.take());
+
+    Timed { inner, steps }
+  }
+}
+
+impl<A, M> Lift<A> for Timed<M>
+where
+  M: Lift<A>,
+{
+  fn lift(a: A) -> Self {
+    Timed {
+      inner: M::lift(a),
+      steps: Vec::new(),
+    }
+  }
+}
+
+/// [`Kind`] of `Timed<K::Of<_>>`.
+#[derive(Debug)]
+pub struct TimedKind<K>(PhantomData<K>);
+
+impl<'a, K> Kind<'a> for TimedKind<K>
+where
+  K: Kind<'a>,
+{
+  type Of<A: 'a> = Timed<K::Of<A>>;
+}
+
+impl<'a, M> Monad<'a> for Timed<M>
+where
+  M: Monad<'a>,
+{
+  type Item = M::Item;
+  type Kind = TimedKind<M::Kind>;
+
+  fn and_then<B, F>(self, f: F) -> Timed<<M::Kind as Kind<'a>>::Of<B>>
+  where
+    B: 'a,
+    F: FnOnce(M::Item) -> Timed<<M::Kind as Kind<'a>>::Of<B>> + 'a,
+  {
+    Timed::and_then::<B, F>(self, f)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::thread;
+
+  #[test]
+  fn timed() {
+    let sleep = |label, ms, r: Option<u64>| {
+      Timed::labeled(label, move || {
+        thread::sleep(Duration::from_millis(ms));
+        r
+      })
+    };
+
+    let r = m! {
+      a <- sleep("a", 20, Some(1));
+      b <- sleep("b", 1, Some(2));
+      return a + b;
+    };
+
+    assert!(r.steps()[0].duration >= Duration::from_millis(20));
+    assert!(r.total() >= Duration::from_millis(21));
+
+    let (r, steps) = r.run();
+    assert_eq!(r, Some(3));
+    assert_eq!(steps.len(), 2);
+
+    let (r, steps) = m! {
+      _ <- sleep("a", 0, None);
+      sleep("b", 0, Some(2))
+    }
+    .run();
+
+    assert_eq!(r, None);
+    assert_eq!(
+      steps.iter().map(|step| step.label).collect::<Vec<_>>(),
+      vec![Some("a")]
+    );
+  }
+}
@@ -0,0 +1,145 @@
+//! Traced do-notation.
+//!
+//! > This module requires the `tracing` feature.
+//!
+//! [`traced_m!`](crate::traced_m) is [`m!`](crate::m) with a [`tracing`] span per statement: the expression of every
+//! bind runs in an `INFO` span named after the binding — `_` for the statements whose value is discarded — so the
+//! logs show which step of a pipeline failed or was slow.
+//!
+//! ```rust
+//! use do_notation::traced_m;
+//!
+//! let r = traced_m! {
+//!   // runs in the `user_id` span
+//!   user_id <- "42".parse::<u32>().ok();
+//!   // runs in the `score` span
+//!   score <- Some(user_id * 10);
+//!   return score;
+//! };
+//!
+//! assert_eq!(r, Some(420));
+//! ```
+//!
+//! The spans only cover the construction of the computations: for monads running their computations later, like
+//! [`State`](crate::state::State), they don’t cover the actual work.
+
+#[doc(hidden)]
+pub use tracing;
+
+/// Traced monadic do-notation.
+///
+/// The syntax is the one of [`m!`](crate::m), without the `lift`, `for`, `tell` and `catch` statements. See the
+/// [`traced`](crate::traced) module.
+#[macro_export]
+macro_rules! traced_m {
+  // return
+  (return $r:expr ;) => {
+    $crate::Lift::lift($r)
+  };
+
+  // let-binding
+  (let $p:pat = $e:expr ; $($r:tt)*) => {{
+    let $p = $e;
+    $crate::traced_m!($($r)*)
+  }};
+
+  // const-bind
+  (_ <- $x:expr ; $($r:tt)*) => {
+    $crate::traced_m!(@span "_", $x).and_then(move |_| { $crate::traced_m!($($r)*) })
+  };
+
+  // bind
+  ($binding:ident <- $x:expr ; $($r:tt)*) => {
+    $crate::traced_m!(@span stringify!($binding), $x).and_then(move |$binding| { $crate::traced_m!($($r)*) })
+  };
+
+  // const-bind
+  ($e:expr ; $($r:tt)*) => {
+    $crate::traced_m!(@span "_", $e).and_then(move |_| { $crate::traced_m!($($r)*) })
+  };
+
+  (@span $name:expr, $x:expr) => {{
+    let _span = $crate::traced::tracing::info_span!($name).entered();
+    $x
+  }};
+
+  // pure
+  ($a:expr) => {
+    $a
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::{Arc, Mutex};
+  use tracing::{
+    span::{Attributes, Id, Record},
+    subscriber, Event, Metadata, Subscriber,
+  };
+
+  /// Subscriber logging the names of the spans, and the name of the span each event happens in.
+  #[derive(Clone, Default)]
+  struct Log {
+    spans: Arc<Mutex<Vec<&'static str>>>,
+    current: Arc<Mutex<Vec<u64>>>,
+    log: Arc<Mutex<Vec<Option<&'static str>>>>,
+  }
+
+  impl Subscriber for Log {
+    fn enabled(&self, _: &Metadata) -> bool {
+      true
+    }
+
+    fn new_span(&self, span: &Attributes) -> Id {
+      let mut spans = self.spans.lock().unwrap();
+      spans.push(span.metadata().name());
+      Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event) {
+      let spans = self.spans.lock().unwrap();
+      let span = self
+        .current
+        .lock()
+        .unwrap()
+        .last()
+        .map(|id| spans[*id as usize - 1]);
+      self.log.lock().unwrap().push(span);
+    }
+
+    fn enter(&self, span: &Id) {
+      self.current.lock().unwrap().push(span.into_u64());
+    }
+
+    fn exit(&self, _: &Id) {
+      self.current.lock().unwrap().pop();
+    }
+  }
+
+  #[test]
+  fn traced_m() {
+    let log = Log::default();
+
+    let r = subscriber::with_default(log.clone(), || {
+      traced_m! {
+        x <- { tracing::info!("x"); Some(1) };
+        let y = x + 1;
+        _ <- Some(y);
+        z <- { tracing::info!("z"); Some(x + y) };
+        { tracing::info!("none"); None::<()> };
+        return z;
+      }
+    });
+
+    assert_eq!(r, None);
+    assert_eq!(*log.spans.lock().unwrap(), vec!["x", "_", "z", "_"]);
+    assert_eq!(
+      *log.log.lock().unwrap(),
+      vec![Some("x"), Some("z"), Some("_")]
+    );
+  }
+}
@@ -0,0 +1,148 @@
+//! Monad transformers.
+//!
+//! A monad transformer adds an effect on top of an inner monad — e.g.
+//! [`ReaderT`](crate::reader::ReaderT) adds an environment. All transformers implement
+//! [`MonadTrans`], which allows to [`lift`] computations from the inner monad.
+//!
+//! In [`m!`](crate::m), `lift` can be used in front of an expression to lift it:
+//!
+//! ```rust
+//! use do_notation::{m, monad::OptionKind, reader::{ask, run_reader_t}};
+//!
+//! let r = m! {
+//!   x <- ask::<i32, OptionKind>();
+//!   y <- lift Some(10);
+//!   return x * y;
+//! };
+//!
+//! assert_eq!(run_reader_t(r, 3), Some(30));
+//! ```
+//!
+//! ## Capabilities
+//!
+//! Lifting by hand is rarely needed, though: the capabilities of the inner monads pass through every transformer,
+//! so that the [`Kind`] of a whole stack can be used directly to reach an effect anywhere in it. Each of
+//! [`MonadReader`](crate::reader::MonadReader), [`MonadState`](crate::state::MonadState),
+//! [`MonadWriter`](crate::writer::MonadWriter), [`MonadError`](crate::result::MonadError) and
+//! [`MonadIo`](crate::io::MonadIo) is implemented by the transformer providing it, and by
+//! [`ReaderT`](crate::reader::ReaderT), [`StateT`](crate::state::StateT), [`WriterT`](crate::writer::WriterT) and
+//! [`ResultT`](crate::result::ResultT) over a monad implementing it. [`ContT`](crate::cont::ContT) passes through
+//! [`MonadReader`](crate::reader::MonadReader), [`MonadState`](crate::state::MonadState) and
+//! [`MonadIo`](crate::io::MonadIo).
+//!
+//! ```rust
+//! use do_notation::{
+//!   io::{IoKind, MonadIo},
+//!   m,
+//!   reader::{run_reader_t, MonadReader, ReaderTKind},
+//!   result::{run_result_t, MonadError, ResultTKind},
+//!   state::{run_state_t, MonadState, StateTKind},
+//! };
+//!
+//! type App = ReaderTKind<i32, StateTKind<i32, ResultTKind<String, IoKind>>>;
+//!
+//! let r = m! {
+//!   step <- App::ask();
+//!   App::modify(move |n| n + step);
+//!   n <- App::get();
+//!   App::lift_io(move || println!("n = {}", n));
+//!   if n > 10 { App::throw_error(format!("{} is too big", n)) } else { App::get() }
+//! };
+//!
+//! assert_eq!(run_result_t(run_state_t(run_reader_t(r, 2), 1)).run(), Ok((3, 3)));
+//! ```
+//!
+//! Two layers providing the same capability — e.g. two [`StateT`](crate::state::StateT) — can’t both be reached
+//! this way: only the outermost one is, and the inner one must be reached with [`lift`].
+
+use crate::monad::{Kind, Monad};
+
+/// Monad transformers over the inner monad `M`.
+///
+/// Because every monad implements [`Lift`](crate::Lift), which has a method of the same name,
+/// [`MonadTrans::lift`] must be called with its fully-qualified path. You will find the [`lift`]
+/// function more convenient.
+pub trait MonadTrans<'a, M>: Monad<'a, Item = M::Item>
+where
+  M: Monad<'a>,
+{
+  /// Lift a computation from the inner monad.
+  fn lift(m: M) -> Self;
+}
+
+/// Lift a computation from the inner monad.
+pub fn lift<'a, T, M>(m: M) -> T
+where
+  T: MonadTrans<'a, M>,
+  M: Monad<'a>,
+{
+  <T as MonadTrans<'a, M>>::lift(m)
+}
+
+/// Lift a computation from the inner monad and bind its value to `f`.
+///
+/// This is what the `x <- lift expr;` statement desugars to in [`m!`](crate::m). Unlike
+/// `lift(m).and_then(f)`, the transformer doesn’t have to be known before `f` is.
+pub fn lift_and_then<'a, M, N, F>(m: M, f: F) -> N
+where
+  M: Monad<'a>,
+  N: Monad<'a>,
+  <N::Kind as Kind<'a>>::Of<M::Item>: MonadTrans<'a, M>,
+  F: FnOnce(M::Item) -> N + 'a,
+{
+  <<N::Kind as Kind<'a>>::Of<M::Item> as MonadTrans<'a, M>>::lift(m).and_then::<N::Item, F>(f)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    identity::IdentityKind,
+    monad::ResultKind,
+    reader::{ask, run_reader_t, MonadReader, ReaderTKind},
+    result::{run_result_t, MonadError, ResultTKind},
+    state::{run_state_t, MonadState, StateTKind},
+    writer::{run_writer, WriterTKind},
+  };
+
+  #[test]
+  fn lift_statements() {
+    let r = m! {
+      x <- ask::<i32, ResultKind<&str>>();
+      lift if x > 0 { Ok(()) } else { Err("negative") };
+      lift Ok(x + 1)
+    };
+
+    assert_eq!(run_reader_t(r, 1), Ok(2));
+
+    let r = m! {
+      x <- ask::<i32, ResultKind<&str>>();
+      lift if x > 0 { Ok(()) } else { Err("negative") };
+      lift Ok(x + 1)
+    };
+
+    assert_eq!(run_reader_t(r, -1), Err("negative"));
+  }
+
+  #[test]
+  fn capabilities() {
+    type Stack = ReaderTKind<
+      i32,
+      StateTKind<i32, ResultTKind<&'static str, WriterTKind<Vec<i32>, IdentityKind>>>,
+    >;
+
+    let r = m! {
+      x <- Stack::ask();
+      tell vec![x];
+      y <- Stack::get();
+      tell vec![y];
+      Stack::put(x + y);
+      z <- Stack::catch_error(Stack::throw_error("nope"), |_| Stack::asks(|x| x * 10));
+      return z;
+    };
+
+    assert_eq!(
+      run_writer(run_result_t(run_state_t(run_reader_t(r, 1), 2))),
+      (Ok((10, 3)), vec![1, 2])
+    );
+  }
+}
@@ -0,0 +1,165 @@
+//! Traversable containers.
+//!
+//! A [`Traversable`] container can run an effect for each of its elements, in order, and collect the results into a
+//! container of the same shape, in any [`Applicative`]:
+//!
+//! ```rust
+//! use do_notation::{monad::{OptionKind, ResultKind}, traversable::{sequence, Traversable}};
+//!
+//! let parse = |s: &str| s.parse::<i32>().map_err(|_| format!("{:?} is not a number", s));
+//!
+//! assert_eq!(vec!["1", "2"].traverse::<ResultKind<_>, _>(parse), Ok(vec![1, 2]));
+//! assert_eq!(vec!["1", "a"].traverse::<ResultKind<_>, _>(parse), Err("\"a\" is not a number".to_owned()));
+//! assert_eq!(sequence::<OptionKind, _, _>(Some(Some(1))), Some(Some(1)));
+//! ```
+
+use crate::{applicative::Applicative, non_empty::NonEmpty};
+
+/// Containers of elements of type [`Traversable::Item`] whose elements can be traversed with an effect.
+pub trait Traversable<'a>: Sized + 'a {
+  /// Type of the elements.
+  type Item: 'a;
+
+  /// Same container, with elements of type `B`.
+  type Of<B: 'a>: 'a;
+
+  /// Run `f` on every element, collecting the results in the applicative `F`.
+  fn traverse<F, B>(self, f: impl FnMut(Self::Item) -> F::Of<B>) -> F::Of<Self::Of<B>>
+  where
+    F: Applicative<'a>,
+    B: 'a;
+}
+
+/// Run the effect of every element, collecting the results in the applicative `F`.
+pub fn sequence<'a, F, T, B>(t: T) -> F::Of<T::Of<B>>
+where
+  F: Applicative<'a>,
+  T: Traversable<'a, Item = F::Of<B>>,
+  B: 'a,
+{
+  t.traverse::<F, B>(|fb| fb)
+}
+
+impl<'a, A: 'a> Traversable<'a> for Vec<A> {
+  type Item = A;
+  type Of<B: 'a> = Vec<B>;
+
+  fn traverse<F, B>(self, mut f: impl FnMut(A) -> F::Of<B>) -> F::Of<Vec<B>>
+  where
+    F: Applicative<'a>,
+    B: 'a,
+  {
+    let len = self.len();
+
+    self
+      .into_iter()
+      .fold(F::pure(Vec::with_capacity(len)), |acc, a| {
+        F::zip_with(acc, f(a), |mut v, b| {
+          v.push(b);
+          v
+        })
+      })
+  }
+}
+
+impl<'a, A: 'a> Traversable<'a> for Option<A> {
+  type Item = A;
+  type Of<B: 'a> = Option<B>;
+
+  fn traverse<F, B>(self, mut f: impl FnMut(A) -> F::Of<B>) -> F::Of<Option<B>>
+  where
+    F: Applicative<'a>,
+    B: 'a,
+  {
+    match self {
+      Some(a) => F::map(f(a), Some),
+      None => F::pure(None),
+    }
+  }
+}
+
+impl<'a, A: 'a, E: 'a> Traversable<'a> for Result<A, E> {
+  type Item = A;
+  type Of<B: 'a> = Result<B, E>;
+
+  fn traverse<F, B>(self, mut f: impl FnMut(A) -> F::Of<B>) -> F::Of<Result<B, E>>
+  where
+    F: Applicative<'a>,
+    B: 'a,
+  {
+    match self {
+      Ok(a) => F::map(f(a), Ok),
+      Err(e) => F::pure(Err(e)),
+    }
+  }
+}
+
+impl<'a, A: 'a> Traversable<'a> for NonEmpty<A> {
+  type Item = A;
+  type Of<B: 'a> = NonEmpty<B>;
+
+  fn traverse<F, B>(self, mut f: impl FnMut(A) -> F::Of<B>) -> F::Of<NonEmpty<B>>
+  where
+    F: Applicative<'a>,
+    B: 'a,
+  {
+    let head = F::map(f(self.head), NonEmpty::new);
+
+    self.tail.into_iter().fold(head, |acc, a| {
+      F::zip_with(acc, f(a), |mut v, b| {
+        v.push(b);
+        v
+      })
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    identity::IdentityKind,
+    monad::OptionKind,
+    state::{run_state, state, StateTKind},
+  };
+
+  #[test]
+  fn traverse() {
+    let half = |n: i32| if n % 2 == 0 { Some(n / 2) } else { None };
+
+    assert_eq!(vec![2, 4].traverse::<OptionKind, _>(half), Some(vec![1, 2]));
+    assert_eq!(vec![2, 3].traverse::<OptionKind, _>(half), None);
+    assert_eq!(None.traverse::<OptionKind, _>(half), Some(None));
+    assert_eq!(
+      Err::<i32, ()>(()).traverse::<OptionKind, _>(half),
+      Some(Err(()))
+    );
+    assert_eq!(
+      NonEmpty::from_vec(vec![2, 4])
+        .unwrap()
+        .traverse::<OptionKind, _>(half),
+      NonEmpty::from_vec(vec![1, 2])
+    );
+  }
+
+  #[test]
+  fn traverse_state() {
+    type S = StateTKind<i32, IdentityKind>;
+    let number = |c: char| state(move |n| ((n, c), n + 1));
+
+    let r = vec!['a', 'b', 'c'].traverse::<S, _>(number);
+    assert_eq!(run_state(r, 0), (vec![(0, 'a'), (1, 'b'), (2, 'c')], 3));
+  }
+
+  #[test]
+  fn sequence() {
+    assert_eq!(
+      super::sequence::<OptionKind, _, _>(vec![Some(1), Some(2)]),
+      Some(vec![1, 2])
+    );
+    assert_eq!(
+      super::sequence::<OptionKind, _, _>(vec![Some(1), None]),
+      None
+    );
+  }
+}
@@ -0,0 +1,214 @@
+//! Database transactions.
+//!
+//! > This module requires the `sqlx` feature.
+//!
+//! [`Tx`] is the monad of computations running in a [`sqlx`] transaction. Binding a computation runs it in the same
+//! transaction as the rest of the block, and [running](Tx::run) the block commits the transaction if it succeeds, or
+//! rolls it back if it fails — including with errors of your own, as long as they can be built from
+//! [`sqlx::Error`].
+//!
+//! ```rust,no_run
+//! use do_notation::{m, tx::Tx, Lift};
+//! use sqlx::{Sqlite, SqlitePool};
+//!
+//! fn transfer(from: i64, to: i64, amount: i64) -> Tx<'static, Sqlite, ()> {
+//!   m! {
+//!     balance <- Tx::new(move |tx| Box::pin(async move {
+//!       sqlx::query_scalar::<_, i64>("SELECT balance FROM accounts WHERE id = ?")
+//!         .bind(from)
+//!         .fetch_one(&mut **tx)
+//!         .await
+//!     }));
+//!     if balance < amount { Tx::throw(sqlx::Error::Protocol("insufficient balance".into())) } else { Tx::lift(()) };
+//!     _ <- Tx::new(move |tx| Box::pin(async move {
+//!       sqlx::query("UPDATE accounts SET balance = balance - ? WHERE id = ?")
+//!         .bind(amount)
+//!         .bind(from)
+//!         .execute(&mut **tx)
+//!         .await
+//!     }));
+//!     _ <- Tx::new(move |tx| Box::pin(async move {
+//!       sqlx::query("UPDATE accounts SET balance = balance + ? WHERE id = ?")
+//!         .bind(amount)
+//!         .bind(to)
+//!         .execute(&mut **tx)
+//!         .await
+//!     }));
+//!     return ();
+//!   }
+//! }
+//!
+//! # async fn run(pool: SqlitePool) -> Result<(), sqlx::Error> {
+//! // both accounts are updated, or none of them
+//! transfer(1, 2, 100).run(&pool).await
+//! # }
+//! ```
+
+use crate::Lift;
+use sqlx::{Database, Pool, Transaction};
+use std::{future::Future, pin::Pin};
+
+/// A boxed, sendable future.
+pub type BoxFuture<'a, A> = Pin<Box<dyn Future<Output = A> + Send + 'a>>;
+
+/// A computation running in a transaction of the database `DB`, producing a value of type `A` or failing with an
+/// error of type `E`.
+#[allow(clippy::type_complexity)]
+pub struct Tx<'c, DB: Database, A, E = sqlx::Error>(
+  Box<dyn for<'t> FnOnce(&'t mut Transaction<'c, DB>) -> BoxFuture<'t, Result<A, E>> + Send + 'c>,
+);
+
+impl<'c, DB, A, E> Tx<'c, DB, A, E>
+where
+  DB: Database,
+  A: 'c,
+  E: 'c,
+{
+  /// Build a computation out of a function running queries in the transaction.
+  pub fn new<F>(f: F) -> Self
+  where
+    F: for<'t> FnOnce(&'t mut Transaction<'c, DB>) -> BoxFuture<'t, Result<A, E>> + Send + 'c,
+  {
+    Tx(Box::new(f))
+  }
+
+  /// Fail with `e`, rolling the transaction back.
+  pub fn throw(e: E) -> Self
+  where
+    A: Send,
+    E: Send,
+  {
+    Tx::new(move |_| Box::pin(async move { Err(e) }))
+  }
+
+  /// Run this computation, then the one built by `f` with its value, in the same transaction.
+  pub fn and_then<B, F>(self, f: F) -> Tx<'c, DB, B, E>
+  where
+    B: 'c,
+    F: FnOnce(A) -> Tx<'c, DB, B, E> + Send + 'c,
+  {
+    Tx::new(move |tx| {
+      Box::pin(async move {
+        let a = (self.0)(&mut *tx).await?;
+        (f(a).0)(tx).await
+      })
+    })
+  }
+
+  /// Run the computation in a transaction begun on `pool`.
+  ///
+  /// The transaction is committed if the computation succeeds, and rolled back if it fails. The error of the
+  /// computation is returned even if the rollback fails: the connection then discards the transaction anyway.
+  pub async fn run(self, pool: &Pool<DB>) -> Result<A, E>
+  where
+    E: From<sqlx::Error>,
+  {
+    let mut tx: Transaction<'c, DB> = pool.begin().await?;
+
+    match (self.0)(&mut tx).await {
+      Ok(a) => {
+        tx.commit().await?;
+        Ok(a)
+      }
+
+      Err(e) => {
+        let _ = tx.rollback().await;
+        Err(e)
+      }
+    }
+  }
+
+  /// Run the computation in an existing transaction, which is neither committed nor rolled back.
+  pub async fn run_in(self, tx: &mut Transaction<'c, DB>) -> Result<A, E> {
+    (self.0)(tx).await
+  }
+}
+
+impl<'c, DB, A, E> Lift<A> for Tx<'c, DB, A, E>
+where
+  DB: Database,
+  A: Send + 'c,
+  E: 'c,
+{
+  fn lift(a: A) -> Self {
+    Tx::new(move |_| Box::pin(async move { Ok(a) }))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use sqlx::{sqlite::SqlitePoolOptions, Sqlite, SqlitePool};
+
+  fn insert(name: &'static str) -> Tx<'static, Sqlite, ()> {
+    Tx::new(move |tx| {
+      Box::pin(async move {
+        sqlx::query("INSERT INTO users (name) VALUES (?)")
+          .bind(name)
+          .execute(&mut **tx)
+          .await?;
+        Ok(())
+      })
+    })
+  }
+
+  fn count() -> Tx<'static, Sqlite, i64> {
+    Tx::new(|tx| Box::pin(sqlx::query_scalar("SELECT COUNT(*) FROM users").fetch_one(&mut **tx)))
+  }
+
+  async fn users(pool: &SqlitePool) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM users")
+      .fetch_one(pool)
+      .await
+      .unwrap()
+  }
+
+  #[test]
+  fn tx() {
+    let rt = tokio::runtime::Builder::new_current_thread()
+      .enable_time()
+      .build()
+      .unwrap();
+
+    rt.block_on(async {
+      let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+      sqlx::query("CREATE TABLE users (name TEXT NOT NULL)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+      let r = m! {
+        insert("alice");
+        insert("bob");
+        n <- count();
+        if n > 1 { Tx::throw(sqlx::Error::RowNotFound) } else { Tx::lift(n) }
+      };
+
+      assert!(matches!(r.run(&pool).await, Err(sqlx::Error::RowNotFound)));
+      assert_eq!(users(&pool).await, 0);
+
+      let r = m! {
+        insert("alice");
+        n <- count();
+        return n;
+      };
+
+      assert_eq!(r.run(&pool).await.unwrap(), 1);
+      assert_eq!(users(&pool).await, 1);
+
+      // the transaction is over when rolling back, which fails
+      let r = m! {
+        insert("bob");
+        Tx::new(|tx| Box::pin(sqlx::query("ROLLBACK").execute(&mut **tx)));
+        Tx::<_, ()>::throw(sqlx::Error::RowNotFound)
+      };
+
+      assert!(matches!(r.run(&pool).await, Err(sqlx::Error::RowNotFound)));
+      assert_eq!(users(&pool).await, 1);
+    });
+  }
+}
@@ -0,0 +1,240 @@
+//! Validations accumulating errors.
+//!
+//! [`Validation<E, A>`] is like `Result<A, E>`, except that combining two invalid values keeps the errors of both,
+//! in a [`NonEmpty`] vector. It is an [`Applicative`] — through [`ValidationKind`] — but not a monad: a validation
+//! can’t depend on the value of another one, so they are all checked, instead of stopping at the first failure.
+//!
+//! The [`validate!`](crate::validate) block binds independent validations, or [`Result`]s, and combines their values
+//! once they are all valid:
+//!
+//! ```rust
+//! use do_notation::validate;
+//!
+//! fn port(s: &str) -> Result<u16, String> {
+//!   s.parse().map_err(|_| format!("invalid port {:?}", s))
+//! }
+//!
+//! fn host(s: &str) -> Result<&str, String> {
+//!   if s.is_empty() { Err("empty host".to_owned()) } else { Ok(s) }
+//! }
+//!
+//! let r = validate! {
+//!   h <- host("localhost");
+//!   p <- port("8080");
+//!   return format!("{}:{}", h, p);
+//! };
+//!
+//! assert_eq!(r.into_result(), Ok("localhost:8080".to_owned()));
+//!
+//! let r = validate! {
+//!   h <- host("");
+//!   p <- port("80x");
+//!   return format!("{}:{}", h, p);
+//! };
+//!
+//! let errors = r.into_result().unwrap_err();
+//! assert_eq!(Vec::from(errors), vec!["empty host".to_owned(), "invalid port \"80x\"".to_owned()]);
+//! ```
+
+use crate::{
+  applicative::{Applicative, Applied, Functor},
+  non_empty::NonEmpty,
+  Lift,
+};
+use std::marker::PhantomData;
+
+/// Values of type `A`, or the errors of type `E` explaining why there’s none.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Validation<E, A> {
+  /// Valid value.
+  Valid(A),
+  /// Errors.
+  Invalid(NonEmpty<E>),
+}
+
+impl<E, A> Validation<E, A> {
+  /// Invalid value, because of `e`.
+  pub fn invalid(e: E) -> Self {
+    Validation::Invalid(NonEmpty::new(e))
+  }
+
+  /// Whether the value is valid.
+  pub fn is_valid(&self) -> bool {
+    matches!(self, Validation::Valid(_))
+  }
+
+  /// Apply `f` to the value, if valid.
+  pub fn map<B>(self, f: impl FnOnce(A) -> B) -> Validation<E, B> {
+    match self {
+      Validation::Valid(a) => Validation::Valid(f(a)),
+      Validation::Invalid(es) => Validation::Invalid(es),
+    }
+  }
+
+  /// Combine the values of `self` and `other` with `f`, or the errors of both.
+  pub fn zip_with<B, C>(
+    self,
+    other: Validation<E, B>,
+    f: impl FnOnce(A, B) -> C,
+  ) -> Validation<E, C> {
+    match (self, other) {
+      (Validation::Valid(a), Validation::Valid(b)) => Validation::Valid(f(a, b)),
+      (Validation::Valid(_), Validation::Invalid(es))
+      | (Validation::Invalid(es), Validation::Valid(_)) => Validation::Invalid(es),
+      (Validation::Invalid(mut es), Validation::Invalid(es_)) => {
+        es.append(es_);
+        Validation::Invalid(es)
+      }
+    }
+  }
+
+  /// Convert to a [`Result`].
+  pub fn into_result(self) -> Result<A, NonEmpty<E>> {
+    match self {
+      Validation::Valid(a) => Ok(a),
+      Validation::Invalid(es) => Err(es),
+    }
+  }
+}
+
+impl<E, A> From<Result<A, E>> for Validation<E, A> {
+  fn from(r: Result<A, E>) -> Self {
+    match r {
+      Ok(a) => Validation::Valid(a),
+      Err(e) => Validation::invalid(e),
+    }
+  }
+}
+
+impl<E, A> Lift<A> for Validation<E, A> {
+  fn lift(a: A) -> Self {
+    Validation::Valid(a)
+  }
+}
+
+/// [`Applicative`] of `Validation<E, _>`.
+#[derive(Debug)]
+pub struct ValidationKind<E>(PhantomData<E>);
+
+impl<'a, E: 'a> Functor<'a> for ValidationKind<E> {
+  type Of<A: 'a> = Validation<E, A>;
+
+  fn map<A: 'a, B: 'a>(fa: Validation<E, A>, f: impl FnOnce(A) -> B + 'a) -> Validation<E, B> {
+    fa.map(f)
+  }
+}
+
+impl<'a, E: 'a> Applicative<'a> for ValidationKind<E> {
+  fn pure<A: 'a>(a: A) -> Validation<E, A> {
+    Validation::Valid(a)
+  }
+
+  fn zip_with<A: 'a, B: 'a, C: 'a>(
+    fa: Validation<E, A>,
+    fb: Validation<E, B>,
+    f: impl FnOnce(A, B) -> C + 'a,
+  ) -> Validation<E, C> {
+    fa.zip_with(fb, f)
+  }
+}
+
+impl<'a, E: 'a, A: 'a> Applied<'a> for Validation<E, A> {
+  type Item = A;
+  type Kind = ValidationKind<E>;
+}
+
+/// Applicative do-notation for [`Validation`]s.
+///
+/// The block is made of binds — `x <- <expr>;`, `_ <- <expr>;` or `<expr>;` — of [`Validation`]s or [`Result`]s,
+/// ending with `return <expr>;`. The bound computations are independent: they can’t use the values bound before
+/// them. They are all checked, in order, and the block is valid with the value of the returned expression if they
+/// are all valid, or invalid with all their errors otherwise. See the [`validation`](crate::validation) module.
+#[macro_export]
+macro_rules! validate {
+  (@acc [$($acc:tt)*] $p:tt return $r:expr ;) => {
+    $crate::validation::Validation::map($($acc)*, move |$p| $r)
+  };
+
+  (@acc [$($acc:tt)*] $p:tt _ <- $x:expr ; $($t:tt)*) => {
+    $crate::validate!(
+      @acc [$crate::validation::Validation::zip_with($($acc)*, $crate::validation::Validation::from($x), |a, b| (a, b))]
+      ($p, _) $($t)*
+    )
+  };
+
+  (@acc [$($acc:tt)*] $p:tt $binding:ident <- $x:expr ; $($t:tt)*) => {
+    $crate::validate!(
+      @acc [$crate::validation::Validation::zip_with($($acc)*, $crate::validation::Validation::from($x), |a, b| (a, b))]
+      ($p, $binding) $($t)*
+    )
+  };
+
+  (@acc [$($acc:tt)*] $p:tt $binding:tt <- $($t:tt)*) => {
+    compile_error!(concat!("malformed bind in a `validate!` block: `", stringify!($binding <- $($t)*), "`"))
+  };
+
+  (@acc [$($acc:tt)*] $p:tt $x:expr ; $($t:tt)*) => {
+    $crate::validate!(@acc [$($acc)*] $p _ <- $x ; $($t)*)
+  };
+
+  (@acc [$($acc:tt)*] $p:tt $($t:tt)*) => {
+    compile_error!("malformed `validate!` block: expected `x <- v;` or `v;` statements, followed by `return e;`")
+  };
+
+  ($($t:tt)*) => {
+    $crate::validate!(@acc [$crate::validation::Validation::Valid(())] () $($t)*)
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn positive(n: i32) -> Validation<String, i32> {
+    if n > 0 {
+      Validation::Valid(n)
+    } else {
+      Validation::invalid(format!("{} isn’t positive", n))
+    }
+  }
+
+  #[test]
+  fn accumulate() {
+    let r = validate! {
+      a <- positive(1);
+      b <- positive(2);
+      return a + b;
+    };
+
+    assert_eq!(r, Validation::Valid(3));
+
+    let r = validate! {
+      a <- positive(-1);
+      positive(2);
+      _ <- Err::<(), _>("nope".to_owned());
+      b <- positive(-3);
+      return a + b;
+    };
+
+    let errors = Vec::from(r.into_result().unwrap_err());
+    assert_eq!(
+      errors,
+      vec!["-1 isn’t positive", "nope", "-3 isn’t positive"]
+    );
+  }
+
+  #[test]
+  fn applicative() {
+    type V = ValidationKind<&'static str>;
+
+    let r = V::zip(
+      Validation::<_, ()>::invalid("a"),
+      V::map(Validation::invalid("b"), |n: i32| n + 1),
+    );
+    assert_eq!(Vec::from(r.into_result().unwrap_err()), vec!["a", "b"]);
+    assert_eq!(
+      V::zip_with(V::pure(1), V::pure(2), |a, b| a + b),
+      Validation::Valid(3)
+    );
+  }
+}
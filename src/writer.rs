@@ -0,0 +1,490 @@
+//! The writer monad and monad transformer.
+//!
+//! [`WriterT<W, M, A>`] accumulates an output of type `W` along the statements of a do-block, in
+//! the monad `M`. The output must be a [`Monoid`]. [`Writer<W, A>`] is the pure version, over
+//! [`Identity`](crate::identity::Identity).
+//!
+//! In [`m!`](crate::m), the `tell <expr>;` statement appends to the output of any monad stack
+//! implementing [`MonadWriter`]:
+//!
+//! ```rust
+//! use do_notation::{m, writer::{run_writer, Writer}};
+//!
+//! fn half(n: u32) -> Writer<'static, Vec<String>, u32> {
+//!   m! {
+//!     tell vec![format!("halving {}", n)];
+//!     return n / 2;
+//!   }
+//! }
+//!
+//! let r = m! {
+//!   a <- half(12);
+//!   b <- half(a);
+//!   return b;
+//! };
+//!
+//! assert_eq!(run_writer(r), (3, vec!["halving 12".to_owned(), "halving 6".to_owned()]));
+//! ```
+//!
+//! The output of a sub-block can be scoped: `x <- listen <expr>;` binds the value of `<expr>` along with its output,
+//! and `x <- censor <expr> => <f>;` binds the value of `<expr>`, transforming its output with `<f>` — e.g. to filter
+//! or prefix its logs:
+//!
+//! ```rust
+//! use do_notation::{m, writer::{run_writer, Writer}};
+//!
+//! fn step(name: &'static str) -> Writer<'static, Vec<String>, ()> {
+//!   m! {
+//!     tell vec![format!("debug: {} started", name)];
+//!     tell vec![format!("info: {} done", name)];
+//!     return ();
+//!   }
+//! }
+//!
+//! let r = m! {
+//!   logs <- listen step("fetch");
+//!   censor step("parse") => |logs: Vec<String>| logs.into_iter().filter(|l| l.starts_with("info")).collect();
+//!   return logs.1.len();
+//! };
+//!
+//! assert_eq!(
+//!   run_writer(r),
+//!   (2, vec!["debug: fetch started".to_owned(), "info: fetch done".to_owned(), "info: parse done".to_owned()])
+//! );
+//! ```
+//!
+//! The type of the computation bound with `listen` must be known on its own — e.g. the return type of a function —
+//! so that the type of its output is known by the rest of the block. [`pass`] is the general form of `censor`, the
+//! output transformation being produced by the computation itself.
+
+use crate::{
+  identity::IdentityKind,
+  monad::{Kind, Monad},
+  monoid::Monoid,
+  reader::{run_reader_t, ReaderT, ReaderTKind},
+  state::{run_state_t, StateT, StateTKind},
+  trans::MonadTrans,
+  Lift,
+};
+use std::marker::PhantomData;
+
+/// Computations accumulating an output of type `W` in the monad `M`, producing a value of type
+/// `A`.
+pub struct WriterT<'a, W: 'a, M: Kind<'a>, A: 'a> {
+  run: M::Of<(A, W)>,
+}
+
+/// Pure computations accumulating an output of type `W`.
+pub type Writer<'a, W, A> = WriterT<'a, W, IdentityKind, A>;
+
+impl<'a, W, M, A> WriterT<'a, W, M, A>
+where
+  W: Monoid + 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  /// Create a computation from a value and its output in the inner monad.
+  pub fn new(m: M::Of<(A, W)>) -> Self {
+    WriterT { run: m }
+  }
+
+  /// Bind the value produced by this computation, appending the outputs.
+  pub fn and_then<B, F>(self, f: F) -> WriterT<'a, W, M, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> WriterT<'a, W, M, B> + 'a,
+  {
+    WriterT {
+      run: self.run.and_then(move |(a, w)| {
+        f(a)
+          .run
+          .and_then(move |(b, w_)| Lift::lift((b, w.combine(w_))))
+      }),
+    }
+  }
+}
+
+impl<'a, W, M, A> Lift<A> for WriterT<'a, W, M, A>
+where
+  W: Monoid + 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  fn lift(a: A) -> Self {
+    writer((a, W::empty()))
+  }
+}
+
+/// [`Kind`] of `WriterT<'_, W, M, _>`.
+#[derive(Debug)]
+pub struct WriterTKind<W, M>(PhantomData<(W, M)>);
+
+impl<'a, W, M> Kind<'a> for WriterTKind<W, M>
+where
+  W: Monoid + 'a,
+  M: Kind<'a>,
+{
+  type Of<A: 'a> = WriterT<'a, W, M, A>;
+}
+
+impl<'a, W, M, A> Monad<'a> for WriterT<'a, W, M, A>
+where
+  W: Monoid + 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  type Item = A;
+  type Kind = WriterTKind<W, M>;
+
+  fn and_then<B, F>(self, f: F) -> WriterT<'a, W, M, B>
+  where
+    B: 'a,
+    F: FnOnce(A) -> WriterT<'a, W, M, B> + 'a,
+  {
+    WriterT::and_then(self, f)
+  }
+}
+
+impl<'a, W, M> MonadTrans<'a, M> for WriterT<'a, W, M::Kind, M::Item>
+where
+  W: Monoid + 'a,
+  M: Monad<'a>,
+{
+  fn lift(m: M) -> Self {
+    WriterT {
+      run: m.and_then::<(M::Item, W), _>(|a| Lift::lift((a, W::empty()))),
+    }
+  }
+}
+
+/// Monads accumulating an output of type `W`.
+///
+/// This trait is implemented by the [`Kind`] of the monads, so that functions can be
+/// written against any monad with an output.
+pub trait MonadWriter<'a, W: 'a>: Kind<'a> {
+  /// Append `w` to the output.
+  fn tell(w: W) -> Self::Of<()>;
+
+  /// Run `m`, producing its output alongside its value.
+  fn listen<A: 'a>(m: Self::Of<A>) -> Self::Of<(A, W)>;
+
+  /// Run `m`, modifying its output with the function it produces alongside its value.
+  fn pass<A: 'a, F: FnOnce(W) -> W + 'a>(m: Self::Of<(A, F)>) -> Self::Of<A>;
+
+  /// Run `m`, modifying its output with `f`.
+  fn censor<A: 'a, F: FnOnce(W) -> W + 'a>(m: Self::Of<A>, f: F) -> Self::Of<A> {
+    Self::pass(m.and_then::<(A, F), _>(move |a| Lift::lift((a, f))))
+  }
+}
+
+impl<'a, W, M> MonadWriter<'a, W> for WriterTKind<W, M>
+where
+  W: Monoid + Clone + 'a,
+  M: Kind<'a>,
+{
+  fn tell(w: W) -> WriterT<'a, W, M, ()> {
+    writer(((), w))
+  }
+
+  fn listen<A: 'a>(m: WriterT<'a, W, M, A>) -> WriterT<'a, W, M, (A, W)> {
+    WriterT {
+      run: m
+        .run
+        .and_then::<((A, W), W), _>(|(a, w)| Lift::lift(((a, w.clone()), w))),
+    }
+  }
+
+  fn pass<A: 'a, F: FnOnce(W) -> W + 'a>(m: WriterT<'a, W, M, (A, F)>) -> WriterT<'a, W, M, A> {
+    WriterT {
+      run: m
+        .run
+        .and_then::<(A, W), _>(|((a, f), w)| Lift::lift((a, f(w)))),
+    }
+  }
+}
+
+impl<'a, R, W, M> MonadWriter<'a, W> for ReaderTKind<R, M>
+where
+  R: Clone + 'a,
+  W: 'a,
+  M: MonadWriter<'a, W>,
+{
+  fn tell(w: W) -> ReaderT<'a, R, M, ()> {
+    ReaderT::new(move |_| M::tell(w))
+  }
+
+  fn listen<A: 'a>(m: ReaderT<'a, R, M, A>) -> ReaderT<'a, R, M, (A, W)> {
+    ReaderT::new(move |r| M::listen(run_reader_t(m, r)))
+  }
+
+  fn pass<A: 'a, F: FnOnce(W) -> W + 'a>(m: ReaderT<'a, R, M, (A, F)>) -> ReaderT<'a, R, M, A> {
+    ReaderT::new(move |r| M::pass(run_reader_t(m, r)))
+  }
+}
+
+impl<'a, S, W, M> MonadWriter<'a, W> for StateTKind<S, M>
+where
+  S: 'a,
+  W: 'a,
+  M: MonadWriter<'a, W>,
+{
+  fn tell(w: W) -> StateT<'a, S, M, ()> {
+    StateT::new(move |s| M::tell(w).and_then::<((), S), _>(move |()| Lift::lift(((), s))))
+  }
+
+  fn listen<A: 'a>(m: StateT<'a, S, M, A>) -> StateT<'a, S, M, (A, W)> {
+    StateT::new(move |s| {
+      let m = M::listen(run_state_t(m, s));
+      m.and_then::<((A, W), S), _>(|((a, s), w)| Lift::lift(((a, w), s)))
+    })
+  }
+
+  fn pass<A: 'a, F: FnOnce(W) -> W + 'a>(m: StateT<'a, S, M, (A, F)>) -> StateT<'a, S, M, A> {
+    StateT::new(move |s| {
+      let m = run_state_t(m, s).and_then::<((A, S), F), _>(|((a, f), s)| Lift::lift(((a, s), f)));
+      M::pass(m)
+    })
+  }
+}
+
+/// Append `w` to the output and bind to `f`.
+///
+/// This is what the `tell expr;` statement desugars to in [`m!`](crate::m). The monad stack
+/// doesn’t have to be known before `f` is.
+pub fn tell_and_then<'a, W, N, F>(w: W, f: F) -> N
+where
+  W: 'a,
+  N: Monad<'a>,
+  N::Kind: MonadWriter<'a, W>,
+  F: FnOnce(()) -> N + 'a,
+{
+  <N::Kind as MonadWriter<'a, W>>::tell(w).and_then::<N::Item, F>(f)
+}
+
+/// Run `m`, producing its output alongside its value, and bind to `f`.
+///
+/// This is what the `x <- listen m;` statement desugars to in [`m!`](crate::m). The monad stack
+/// is the one of `m`, so that the type of the output is known before `f` is.
+pub fn listen_and_then<'a, W, M, B, F>(m: M, f: F) -> <M::Kind as Kind<'a>>::Of<B>
+where
+  W: 'a,
+  M: Monad<'a>,
+  M::Kind: MonadWriter<'a, W>,
+  B: 'a,
+  F: FnOnce((M::Item, W)) -> <M::Kind as Kind<'a>>::Of<B> + 'a,
+{
+  <M::Kind as MonadWriter<'a, W>>::listen::<M::Item>(m).and_then::<B, F>(f)
+}
+
+/// Run `m`, modifying its output with `g`, and bind to `f`.
+///
+/// This is what the `x <- censor m => g;` statement desugars to in [`m!`](crate::m). The monad
+/// stack doesn’t have to be known before `f` is.
+pub fn censor_and_then<'a, W, N, A, G, F>(m: <N::Kind as Kind<'a>>::Of<A>, g: G, f: F) -> N
+where
+  W: 'a,
+  N: Monad<'a>,
+  N::Kind: MonadWriter<'a, W>,
+  A: 'a,
+  G: FnOnce(W) -> W + 'a,
+  F: FnOnce(A) -> N + 'a,
+{
+  <N::Kind as MonadWriter<'a, W>>::censor::<A, G>(m, g).and_then::<N::Item, F>(f)
+}
+
+/// Embed a value and its output into the monad.
+pub fn writer<'a, W, M, A>(aw: (A, W)) -> WriterT<'a, W, M, A>
+where
+  W: 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  WriterT {
+    run: Lift::lift(aw),
+  }
+}
+
+/// Append `w` to the output.
+pub fn tell<'a, W, M>(w: W) -> WriterT<'a, W, M, ()>
+where
+  W: 'a,
+  M: Kind<'a>,
+{
+  writer(((), w))
+}
+
+/// Run `m`, producing its output alongside its value.
+pub fn listen<'a, W, M, A>(m: WriterT<'a, W, M, A>) -> WriterT<'a, W, M, (A, W)>
+where
+  W: Monoid + Clone + 'a,
+  M: Kind<'a>,
+  A: 'a,
+{
+  WriterTKind::listen(m)
+}
+
+/// Run `m`, modifying its output with the function it produces alongside its value.
+pub fn pass<'a, W, M, A, F>(m: WriterT<'a, W, M, (A, F)>) -> WriterT<'a, W, M, A>
+where
+  W: Monoid + Clone + 'a,
+  M: Kind<'a>,
+  A: 'a,
+  F: FnOnce(W) -> W + 'a,
+{
+  WriterTKind::pass(m)
+}
+
+/// Run `m`, modifying its output with `f`.
+pub fn censor<'a, W, M, A, F>(m: WriterT<'a, W, M, A>, f: F) -> WriterT<'a, W, M, A>
+where
+  W: Monoid + Clone + 'a,
+  M: Kind<'a>,
+  A: 'a,
+  F: FnOnce(W) -> W + 'a,
+{
+  WriterTKind::censor(m, f)
+}
+
+/// Run a computation, returning its value and output in the inner monad.
+pub fn run_writer_t<'a, W, M, A>(m: WriterT<'a, W, M, A>) -> M::Of<(A, W)>
+where
+  M: Kind<'a>,
+{
+  m.run
+}
+
+/// Run a pure computation, returning its value and output.
+pub fn run_writer<'a, W, A>(m: Writer<'a, W, A>) -> (A, W) {
+  run_writer_t(m).run()
+}
+
+/// Run a pure computation, returning its output.
+pub fn exec_writer<'a, W, A>(m: Writer<'a, W, A>) -> W {
+  run_writer(m).1
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    monad::OptionKind,
+    reader::{ask, run_reader_t},
+    state::{get, put, run_state_t},
+  };
+
+  #[test]
+  fn writer() {
+    let r: Writer<String, i32> = m! {
+      tell "a".to_owned();
+      x <- lift_writer(1, "b");
+      tell "c".to_owned();
+      return x + 1;
+    };
+
+    assert_eq!(run_writer(r), (2, "abc".to_owned()));
+  }
+
+  fn lift_writer<'a>(a: i32, w: &str) -> Writer<'a, String, i32> {
+    super::writer((a, w.to_owned()))
+  }
+
+  #[test]
+  fn writer_t() {
+    let r: WriterT<Vec<i32>, OptionKind, i32> = m! {
+      tell vec![1];
+      x <- lift Some(2);
+      tell vec![x];
+      return x;
+    };
+
+    assert_eq!(run_writer_t(r), Some((2, vec![1, 2])));
+
+    let r: WriterT<Vec<i32>, OptionKind, i32> = m! {
+      tell vec![1];
+      x <- lift None;
+      tell vec![x];
+      return x;
+    };
+
+    assert_eq!(run_writer_t(r), None);
+  }
+
+  #[test]
+  fn listen_pass() {
+    let r: Writer<Vec<i32>, _> = m! {
+      tell vec![1];
+      x <- listen(m! { tell vec![2]; tell vec![3]; return 'a'; });
+      y <- pass(m! {
+        tell vec![4];
+        return ('b', |w: Vec<i32>| w.into_iter().map(|x| x * 10).collect());
+      });
+      return (x, y);
+    };
+
+    assert_eq!(run_writer(r), ((('a', vec![2, 3]), 'b'), vec![1, 2, 3, 40]));
+  }
+
+  #[test]
+  fn listen_censor_statements() {
+    fn log<'a>(n: i32) -> Writer<'a, Vec<i32>, i32> {
+      super::writer((n, vec![n]))
+    }
+
+    let double = |w: Vec<i32>| w.into_iter().map(|x| x * 2).collect();
+
+    let r = m! {
+      tell vec![1];
+      x <- censor log(2) => double;
+      _ <- censor tell(vec![3]) => double;
+      censor tell(vec![4]) => double;
+      y <- listen log(5);
+      censor log(x + y.0) => |_| vec![0]
+    };
+
+    assert_eq!(run_writer(r), (7, vec![1, 4, 6, 8, 5, 0]));
+
+    let r = m! {
+      x <- listen censor(log(1), double);
+      return x.1.len();
+    };
+    assert_eq!(run_writer(r), (1, vec![2]));
+
+    let r = m! {
+      _ <- log(1);
+      listen log(2)
+    };
+    assert_eq!(run_writer(r), ((2, vec![2]), vec![1, 2]));
+  }
+
+  #[test]
+  fn censor_stack() {
+    type Stack = StateTKind<i32, WriterTKind<Vec<i32>, IdentityKind>>;
+    let r: ReaderT<i32, Stack, i32> = m! {
+      x <- ask();
+      y <- censor m! { tell vec![x]; lift get() } => |w: Vec<i32>| w.into_iter().map(|x| -x).collect();
+      return x + y;
+    };
+
+    assert_eq!(
+      run_writer(run_state_t(run_reader_t(r, 1), 2)),
+      ((3, 2), vec![-1])
+    );
+  }
+
+  #[test]
+  fn monad_writer_stack() {
+    type Stack = StateTKind<i32, WriterTKind<Vec<i32>, IdentityKind>>;
+    let r: ReaderT<i32, Stack, ()> = m! {
+      x <- ask();
+      tell vec![x];
+      y <- lift get();
+      tell vec![y];
+      lift put(x + y)
+    };
+
+    assert_eq!(
+      run_writer(run_state_t(run_reader_t(r, 1), 2)),
+      (((), 3), vec![1, 2])
+    );
+  }
+}
@@ -0,0 +1,145 @@
+//! Check that do-blocks optimize as well as the hand-written code they replace.
+//!
+//! The functions of `tests/codegen/blocks.rs` are compiled to optimized LLVM IR, with the compiler of the toolchain
+//! running the tests. Every function built with a do-block is compared with its hand-written counterpart: the
+//! closures of the block must be entirely inlined — no call nor stack allocation may remain — and the block must not
+//! be much bigger. LLVM doesn’t produce the exact same IR for equivalent code, so the sizes are compared with some
+//! slack.
+
+use std::{
+  collections::HashMap,
+  env, fs,
+  path::{Path, PathBuf},
+  process::Command,
+};
+
+/// Metrics of an optimized function.
+#[derive(Debug, Default)]
+struct Metrics {
+  instructions: usize,
+  calls: usize,
+  allocas: usize,
+}
+
+/// The compiler of the toolchain running the tests: `$RUSTC` if set, or the `rustc` installed next to `cargo`.
+fn rustc() -> Command {
+  let rustc = env::var_os("RUSTC").map(PathBuf::from).unwrap_or_else(|| {
+    let rustc =
+      Path::new(env!("CARGO")).with_file_name(format!("rustc{}", env::consts::EXE_SUFFIX));
+    if rustc.exists() {
+      rustc
+    } else {
+      PathBuf::from("rustc")
+    }
+  });
+
+  let mut command = Command::new(rustc);
+  command
+    .args(["--edition", "2018"])
+    .args(["-C", "opt-level=3", "-C", "debuginfo=0"]);
+  command
+}
+
+/// Compile `fixture` to optimized LLVM IR.
+///
+/// The library is built from source with the same compiler, rather than picked among the builds of the target
+/// directory, which may have been built with other features or by another toolchain. Without features, it has no
+/// dependencies.
+fn compile(fixture: &str) -> String {
+  let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+  let dir = env::temp_dir().join(format!("do-notation-codegen-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  let library = dir.join("libdo_notation.rlib");
+  let out = dir.join("fixture.ll");
+
+  let status = rustc()
+    .args(["--crate-type", "rlib", "--crate-name", "do_notation"])
+    .arg("-o")
+    .arg(&library)
+    .arg(root.join("src/lib.rs"))
+    .status()
+    .unwrap();
+  assert!(status.success(), "cannot compile do_notation");
+
+  let status = rustc()
+    .args(["--crate-type", "lib", "--emit", "llvm-ir"])
+    .arg("--extern")
+    .arg(format!("do_notation={}", library.display()))
+    .arg("-o")
+    .arg(&out)
+    .arg(root.join(fixture))
+    .status()
+    .unwrap();
+  assert!(status.success(), "cannot compile {}", fixture);
+
+  let ir = fs::read_to_string(&out).unwrap();
+  fs::remove_dir_all(&dir).unwrap();
+  ir
+}
+
+/// Metrics of the functions defined in `ir`, by name.
+fn metrics(ir: &str) -> HashMap<String, Metrics> {
+  let mut functions = HashMap::new();
+  let mut current = None;
+
+  for line in ir.lines() {
+    if line.starts_with("define ") {
+      let name = line.split('@').nth(1).unwrap();
+      let name = &name[..name.find('(').unwrap()];
+      current = Some(
+        functions
+          .entry(name.to_owned())
+          .or_insert_with(Metrics::default),
+      );
+    } else if line == "}" {
+      current = None;
+    } else if let Some(metrics) = current.as_mut() {
+      let indented = line.starts_with(' ');
+      let line = line.trim();
+
+      // instructions are indented; skip labels and blank lines
+      if indented && !line.is_empty() {
+        metrics.instructions += 1;
+        metrics.calls += (line.contains("call ") || line.contains("invoke ")) as usize;
+        metrics.allocas += line.contains("alloca ") as usize;
+      }
+    }
+  }
+
+  functions
+}
+
+#[test]
+fn blocks_optimize_as_hand_written_code() {
+  let functions = metrics(&compile("tests/codegen/blocks.rs"));
+  let blocks = functions.keys().filter(|name| !name.starts_with("manual_"));
+  let mut checked = 0;
+
+  for name in blocks {
+    let block = &functions[name];
+    let suffix = name.trim_start_matches("flat_").trim_start_matches("m_");
+    let manual = &functions[&format!("manual_{}", suffix)];
+
+    assert_eq!(
+      block.calls, manual.calls,
+      "{}: {:?} / {:?}",
+      name, block, manual
+    );
+    assert_eq!(
+      block.allocas, manual.allocas,
+      "{}: {:?} / {:?}",
+      name, block, manual
+    );
+    assert!(
+      block.instructions <= manual.instructions * 2,
+      "{}: {:?} / {:?}",
+      name,
+      block,
+      manual
+    );
+
+    checked += 1;
+  }
+
+  assert_eq!(checked, 3);
+}
@@ -0,0 +1,45 @@
+// Do-blocks and the hand-written code they must compile to. Every `m_*` or `flat_m_*` function must compile to the
+// same code as the function with the same suffix starting with `manual_`.
+
+use do_notation::{flat_m, m};
+
+#[no_mangle]
+pub fn m_option(v: &[u32]) -> Option<u32> {
+  m! {
+    x <- v.get(3);
+    y <- v.get(*x as usize);
+    z <- v.get(*y as usize);
+    return x + y + z;
+  }
+}
+
+#[no_mangle]
+pub fn manual_option(v: &[u32]) -> Option<u32> {
+  let x = v.get(3)?;
+  let y = v.get(*x as usize)?;
+  let z = v.get(*y as usize)?;
+  Some(x + y + z)
+}
+
+#[no_mangle]
+pub fn m_result(a: Result<u32, u8>, b: Result<u32, u8>) -> Result<u32, u8> {
+  m! {
+    x <- a;
+    y <- b;
+    return x.wrapping_mul(y);
+  }
+}
+
+#[no_mangle]
+pub fn flat_m_result(a: Result<u32, u8>, b: Result<u32, u8>) -> Result<u32, u8> {
+  flat_m! {
+    x <- a;
+    y <- b;
+    return x.wrapping_mul(y);
+  }
+}
+
+#[no_mangle]
+pub fn manual_result(a: Result<u32, u8>, b: Result<u32, u8>) -> Result<u32, u8> {
+  Ok(a?.wrapping_mul(b?))
+}
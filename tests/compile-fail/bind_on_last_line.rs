@@ -0,0 +1,8 @@
+use do_notation::m;
+
+fn main() {
+  let _: Option<i32> = m! {
+    x <- Some(1);
+    y <- Some(x + 1)
+  };
+}
@@ -0,0 +1,10 @@
+use do_notation::flat_m;
+
+fn main() {
+  let _: Option<i32> = flat_m! {
+    x <- Some(1);
+    return x;
+    y <- Some(x + 1);
+    return y;
+  };
+}
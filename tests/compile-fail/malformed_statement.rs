@@ -0,0 +1,10 @@
+use do_notation::m;
+
+fn main() {
+  let _: Option<i32> = m! {
+    ref;
+    x <- Some(1);
+    y z;
+    return x;
+  };
+}
@@ -0,0 +1,10 @@
+use do_notation::m;
+
+fn main() {
+  let _: Option<i32> = m! {
+    x <- Some(1);
+    return x;
+    y <- Some(x + 1);
+    return y;
+  };
+}
@@ -0,0 +1,9 @@
+use do_notation::m;
+
+fn main() {
+  let _: Option<i32> = m! {
+    x <- Some(1)
+    y <- Some(x + 1);
+    return y;
+  };
+}
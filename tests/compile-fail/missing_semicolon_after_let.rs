@@ -0,0 +1,10 @@
+use do_notation::m;
+
+fn main() {
+  let _: Option<i32> = m! {
+    x <- Some(1);
+    let y = x + 1
+    let z = y * 2;
+    return z;
+  };
+}
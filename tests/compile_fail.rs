@@ -0,0 +1,9 @@
+//! Check the diagnostics of malformed do-blocks.
+//!
+//! Every file of `tests/compile-fail` must fail to compile with the error of the `.stderr` file of the same name.
+//! Run with `TRYBUILD=overwrite` to update the expected errors.
+
+#[test]
+fn compile_fail() {
+  trybuild::TestCases::new().compile_fail("tests/compile-fail/*.rs");
+}